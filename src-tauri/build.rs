@@ -1,3 +1,13 @@
 fn main() {
+    // protoc ships with the build, so nobody has to install it
+    std::env::set_var(
+        "PROTOC",
+        protoc_bin_vendored::protoc_bin_path().expect("protoc is bundled for this platform"),
+    );
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/detector.proto"], &["proto"])
+        .expect("Failed to compile the gRPC protocol");
+
     tauri_build::build()
 }
@@ -0,0 +1,12 @@
+const COMMANDS: &[&str] = &[
+    "start",
+    "stop",
+    "is_running",
+    "get_selection",
+    "check_permissions",
+    "request_permissions",
+];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS).build();
+}
@@ -0,0 +1,48 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export interface SelectionEvent {
+  text: string;
+  app_name: string;
+  timestamp: number;
+  selection_type: "Selected" | "Hovered" | "Focused" | "Image";
+}
+
+export type DetectorError =
+  | "PermissionDenied"
+  | "Unsupported"
+  | "Timeout"
+  | { Platform: string };
+
+export async function start(): Promise<void> {
+  await invoke("plugin:text-detection|start");
+}
+
+export async function stop(): Promise<void> {
+  await invoke("plugin:text-detection|stop");
+}
+
+export async function isRunning(): Promise<boolean> {
+  return await invoke("plugin:text-detection|is_running");
+}
+
+export async function getSelection(): Promise<SelectionEvent | null> {
+  return await invoke("plugin:text-detection|get_selection");
+}
+
+export async function checkPermissions(): Promise<boolean> {
+  return await invoke("plugin:text-detection|check_permissions");
+}
+
+export async function requestPermissions(): Promise<void> {
+  await invoke("plugin:text-detection|request_permissions");
+}
+
+export async function onSelectionChanged(
+  handler: (event: SelectionEvent) => void
+): Promise<UnlistenFn> {
+  return await listen<SelectionEvent>(
+    "text-detection://selection-changed",
+    (event) => handler(event.payload)
+  );
+}
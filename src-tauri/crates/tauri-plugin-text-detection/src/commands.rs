@@ -0,0 +1,35 @@
+use tauri::{command, AppHandle, Runtime};
+use text_detection::{DetectorError, SelectionEvent};
+
+use crate::TextDetectionExt;
+
+#[command]
+pub(crate) async fn start<R: Runtime>(app: AppHandle<R>) -> Result<(), DetectorError> {
+    app.text_detection().start()
+}
+
+#[command]
+pub(crate) async fn stop<R: Runtime>(app: AppHandle<R>) -> Result<(), DetectorError> {
+    app.text_detection().stop();
+    Ok(())
+}
+
+#[command]
+pub(crate) async fn is_running<R: Runtime>(app: AppHandle<R>) -> Result<bool, DetectorError> {
+    Ok(app.text_detection().is_running())
+}
+
+#[command]
+pub(crate) async fn get_selection<R: Runtime>(app: AppHandle<R>) -> Result<Option<SelectionEvent>, DetectorError> {
+    app.text_detection().detector().selection()
+}
+
+#[command]
+pub(crate) async fn check_permissions<R: Runtime>(app: AppHandle<R>) -> Result<bool, DetectorError> {
+    Ok(app.text_detection().detector().has_permissions())
+}
+
+#[command]
+pub(crate) async fn request_permissions<R: Runtime>(app: AppHandle<R>) -> Result<(), DetectorError> {
+    app.text_detection().detector().request_permissions()
+}
@@ -0,0 +1,106 @@
+//! Drop-in selection monitoring for Tauri apps.
+//!
+//! ```ignore
+//! tauri::Builder::default()
+//!     .plugin(tauri_plugin_text_detection::Builder::new().interval_ms(250).build())
+//! ```
+//!
+//! The monitor emits `text-detection://selection-changed` with a
+//! [`SelectionEvent`] payload whenever the selection changes.
+
+use std::time::Duration;
+use tauri::{
+    plugin::{Builder as PluginBuilder, TauriPlugin},
+    Manager, Runtime,
+};
+
+mod commands;
+mod monitor;
+
+pub use monitor::Monitor;
+pub use text_detection::{Detector, DetectorError, SelectionEvent, SelectionType};
+
+pub const SELECTION_CHANGED_EVENT: &str = "text-detection://selection-changed";
+
+/// Extension trait giving access to the plugin's [`Monitor`] from any manager.
+pub trait TextDetectionExt<R: Runtime> {
+    fn text_detection(&self) -> &Monitor<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> TextDetectionExt<R> for T {
+    fn text_detection(&self) -> &Monitor<R> {
+        self.state::<Monitor<R>>().inner()
+    }
+}
+
+pub struct Builder {
+    interval: Duration,
+    autostart: bool,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(500),
+            autostart: false,
+        }
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often the focused element is polled.
+    ///
+    /// # Panics
+    ///
+    /// If `ms` is 0, which would poll in a busy loop.
+    pub fn interval_ms(mut self, ms: u64) -> Self {
+        assert!(ms > 0, "text detection interval must be at least 1ms");
+        self.interval = Duration::from_millis(ms);
+        self
+    }
+
+    /// Start monitoring as soon as the plugin is set up.
+    pub fn autostart(mut self, autostart: bool) -> Self {
+        self.autostart = autostart;
+        self
+    }
+
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R> {
+        let Builder { interval, autostart } = self;
+
+        PluginBuilder::new("text-detection")
+            .invoke_handler(tauri::generate_handler![
+                commands::start,
+                commands::stop,
+                commands::is_running,
+                commands::get_selection,
+                commands::check_permissions,
+                commands::request_permissions,
+            ])
+            .setup(move |app, _api| {
+                let monitor = Monitor::new(app.clone(), interval);
+                if autostart {
+                    if let Err(e) = monitor.start() {
+                        eprintln!("Failed to autostart text detection: {}", e);
+                    }
+                }
+                app.manage(monitor);
+                Ok(())
+            })
+            .on_drop(|app| {
+                if let Some(monitor) = app.try_state::<Monitor<R>>() {
+                    monitor.stop();
+                }
+            })
+            .build()
+    }
+}
+
+/// Initializes the plugin with default settings.
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new().build()
+}
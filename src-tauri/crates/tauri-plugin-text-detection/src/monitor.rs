@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use text_detection::{Detector, DetectorError};
+
+use crate::SELECTION_CHANGED_EVENT;
+
+/// Polls the [`Detector`] on the async runtime and emits changes.
+pub struct Monitor<R: Runtime> {
+    app: AppHandle<R>,
+    detector: Detector,
+    interval: Duration,
+    is_running: Arc<AtomicBool>,
+    /// Bumped on every start and stop; a polling loop exits once it no
+    /// longer matches the one it was started with, so a quick stop and
+    /// start never leaves two running.
+    generation: Arc<AtomicU64>,
+    last_selection: Arc<Mutex<Option<String>>>,
+}
+
+impl<R: Runtime> Monitor<R> {
+    pub(crate) fn new(app: AppHandle<R>, interval: Duration) -> Self {
+        Self {
+            app,
+            detector: Detector::new(),
+            interval,
+            is_running: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(0)),
+            last_selection: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn detector(&self) -> &Detector {
+        &self.detector
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::SeqCst)
+    }
+
+    pub fn start(&self) -> Result<(), DetectorError> {
+        if !self.detector.is_supported() {
+            return Err(DetectorError::Unsupported);
+        }
+        if !self.detector.has_permissions() {
+            return Err(DetectorError::PermissionDenied);
+        }
+        if self.is_running.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let app = self.app.clone();
+        let detector = self.detector.clone();
+        let interval = self.interval;
+        let is_running = Arc::clone(&self.is_running);
+        let current = Arc::clone(&self.generation);
+        let generation = current.fetch_add(1, Ordering::SeqCst) + 1;
+        let last_selection = Arc::clone(&self.last_selection);
+
+        tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio_interval(interval);
+            loop {
+                ticker.tick().await;
+                if current.load(Ordering::SeqCst) != generation || !is_running.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                if let Ok(Some(selection)) = detector.selection() {
+                    let mut last = last_selection.lock().unwrap();
+                    if last.as_ref() != Some(&selection.text) {
+                        *last = Some(selection.text.clone());
+                        let _ = app.emit(SELECTION_CHANGED_EVENT, &selection);
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.is_running.store(false, Ordering::SeqCst);
+        *self.last_selection.lock().unwrap() = None;
+    }
+}
+
+fn tokio_interval(period: Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    interval
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DetectorError {
+    /// The OS has not granted accessibility access to this process.
+    PermissionDenied,
+    /// No backend is compiled in for the current platform.
+    Unsupported,
+    /// The target application didn't answer within the messaging timeout.
+    Timeout,
+    /// The focused application runs elevated (as administrator) and this
+    /// process doesn't, so the OS hides its UI from us.
+    ElevationRequired,
+    /// The platform API returned an error code we can't recover from.
+    Platform(String),
+}
+
+impl fmt::Display for DetectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectorError::PermissionDenied => write!(f, "Accessibility permissions not granted"),
+            DetectorError::Unsupported => write!(f, "Text detection is not supported on this platform"),
+            DetectorError::Timeout => write!(f, "The application did not respond in time"),
+            DetectorError::ElevationRequired => {
+                write!(f, "The application runs as administrator; restart elevated to read it")
+            }
+            DetectorError::Platform(msg) => write!(f, "Platform error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DetectorError {}
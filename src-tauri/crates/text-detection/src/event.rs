@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionEvent {
+    pub text: String,
+    pub app_name: String,
+    pub timestamp: u64,
+    pub selection_type: SelectionType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SelectionType {
+    Selected,
+    Hovered,
+    Focused,
+    /// The focused element is an image; see [`ImageRegion`].
+    Image,
+}
+
+/// Where a selected image is on screen, in points from the top-left of the
+/// main display.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImageRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A span of an element's text, in UTF-16 code units as the accessibility
+/// APIs count them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextRange {
+    pub location: usize,
+    pub length: usize,
+}
+
+/// What the focused element says about itself, for bug reports about text
+/// read from the wrong place. Values are cut short, so titles and names
+/// carry little of what's on screen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElementDump {
+    /// Attribute name to value, in the platform's own terms (`AXRole`,
+    /// `AXSubrole`, `AutomationId`, ...).
+    pub attributes: BTreeMap<String, String>,
+    /// Roles of the element's ancestors, nearest first.
+    pub ancestors: Vec<String>,
+    /// Bundle identifier of the app the element belongs to.
+    pub app: Option<String>,
+}
+
+impl SelectionEvent {
+    /// Builds an event stamped with the current time.
+    pub fn new(text: impl Into<String>, app_name: impl Into<String>, selection_type: SelectionType) -> Self {
+        Self {
+            text: text.into(),
+            app_name: app_name.into(),
+            timestamp: now_secs(),
+            selection_type,
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
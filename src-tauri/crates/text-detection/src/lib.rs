@@ -0,0 +1,267 @@
+//! Platform-independent access to the text the user currently has selected.
+//!
+//! The crate has no Tauri dependency so it can be embedded in other Rust
+//! projects. Everything goes through [`Detector`]:
+//!
+//! ```no_run
+//! let detector = text_detection::Detector::new();
+//! if let Ok(Some(selection)) = detector.selection() {
+//!     println!("{}: {}", selection.app_name, selection.text);
+//! }
+//! ```
+
+mod error;
+mod event;
+mod platform;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub use error::DetectorError;
+pub use event::{ElementDump, ImageRegion, SelectionEvent, SelectionType, TextRange};
+
+/// Longest value kept in an [`ElementDump`], in characters.
+pub const DUMP_VALUE_CHARS: usize = 80;
+
+/// Cheap identity of what has focus: the app, the focused element and its
+/// selection range. While it stays the same the selection hasn't changed,
+/// so a poller can skip the full text fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusFingerprint {
+    pub(crate) pid: i32,
+    pub(crate) element: u64,
+    pub(crate) selection: Option<(isize, isize)>,
+}
+
+/// Elements whose focused value/title is UI chrome (button labels, menu
+/// items) rather than content. Roles are the platform's own names, e.g.
+/// `AXButton` on macOS or `Button` on Windows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoredRole {
+    pub role: String,
+    /// Only when the element is inside one with this role, e.g.
+    /// `AXStaticText` within `AXToolbar`.
+    #[serde(default)]
+    pub within: Option<String>,
+}
+
+impl IgnoredRole {
+    /// `roles` is the focused element's role followed by its ancestors'.
+    fn matches(&self, roles: &[String]) -> bool {
+        let Some((role, ancestors)) = roles.split_first() else {
+            return false;
+        };
+        *role == self.role
+            && self
+                .within
+                .as_ref()
+                .is_none_or(|within| ancestors.iter().any(|ancestor| ancestor == within))
+    }
+}
+
+/// Entry point for reading selections from the focused application.
+#[derive(Debug, Default, Clone)]
+pub struct Detector {
+    timeout: Option<Duration>,
+    app_timeouts: HashMap<String, Duration>,
+    selected_only: bool,
+    ignored_roles: Vec<IgnoredRole>,
+}
+
+impl Detector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps how long a query may wait for an unresponsive application before
+    /// failing with [`DetectorError::Timeout`]. Without one the OS default
+    /// applies, which can be several seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Overrides the timeout for one application, by bundle identifier.
+    pub fn with_app_timeout(mut self, bundle_id: impl Into<String>, timeout: Duration) -> Self {
+        self.app_timeouts.insert(bundle_id.into(), timeout);
+        self
+    }
+
+    /// Makes [`Detector::selection`] return only genuine selections, never
+    /// the focused value/title fallback, which in many apps is a whole
+    /// document or a button label.
+    pub fn with_selected_only(mut self, selected_only: bool) -> Self {
+        self.selected_only = selected_only;
+        self
+    }
+
+    /// Skips the focused value/title fallback for elements matching any of
+    /// `roles`. Genuine selections are never filtered.
+    pub fn with_ignored_roles(mut self, roles: Vec<IgnoredRole>) -> Self {
+        self.ignored_roles = roles;
+        self
+    }
+
+    /// See [`Detector::with_selected_only`].
+    pub fn is_selected_only(&self) -> bool {
+        self.selected_only
+    }
+
+    /// Whether this build has a backend for the current platform.
+    pub fn is_supported(&self) -> bool {
+        platform::SUPPORTED
+    }
+
+    /// Whether the process is allowed to query other applications.
+    pub fn has_permissions(&self) -> bool {
+        platform::check_permissions()
+    }
+
+    /// Asks the OS to prompt the user for the permissions the backend needs.
+    pub fn request_permissions(&self) -> Result<(), DetectorError> {
+        platform::request_permissions()
+    }
+
+    /// Returns the selected text of the focused element, falling back to its
+    /// focused value/title when nothing is selected (unless
+    /// [`Detector::with_selected_only`] is set).
+    pub fn selection(&self) -> Result<Option<SelectionEvent>, DetectorError> {
+        self.read_selection(None)
+    }
+
+    /// Returns only a genuine selection, never the focused-value fallback.
+    pub fn selected_text(&self) -> Result<Option<String>, DetectorError> {
+        platform::selected_text(None, self.timeout_for(None))
+    }
+
+    /// Like [`Detector::selection`], but reads the focused element of the
+    /// application with process id `pid` even if it isn't frontmost.
+    pub fn selection_in_app(&self, pid: i32) -> Result<Option<SelectionEvent>, DetectorError> {
+        self.read_selection(Some(pid))
+    }
+
+    /// Like [`Detector::selected_text`], scoped to one application.
+    pub fn selected_text_in_app(&self, pid: i32) -> Result<Option<String>, DetectorError> {
+        platform::selected_text(Some(pid), self.timeout_for(Some(pid)))
+    }
+
+    /// Process id of the application that currently owns keyboard focus.
+    pub fn frontmost_app_pid(&self) -> Option<i32> {
+        platform::frontmost_app_pid()
+    }
+
+    /// The screen region of the focused element when it is an image (e.g.
+    /// a picture open in Preview), for callers that want to grab its pixels.
+    pub fn selected_image(&self) -> Result<Option<ImageRegion>, DetectorError> {
+        platform::selected_image(None, self.timeout_for(None))
+    }
+
+    /// The screen region of the selected text, or of the focused element
+    /// when the app doesn't report where the selection is.
+    pub fn selection_region(&self) -> Result<Option<ImageRegion>, DetectorError> {
+        platform::selection_region(None, self.timeout_for(None))
+    }
+
+    /// Where the selection is within the focused element's text, so the
+    /// same span can be read again later with [`Detector::text_in_range`].
+    pub fn selected_range(&self) -> Result<Option<TextRange>, DetectorError> {
+        platform::selected_range(None, self.timeout_for(None))
+    }
+
+    /// The text of `range` in the focused element of the application with
+    /// process id `pid`, as it is now.
+    pub fn text_in_range(&self, pid: i32, range: TextRange) -> Result<Option<String>, DetectorError> {
+        platform::text_in_range(Some(pid), range, self.timeout_for(Some(pid)))
+    }
+
+    /// Brings the application with process id `pid` to the front. Returns
+    /// whether it was asked to.
+    pub fn activate_app(&self, pid: i32) -> bool {
+        platform::activate_app(pid)
+    }
+
+    /// Only the on-screen part of the focused element's text. Useful for
+    /// elements like terminals whose full value is the entire scrollback.
+    pub fn visible_text(&self) -> Result<Option<String>, DetectorError> {
+        platform::visible_text(None, self.timeout_for(None))
+    }
+
+    /// Role of the focused element followed by its ancestors' roles, nearest
+    /// first, as matched by [`IgnoredRole`].
+    pub fn focused_roles(&self) -> Result<Vec<String>, DetectorError> {
+        platform::focused_roles(None, self.timeout_for(None))
+    }
+
+    /// The focused element's role, identifiers and the like, and its
+    /// ancestors' roles, each value cut to [`DUMP_VALUE_CHARS`].
+    pub fn focused_element_dump(&self) -> Result<ElementDump, DetectorError> {
+        let timeout = self.timeout_for(None);
+        let trim = |value: String| match value.char_indices().nth(DUMP_VALUE_CHARS) {
+            Some((end, _)) => format!("{}…", &value[..end]),
+            None => value,
+        };
+        let attributes = platform::element_attributes(None, timeout)?
+            .into_iter()
+            .map(|(name, value)| (name, trim(value)))
+            .collect();
+        Ok(ElementDump {
+            attributes,
+            ancestors: platform::focused_roles(None, timeout)?.into_iter().skip(1).collect(),
+            app: platform::frontmost_app_pid().and_then(platform::bundle_id_for_pid),
+        })
+    }
+
+    /// Bundle identifier of the application with process id `pid`.
+    pub fn bundle_id(&self, pid: i32) -> Option<String> {
+        platform::bundle_id_for_pid(pid)
+    }
+
+    /// Title of the focused window of the application with process id
+    /// `pid`, e.g. the document name.
+    pub fn window_title(&self, pid: i32) -> Option<String> {
+        platform::window_title(pid)
+    }
+
+    /// See [`FocusFingerprint`]. `None` when the backend can't tell cheaply,
+    /// in which case callers should always do the full fetch.
+    pub fn focus_fingerprint(&self) -> Option<FocusFingerprint> {
+        platform::focus_fingerprint(self.timeout_for(None))
+    }
+
+    /// Process id of a running application, looked up by bundle identifier.
+    pub fn find_app_pid(&self, bundle_id: &str) -> Option<i32> {
+        platform::pid_for_app(bundle_id)
+    }
+
+    /// The timeout for queries against `pid` (or the frontmost app).
+    fn timeout_for(&self, pid: Option<i32>) -> Option<Duration> {
+        if !self.app_timeouts.is_empty() {
+            let bundle_id = pid.or_else(platform::frontmost_app_pid).and_then(platform::bundle_id_for_pid);
+            if let Some(timeout) = bundle_id.and_then(|id| self.app_timeouts.get(&id)) {
+                return Some(*timeout);
+            }
+        }
+        self.timeout
+    }
+
+    fn read_selection(&self, pid: Option<i32>) -> Result<Option<SelectionEvent>, DetectorError> {
+        let timeout = self.timeout_for(pid);
+        if let Some(text) = platform::selected_text(pid, timeout)? {
+            return Ok(Some(SelectionEvent::new(text, "Unknown", SelectionType::Selected)));
+        }
+        if self.selected_only {
+            return Ok(None);
+        }
+        // Roles that can't be read (e.g. other processes on Windows) aren't filtered
+        if !self.ignored_roles.is_empty() {
+            let roles = platform::focused_roles(pid, timeout).unwrap_or_default();
+            if self.ignored_roles.iter().any(|ignored| ignored.matches(&roles)) {
+                return Ok(None);
+            }
+        }
+
+        Ok(platform::focused_text(pid, timeout)?
+            .map(|text| SelectionEvent::new(text, "Unknown", SelectionType::Focused)))
+    }
+}
@@ -0,0 +1,500 @@
+use crate::{DetectorError, FocusFingerprint, ImageRegion, TextRange};
+use accessibility_sys::*;
+use core_foundation::base::{CFHash, CFRange, CFRelease, CFTypeRef, TCFType};
+use core_foundation::string::{CFString, CFStringRef};
+use objc::runtime::{Object, BOOL, NO};
+use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::time::Duration;
+
+// NSRunningApplication and NSWorkspace live in AppKit
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {}
+
+pub(crate) const SUPPORTED: bool = true;
+
+/// How many levels [`focused_roles`] walks up from the focused element.
+const MAX_ROLE_DEPTH: usize = 8;
+
+pub(crate) fn check_permissions() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+pub(crate) fn request_permissions() -> Result<(), DetectorError> {
+    unsafe {
+        // Use a null pointer instead of creating a dictionary to avoid type conflicts
+        AXIsProcessTrustedWithOptions(std::ptr::null());
+    }
+    Ok(())
+}
+
+pub(crate) fn selected_text(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    unsafe {
+        match focused_element(pid, timeout)? {
+            Some(focused) => get_selected_text(focused),
+            None => Ok(None),
+        }
+    }
+}
+
+pub(crate) fn focused_text(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    unsafe {
+        match focused_element(pid, timeout)? {
+            Some(focused) => get_focused_text(focused),
+            None => Ok(None),
+        }
+    }
+}
+
+pub(crate) fn focused_roles(pid: Option<i32>, timeout: Option<Duration>) -> Result<Vec<String>, DetectorError> {
+    unsafe {
+        let Some(mut element) = focused_element(pid, timeout)? else {
+            return Ok(Vec::new());
+        };
+        let parent_attr = CFString::new(kAXParentAttribute);
+        let mut roles = Vec::new();
+        loop {
+            // Keep positions meaningful even for elements without a role
+            roles.push(copy_string(element, kAXRoleAttribute).unwrap_or_default());
+
+            let mut parent: AXUIElementRef = std::ptr::null_mut();
+            let result = if roles.len() < MAX_ROLE_DEPTH {
+                AXUIElementCopyAttributeValue(element, parent_attr.as_concrete_TypeRef(), &mut parent as *mut _ as *mut CFTypeRef)
+            } else {
+                kAXErrorNoValue
+            };
+            CFRelease(element as CFTypeRef);
+            if result != kAXErrorSuccess || parent.is_null() {
+                return Ok(roles);
+            }
+            element = parent;
+        }
+    }
+}
+
+pub(crate) fn visible_text(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(None);
+        };
+
+        let mut range_ref: CFTypeRef = std::ptr::null_mut();
+        let range_attr = CFString::new(kAXVisibleCharacterRangeAttribute);
+        let result = AXUIElementCopyAttributeValue(focused, range_attr.as_concrete_TypeRef(), &mut range_ref);
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        if result != kAXErrorSuccess || range_ref.is_null() {
+            return Ok(None);
+        }
+
+        let mut text_ref: CFTypeRef = std::ptr::null_mut();
+        let string_for_range = CFString::new(kAXStringForRangeParameterizedAttribute);
+        let result = AXUIElementCopyParameterizedAttributeValue(
+            focused,
+            string_for_range.as_concrete_TypeRef(),
+            range_ref,
+            &mut text_ref,
+        );
+        CFRelease(range_ref);
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        if result != kAXErrorSuccess || text_ref.is_null() {
+            return Ok(None);
+        }
+
+        let cf_string: CFString = TCFType::wrap_under_create_rule(text_ref as CFStringRef);
+        let text = cf_string.to_string();
+        Ok(Some(text).filter(|text| !text.trim().is_empty()))
+    }
+}
+
+pub(crate) fn selected_image(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(None);
+        };
+        if copy_string(focused, kAXRoleAttribute).as_deref() != Some(kAXImageRole) {
+            return Ok(None);
+        }
+        Ok(element_region(focused))
+    }
+}
+
+pub(crate) fn selection_region(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(None);
+        };
+
+        let mut range_ref: CFTypeRef = std::ptr::null_mut();
+        let range_attr = CFString::new(kAXSelectedTextRangeAttribute);
+        let result = AXUIElementCopyAttributeValue(focused, range_attr.as_concrete_TypeRef(), &mut range_ref);
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        if result == kAXErrorSuccess && !range_ref.is_null() {
+            let mut bounds_ref: CFTypeRef = std::ptr::null_mut();
+            let bounds_for_range = CFString::new(kAXBoundsForRangeParameterizedAttribute);
+            let result = AXUIElementCopyParameterizedAttributeValue(
+                focused,
+                bounds_for_range.as_concrete_TypeRef(),
+                range_ref,
+                &mut bounds_ref,
+            );
+            CFRelease(range_ref);
+            if result == kAXErrorCannotComplete {
+                return Err(DetectorError::Timeout);
+            }
+            if result == kAXErrorSuccess && !bounds_ref.is_null() {
+                // CGRect: origin then size
+                let mut rect = [0f64; 4];
+                let ok = AXValueGetValue(bounds_ref as AXValueRef, kAXValueTypeCGRect, rect.as_mut_ptr() as *mut c_void);
+                CFRelease(bounds_ref);
+                if ok && rect[2] >= 1.0 && rect[3] >= 1.0 {
+                    return Ok(Some(ImageRegion {
+                        x: rect[0],
+                        y: rect[1],
+                        width: rect[2],
+                        height: rect[3],
+                    }));
+                }
+            }
+        }
+        // Apps that don't report bounds for text ranges
+        Ok(element_region(focused))
+    }
+}
+
+pub(crate) fn selected_range(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<TextRange>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(None);
+        };
+        let mut range_ref: CFTypeRef = std::ptr::null_mut();
+        let range_attr = CFString::new(kAXSelectedTextRangeAttribute);
+        let result = AXUIElementCopyAttributeValue(focused, range_attr.as_concrete_TypeRef(), &mut range_ref);
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        if result != kAXErrorSuccess || range_ref.is_null() {
+            return Ok(None);
+        }
+        let mut range = CFRange { location: 0, length: 0 };
+        let ok = AXValueGetValue(range_ref as AXValueRef, kAXValueTypeCFRange, &mut range as *mut _ as *mut c_void);
+        CFRelease(range_ref);
+        if !ok || range.location < 0 || range.length <= 0 {
+            return Ok(None);
+        }
+        Ok(Some(TextRange {
+            location: range.location as usize,
+            length: range.length as usize,
+        }))
+    }
+}
+
+/// String attributes worth knowing when a capture comes out wrong.
+const DUMPED_ATTRIBUTES: &[&str] = &[
+    kAXRoleAttribute,
+    kAXSubroleAttribute,
+    kAXRoleDescriptionAttribute,
+    "AXIdentifier",
+    "AXDOMIdentifier",
+    kAXTitleAttribute,
+    kAXDescriptionAttribute,
+    kAXPlaceholderValueAttribute,
+];
+
+pub(crate) fn element_attributes(
+    pid: Option<i32>,
+    timeout: Option<Duration>,
+) -> Result<Vec<(String, String)>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(Vec::new());
+        };
+        let attributes = DUMPED_ATTRIBUTES
+            .iter()
+            .filter_map(|attribute| Some((attribute.to_string(), copy_string(focused, attribute)?)))
+            .collect();
+        CFRelease(focused as CFTypeRef);
+        Ok(attributes)
+    }
+}
+
+pub(crate) fn text_in_range(
+    pid: Option<i32>,
+    range: TextRange,
+    timeout: Option<Duration>,
+) -> Result<Option<String>, DetectorError> {
+    unsafe {
+        let Some(focused) = focused_element(pid, timeout)? else {
+            return Ok(None);
+        };
+        let cf_range = CFRange {
+            location: range.location as isize,
+            length: range.length as isize,
+        };
+        let range_ref = AXValueCreate(kAXValueTypeCFRange, &cf_range as *const _ as *const c_void);
+        if range_ref.is_null() {
+            return Ok(None);
+        }
+
+        let mut text_ref: CFTypeRef = std::ptr::null_mut();
+        let string_for_range = CFString::new(kAXStringForRangeParameterizedAttribute);
+        let result = AXUIElementCopyParameterizedAttributeValue(
+            focused,
+            string_for_range.as_concrete_TypeRef(),
+            range_ref as CFTypeRef,
+            &mut text_ref,
+        );
+        CFRelease(range_ref as CFTypeRef);
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        // Out of range once the text got shorter
+        if result != kAXErrorSuccess || text_ref.is_null() {
+            return Ok(None);
+        }
+        let cf_string: CFString = TCFType::wrap_under_create_rule(text_ref as CFStringRef);
+        Ok(Some(cf_string.to_string()))
+    }
+}
+
+pub(crate) fn activate_app(pid: i32) -> bool {
+    // NSApplicationActivateIgnoringOtherApps
+    const IGNORING_OTHER_APPS: usize = 1 << 1;
+    unsafe {
+        let app: *mut Object = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+        if app.is_null() {
+            return false;
+        }
+        let activated: BOOL = msg_send![app, activateWithOptions: IGNORING_OTHER_APPS];
+        activated != NO
+    }
+}
+
+/// The on-screen rectangle of `element`, if it has a usable one.
+unsafe fn element_region(element: AXUIElementRef) -> Option<ImageRegion> {
+    let mut origin = [0f64; 2];
+    let mut size = [0f64; 2];
+    if !copy_value(element, kAXPositionAttribute, kAXValueTypeCGPoint, origin.as_mut_ptr() as *mut c_void)
+        || !copy_value(element, kAXSizeAttribute, kAXValueTypeCGSize, size.as_mut_ptr() as *mut c_void)
+        || size[0] < 1.0
+        || size[1] < 1.0
+    {
+        return None;
+    }
+    Some(ImageRegion {
+        x: origin[0],
+        y: origin[1],
+        width: size[0],
+        height: size[1],
+    })
+}
+
+pub(crate) fn bundle_id_for_pid(pid: i32) -> Option<String> {
+    unsafe {
+        let app: *mut Object = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+        if app.is_null() {
+            return None;
+        }
+        let bundle_id: *mut Object = msg_send![app, bundleIdentifier];
+        if bundle_id.is_null() {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![bundle_id, UTF8String];
+        (!utf8.is_null()).then(|| CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+}
+
+pub(crate) fn pid_for_app(bundle_id: &str) -> Option<i32> {
+    let bundle_id = CString::new(bundle_id).ok()?;
+    unsafe {
+        let ns_bundle_id: *mut Object = msg_send![class!(NSString), stringWithUTF8String: bundle_id.as_ptr()];
+        let apps: *mut Object =
+            msg_send![class!(NSRunningApplication), runningApplicationsWithBundleIdentifier: ns_bundle_id];
+        let count: usize = msg_send![apps, count];
+        if count == 0 {
+            return None;
+        }
+        let app: *mut Object = msg_send![apps, objectAtIndex: 0usize];
+        let pid: i32 = msg_send![app, processIdentifier];
+        Some(pid)
+    }
+}
+
+pub(crate) fn frontmost_app_pid() -> Option<i32> {
+    unsafe {
+        let workspace: *mut Object = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let app: *mut Object = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return None;
+        }
+        let pid: i32 = msg_send![app, processIdentifier];
+        Some(pid)
+    }
+}
+
+pub(crate) fn window_title(pid: i32) -> Option<String> {
+    unsafe {
+        if !AXIsProcessTrusted() {
+            return None;
+        }
+        let app = AXUIElementCreateApplication(pid);
+        let mut window: CFTypeRef = std::ptr::null_mut();
+        let window_attr = CFString::new(kAXFocusedWindowAttribute);
+        let result = AXUIElementCopyAttributeValue(app, window_attr.as_concrete_TypeRef(), &mut window);
+        CFRelease(app as CFTypeRef);
+        if result != kAXErrorSuccess || window.is_null() {
+            return None;
+        }
+        let title = copy_string(window as AXUIElementRef, kAXTitleAttribute);
+        CFRelease(window);
+        title.filter(|title| !title.is_empty())
+    }
+}
+
+pub(crate) fn focus_fingerprint(timeout: Option<Duration>) -> Option<FocusFingerprint> {
+    let pid = frontmost_app_pid()?;
+    unsafe {
+        let focused = focused_element(None, timeout).ok()??;
+        let element = CFHash(focused as CFTypeRef) as u64;
+
+        let mut range_ref: CFTypeRef = std::ptr::null_mut();
+        let range_attr = CFString::new(kAXSelectedTextRangeAttribute);
+        let result = AXUIElementCopyAttributeValue(focused, range_attr.as_concrete_TypeRef(), &mut range_ref);
+        let selection = if result == kAXErrorSuccess && !range_ref.is_null() {
+            let mut range = CFRange { location: 0, length: 0 };
+            let ok = AXValueGetValue(
+                range_ref as AXValueRef,
+                kAXValueTypeCFRange,
+                &mut range as *mut _ as *mut c_void,
+            );
+            CFRelease(range_ref);
+            ok.then_some((range.location, range.length))
+        } else {
+            None
+        };
+        CFRelease(focused as CFTypeRef);
+
+        Some(FocusFingerprint { pid, element, selection })
+    }
+}
+
+/// Focused element of the whole system, or of one application when `pid`
+/// is given (which works even while that app is in the background).
+unsafe fn focused_element(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<AXUIElementRef>, DetectorError> {
+    if !AXIsProcessTrusted() {
+        return Err(DetectorError::PermissionDenied);
+    }
+
+    let root = match pid {
+        Some(pid) => AXUIElementCreateApplication(pid),
+        None => AXUIElementCreateSystemWide(),
+    };
+    // On the system-wide element this sets the timeout for every element,
+    // including the focused one we query next
+    if let Some(timeout) = timeout {
+        AXUIElementSetMessagingTimeout(root, timeout.as_secs_f32());
+    }
+    let mut focused: AXUIElementRef = std::ptr::null_mut();
+
+    // Create CFString for the attribute
+    let focused_attr = CFString::new(kAXFocusedUIElementAttribute);
+    let result = AXUIElementCopyAttributeValue(
+        root,
+        focused_attr.as_concrete_TypeRef(),
+        &mut focused as *mut _ as *mut CFTypeRef,
+    );
+
+    if result == kAXErrorAPIDisabled {
+        return Err(DetectorError::PermissionDenied);
+    }
+    if result == kAXErrorCannotComplete {
+        return Err(DetectorError::Timeout);
+    }
+    if result != kAXErrorSuccess || focused.is_null() {
+        return Ok(None);
+    }
+    if let Some(timeout) = timeout {
+        AXUIElementSetMessagingTimeout(focused, timeout.as_secs_f32());
+    }
+
+    Ok(Some(focused))
+}
+
+unsafe fn get_selected_text(element: AXUIElementRef) -> Result<Option<String>, DetectorError> {
+    let mut selected_text_ref: CFTypeRef = std::ptr::null_mut();
+    let selected_attr = CFString::new(kAXSelectedTextAttribute);
+    let result = AXUIElementCopyAttributeValue(
+        element,
+        selected_attr.as_concrete_TypeRef(),
+        &mut selected_text_ref,
+    );
+
+    if result == kAXErrorCannotComplete {
+        return Err(DetectorError::Timeout);
+    }
+    if result == kAXErrorSuccess && !selected_text_ref.is_null() {
+        let cf_string: CFString = TCFType::wrap_under_create_rule(selected_text_ref as CFStringRef);
+        let text = cf_string.to_string();
+        if !text.trim().is_empty() {
+            return Ok(Some(text));
+        }
+    }
+    Ok(None)
+}
+
+unsafe fn get_focused_text(element: AXUIElementRef) -> Result<Option<String>, DetectorError> {
+    // Try different attributes that might contain text
+    let attributes = [
+        kAXValueAttribute,
+        kAXTitleAttribute,
+        kAXDescriptionAttribute,
+        kAXHelpAttribute,
+    ];
+
+    for attr_name in &attributes {
+        let mut text_ref: CFTypeRef = std::ptr::null_mut();
+        let attr = CFString::new(attr_name);
+        let result = AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut text_ref);
+
+        // Once the app has stopped answering, the other attributes would too
+        if result == kAXErrorCannotComplete {
+            return Err(DetectorError::Timeout);
+        }
+        if result == kAXErrorSuccess && !text_ref.is_null() {
+            let cf_string: CFString = TCFType::wrap_under_create_rule(text_ref as CFStringRef);
+            let text = cf_string.to_string();
+            if !text.trim().is_empty() && text.len() > 2 {
+                return Ok(Some(text));
+            }
+        }
+    }
+    Ok(None)
+}
+
+unsafe fn copy_string(element: AXUIElementRef, attribute: &str) -> Option<String> {
+    let mut value: CFTypeRef = std::ptr::null_mut();
+    let attr = CFString::new(attribute);
+    if AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut value) != kAXErrorSuccess || value.is_null() {
+        return None;
+    }
+    let cf_string: CFString = TCFType::wrap_under_create_rule(value as CFStringRef);
+    Some(cf_string.to_string())
+}
+
+/// Reads an `AXValue` attribute (point, size, range) into `out`.
+unsafe fn copy_value(element: AXUIElementRef, attribute: &str, value_type: AXValueType, out: *mut c_void) -> bool {
+    let mut value: CFTypeRef = std::ptr::null_mut();
+    let attr = CFString::new(attribute);
+    if AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut value) != kAXErrorSuccess || value.is_null() {
+        return false;
+    }
+    let ok = AXValueGetValue(value as AXValueRef, value_type, out);
+    CFRelease(value);
+    ok
+}
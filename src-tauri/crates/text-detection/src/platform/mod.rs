@@ -0,0 +1,29 @@
+//! Backend selection. Exactly one of the modules below provides the
+//! `imp` functions for a given target/feature combination.
+
+#[cfg(all(target_os = "macos", feature = "macos"))]
+mod macos;
+#[cfg(all(target_os = "macos", feature = "macos"))]
+use macos as imp;
+
+#[cfg(all(target_os = "windows", feature = "windows"))]
+mod windows;
+#[cfg(all(target_os = "windows", feature = "windows"))]
+use self::windows as imp;
+
+#[cfg(not(any(
+    all(target_os = "macos", feature = "macos"),
+    all(target_os = "windows", feature = "windows"),
+)))]
+mod unsupported;
+#[cfg(not(any(
+    all(target_os = "macos", feature = "macos"),
+    all(target_os = "windows", feature = "windows"),
+)))]
+use unsupported as imp;
+
+pub(crate) use imp::{
+    activate_app, bundle_id_for_pid, check_permissions, element_attributes, focus_fingerprint, focused_roles,
+    focused_text, frontmost_app_pid, pid_for_app, request_permissions, selected_image, selected_range,
+    selected_text, selection_region, text_in_range, visible_text, window_title, SUPPORTED,
+};
@@ -0,0 +1,79 @@
+use crate::{DetectorError, FocusFingerprint, ImageRegion, TextRange};
+use std::time::Duration;
+
+pub(crate) const SUPPORTED: bool = false;
+
+pub(crate) fn check_permissions() -> bool {
+    true // Nothing to grant when there is no backend
+}
+
+pub(crate) fn request_permissions() -> Result<(), DetectorError> {
+    Ok(())
+}
+
+pub(crate) fn selected_text(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn selected_image(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn selection_region(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn selected_range(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<TextRange>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn text_in_range(
+    _pid: Option<i32>,
+    _range: TextRange,
+    _timeout: Option<Duration>,
+) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn activate_app(_pid: i32) -> bool {
+    false
+}
+
+pub(crate) fn focused_text(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn focused_roles(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Vec<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn element_attributes(
+    _pid: Option<i32>,
+    _timeout: Option<Duration>,
+) -> Result<Vec<(String, String)>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn visible_text(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn pid_for_app(_bundle_id: &str) -> Option<i32> {
+    None
+}
+
+pub(crate) fn focus_fingerprint(_timeout: Option<Duration>) -> Option<FocusFingerprint> {
+    None
+}
+
+pub(crate) fn frontmost_app_pid() -> Option<i32> {
+    None
+}
+
+pub(crate) fn bundle_id_for_pid(_pid: i32) -> Option<String> {
+    None
+}
+
+pub(crate) fn window_title(_pid: i32) -> Option<String> {
+    None
+}
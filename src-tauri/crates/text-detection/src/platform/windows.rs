@@ -0,0 +1,327 @@
+use crate::{DetectorError, FocusFingerprint, ImageRegion, TextRange};
+use std::time::Duration;
+use windows::core::Interface;
+use windows::Win32::Foundation::{CloseHandle, BOOL, HANDLE};
+use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+use windows::Win32::System::StationsAndDesktops::{
+    CloseDesktop, OpenInputDesktop, DESKTOP_CONTROL_FLAGS, DESKTOP_READOBJECTS,
+};
+use windows::Win32::System::Threading::{
+    GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId};
+use windows::Win32::UI::Accessibility::{
+    CUIAutomation, IUIAutomation, IUIAutomation2, IUIAutomationElement, IUIAutomationTextPattern,
+    IUIAutomationValuePattern, UIA_ButtonControlTypeId, UIA_CheckBoxControlTypeId, UIA_DocumentControlTypeId,
+    UIA_EditControlTypeId, UIA_HyperlinkControlTypeId, UIA_MenuBarControlTypeId, UIA_MenuControlTypeId,
+    UIA_MenuItemControlTypeId, UIA_StatusBarControlTypeId, UIA_TabItemControlTypeId, UIA_TextControlTypeId,
+    UIA_TextPatternId, UIA_TitleBarControlTypeId, UIA_ToolBarControlTypeId, UIA_ToolTipControlTypeId,
+    UIA_ValuePatternId, UIA_CONTROLTYPE_ID,
+};
+
+pub(crate) const SUPPORTED: bool = true;
+
+/// How many levels [`focused_roles`] walks up from the focused element.
+const MAX_ROLE_DEPTH: usize = 8;
+
+/// Names for the control types worth filtering on; others are reported by
+/// number.
+const CONTROL_TYPE_NAMES: &[(UIA_CONTROLTYPE_ID, &str)] = &[
+    (UIA_ButtonControlTypeId, "Button"),
+    (UIA_CheckBoxControlTypeId, "CheckBox"),
+    (UIA_DocumentControlTypeId, "Document"),
+    (UIA_EditControlTypeId, "Edit"),
+    (UIA_HyperlinkControlTypeId, "Hyperlink"),
+    (UIA_MenuControlTypeId, "Menu"),
+    (UIA_MenuBarControlTypeId, "MenuBar"),
+    (UIA_MenuItemControlTypeId, "MenuItem"),
+    (UIA_StatusBarControlTypeId, "StatusBar"),
+    (UIA_TabItemControlTypeId, "TabItem"),
+    (UIA_TextControlTypeId, "Text"),
+    (UIA_TitleBarControlTypeId, "TitleBar"),
+    (UIA_ToolBarControlTypeId, "ToolBar"),
+    (UIA_ToolTipControlTypeId, "ToolTip"),
+];
+
+/// HRESULT UI Automation returns when the provider didn't answer in time.
+const UIA_E_TIMEOUT: i32 = 0x80131505_u32 as i32;
+
+pub(crate) fn check_permissions() -> bool {
+    true // UI Automation needs no user-granted permission
+}
+
+pub(crate) fn request_permissions() -> Result<(), DetectorError> {
+    Ok(())
+}
+
+pub(crate) fn selected_text(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    if pid.is_some() {
+        return Err(DetectorError::Unsupported);
+    }
+    unsafe {
+        let Some(element) = focused_element(timeout)? else {
+            return Ok(None);
+        };
+
+        // Elements without a text pattern simply have no selection to offer
+        let Ok(pattern) = element.GetCurrentPatternAs::<IUIAutomationTextPattern>(UIA_TextPatternId) else {
+            return Ok(None);
+        };
+        let ranges = pattern.GetSelection().map_err(platform_error)?;
+        if ranges.Length().map_err(platform_error)? == 0 {
+            return Ok(None);
+        }
+
+        let text = ranges
+            .GetElement(0)
+            .and_then(|range| range.GetText(-1))
+            .map_err(platform_error)?
+            .to_string();
+        Ok(Some(text).filter(|t| !t.trim().is_empty()))
+    }
+}
+
+/// Image elements expose no pixels through UI Automation.
+pub(crate) fn selected_image(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    Ok(None)
+}
+
+/// Not read yet: nothing grabs screen regions on Windows.
+pub(crate) fn selection_region(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<ImageRegion>, DetectorError> {
+    Ok(None)
+}
+
+/// Text ranges aren't remembered on Windows: UI Automation ranges don't
+/// outlive the provider that handed them out.
+pub(crate) fn selected_range(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<TextRange>, DetectorError> {
+    Ok(None)
+}
+
+pub(crate) fn text_in_range(
+    _pid: Option<i32>,
+    _range: TextRange,
+    _timeout: Option<Duration>,
+) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn activate_app(_pid: i32) -> bool {
+    false
+}
+
+pub(crate) fn focused_text(pid: Option<i32>, timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    if pid.is_some() {
+        return Err(DetectorError::Unsupported);
+    }
+    unsafe {
+        let Some(element) = focused_element(timeout)? else {
+            return Ok(None);
+        };
+
+        if let Ok(pattern) = element.GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId) {
+            if let Some(text) = pattern.CurrentValue().ok().and_then(|v| non_empty(v.to_string())) {
+                return Ok(Some(text));
+            }
+        }
+
+        Ok(element.CurrentName().ok().and_then(|v| non_empty(v.to_string())))
+    }
+}
+
+pub(crate) fn focused_roles(pid: Option<i32>, timeout: Option<Duration>) -> Result<Vec<String>, DetectorError> {
+    if pid.is_some() {
+        return Err(DetectorError::Unsupported);
+    }
+    unsafe {
+        let Some(element) = focused_element(timeout)? else {
+            return Ok(Vec::new());
+        };
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER).map_err(platform_error)?;
+        let walker = automation.ControlViewWalker().map_err(platform_error)?;
+
+        let mut roles = Vec::new();
+        let mut current = Some(element);
+        while let Some(element) = current.take() {
+            let control_type = element.CurrentControlType().map_err(platform_error)?;
+            let name = CONTROL_TYPE_NAMES
+                .iter()
+                .find(|(id, _)| *id == control_type)
+                .map_or_else(|| control_type.0.to_string(), |(_, name)| name.to_string());
+            roles.push(name);
+            if roles.len() < MAX_ROLE_DEPTH {
+                current = walker.GetParentElement(&element).ok();
+            }
+        }
+        Ok(roles)
+    }
+}
+
+pub(crate) fn element_attributes(
+    pid: Option<i32>,
+    timeout: Option<Duration>,
+) -> Result<Vec<(String, String)>, DetectorError> {
+    if pid.is_some() {
+        return Err(DetectorError::Unsupported);
+    }
+    unsafe {
+        let Some(element) = focused_element(timeout)? else {
+            return Ok(Vec::new());
+        };
+        let attributes = [
+            ("AutomationId", element.CurrentAutomationId()),
+            ("ClassName", element.CurrentClassName()),
+            ("FrameworkId", element.CurrentFrameworkId()),
+            ("LocalizedControlType", element.CurrentLocalizedControlType()),
+            ("Name", element.CurrentName()),
+        ];
+        Ok(attributes
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.ok()?.to_string())))
+            .filter(|(_, value)| !value.is_empty())
+            .collect())
+    }
+}
+
+pub(crate) fn visible_text(_pid: Option<i32>, _timeout: Option<Duration>) -> Result<Option<String>, DetectorError> {
+    Err(DetectorError::Unsupported)
+}
+
+pub(crate) fn pid_for_app(_bundle_id: &str) -> Option<i32> {
+    None
+}
+
+pub(crate) fn bundle_id_for_pid(_pid: i32) -> Option<String> {
+    None
+}
+
+/// UI Automation has no cheap way to read the selection range, so Windows
+/// always does the full fetch.
+pub(crate) fn focus_fingerprint(_timeout: Option<Duration>) -> Option<FocusFingerprint> {
+    None
+}
+
+pub(crate) fn frontmost_app_pid() -> Option<i32> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        (pid != 0).then_some(pid as i32)
+    }
+}
+
+/// Title of the foreground window, when it belongs to `pid`.
+pub(crate) fn window_title(pid: i32) -> Option<String> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut owner = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut owner));
+        if owner as i32 != pid {
+            return None;
+        }
+        let mut buffer = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut buffer).max(0) as usize;
+        let title = String::from_utf16_lossy(&buffer[..len]);
+        (!title.trim().is_empty()).then_some(title)
+    }
+}
+
+/// Whether `process` runs with an elevated token, if it can be queried.
+unsafe fn is_elevated(process: HANDLE) -> Option<bool> {
+    let mut token = HANDLE::default();
+    OpenProcessToken(process, TOKEN_QUERY, &mut token).ok()?;
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut size = 0u32;
+    let result = GetTokenInformation(
+        token,
+        TokenElevation,
+        Some(&mut elevation as *mut _ as *mut _),
+        std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+        &mut size,
+    );
+    let _ = CloseHandle(token);
+    result.ok()?;
+    Some(elevation.TokenIsElevated != 0)
+}
+
+/// UIPI keeps an unelevated process from reading the UI of an elevated
+/// one; UI Automation then just comes back empty, which looks like there is
+/// no selection.
+unsafe fn blocked_by_elevation() -> bool {
+    let Some(pid) = frontmost_app_pid() else {
+        return false;
+    };
+    if is_elevated(GetCurrentProcess()) != Some(false) {
+        return false;
+    }
+    match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, BOOL::from(false), pid as u32) {
+        Ok(process) => {
+            let elevated = is_elevated(process);
+            let _ = CloseHandle(process);
+            elevated == Some(true)
+        }
+        // Protected processes can't be queried at all; leave those to UIA
+        Err(_) => false,
+    }
+}
+
+/// The secure desktop (UAC prompts, the lock and login screens) can't be
+/// opened by user processes. UI Automation queries made while it is up
+/// fail or stall until it goes away.
+unsafe fn secure_desktop_active() -> bool {
+    match OpenInputDesktop(DESKTOP_CONTROL_FLAGS(0), BOOL::from(false), DESKTOP_READOBJECTS) {
+        Ok(desktop) => {
+            let _ = CloseDesktop(desktop);
+            false
+        }
+        Err(_) => true,
+    }
+}
+
+unsafe fn focused_element(timeout: Option<Duration>) -> Result<Option<IUIAutomationElement>, DetectorError> {
+    // Nothing the user could have selected is reachable; don't even ask
+    if secure_desktop_active() {
+        return Ok(None);
+    }
+    if blocked_by_elevation() {
+        return Err(DetectorError::ElevationRequired);
+    }
+
+    // Safe to call repeatedly; later calls on an initialised thread are no-ops
+    let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+    let automation: IUIAutomation =
+        CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER).map_err(platform_error)?;
+    if let (Some(timeout), Ok(automation)) = (timeout, automation.cast::<IUIAutomation2>()) {
+        let millis = timeout.as_millis().min(u32::MAX as u128) as u32;
+        let _ = automation.SetConnectionTimeout(millis);
+        let _ = automation.SetTransactionTimeout(millis);
+    }
+    match automation.GetFocusedElement() {
+        Ok(element) => Ok(Some(element)),
+        Err(e) if e.code().0 == UIA_E_TIMEOUT => Err(DetectorError::Timeout),
+        Err(_) => Ok(None),
+    }
+}
+
+fn non_empty(text: String) -> Option<String> {
+    if !text.trim().is_empty() && text.len() > 2 {
+        Some(text)
+    } else {
+        None
+    }
+}
+
+fn platform_error(e: windows::core::Error) -> DetectorError {
+    if e.code().0 == UIA_E_TIMEOUT {
+        return DetectorError::Timeout;
+    }
+    DetectorError::Platform(e.message().to_string())
+}
@@ -0,0 +1,13 @@
+-- Which app and window had focus, and when, recorded only while the focus
+-- timeline is turned on. No text is stored; captures are matched to spans
+-- by time. `ended_at` is when the span was last seen, refreshed while it
+-- goes on, so a crash loses at most half a minute of it.
+CREATE TABLE focus_spans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    app TEXT NOT NULL,
+    window_title TEXT,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_focus_spans_started_at ON focus_spans (started_at);
@@ -0,0 +1,15 @@
+-- Pages queued from browser captures while reading list mode is on, one row
+-- per URL. `history_id` is the latest capture from the page and, like in
+-- the action log, not a foreign key: the item outlives deleted entries.
+CREATE TABLE reading_list (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    excerpt TEXT NOT NULL,
+    history_id INTEGER,
+    added_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    read_at INTEGER
+);
+
+CREATE INDEX idx_reading_list_updated_at ON reading_list (updated_at);
@@ -0,0 +1,6 @@
+-- Simhash of each entry's words, for finding near-duplicates of a new
+-- capture. Existing rows, and rows restored from the trash, are hashed in
+-- the background after the database is opened.
+ALTER TABLE history ADD COLUMN simhash INTEGER;
+
+CREATE INDEX idx_history_simhash_missing ON history (id) WHERE simhash IS NULL;
@@ -0,0 +1,41 @@
+-- Chinese and Japanese have no spaces between words, so the tokenizer saw
+-- a whole sentence as one token and only the start of it could be found.
+-- The index now reads text through segment_words(), which the app
+-- registers on its connection and which spaces out every word boundary.
+-- A view applies it, so rebuilds and integrity checks see the same text
+-- the triggers index.
+DROP TRIGGER history_fts_insert;
+DROP TRIGGER history_fts_delete;
+DROP TRIGGER history_fts_update;
+DROP TABLE history_fts;
+
+CREATE VIEW history_fts_content AS
+    SELECT id, segment_words(text) AS text, segment_words(title) AS title, segment_words(note) AS note FROM history;
+
+CREATE VIRTUAL TABLE history_fts USING fts5(
+    text,
+    title,
+    note,
+    content = 'history_fts_content',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO history_fts (history_fts) VALUES ('rebuild');
+
+CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, text, title, note)
+        VALUES (new.id, segment_words(new.text), segment_words(new.title), segment_words(new.note));
+END;
+
+CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title, note)
+        VALUES ('delete', old.id, segment_words(old.text), segment_words(old.title), segment_words(old.note));
+END;
+
+CREATE TRIGGER history_fts_update AFTER UPDATE OF text, title, note ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title, note)
+        VALUES ('delete', old.id, segment_words(old.text), segment_words(old.title), segment_words(old.note));
+    INSERT INTO history_fts (rowid, text, title, note)
+        VALUES (new.id, segment_words(new.text), segment_words(new.title), segment_words(new.note));
+END;
@@ -0,0 +1,10 @@
+-- Small screenshots of where a capture was selected, one per entry. Like
+-- the action log, `history_id` is not a foreign key: a thumbnail stays
+-- while its entry is in the trash, and is pruned once the entry is gone.
+CREATE TABLE thumbnails (
+    history_id INTEGER PRIMARY KEY,
+    png BLOB NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_thumbnails_created_at ON thumbnails (created_at);
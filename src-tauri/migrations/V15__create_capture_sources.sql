@@ -0,0 +1,16 @@
+-- Where a capture was read from, recorded only while `record_sources` is
+-- on, so it can be read again later: the app and window, or the page, and
+-- the span of the element's text. Like thumbnails, `history_id` is not a
+-- foreign key so the source survives a trip through the trash.
+CREATE TABLE capture_sources (
+    history_id INTEGER PRIMARY KEY,
+    app TEXT,
+    window_title TEXT,
+    url TEXT,
+    range_location INTEGER,
+    range_length INTEGER,
+    -- Set by the last recapture
+    checked_at INTEGER,
+    changed INTEGER NOT NULL DEFAULT 0,
+    current_text TEXT
+);
@@ -0,0 +1,3 @@
+-- Short, scannable title for each capture. NULL for rows stored before
+-- titles existed; those fall back to a title derived from the text on read.
+ALTER TABLE history ADD COLUMN title TEXT;
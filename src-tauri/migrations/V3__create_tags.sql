@@ -0,0 +1,7 @@
+CREATE TABLE tags (
+    history_id INTEGER NOT NULL REFERENCES history(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (history_id, tag)
+);
+
+CREATE INDEX idx_tags_tag ON tags(tag);
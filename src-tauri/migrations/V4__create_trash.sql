@@ -0,0 +1,15 @@
+-- Deleted history entries, kept until purged so deletes can be undone.
+-- `id` is the original history id; history ids are AUTOINCREMENT so a
+-- restored entry can always take its old id back.
+CREATE TABLE trash (
+    id INTEGER PRIMARY KEY,
+    text TEXT NOT NULL,
+    app_name TEXT NOT NULL,
+    selection_type TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    title TEXT,
+    tags TEXT,
+    deleted_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_trash_deleted_at ON trash (deleted_at);
@@ -0,0 +1,25 @@
+-- Full-text index over capture text and titles for the search palette.
+-- External content: the text lives only in `history`, triggers keep the
+-- index in step.
+CREATE VIRTUAL TABLE history_fts USING fts5(
+    text,
+    title,
+    content = 'history',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO history_fts (rowid, text, title) SELECT id, text, title FROM history;
+
+CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, text, title) VALUES (new.id, new.text, new.title);
+END;
+
+CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title) VALUES ('delete', old.id, old.text, old.title);
+END;
+
+CREATE TRIGGER history_fts_update AFTER UPDATE OF text, title ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title) VALUES ('delete', old.id, old.text, old.title);
+    INSERT INTO history_fts (rowid, text, title) VALUES (new.id, new.text, new.title);
+END;
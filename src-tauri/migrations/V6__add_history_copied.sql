@@ -0,0 +1,5 @@
+-- Whether the text of a capture was also copied to the clipboard, so
+-- selections that were never copied can be found later. Rows stored before
+-- copies were tracked count as not copied.
+ALTER TABLE history ADD COLUMN copied INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE trash ADD COLUMN copied INTEGER NOT NULL DEFAULT 0;
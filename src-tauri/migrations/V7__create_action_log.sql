@@ -0,0 +1,15 @@
+-- Every action an automation (rule or script) carried out, for auditing.
+-- `history_id` is the capture that triggered it and deliberately not a
+-- foreign key: the log outlives deleted entries.
+CREATE TABLE action_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    trigger TEXT NOT NULL,
+    history_id INTEGER,
+    succeeded INTEGER NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX idx_action_log_timestamp ON action_log (timestamp);
@@ -0,0 +1,33 @@
+-- Free-form user notes on captures, searchable along with the text. FTS5
+-- tables can't gain columns, so the index is rebuilt with one for notes.
+ALTER TABLE history ADD COLUMN note TEXT;
+ALTER TABLE trash ADD COLUMN note TEXT;
+
+DROP TRIGGER history_fts_insert;
+DROP TRIGGER history_fts_delete;
+DROP TRIGGER history_fts_update;
+DROP TABLE history_fts;
+
+CREATE VIRTUAL TABLE history_fts USING fts5(
+    text,
+    title,
+    note,
+    content = 'history',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO history_fts (rowid, text, title, note) SELECT id, text, title, note FROM history;
+
+CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, text, title, note) VALUES (new.id, new.text, new.title, new.note);
+END;
+
+CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title, note) VALUES ('delete', old.id, old.text, old.title, old.note);
+END;
+
+CREATE TRIGGER history_fts_update AFTER UPDATE OF text, title, note ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, text, title, note) VALUES ('delete', old.id, old.text, old.title, old.note);
+    INSERT INTO history_fts (rowid, text, title, note) VALUES (new.id, new.text, new.title, new.note);
+END;
@@ -0,0 +1,5 @@
+-- HMAC over a capture's text, timestamp and source app, set when signing is
+-- on, so an exported trail can later be shown to be unmodified. Rows stored
+-- before, or with signing off, stay unsigned.
+ALTER TABLE history ADD COLUMN signature TEXT;
+ALTER TABLE trash ADD COLUMN signature TEXT;
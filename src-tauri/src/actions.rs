@@ -0,0 +1,56 @@
+//! Things a capture can be sent to outside the app, used both as rule
+//! actions and on demand from the history view.
+
+use tauri::{AppHandle, Manager};
+
+use crate::capture::Capture;
+use crate::history::{HistoryEntry, HistoryStore};
+
+pub mod calendar;
+pub mod chat;
+pub mod email;
+pub mod push;
+pub mod tasks;
+
+/// The parts of a capture an action passes on.
+#[derive(Debug, Clone)]
+pub struct ActionInput {
+    pub text: String,
+    /// Where the text came from: the app, plus the page for browser captures.
+    pub source: String,
+}
+
+impl ActionInput {
+    pub fn from_capture(capture: &Capture) -> Self {
+        let source = match &capture.page {
+            Some(page) => format!("{} ({})", capture.event.app_name, page.url),
+            None => capture.event.app_name.clone(),
+        };
+        Self {
+            text: capture.event.text.clone(),
+            source,
+        }
+    }
+
+    pub fn from_entry(entry: &HistoryEntry) -> Self {
+        Self {
+            text: entry.text.clone(),
+            source: entry.app_name.clone(),
+        }
+    }
+
+    /// The full text followed by an attribution line.
+    pub fn body(&self) -> String {
+        format!("{}\n\n— from {}", self.text.trim(), self.source)
+    }
+}
+
+/// Looks up history entry `id` for an action.
+pub(crate) fn input_for_entry(app: &AppHandle, id: i64) -> Result<ActionInput, String> {
+    let history = app.try_state::<HistoryStore>().ok_or("History is not available")?;
+    let entry = history
+        .get(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No history entry {}", id))?;
+    Ok(ActionInput::from_entry(&entry))
+}
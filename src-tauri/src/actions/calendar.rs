@@ -0,0 +1,30 @@
+//! Turns a capture that mentions a date ("meet Tue 3pm") into a calendar
+//! event, with the capture and its source as the event's notes.
+
+use tauri::AppHandle;
+
+use super::{input_for_entry, ActionInput};
+use crate::dates::{self, DateMention};
+use crate::text_utils;
+
+const SUMMARY_CHARS: usize = 80;
+
+/// Opens a new event for `input` at `mention` in the default calendar app.
+pub fn create(
+    app: &AppHandle,
+    mention: &DateMention,
+    duration_minutes: Option<u32>,
+    input: &ActionInput,
+) -> Result<(), String> {
+    let summary = text_utils::preview(&input.text, SUMMARY_CHARS);
+    dates::open_event(app, mention, duration_minutes, &summary, &input.body()).map(|_| ())
+}
+
+/// Creates a calendar event from history entry `id`, at the date or time
+/// its text mentions.
+#[tauri::command]
+pub async fn create_calendar_event(app: AppHandle, id: i64, duration_minutes: Option<u32>) -> Result<(), String> {
+    let input = input_for_entry(&app, id)?;
+    let mention = dates::detect(&input.text).ok_or_else(|| format!("No date or time found in entry {}", id))?;
+    create(&app, &mention, duration_minutes, &input)
+}
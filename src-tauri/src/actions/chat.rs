@@ -0,0 +1,193 @@
+//! Sends a capture to a Slack channel or a Discord webhook, so a quote can
+//! be shared with the team straight from a hotkey instead of through a
+//! hand-built webhook rule.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use super::{input_for_entry, ActionInput};
+use crate::{secrets, settings};
+
+const SLACK_POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+const SLACK_CONVERSATIONS_URL: &str = "https://slack.com/api/conversations.list";
+/// Discord rejects longer messages.
+const DISCORD_MAX_CHARS: usize = 2000;
+/// How long the channel list is reused before asking Slack again.
+const CHANNELS_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatTarget {
+    Slack,
+    Discord,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    /// Used when a rule, command or the share shortcut doesn't name one.
+    pub default_target: Option<ChatTarget>,
+    /// A bot token with `chat:write`, and `channels:read` for the channel
+    /// picker.
+    pub slack_token: Option<String>,
+    /// Channel id or name posted to when none is given.
+    pub slack_channel: Option<String>,
+    /// The channel's incoming webhook URL; it decides where messages go.
+    pub discord_webhook_url: Option<String>,
+    /// Captures the selection and sends it to the default target.
+    pub share_shortcut: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackChannel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+#[derive(Deserialize)]
+struct SlackResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    channels: Vec<SlackChannel>,
+    #[serde(default)]
+    response_metadata: Option<SlackMetadata>,
+}
+
+#[derive(Deserialize)]
+struct SlackMetadata {
+    #[serde(default)]
+    next_cursor: String,
+}
+
+/// The last channel list, when it was fetched and for which token.
+static CHANNELS: Mutex<Option<(Instant, String, Vec<SlackChannel>)>> = Mutex::new(None);
+
+/// Sends `input` to `target`, or the default one. `channel` only matters
+/// for Slack, where it overrides the configured channel.
+pub async fn send(
+    settings: &ChatSettings,
+    target: Option<ChatTarget>,
+    channel: Option<&str>,
+    input: &ActionInput,
+) -> Result<(), String> {
+    let target = target
+        .or(settings.default_target)
+        .ok_or("Choose Slack or Discord in settings first")?;
+    match target {
+        ChatTarget::Slack => slack(settings, channel, input).await,
+        ChatTarget::Discord => discord(settings, input).await,
+    }
+}
+
+fn slack_token(settings: &ChatSettings) -> Result<String, String> {
+    let token = settings.slack_token.as_deref().ok_or("Set a Slack bot token in settings first")?;
+    secrets::resolve(token)
+}
+
+/// Slack answers 200 even on failure, with `ok: false` and a reason.
+async fn slack_call(request: reqwest::RequestBuilder) -> Result<SlackResponse, String> {
+    let response = request.send().await.map_err(|e| format!("Slack request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Slack returned {}", response.status()));
+    }
+    let body: SlackResponse = response.json().await.map_err(|e| e.to_string())?;
+    if !body.ok {
+        return Err(format!("Slack refused: {}", body.error.as_deref().unwrap_or("unknown error")));
+    }
+    Ok(body)
+}
+
+async fn slack(settings: &ChatSettings, channel: Option<&str>, input: &ActionInput) -> Result<(), String> {
+    let channel = channel
+        .filter(|channel| !channel.trim().is_empty())
+        .or(settings.slack_channel.as_deref())
+        .ok_or("Pick a Slack channel first")?;
+    let request = reqwest::Client::new()
+        .post(SLACK_POST_MESSAGE_URL)
+        .bearer_auth(slack_token(settings)?)
+        .json(&serde_json::json!({
+            "channel": channel.trim_start_matches('#'),
+            "text": input.body(),
+        }));
+    slack_call(request).await.map(|_| ())
+}
+
+async fn discord(settings: &ChatSettings, input: &ActionInput) -> Result<(), String> {
+    let url = settings
+        .discord_webhook_url
+        .as_deref()
+        .ok_or("Set a Discord webhook URL in settings first")?;
+    let response = reqwest::Client::new()
+        .post(secrets::resolve(url)?)
+        .json(&serde_json::json!({
+            "content": input.body().chars().take(DISCORD_MAX_CHARS).collect::<String>(),
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Discord request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Discord returned {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Every channel the bot can see, following Slack's pagination.
+async fn fetch_channels(token: &str) -> Result<Vec<SlackChannel>, String> {
+    let mut channels = Vec::new();
+    let mut cursor = String::new();
+    loop {
+        let request = reqwest::Client::new()
+            .get(SLACK_CONVERSATIONS_URL)
+            .bearer_auth(token)
+            .query(&[
+                ("types", "public_channel,private_channel"),
+                ("exclude_archived", "true"),
+                ("limit", "200"),
+                ("cursor", cursor.as_str()),
+            ]);
+        let body = slack_call(request).await?;
+        channels.extend(body.channels);
+        cursor = body.response_metadata.map(|metadata| metadata.next_cursor).unwrap_or_default();
+        if cursor.is_empty() {
+            break;
+        }
+    }
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(channels)
+}
+
+/// Slack channels for the channel picker. The list is kept for a few
+/// minutes unless `refresh` is set.
+#[tauri::command]
+pub async fn list_slack_channels(app: AppHandle, refresh: Option<bool>) -> Result<Vec<SlackChannel>, String> {
+    let token = slack_token(&settings::current(&app).chat)?;
+    if !refresh.unwrap_or(false) {
+        let cached = CHANNELS.lock().unwrap();
+        if let Some((at, cached_token, channels)) = cached.as_ref() {
+            if *cached_token == token && at.elapsed() < CHANNELS_TTL {
+                return Ok(channels.clone());
+            }
+        }
+    }
+    let channels = fetch_channels(&token).await?;
+    *CHANNELS.lock().unwrap() = Some((Instant::now(), token, channels.clone()));
+    Ok(channels)
+}
+
+/// Sends history entry `id` to Slack or Discord.
+#[tauri::command]
+pub async fn send_to_chat(
+    app: AppHandle,
+    id: i64,
+    target: Option<ChatTarget>,
+    channel: Option<String>,
+) -> Result<(), String> {
+    let input = input_for_entry(&app, id)?;
+    send(&settings::current(&app).chat, target, channel.as_deref(), &input).await
+}
@@ -0,0 +1,78 @@
+//! "Send as email": opens a new message with the capture and its source
+//! filled in, through a mailto: link or, if preferred, Mail.app directly.
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{input_for_entry, ActionInput};
+use crate::{settings, text_utils};
+
+const SUBJECT_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailSettings {
+    /// Address filled in when a rule or command doesn't give one.
+    pub default_to: Option<String>,
+    /// Script Mail.app instead of opening a mailto: link. mailto: bodies get
+    /// truncated by some clients, Mail.app ones don't.
+    pub use_mail_app: bool,
+}
+
+fn subject(input: &ActionInput) -> String {
+    text_utils::preview(&input.text, SUBJECT_CHARS)
+}
+
+/// Opens a draft of `input` addressed to `to` (which may be empty).
+pub fn compose(settings: &EmailSettings, to: Option<&str>, input: &ActionInput) -> Result<(), String> {
+    let to = to.or(settings.default_to.as_deref()).unwrap_or_default();
+    if settings.use_mail_app && cfg!(target_os = "macos") {
+        mail_app(to, input)
+    } else {
+        mailto(to, input)
+    }
+}
+
+fn mailto(to: &str, input: &ActionInput) -> Result<(), String> {
+    let url = format!(
+        "mailto:{}?subject={}&body={}",
+        utf8_percent_encode(to, NON_ALPHANUMERIC),
+        utf8_percent_encode(&subject(input), NON_ALPHANUMERIC),
+        utf8_percent_encode(&input.body(), NON_ALPHANUMERIC)
+    );
+    tauri_plugin_opener::open_url(url, None::<&str>).map_err(|e| e.to_string())
+}
+
+fn mail_app(to: &str, input: &ActionInput) -> Result<(), String> {
+    use std::process::Command;
+
+    // Passed as arguments so the text never has to be escaped into the script
+    let script = r#"on run {recipient, messageSubject, messageBody}
+    tell application "Mail"
+        set newMessage to make new outgoing message with properties {subject:messageSubject, content:messageBody, visible:true}
+        if recipient is not "" then
+            tell newMessage to make new to recipient at end of to recipients with properties {address:recipient}
+        end if
+        activate
+    end tell
+end run"#;
+    let output = Command::new("osascript")
+        .args(["-e", script, to, &subject(input), &input.body()])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Mail couldn't create the message: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Opens a draft email of history entry `id`.
+#[tauri::command]
+pub async fn send_as_email(app: AppHandle, id: i64, to: Option<String>) -> Result<(), String> {
+    let input = input_for_entry(&app, id)?;
+    compose(&settings::current(&app).email, to.as_deref(), &input)
+}
@@ -0,0 +1,154 @@
+//! Pushes a capture to the user's phone through ntfy, Pushover or
+//! Pushbullet, so an address or a code is there without retyping it.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{input_for_entry, ActionInput};
+use crate::{secrets, settings, text_utils};
+
+const TITLE_CHARS: usize = 60;
+const PUSHOVER_MESSAGES_URL: &str = "https://api.pushover.net/1/messages.json";
+/// Pushover rejects longer messages.
+const PUSHOVER_MAX_CHARS: usize = 1024;
+const PUSHBULLET_PUSHES_URL: &str = "https://api.pushbullet.com/v2/pushes";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushProvider {
+    Ntfy,
+    Pushover,
+    Pushbullet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushSettings {
+    /// Used when a rule or command doesn't name a provider.
+    pub default_provider: Option<PushProvider>,
+    pub ntfy_server: String,
+    /// Topic pushed to when a rule doesn't give one.
+    pub ntfy_topic: Option<String>,
+    /// Access token, for protected topics.
+    pub ntfy_token: Option<String>,
+    /// The application's API token.
+    pub pushover_token: Option<String>,
+    pub pushover_user: Option<String>,
+    pub pushbullet_token: Option<String>,
+}
+
+impl Default for PushSettings {
+    fn default() -> Self {
+        Self {
+            default_provider: None,
+            ntfy_server: "https://ntfy.sh".to_string(),
+            ntfy_topic: None,
+            ntfy_token: None,
+            pushover_token: None,
+            pushover_user: None,
+            pushbullet_token: None,
+        }
+    }
+}
+
+fn title(input: &ActionInput) -> String {
+    text_utils::preview(&input.text, TITLE_CHARS)
+}
+
+/// Pushes `input` through `provider`, or the default one. `to` picks where
+/// it goes: a topic for ntfy, a device name for Pushover, a device iden or
+/// email address for Pushbullet; everything the account reaches if unset.
+pub async fn push(
+    settings: &PushSettings,
+    provider: Option<PushProvider>,
+    to: Option<&str>,
+    input: &ActionInput,
+) -> Result<(), String> {
+    let provider = provider
+        .or(settings.default_provider)
+        .ok_or("Choose a push provider in settings first")?;
+    let to = to.filter(|to| !to.trim().is_empty());
+    let request = match provider {
+        PushProvider::Ntfy => ntfy(settings, to, input)?,
+        PushProvider::Pushover => pushover(settings, to, input)?,
+        PushProvider::Pushbullet => pushbullet(settings, to, input)?,
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("{:?} request failed: {}", provider, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{:?} returned {}", provider, response.status()));
+    }
+    Ok(())
+}
+
+fn ntfy(settings: &PushSettings, to: Option<&str>, input: &ActionInput) -> Result<reqwest::RequestBuilder, String> {
+    let topic = to
+        .or(settings.ntfy_topic.as_deref())
+        .ok_or("Set an ntfy topic in settings first")?;
+    let mut request = reqwest::Client::new()
+        .post(settings.ntfy_server.trim_end_matches('/'))
+        .json(&serde_json::json!({
+            "topic": topic,
+            "title": title(input),
+            "message": input.body(),
+        }));
+    if let Some(token) = &settings.ntfy_token {
+        request = request.bearer_auth(secrets::resolve(token)?);
+    }
+    Ok(request)
+}
+
+fn pushover(settings: &PushSettings, to: Option<&str>, input: &ActionInput) -> Result<reqwest::RequestBuilder, String> {
+    let (Some(token), Some(user)) = (&settings.pushover_token, &settings.pushover_user) else {
+        return Err("Set a Pushover API token and user key in settings first".to_string());
+    };
+    let mut message = serde_json::json!({
+        "token": secrets::resolve(token)?,
+        "user": secrets::resolve(user)?,
+        "title": title(input),
+        "message": input.body().chars().take(PUSHOVER_MAX_CHARS).collect::<String>(),
+    });
+    if let Some(device) = to {
+        message["device"] = device.into();
+    }
+    Ok(reqwest::Client::new().post(PUSHOVER_MESSAGES_URL).json(&message))
+}
+
+fn pushbullet(
+    settings: &PushSettings,
+    to: Option<&str>,
+    input: &ActionInput,
+) -> Result<reqwest::RequestBuilder, String> {
+    let token = settings
+        .pushbullet_token
+        .as_deref()
+        .ok_or("Set a Pushbullet access token in settings first")?;
+    let mut push = serde_json::json!({
+        "type": "note",
+        "title": title(input),
+        "body": input.body(),
+    });
+    match to {
+        Some(email) if email.contains('@') => push["email"] = email.into(),
+        Some(device) => push["device_iden"] = device.into(),
+        None => {}
+    }
+    Ok(reqwest::Client::new()
+        .post(PUSHBULLET_PUSHES_URL)
+        .header("Access-Token", secrets::resolve(token)?)
+        .json(&push))
+}
+
+/// Pushes history entry `id` to the phone.
+#[tauri::command]
+pub async fn push_to_phone(
+    app: AppHandle,
+    id: i64,
+    provider: Option<PushProvider>,
+    to: Option<String>,
+) -> Result<(), String> {
+    let input = input_for_entry(&app, id)?;
+    push(&settings::current(&app).push, provider, to.as_deref(), &input).await
+}
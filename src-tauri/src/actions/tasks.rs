@@ -0,0 +1,130 @@
+//! Turns a capture into a task in Todoist, Things or Reminders. The title
+//! is the start of the text; the note has the whole text and its source.
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::{input_for_entry, ActionInput};
+use crate::{secrets, settings, text_utils};
+
+const TITLE_CHARS: usize = 80;
+const TODOIST_TASKS_URL: &str = "https://api.todoist.com/rest/v2/tasks";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskTarget {
+    Todoist,
+    Things,
+    Reminders,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskSettings {
+    /// Used when a command doesn't name a target.
+    pub default_target: Option<TaskTarget>,
+    /// Todoist API token, from Settings → Integrations.
+    pub todoist_token: Option<String>,
+    /// Todoist project for new tasks; the inbox if unset.
+    pub todoist_project_id: Option<String>,
+    /// Reminders list for new tasks; the default list if unset.
+    pub reminders_list: Option<String>,
+}
+
+fn title(input: &ActionInput) -> String {
+    text_utils::preview(&input.text, TITLE_CHARS)
+}
+
+/// Creates a task for `input` in `target`.
+pub async fn create(settings: &TaskSettings, target: TaskTarget, input: &ActionInput) -> Result<(), String> {
+    match target {
+        TaskTarget::Todoist => todoist(settings, input).await,
+        TaskTarget::Things => things(input),
+        TaskTarget::Reminders => reminders(settings, input),
+    }
+}
+
+async fn todoist(settings: &TaskSettings, input: &ActionInput) -> Result<(), String> {
+    let token = settings
+        .todoist_token
+        .as_deref()
+        .ok_or("Set a Todoist API token in settings first")?;
+    let token = secrets::resolve(token)?;
+    let mut task = serde_json::json!({
+        "content": title(input),
+        "description": input.body(),
+    });
+    if let Some(project_id) = &settings.todoist_project_id {
+        task["project_id"] = project_id.clone().into();
+    }
+
+    let response = reqwest::Client::new()
+        .post(TODOIST_TASKS_URL)
+        .bearer_auth(token)
+        .json(&task)
+        .send()
+        .await
+        .map_err(|e| format!("Todoist request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Todoist returned {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Things' `add` URL command; needs no token.
+fn things(input: &ActionInput) -> Result<(), String> {
+    let url = format!(
+        "things:///add?title={}&notes={}",
+        utf8_percent_encode(&title(input), NON_ALPHANUMERIC),
+        utf8_percent_encode(&input.body(), NON_ALPHANUMERIC)
+    );
+    tauri_plugin_opener::open_url(url, None::<&str>).map_err(|e| e.to_string())
+}
+
+/// Adds the reminder through the Reminders app's scripting interface, which
+/// writes to the user's EventKit store.
+#[cfg(target_os = "macos")]
+fn reminders(settings: &TaskSettings, input: &ActionInput) -> Result<(), String> {
+    use std::process::Command;
+
+    // Passed as arguments so the text never has to be escaped into the script
+    let script = r#"on run {reminderName, reminderBody, listName}
+    tell application "Reminders"
+        if listName is "" then
+            set targetList to default list
+        else
+            set targetList to list listName
+        end if
+        make new reminder at end of targetList with properties {name:reminderName, body:reminderBody}
+    end tell
+end run"#;
+    let output = Command::new("osascript")
+        .args(["-e", script, &title(input), &input.body()])
+        .arg(settings.reminders_list.as_deref().unwrap_or_default())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Reminders refused the task: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn reminders(_settings: &TaskSettings, _input: &ActionInput) -> Result<(), String> {
+    Err("Reminders is only available on macOS".to_string())
+}
+
+/// Creates a task from history entry `id` in `target`, or the default target.
+#[tauri::command]
+pub async fn create_task(app: AppHandle, id: i64, target: Option<TaskTarget>) -> Result<(), String> {
+    let settings = settings::current(&app).tasks;
+    let target = target
+        .or(settings.default_target)
+        .ok_or("Choose where to create the task")?;
+    let input = input_for_entry(&app, id)?;
+    create(&settings, target, &input).await
+}
@@ -0,0 +1,98 @@
+//! Spoken confirmation of hotkey captures for screen reader users, who get
+//! nothing from the flash or the popup. On macOS this asks VoiceOver to
+//! read out an announcement; elsewhere it does nothing yet.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::capture::Capture;
+use crate::{settings, text_utils};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnounceSettings {
+    pub enabled: bool,
+    /// Read out the start of the captured text, not just that it worked.
+    pub include_preview: bool,
+    pub preview_chars: usize,
+}
+
+impl Default for AnnounceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            include_preview: true,
+            preview_chars: 60,
+        }
+    }
+}
+
+fn message(settings: &AnnounceSettings, capture: &Capture) -> String {
+    if settings.include_preview && settings.preview_chars > 0 {
+        format!("Captured: {}", text_utils::preview(&capture.event.text, settings.preview_chars))
+    } else {
+        "Captured".to_string()
+    }
+}
+
+/// Announces `capture` if announcements are turned on. Doesn't block.
+pub fn captured(app: &AppHandle, capture: &Capture) {
+    let settings = settings::current(app).announce;
+    if !settings.enabled {
+        return;
+    }
+    let message = message(&settings, capture);
+    // AppKit wants accessibility notifications posted from the main thread
+    if let Err(e) = app.run_on_main_thread(move || platform::announce(&message)) {
+        eprintln!("Failed to announce capture: {}", e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSAccessibilityAnnouncementRequestedNotification: *mut Object;
+        static NSAccessibilityAnnouncementKey: *mut Object;
+        static NSAccessibilityPriorityKey: *mut Object;
+        fn NSAccessibilityPostNotificationWithUserInfo(
+            element: *mut Object,
+            notification: *mut Object,
+            info: *mut Object,
+        );
+    }
+
+    /// `NSAccessibilityPriorityHigh`, which interrupts whatever VoiceOver
+    /// is reading so the confirmation follows the keypress.
+    const PRIORITY_HIGH: i64 = 90;
+
+    pub fn announce(message: &str) {
+        let Ok(message) = std::ffi::CString::new(message) else {
+            return;
+        };
+        unsafe {
+            let text: *mut Object = msg_send![class!(NSString), stringWithUTF8String: message.as_ptr()];
+            let priority: *mut Object = msg_send![class!(NSNumber), numberWithLongLong: PRIORITY_HIGH];
+            let keys = [NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey];
+            let values = [text, priority];
+            let info: *mut Object = msg_send![class!(NSDictionary),
+                dictionaryWithObjects: values.as_ptr()
+                forKeys: keys.as_ptr()
+                count: keys.len()];
+            let ns_app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            NSAccessibilityPostNotificationWithUserInfo(
+                ns_app,
+                NSAccessibilityAnnouncementRequestedNotification,
+                info,
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub fn announce(_message: &str) {}
+}
@@ -0,0 +1,91 @@
+//! Temporary per-app mutes ("ignore this app for an hour"), layered over
+//! the permanent `excluded_apps` filter. Mutes live in memory only, so a
+//! restart lifts them.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::state_broadcast;
+use crate::text_detector::DetectionSettings;
+
+pub const DEFAULT_MUTE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Default)]
+pub struct AppMutes(Mutex<HashMap<String, SystemTime>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppMute {
+    pub app_name: String,
+    /// Unix seconds.
+    pub until: u64,
+}
+
+impl AppMutes {
+    /// Current mutes, dropping any that have run out.
+    fn active(&self) -> Vec<AppMute> {
+        let mut mutes = self.0.lock().unwrap();
+        let now = SystemTime::now();
+        mutes.retain(|_, until| *until > now);
+        mutes
+            .iter()
+            .map(|(app_name, until)| AppMute {
+                app_name: app_name.clone(),
+                until: until.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            })
+            .collect()
+    }
+
+    fn is_muted(&self, app_name: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .get(app_name)
+            .is_some_and(|until| *until > SystemTime::now())
+    }
+}
+
+/// Whether selections from `app_name` should be ignored, permanently or
+/// for now.
+pub fn is_ignored(app: &AppHandle, settings: &DetectionSettings, app_name: &str) -> bool {
+    settings.excluded_apps.iter().any(|excluded| excluded == app_name)
+        || app.try_state::<AppMutes>().is_some_and(|mutes| mutes.is_muted(app_name))
+}
+
+/// Mutes `app_name`, or the app of the last capture, for `duration`.
+pub fn mute(app: &AppHandle, app_name: Option<String>, duration: Duration) -> Result<AppMute, String> {
+    let app_name = app_name
+        .or_else(|| state_broadcast::last_app(app))
+        .ok_or("Nothing has been captured yet, so there's no app to mute")?;
+    let until = SystemTime::now() + duration;
+    let mutes = app.state::<AppMutes>();
+    mutes.0.lock().unwrap().insert(app_name.clone(), until);
+    println!("Muted {} for {} minutes", app_name, duration.as_secs() / 60);
+    let _ = app.emit("app-mutes-changed", mutes.active());
+    Ok(AppMute {
+        app_name,
+        until: until.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+    })
+}
+
+/// Ignores captures from `app_name` (default: the app of the last capture)
+/// for `minutes` (default 60).
+#[tauri::command]
+pub async fn mute_app(app: AppHandle, app_name: Option<String>, minutes: Option<u64>) -> Result<AppMute, String> {
+    let duration = minutes.map_or(DEFAULT_MUTE, |minutes| Duration::from_secs(minutes * 60));
+    mute(&app, app_name, duration)
+}
+
+#[tauri::command]
+pub async fn unmute_app(app: AppHandle, mutes: State<'_, AppMutes>, app_name: String) -> Result<(), String> {
+    mutes.0.lock().unwrap().remove(&app_name);
+    let _ = app.emit("app-mutes-changed", mutes.active());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_app_mutes(mutes: State<'_, AppMutes>) -> Result<Vec<AppMute>, String> {
+    Ok(mutes.active())
+}
@@ -0,0 +1,152 @@
+//! Capture backends in the user's priority order, and failing over between
+//! them. When the active backend keeps erroring (accessibility switched off
+//! mid-session, say) detection moves to the next one and says so with a
+//! `backend-switched` event, instead of quietly capturing nothing. The
+//! preferred backend is retried every so often and taken back once it works.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::rules;
+
+/// Ticks between attempts to go back to the preferred backend.
+const RETRY_PREFERRED_EVERY: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The platform accessibility API, with the IDE and terminal adapters.
+    Accessibility,
+    /// Whatever the user copies. Cruder, but needs no permissions.
+    Clipboard,
+}
+
+pub fn default_backends() -> Vec<Backend> {
+    vec![Backend::Accessibility, Backend::Clipboard]
+}
+
+/// Sent as `backend-switched`. `to` is `None` when there's nothing left to
+/// fall back to.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendSwitch {
+    pub from: Backend,
+    pub to: Option<Backend>,
+    pub reason: String,
+    /// Consecutive failures that led to the switch.
+    pub failures: u32,
+}
+
+pub struct Failover {
+    backends: Vec<Backend>,
+    active: usize,
+    failures: u32,
+    threshold: u32,
+    /// Set once the last backend has failed, so that's only reported once.
+    exhausted: bool,
+    ticks_on_fallback: u32,
+}
+
+impl Failover {
+    pub fn new(mut backends: Vec<Backend>, threshold: u32) -> Self {
+        backends.dedup();
+        if backends.is_empty() {
+            backends = default_backends();
+        }
+        Self {
+            backends,
+            active: 0,
+            failures: 0,
+            threshold: threshold.max(1),
+            exhausted: false,
+            ticks_on_fallback: 0,
+        }
+    }
+
+    pub fn active(&self) -> Backend {
+        self.backends[self.active]
+    }
+
+    pub fn succeeded(&mut self) {
+        self.failures = 0;
+        self.exhausted = false;
+    }
+
+    /// Counts a failure of the active backend, moving on to the next one
+    /// once there have been enough in a row.
+    pub fn failed(&mut self, reason: String) -> Option<BackendSwitch> {
+        self.failures += 1;
+        if self.failures < self.threshold || self.exhausted {
+            return None;
+        }
+        let from = self.active();
+        let to = self.backends.get(self.active + 1).copied();
+        let switch = BackendSwitch {
+            from,
+            to,
+            reason,
+            failures: self.failures,
+        };
+        match to {
+            Some(_) => {
+                self.active += 1;
+                self.failures = 0;
+                self.ticks_on_fallback = 0;
+            }
+            None => self.exhausted = true,
+        }
+        Some(switch)
+    }
+
+    /// While on a fallback, whether this tick should try the preferred
+    /// backend again.
+    pub fn should_retry_preferred(&mut self) -> bool {
+        if self.active == 0 {
+            return false;
+        }
+        self.ticks_on_fallback += 1;
+        self.ticks_on_fallback.is_multiple_of(RETRY_PREFERRED_EVERY)
+    }
+
+    /// The preferred backend works again; switch back to it.
+    pub fn recovered(&mut self) -> BackendSwitch {
+        let from = self.active();
+        self.active = 0;
+        self.failures = 0;
+        self.exhausted = false;
+        BackendSwitch {
+            from,
+            to: Some(self.active()),
+            reason: "Recovered".to_string(),
+            failures: 0,
+        }
+    }
+}
+
+pub fn announce(app: &AppHandle, switch: &BackendSwitch) {
+    match switch.to {
+        Some(to) => eprintln!("Switching capture from {:?} to {:?}: {}", switch.from, to, switch.reason),
+        None => eprintln!("Capture backend {:?} is failing and there's no other to try: {}", switch.from, switch.reason),
+    }
+    let _ = app.emit("backend-switched", switch);
+}
+
+/// The clipboard backend: every new copy is a capture.
+#[derive(Default)]
+pub struct ClipboardSource {
+    last_hash: Option<u32>,
+}
+
+impl ClipboardSource {
+    pub fn poll(&mut self, app: &AppHandle) -> Option<SelectionEvent> {
+        let text = app.clipboard().read_text().ok()?;
+        let hash = crc32fast::hash(text.as_bytes());
+        // The first read is only a baseline: that copy predates the switch
+        let previous = self.last_hash.replace(hash)?;
+        if previous == hash || text.trim().is_empty() || rules::was_auto_copied(&text) {
+            return None;
+        }
+        Some(SelectionEvent::new(text, "Clipboard", SelectionType::Selected))
+    }
+}
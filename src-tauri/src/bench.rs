@@ -0,0 +1,101 @@
+//! Benchmarks for the work done on every capture, so changes to the
+//! pipeline can be measured. The app is a binary, so they're built into it
+//! behind the `bench` feature rather than living in `benches/`:
+//!
+//! ```sh
+//! cargo run --release --features bench -- --bench [filter]
+//! ```
+//!
+//! Criterion's usual options follow `--bench`. The accessibility read is
+//! mocked with fixed texts; everything after it runs for real, with the
+//! history insert going to a scratch database.
+
+use criterion::{Criterion, Throughput};
+use std::hint::black_box;
+use std::path::PathBuf;
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::capture;
+use crate::history::HistoryStore;
+use crate::normalize::{self, NormalizeSettings};
+use crate::settings::Settings;
+
+const PARAGRAPH: &str = "The meeting moved to Thursday, March 14 at 3:30pm in room 4B. Call Jane Doe on \
+    +1 (415) 555-0100 or mail jane.doe@example.com if that doesn't work, and see rust-lang/rust#1 \
+    for the background. Tracking 1Z999AA10123456784; order total $1,249.99.";
+const CJK: &str = "東京は日本の首都であり、世界有数の大都市です。会議は木曜日の午後三時から始まります。";
+
+/// Captures of typical shapes, by name.
+fn samples() -> Vec<(&'static str, String)> {
+    vec![
+        ("word", "Serendipity".to_string()),
+        ("paragraph", PARAGRAPH.to_string()),
+        ("cjk", CJK.repeat(4)),
+        ("page", PARAGRAPH.repeat(40)),
+    ]
+}
+
+/// Stands in for the platform read: the same text, stamped like a real
+/// selection.
+fn fetch(text: &str) -> SelectionEvent {
+    SelectionEvent::new(text, "Bench", SelectionType::Selected)
+}
+
+/// A history database of its own, removed afterwards.
+struct ScratchHistory {
+    dir: PathBuf,
+    store: HistoryStore,
+}
+
+impl ScratchHistory {
+    fn open() -> Self {
+        let dir = std::env::temp_dir().join(format!("acmi-bench-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = HistoryStore::open(&dir).expect("Failed to open scratch history database");
+        Self { dir, store }
+    }
+}
+
+impl Drop for ScratchHistory {
+    fn drop(&mut self) {
+        let _ = self.store.close();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn stages(c: &mut Criterion, history: &HistoryStore) {
+    let settings = Settings::default();
+    let normalize_settings = NormalizeSettings {
+        enabled: true,
+        ..Default::default()
+    };
+
+    for (name, text) in samples() {
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        let event = fetch(&text);
+        let annotated = capture::detect(&settings, event.clone());
+
+        group.bench_function("normalize", |b| b.iter(|| normalize::clean(&normalize_settings, black_box(&text))));
+        group.bench_function("detect", |b| b.iter(|| capture::detect(&settings, black_box(event.clone()))));
+        group.bench_function("serialize", |b| b.iter(|| serde_json::to_string(black_box(&annotated)).unwrap()));
+        group.bench_function("insert", |b| b.iter(|| history.insert(black_box(&event)).unwrap()));
+        group.bench_function("capture", |b| {
+            b.iter(|| {
+                let mut event = fetch(black_box(&text));
+                event.text = normalize::clean(&normalize_settings, &event.text);
+                history.insert(&event).unwrap();
+                let annotated = capture::detect(&settings, event);
+                serde_json::to_string(&annotated).unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+pub fn run() {
+    let history = ScratchHistory::open();
+    let mut c = Criterion::default().configure_from_args();
+    stages(&mut c, &history.store);
+    c.final_summary();
+}
@@ -0,0 +1,320 @@
+//! Companion bridge for the optional Chrome/Firefox extensions, which know
+//! the exact page selection where AX only sees a web view.
+//!
+//! The browser starts this same executable as a native messaging host. In
+//! that mode it relays each message from stdin to the running app over a
+//! localhost socket, where it goes through the normal capture pipeline.
+//! The socket is open to every local process and, as plain HTTP, to web
+//! pages, so each message carries a per-install token the app writes to a
+//! file only the user can read; anything without it is dropped.
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use text_detection::{SelectionEvent, SelectionType};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::history::{reading_list, sources};
+use crate::{capture, secrets, settings, trace};
+
+pub const HOST_NAME: &str = "com.acmi_desktop.bridge";
+/// Fixed, since the host process starts without access to the settings.
+const BRIDGE_PORT: u16 = 17842;
+/// Browsers cap messages to the host at 4 GB, but nothing sane is that big.
+const MAX_MESSAGE_BYTES: usize = 8 * 1024 * 1024;
+/// Matches the identifier in tauri.conf.json; see [`token_path`].
+const APP_DIR_NAME: &str = "com.acmi-desktop.app";
+const TOKEN_FILE_NAME: &str = "bridge-token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserBridgeSettings {
+    pub enabled: bool,
+    /// Extension ids allowed to talk to the host, per browser.
+    pub chrome_extension_ids: Vec<String>,
+    pub firefox_extension_ids: Vec<String>,
+    /// Queue the page of every selection sent from a browser; see
+    /// [`crate::history::reading_list`].
+    pub reading_list: bool,
+}
+
+impl Default for BrowserBridgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            chrome_extension_ids: Vec::new(),
+            firefox_extension_ids: Vec::new(),
+            reading_list: false,
+        }
+    }
+}
+
+/// Where a browser selection came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageContext {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// Text around the selection, e.g. its paragraph.
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub browser: Option<String>,
+}
+
+/// What an extension sends.
+#[derive(Debug, Serialize, Deserialize)]
+struct BrowserSelection {
+    text: String,
+    #[serde(flatten)]
+    page: PageContext,
+}
+
+/// What the host relays to the app: a message with the install's token.
+#[derive(Debug, Serialize, Deserialize)]
+struct HostFrame {
+    token: String,
+    selection: BrowserSelection,
+}
+
+/// Where the token is kept. Always the OS's default config directory, not
+/// one moved with `--data-dir`, since the host is started by the browser
+/// without the app's arguments.
+fn token_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let dir = if cfg!(target_os = "macos") {
+        home?.join("Library/Application Support")
+    } else if cfg!(target_os = "windows") {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".config")))?
+    };
+    Some(dir.join(APP_DIR_NAME).join(TOKEN_FILE_NAME))
+}
+
+fn write_token(path: &Path, token: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+/// The install's token, made up and written the first time.
+fn token() -> Result<String, String> {
+    let path = token_path().ok_or("No home directory for the browser bridge token")?;
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    let token = hex::encode(bytes);
+    write_token(&path, &token).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(token)
+}
+
+/// Whether the browser launched us as a native messaging host: Chrome
+/// passes the extension origin, Firefox the manifest path and extension id.
+pub fn is_host_invocation() -> bool {
+    std::env::args()
+        .skip(1)
+        .any(|arg| arg.starts_with("chrome-extension://") || arg.ends_with(&format!("{}.json", HOST_NAME)))
+}
+
+/// Native messaging host mode: relays messages until the browser closes
+/// stdin. Runs instead of the app.
+pub fn run_host() {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+
+    while let Some(message) = read_message(&mut stdin) {
+        let reply = match relay(&message) {
+            Ok(()) => serde_json::json!({ "ok": true }),
+            Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+        };
+        if write_message(&mut stdout, &reply).is_err() {
+            break;
+        }
+    }
+}
+
+fn read_message(input: &mut impl Read) -> Option<Vec<u8>> {
+    let mut length = [0u8; 4];
+    input.read_exact(&mut length).ok()?;
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > MAX_MESSAGE_BYTES {
+        return None;
+    }
+    let mut message = vec![0u8; length];
+    input.read_exact(&mut message).ok()?;
+    Some(message)
+}
+
+fn write_message(output: &mut impl Write, message: &serde_json::Value) -> std::io::Result<()> {
+    let bytes = serde_json::to_vec(message)?;
+    output.write_all(&(bytes.len() as u32).to_ne_bytes())?;
+    output.write_all(&bytes)?;
+    output.flush()
+}
+
+/// Forwards one message to the app as a line of JSON, with the token.
+fn relay(message: &[u8]) -> std::io::Result<()> {
+    let selection: BrowserSelection = serde_json::from_slice(message)?;
+    let path = token_path().ok_or_else(|| std::io::Error::other("No home directory"))?;
+    let token = std::fs::read_to_string(path)?.trim().to_string();
+    let mut stream = TcpStream::connect(("127.0.0.1", BRIDGE_PORT))?;
+    serde_json::to_writer(&mut stream, &HostFrame { token, selection })?;
+    stream.write_all(b"\n")
+}
+
+/// Starts accepting selections relayed by the host.
+pub fn init(app: &AppHandle) {
+    let settings = settings::current(app).browser_bridge;
+    if !settings.enabled {
+        return;
+    }
+    let token = match token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Not starting the browser bridge: {}", e);
+            return;
+        }
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", BRIDGE_PORT)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to start browser bridge on port {}: {}", BRIDGE_PORT, e);
+                return;
+            }
+        };
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let (app, token) = (app.clone(), token.clone());
+            tauri::async_runtime::spawn(async move {
+                let mut lines = BufReader::new(stream).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    // Whoever sent this isn't the host; stop listening to them
+                    let Ok(frame) = serde_json::from_str::<HostFrame>(&line) else {
+                        eprintln!("Dropping a browser bridge connection that didn't send a host message");
+                        break;
+                    };
+                    if !secrets::constant_time_eq(&frame.token, &token) {
+                        eprintln!("Dropping a browser bridge connection with the wrong token");
+                        break;
+                    }
+                    deliver(&app, frame.selection);
+                }
+            });
+        }
+    });
+}
+
+fn deliver(app: &AppHandle, selection: BrowserSelection) {
+    if selection.text.trim().is_empty() {
+        return;
+    }
+    let app_name = selection.page.browser.clone().unwrap_or_else(|| "Browser".to_string());
+    let event = SelectionEvent::new(selection.text, app_name, SelectionType::Selected);
+    let Some(capture) = capture::process(app, event) else {
+        return;
+    };
+    trace::source(app, capture.id, "browser extension");
+    let capture = capture::Capture {
+        page: Some(selection.page),
+        ..capture
+    };
+    capture::emit(app, "text-selection-changed", &capture);
+    let _ = app.emit("browser-selection-received", &capture.page);
+    sources::remember(app, &capture);
+    reading_list::add_if_enabled(app, &capture);
+}
+
+/// Per-user directories browsers look in for host manifests.
+fn manifest_dirs() -> Vec<(&'static str, PathBuf)> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    if cfg!(target_os = "macos") {
+        let support = home.join("Library/Application Support");
+        vec![
+            ("chrome", support.join("Google/Chrome/NativeMessagingHosts")),
+            ("chrome", support.join("Chromium/NativeMessagingHosts")),
+            ("firefox", support.join("Mozilla/NativeMessagingHosts")),
+        ]
+    } else if cfg!(target_os = "linux") {
+        vec![
+            ("chrome", home.join(".config/google-chrome/NativeMessagingHosts")),
+            ("chrome", home.join(".config/chromium/NativeMessagingHosts")),
+            ("firefox", home.join(".mozilla/native-messaging-hosts")),
+        ]
+    } else {
+        // Windows registers hosts in the registry instead
+        Vec::new()
+    }
+}
+
+/// Writes the host manifests so the configured extensions can connect.
+/// Returns the files written.
+#[tauri::command]
+pub async fn install_browser_bridge(app: AppHandle) -> Result<Vec<String>, String> {
+    let settings = settings::current(&app).browser_bridge;
+    // The host can't relay anything until there's a token to send
+    token()?;
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let dirs = manifest_dirs();
+    if dirs.is_empty() {
+        return Err("Installing the browser bridge isn't supported on this platform".to_string());
+    }
+
+    let mut written = Vec::new();
+    for (browser, dir) in dirs {
+        let mut manifest = serde_json::json!({
+            "name": HOST_NAME,
+            "description": "acmi-desktop selection bridge",
+            "path": exe,
+            "type": "stdio",
+        });
+        if browser == "chrome" {
+            manifest["allowed_origins"] = settings
+                .chrome_extension_ids
+                .iter()
+                .map(|id| format!("chrome-extension://{}/", id))
+                .collect();
+        } else {
+            manifest["allowed_extensions"] = settings.firefox_extension_ids.clone().into();
+        }
+
+        // Only install for browsers that are actually there
+        if !dir.parent().is_some_and(|parent| parent.exists()) {
+            continue;
+        }
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("{}.json", HOST_NAME));
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+    Ok(written)
+}
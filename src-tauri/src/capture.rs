@@ -0,0 +1,278 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::{ElementDump, SelectionEvent, SelectionType};
+
+use crate::browser_bridge::PageContext;
+use crate::color::{self, Color};
+use crate::confidence::{self, Confidence};
+use crate::dates::{self, DateMention};
+use crate::entities::address::{self, Address};
+use crate::entities::contact::{self, Contact};
+use crate::entities::identifiers::{self, Identifier};
+use crate::entities::issues::{self, IssueRef};
+use crate::entities::phone::{self, PhoneNumber};
+use crate::entities::securities::{self, Security};
+use crate::entities::tracking::{self, TrackingNumber};
+use crate::fs_actions::{self, FileInfo};
+use crate::image_selection::CapturedImage;
+use crate::readability::{self, Readability};
+use crate::settings::Settings;
+use crate::sinks::{self, Sink};
+use crate::trace::{self, Recorder, Stage};
+use crate::{capture_lengths, compute, grpc, history, mqtt, normalize, pause, plugins, regex_tester, rules, scripts, settings, spellcheck, state_broadcast, text_utils};
+
+/// How many large bodies stay fetchable after being announced.
+const KEPT_LARGE_BODIES: usize = 8;
+const LARGE_PREVIEW_CHARS: usize = 200;
+
+/// A selection after it has been through the capture pipeline. Serializes
+/// as the plain event plus whichever annotations applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capture {
+    #[serde(flatten)]
+    pub event: SelectionEvent,
+    /// History row id, if the capture was stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// An earlier history entry with nearly the same text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar_to: Option<i64>,
+    pub confidence: Confidence,
+    /// How many selections from a single drag were merged into this one.
+    pub revisions: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readability: Option<Readability>,
+    /// Result of evaluating the capture as an expression or quantity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<DateMention>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    /// Set when the capture looks like a signature block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub phones: Vec<PhoneNumber>,
+    /// Coordinates follow in an `address-geocoded` event, if a geocoder is
+    /// configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tracking: Vec<TrackingNumber>,
+    /// UUIDs, SHAs, tokens and the like.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub identifiers: Vec<Identifier>,
+    /// Prices follow in `security-quote` events, if a quote API is
+    /// configured.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub securities: Vec<Security>,
+    /// Titles follow in `issue-resolved` events, if credentials are set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<IssueRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<CapturedImage>,
+    /// Set when the capture is a path to something on disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileInfo>,
+    /// The page a browser extension sent the selection from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageContext>,
+    /// The focused element the text was read from, in debug mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<ElementDump>,
+    /// What plugins attached, by plugin name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, serde_json::Value>,
+}
+
+/// Runs a fresh capture through everything that should see it, regardless
+/// of which path (polling, hotkey, explicit capture) produced it. `None`
+/// if a plugin or script dropped it.
+pub fn process(app: &AppHandle, event: SelectionEvent) -> Option<Capture> {
+    if pause::is_paused(app) {
+        return None;
+    }
+    let mut recorder = Recorder::default();
+    let before = event.text.clone();
+    let event = normalize::run(app, event);
+    if event.text != before {
+        recorder.step(Stage::Transform, "normalize", None);
+    }
+    let (event, annotations) = plugins::run(app, event)?;
+    if !annotations.is_empty() {
+        let plugins = annotations.keys().cloned().collect::<Vec<_>>().join(", ");
+        recorder.step(Stage::Transform, "plugins", Some(format!("Annotated by {}", plugins)));
+    }
+    let text = event.text.clone();
+    let (event, scripted) = scripts::run(app, event)?;
+    if event.text != text {
+        recorder.step(Stage::Transform, "scripts", None);
+    }
+
+    let id = history::record(app, &event);
+    recorder.step(Stage::Sink, "history", None);
+    trace::begin(app, id, recorder);
+    capture_lengths::observe(app, &event);
+    spellcheck::flag_typos(app, &event);
+    regex_tester::run(app, &event);
+
+    let capture = Capture {
+        id,
+        similar_to: id.and_then(|id| history::similarity::find(app, id, &event.text)),
+        annotations,
+        ..annotate(app, event)
+    };
+    if let Some(address) = &capture.address {
+        address::geocode_in_background(app, capture.id, address);
+    }
+    securities::quote_in_background(app, capture.id, &capture.securities);
+    issues::fetch_in_background(app, capture.id, &capture.issues);
+    let (rules_app, rules_capture) = (app.clone(), capture.clone());
+    if let Err(e) = sinks::submit(app, Sink::Rules, move || rules::run(&rules_app, &rules_capture)) {
+        eprintln!("Skipped rules for capture: {}", e);
+    }
+    if !scripted.is_empty() {
+        let (scripts_app, scripts_capture) = (app.clone(), capture.clone());
+        if let Err(e) = sinks::submit(app, Sink::Rules, move || scripted.apply(&scripts_app, &scripts_capture)) {
+            eprintln!("Skipped script actions for capture: {}", e);
+        }
+    }
+    Some(capture)
+}
+
+/// The capture `event` would become, with everything detected but nothing
+/// stored, fetched or acted on.
+pub fn annotate(app: &AppHandle, event: SelectionEvent) -> Capture {
+    Capture {
+        computed: compute::evaluate(app, &event.text),
+        ..detect(&settings::current(app), event)
+    }
+}
+
+/// What [`annotate`] finds without the app, i.e. all but the computed
+/// result, which may need exchange rates.
+pub fn detect(settings: &Settings, event: SelectionEvent) -> Capture {
+    Capture {
+        id: None,
+        similar_to: None,
+        confidence: confidence::assess(&event),
+        revisions: 1,
+        readability: readability::annotate(&settings.readability, &event.text),
+        computed: None,
+        date: dates::detect(&event.text),
+        color: color::parse(&event.text),
+        contact: contact::detect(&event.text),
+        phones: phone::detect(&event.text, &settings.entities.phone_region),
+        address: address::detect(&event.text),
+        tracking: tracking::detect(&event.text),
+        identifiers: identifiers::detect(&event.text),
+        securities: securities::detect(&event.text),
+        issues: issues::detect(&event.text, &settings.entities.issues),
+        image: None,
+        file: fs_actions::inspect(&event.text),
+        page: None,
+        element: None,
+        annotations: BTreeMap::new(),
+        event,
+    }
+}
+
+/// [`process`] for an image selection; the event's text is its OCR result.
+pub fn process_image(app: &AppHandle, event: SelectionEvent, image: CapturedImage) -> Option<Capture> {
+    Some(Capture {
+        image: Some(image),
+        ..process(app, event)?
+    })
+}
+
+/// Bodies of recent captures too big to send through the event bus, kept
+/// until the frontend fetches them with [`fetch_capture_body`].
+#[derive(Default)]
+pub struct LargeCaptures {
+    next_id: Mutex<u64>,
+    bodies: Mutex<VecDeque<(u64, String)>>,
+}
+
+impl LargeCaptures {
+    fn store(&self, text: String) -> u64 {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+        let mut bodies = self.bodies.lock().unwrap();
+        if bodies.len() == KEPT_LARGE_BODIES {
+            bodies.pop_front();
+        }
+        bodies.push_back((id, text));
+        id
+    }
+}
+
+/// What `selection-large` carries instead of the capture itself.
+#[derive(Debug, Clone, Serialize)]
+pub struct LargeCapture<'a> {
+    /// Pass to `fetch_capture_body`.
+    pub body_id: u64,
+    /// The event the capture would normally have been sent as.
+    pub source: &'a str,
+    pub id: Option<i64>,
+    pub app_name: &'a str,
+    pub selection_type: &'a SelectionType,
+    pub timestamp: u64,
+    pub size: usize,
+    pub preview: String,
+}
+
+/// Sends a processed capture to the frontend as `event`, or as metadata in
+/// a `selection-large` event when it is over the configured size.
+pub fn emit(app: &AppHandle, event: &str, capture: &Capture) {
+    state_broadcast::captured(app, capture);
+    let (sink_app, sink_capture) = (app.clone(), capture.clone());
+    let _ = sinks::submit(app, Sink::Mqtt, move || mqtt::publish(&sink_app, &sink_capture));
+    let (sink_app, sink_capture) = (app.clone(), capture.clone());
+    let _ = sinks::submit(app, Sink::Grpc, move || {
+        grpc::publish(&sink_app, &sink_capture);
+        Ok(())
+    });
+
+    let threshold = settings::current(app).detection.large_capture_bytes;
+    let text = &capture.event.text;
+    let Some(large) = app.try_state::<LargeCaptures>().filter(|_| text.len() > threshold) else {
+        let _ = app.emit(event, capture);
+        return;
+    };
+
+    // Only the start is needed for the preview, not megabytes of whitespace
+    let head = text_utils::truncate(text, LARGE_PREVIEW_CHARS * 4);
+    let _ = app.emit(
+        "selection-large",
+        LargeCapture {
+            body_id: large.store(text.clone()),
+            source: event,
+            id: capture.id,
+            app_name: &capture.event.app_name,
+            selection_type: &capture.event.selection_type,
+            timestamp: capture.event.timestamp,
+            size: text.len(),
+            preview: text_utils::preview(head, LARGE_PREVIEW_CHARS),
+        },
+    );
+}
+
+/// The full text of a capture announced through `selection-large`, as raw
+/// UTF-8 so it skips JSON encoding.
+#[tauri::command]
+pub async fn fetch_capture_body(large: State<'_, LargeCaptures>, body_id: u64) -> Result<tauri::ipc::Response, String> {
+    large
+        .bodies
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(id, _)| *id == body_id)
+        .map(|(_, text)| tauri::ipc::Response::new(text.clone().into_bytes()))
+        .ok_or_else(|| format!("Capture body {} is no longer available", body_id))
+}
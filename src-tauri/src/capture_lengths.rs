@@ -0,0 +1,132 @@
+//! How long captures from each app usually are, to spot an app that
+//! suddenly produces huge ones. That almost always means the focused-value
+//! fallback is reading a whole document instead of a selection, and the
+//! app needs an adapter or a noise rule.
+//!
+//! Lengths are counted in power-of-two buckets; a capture is anomalous when
+//! it lands several buckets above the app's median.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::SelectionEvent;
+
+/// Bucket `i` counts lengths in `[2^i, 2^(i+1))`; the last one everything
+/// longer.
+const BUCKETS: usize = 24;
+/// Samples needed before an app's median means anything.
+const MIN_SAMPLES: u32 = 20;
+/// Buckets above the median to count as anomalous, i.e. at least 32x.
+const ANOMALY_BUCKETS: usize = 5;
+/// Shorter captures are never anomalous, however short the app's usual ones.
+const MIN_ANOMALY_CHARS: usize = 4096;
+/// One warning per app in this window.
+const WARN_EVERY: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LengthHistogram {
+    pub app_name: String,
+    pub samples: u32,
+    /// Capture counts by length, bucket `i` covering `[2^i, 2^(i+1))` chars.
+    pub buckets: Vec<u32>,
+    /// Lower bound of the median bucket.
+    pub typical: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LengthAnomaly {
+    pub app_name: String,
+    pub length: usize,
+    pub typical: usize,
+}
+
+struct AppLengths {
+    buckets: [u32; BUCKETS],
+    warned: Option<Instant>,
+}
+
+impl AppLengths {
+    fn samples(&self) -> u32 {
+        self.buckets.iter().sum()
+    }
+
+    fn median_bucket(&self) -> usize {
+        let half = self.samples().div_ceil(2);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= half {
+                return bucket;
+            }
+        }
+        0
+    }
+}
+
+fn bucket(length: usize) -> usize {
+    (usize::BITS - length.max(1).leading_zeros() - 1).min(BUCKETS as u32 - 1) as usize
+}
+
+#[derive(Default)]
+pub struct CaptureLengths(Mutex<HashMap<String, AppLengths>>);
+
+impl CaptureLengths {
+    /// Counts `event`, returning an anomaly if it is far longer than usual
+    /// for its app (and the app hasn't been warned about lately).
+    fn observe(&self, event: &SelectionEvent) -> Option<LengthAnomaly> {
+        let length = event.text.chars().count();
+        let mut apps = self.0.lock().unwrap();
+        let lengths = apps.entry(event.app_name.clone()).or_insert(AppLengths {
+            buckets: [0; BUCKETS],
+            warned: None,
+        });
+
+        // Judge against the history before this capture
+        let median = lengths.median_bucket();
+        let anomalous = lengths.samples() >= MIN_SAMPLES
+            && length >= MIN_ANOMALY_CHARS
+            && bucket(length) >= median + ANOMALY_BUCKETS
+            && lengths.warned.is_none_or(|warned| warned.elapsed() >= WARN_EVERY);
+        lengths.buckets[bucket(length)] += 1;
+
+        if !anomalous {
+            return None;
+        }
+        lengths.warned = Some(Instant::now());
+        Some(LengthAnomaly {
+            app_name: event.app_name.clone(),
+            length,
+            typical: 1 << median,
+        })
+    }
+}
+
+pub fn observe(app: &AppHandle, event: &SelectionEvent) {
+    let Some(anomaly) = app.try_state::<CaptureLengths>().and_then(|lengths| lengths.observe(event)) else {
+        return;
+    };
+    eprintln!(
+        "Unusually long capture from {}: {} chars, usually around {}. The focused-text fallback may be reading the whole document",
+        anomaly.app_name, anomaly.length, anomaly.typical
+    );
+    let _ = app.emit("capture-length-anomaly", anomaly);
+}
+
+/// Length distribution of this session's captures, per app.
+#[tauri::command]
+pub async fn get_capture_lengths(lengths: State<'_, CaptureLengths>) -> Result<Vec<LengthHistogram>, String> {
+    let apps = lengths.0.lock().unwrap();
+    let mut histograms: Vec<LengthHistogram> = apps
+        .iter()
+        .map(|(app_name, lengths)| LengthHistogram {
+            app_name: app_name.clone(),
+            samples: lengths.samples(),
+            buckets: lengths.buckets.to_vec(),
+            typical: 1 << lengths.median_bucket(),
+        })
+        .collect();
+    histograms.sort_by_key(|histogram| std::cmp::Reverse(histogram.samples));
+    Ok(histograms)
+}
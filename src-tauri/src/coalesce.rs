@@ -0,0 +1,67 @@
+//! Merges the burst of selections a drag produces. Each tick while the user
+//! is still extending a selection looks like a new capture; instead, a
+//! change is held until the app has gone quiet for the coalescing window,
+//! and only the final text is emitted, with a count of how many it replaced.
+
+use std::time::{Duration, Instant};
+use text_detection::SelectionEvent;
+
+struct Pending {
+    event: SelectionEvent,
+    pid: Option<i32>,
+    revisions: u32,
+    last_change: Instant,
+}
+
+pub struct Coalescer {
+    window: Duration,
+    pending: Option<Pending>,
+}
+
+impl Coalescer {
+    /// A zero `window` turns coalescing off: every selection is emitted
+    /// straight away.
+    pub fn new(window: Duration) -> Self {
+        Self { window, pending: None }
+    }
+
+    /// Takes a changed selection from the app with process id `pid`.
+    /// Returns whatever is ready to emit now, with its revision count.
+    pub fn push(&mut self, event: SelectionEvent, pid: Option<i32>) -> Option<(SelectionEvent, u32)> {
+        if self.window.is_zero() {
+            return Some((event, 1));
+        }
+        match self.pending.take() {
+            Some(mut pending) if pending.pid == pid && pending.last_change.elapsed() < self.window => {
+                pending.event = event;
+                pending.revisions += 1;
+                pending.last_change = Instant::now();
+                self.pending = Some(pending);
+                None
+            }
+            // Another app, or a fresh selection after a pause
+            previous => {
+                self.pending = Some(Pending {
+                    event,
+                    pid,
+                    revisions: 1,
+                    last_change: Instant::now(),
+                });
+                previous.map(|pending| (pending.event, pending.revisions))
+            }
+        }
+    }
+
+    /// The selection being held back, and how many it has replaced so far.
+    pub fn pending(&self) -> Option<(&SelectionEvent, u32)> {
+        self.pending.as_ref().map(|pending| (&pending.event, pending.revisions))
+    }
+
+    /// The held selection, once it has stopped changing for the window.
+    pub fn flush_due(&mut self) -> Option<(SelectionEvent, u32)> {
+        if self.pending.as_ref()?.last_change.elapsed() < self.window {
+            return None;
+        }
+        self.pending.take().map(|pending| (pending.event, pending.revisions))
+    }
+}
@@ -0,0 +1,133 @@
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+use tauri::ipc::Response;
+
+const SWATCH_SIZE: u32 = 32;
+
+/// A color literal found in a capture, normalized to sRGB.
+#[derive(Debug, Clone, Serialize)]
+pub struct Color {
+    /// `#rrggbb`, or `#rrggbbaa` when not fully opaque.
+    pub hex: String,
+    pub rgb: [u8; 3],
+    pub alpha: f32,
+    pub original: String,
+}
+
+impl Color {
+    fn new(r: u8, g: u8, b: u8, alpha: f32, original: &str) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let hex = if alpha < 1.0 {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, (alpha * 255.0).round() as u8)
+        } else {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        };
+        Self {
+            hex,
+            rgb: [r, g, b],
+            alpha,
+            original: original.to_string(),
+        }
+    }
+}
+
+fn hex_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^#?([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$").unwrap())
+}
+
+fn function_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)^(rgba?|hsla?)\(\s*([\d.]+%?)[\s,]+([\d.]+%?)[\s,]+([\d.]+%?)(?:\s*[,/]\s*([\d.]+%?))?\s*\)$").unwrap()
+    })
+}
+
+/// Parses `#ff8800`, `#f80`, `rgb(255, 136, 0)`, `rgba(…)`, `hsl(32 100% 50%)`
+/// and `hsla(…)`. Bare hex is only accepted with a leading `#` so words
+/// like "cafe" aren't mistaken for colors.
+pub fn parse(text: &str) -> Option<Color> {
+    let text = text.trim().trim_end_matches(';');
+    if text.starts_with('#') {
+        return parse_hex(text);
+    }
+
+    let captures = function_pattern().captures(text)?;
+    let alpha = captures.get(5).map_or(Some(1.0), |m| fraction(m.as_str(), 1.0))?;
+    let [a, b, c] = [2, 3, 4].map(|i| captures[i].to_string());
+
+    if captures[1].to_lowercase().starts_with("rgb") {
+        let channel = |value: &str| fraction(value, 255.0).map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8);
+        Some(Color::new(channel(&a)?, channel(&b)?, channel(&c)?, alpha as f32, text))
+    } else {
+        let hue: f32 = a.trim_end_matches("deg").parse().ok()?;
+        let saturation = fraction(&b, 100.0)?;
+        let lightness = fraction(&c, 100.0)?;
+        let [r, g, b] = hsl_to_rgb(hue, saturation as f32, lightness as f32);
+        Some(Color::new(r, g, b, alpha as f32, text))
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Color> {
+    let digits = hex_pattern().captures(text)?.get(1)?.as_str();
+    // Expand the short forms (#rgb, #rgba) to two digits per channel
+    let digits: String = if digits.len() <= 4 {
+        digits.chars().flat_map(|c| [c, c]).collect()
+    } else {
+        digits.to_string()
+    };
+
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    let alpha = if digits.len() == 8 { channel(6)? as f32 / 255.0 } else { 1.0 };
+    Some(Color::new(channel(0)?, channel(2)?, channel(4)?, alpha, text))
+}
+
+/// `value` as a 0–1 fraction: percentages are relative to 100, plain
+/// numbers relative to `scale`.
+fn fraction(value: &str, scale: f64) -> Option<f64> {
+    let fraction = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok()? / 100.0,
+        None => value.parse::<f64>().ok()? / scale,
+    };
+    Some(fraction.clamp(0.0, 1.0))
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|v| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+/// A solid square PNG of `color`.
+fn swatch_png(color: &Color) -> Result<Vec<u8>, String> {
+    let [r, g, b] = color.rgb;
+    let a = (color.alpha * 255.0).round() as u8;
+    let pixels: Vec<u8> = [r, g, b, a].repeat((SWATCH_SIZE * SWATCH_SIZE) as usize);
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, SWATCH_SIZE, SWATCH_SIZE);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+/// Returns a swatch PNG for a color literal as raw bytes.
+#[tauri::command]
+pub async fn preview_color(value: String) -> Result<Response, String> {
+    let color = parse(&value).ok_or_else(|| format!("\"{}\" is not a color", value))?;
+    Ok(Response::new(swatch_png(&color)?))
+}
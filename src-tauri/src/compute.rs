@@ -0,0 +1,36 @@
+//! Spotlight-style evaluation of numeric captures: arithmetic expressions,
+//! quantities with a unit and currency amounts.
+
+use tauri::AppHandle;
+
+pub mod currency;
+mod expr;
+mod units;
+
+/// Evaluates `text` if it looks like an expression, quantity or amount,
+/// e.g. `15*89` → `15*89 = 1335`, `12 mi` → `12 mi = 19.3121 km`.
+pub fn evaluate(app: &AppHandle, text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > 200 {
+        return None;
+    }
+
+    if let Some(converted) = units::convert(text) {
+        return Some(converted);
+    }
+    if let Some(converted) = currency::convert(app, text) {
+        return Some(converted);
+    }
+    expr::evaluate(text).map(|value| format!("{} = {}", text, format_number(value)))
+}
+
+/// At most four decimals, without trailing zeros.
+pub(crate) fn format_number(value: f64) -> String {
+    let formatted = format!("{:.4}", value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    if formatted == "-0" {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
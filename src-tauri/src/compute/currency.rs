@@ -0,0 +1,191 @@
+//! Currency amounts, converted to the home currency with cached rates.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::{locations, settings};
+
+const RATES_FILE_NAME: &str = "exchange_rates.json";
+/// Rates older than this are refreshed in the background on startup.
+const MAX_AGE: Duration = Duration::from_secs(12 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencySettings {
+    /// ISO 4217 code amounts are converted into.
+    pub home_currency: String,
+    /// An open.er-api.com-compatible "latest rates" endpoint.
+    pub feed_url: String,
+}
+
+impl Default for CurrencySettings {
+    fn default() -> Self {
+        Self {
+            home_currency: "USD".to_string(),
+            feed_url: "https://open.er-api.com/v6/latest/USD".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rates {
+    pub base: String,
+    /// Units of each currency per one unit of `base`.
+    pub rates: HashMap<String, f64>,
+    /// Unix seconds when the provider last updated the rates.
+    pub updated_at: u64,
+}
+
+impl Rates {
+    fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        let rate = |code: &str| {
+            if code == self.base {
+                Some(1.0)
+            } else {
+                self.rates.get(code).copied()
+            }
+        };
+        Some(amount / rate(from)? * rate(to)?)
+    }
+
+    fn is_stale(&self) -> bool {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        now.saturating_sub(self.updated_at) > MAX_AGE.as_secs()
+    }
+}
+
+#[derive(Deserialize)]
+struct FeedResponse {
+    base_code: String,
+    rates: HashMap<String, f64>,
+    time_last_update_unix: u64,
+}
+
+/// The last successfully fetched rates, persisted so conversion keeps
+/// working offline.
+pub struct RateCache {
+    path: PathBuf,
+    rates: Mutex<Option<Rates>>,
+}
+
+impl RateCache {
+    fn load(dir: &Path) -> Self {
+        let path = dir.join(RATES_FILE_NAME);
+        let rates = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        Self {
+            path,
+            rates: Mutex::new(rates),
+        }
+    }
+
+    pub fn get(&self) -> Option<Rates> {
+        self.rates.lock().unwrap().clone()
+    }
+
+    fn store(&self, rates: Rates) -> Result<(), String> {
+        let contents = serde_json::to_string(&rates).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, contents).map_err(|e| e.to_string())?;
+        *self.rates.lock().unwrap() = Some(rates);
+        Ok(())
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = locations::data_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    let cache = RateCache::load(&dir);
+    let needs_refresh = cache.get().is_none_or(|rates| rates.is_stale());
+    app.manage(cache);
+
+    if needs_refresh {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = refresh(&app).await {
+                eprintln!("Failed to refresh exchange rates, using cached rates: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Fetches new rates if the cached ones are missing or stale, e.g. after
+/// the network comes back.
+pub async fn refresh_if_stale(app: &AppHandle) {
+    let stale = app
+        .try_state::<RateCache>()
+        .is_some_and(|cache| cache.get().is_none_or(|rates| rates.is_stale()));
+    if stale {
+        if let Err(e) = refresh(app).await {
+            eprintln!("Failed to refresh exchange rates, using cached rates: {}", e);
+        }
+    }
+}
+
+async fn refresh(app: &AppHandle) -> Result<Rates, String> {
+    let url = settings::current(app).currency.feed_url;
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Exchange rate request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Exchange rate feed returned {}", response.status()));
+    }
+
+    let feed: FeedResponse = response.json().await.map_err(|e| e.to_string())?;
+    let rates = Rates {
+        base: feed.base_code,
+        rates: feed.rates,
+        updated_at: feed.time_last_update_unix,
+    };
+    app.state::<RateCache>().store(rates.clone())?;
+    Ok(rates)
+}
+
+const SYMBOLS: &[(&str, &str)] = &[("$", "USD"), ("€", "EUR"), ("£", "GBP"), ("¥", "JPY"), ("₹", "INR"), ("₩", "KRW"), ("Rp", "IDR")];
+
+fn amount_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(?:(?P<prefix>[$€£¥₹₩]|Rp|[A-Za-z]{3})\s?(?P<amount>\d[\d,]*(?:\.\d+)?)|(?P<amount2>\d[\d,]*(?:\.\d+)?)\s?(?P<suffix>[$€£¥₹₩]|[A-Za-z]{3}))$").unwrap()
+    })
+}
+
+/// Parses `$12.50`, `€30`, `12.99 usd` or `USD 40` into an amount and code.
+fn parse_amount(text: &str) -> Option<(f64, String)> {
+    let captures = amount_pattern().captures(text)?;
+    let amount = captures.name("amount").or(captures.name("amount2"))?;
+    let unit = captures.name("prefix").or(captures.name("suffix"))?.as_str();
+
+    let code = SYMBOLS
+        .iter()
+        .find(|(symbol, _)| *symbol == unit)
+        .map(|(_, code)| code.to_string())
+        .unwrap_or_else(|| unit.to_uppercase());
+    Some((amount.as_str().replace(',', "").parse().ok()?, code))
+}
+
+/// Converts a currency amount into the home currency, if rates are known
+/// for both.
+pub fn convert(app: &AppHandle, text: &str) -> Option<String> {
+    let (amount, from) = parse_amount(text)?;
+    let home = settings::current(app).currency.home_currency.to_uppercase();
+    if from == home {
+        return None;
+    }
+
+    let rates = app.try_state::<RateCache>()?.get()?;
+    let converted = rates.convert(amount, &from, &home)?;
+    Some(format!("{} = {:.2} {}", text, converted, home))
+}
+
+/// Fetches fresh rates; on failure the previously cached rates stay in use.
+#[tauri::command]
+pub async fn refresh_rates(app: AppHandle) -> Result<Rates, String> {
+    refresh(&app).await
+}
@@ -0,0 +1,147 @@
+//! A small recursive-descent evaluator for `+ - * / % ^` and parentheses.
+
+/// Evaluates an arithmetic expression. Plain numbers are not expressions,
+/// so they yield `None` rather than echoing themselves back.
+pub fn evaluate(text: &str) -> Option<f64> {
+    let tokens = tokenize(text)?;
+    if !tokens.iter().any(|t| matches!(t, Token::Op(_))) {
+        return None;
+    }
+
+    let mut parser = Parser { tokens: &tokens, pos: 0 };
+    let value = parser.expression()?;
+    (parser.pos == tokens.len() && value.is_finite()).then_some(value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f64),
+    Op(char),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    match c {
+                        '0'..='9' | '.' => number.push(c),
+                        // Thousands separators
+                        ',' | '_' => {}
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse().ok()?));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '×' | 'x' => {
+                tokens.push(Token::Op('*'));
+                chars.next();
+            }
+            '÷' => {
+                tokens.push(Token::Op('/'));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.power()?;
+        while let Some(Token::Op(op @ ('*' | '/' | '%'))) = self.peek() {
+            self.pos += 1;
+            let rhs = self.power()?;
+            value = match op {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Some(value)
+    }
+
+    /// Right-associative, binds tighter than unary minus on its left.
+    fn power(&mut self) -> Option<f64> {
+        let base = self.unary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            let exponent = self.power()?;
+            return Some(base.powf(exponent));
+        }
+        Some(base)
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        match self.peek()? {
+            Token::Op('-') => {
+                self.pos += 1;
+                Some(-self.unary()?)
+            }
+            Token::Op('+') => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Option<f64> {
+        match self.next()? {
+            Token::Number(n) => Some(n),
+            Token::Open => {
+                let value = self.expression()?;
+                (self.next()? == Token::Close).then_some(value)
+            }
+            _ => None,
+        }
+    }
+}
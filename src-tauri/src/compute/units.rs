@@ -0,0 +1,91 @@
+//! Quantity conversion between metric and imperial units.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+use super::format_number;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Speed,
+    Temperature,
+}
+
+struct Unit {
+    symbols: &'static [&'static str],
+    dimension: Dimension,
+    /// Multiplier to the dimension's base unit (m, kg, l, m/s).
+    factor: f64,
+    /// Symbol of the unit this one converts to by default.
+    converts_to: &'static str,
+}
+
+const UNITS: &[Unit] = &[
+    Unit { symbols: &["mm", "millimeter", "millimeters"], dimension: Dimension::Length, factor: 0.001, converts_to: "in" },
+    Unit { symbols: &["cm", "centimeter", "centimeters"], dimension: Dimension::Length, factor: 0.01, converts_to: "in" },
+    Unit { symbols: &["m", "meter", "meters", "metre", "metres"], dimension: Dimension::Length, factor: 1.0, converts_to: "ft" },
+    Unit { symbols: &["km", "kilometer", "kilometers", "kilometre", "kilometres"], dimension: Dimension::Length, factor: 1000.0, converts_to: "mi" },
+    Unit { symbols: &["in", "inch", "inches", "\""], dimension: Dimension::Length, factor: 0.0254, converts_to: "cm" },
+    Unit { symbols: &["ft", "foot", "feet", "'"], dimension: Dimension::Length, factor: 0.3048, converts_to: "m" },
+    Unit { symbols: &["yd", "yard", "yards"], dimension: Dimension::Length, factor: 0.9144, converts_to: "m" },
+    Unit { symbols: &["mi", "mile", "miles"], dimension: Dimension::Length, factor: 1609.344, converts_to: "km" },
+    Unit { symbols: &["g", "gram", "grams"], dimension: Dimension::Mass, factor: 0.001, converts_to: "oz" },
+    Unit { symbols: &["kg", "kilogram", "kilograms"], dimension: Dimension::Mass, factor: 1.0, converts_to: "lb" },
+    Unit { symbols: &["oz", "ounce", "ounces"], dimension: Dimension::Mass, factor: 0.028349523125, converts_to: "g" },
+    Unit { symbols: &["lb", "lbs", "pound", "pounds"], dimension: Dimension::Mass, factor: 0.45359237, converts_to: "kg" },
+    Unit { symbols: &["ml", "milliliter", "milliliters"], dimension: Dimension::Volume, factor: 0.001, converts_to: "fl oz" },
+    Unit { symbols: &["l", "liter", "liters", "litre", "litres"], dimension: Dimension::Volume, factor: 1.0, converts_to: "gal" },
+    Unit { symbols: &["fl oz"], dimension: Dimension::Volume, factor: 0.0295735295625, converts_to: "ml" },
+    Unit { symbols: &["gal", "gallon", "gallons"], dimension: Dimension::Volume, factor: 3.785411784, converts_to: "l" },
+    Unit { symbols: &["km/h", "kph"], dimension: Dimension::Speed, factor: 1.0 / 3.6, converts_to: "mph" },
+    Unit { symbols: &["mph"], dimension: Dimension::Speed, factor: 0.44704, converts_to: "km/h" },
+    Unit { symbols: &["°c", "c", "celsius"], dimension: Dimension::Temperature, factor: 1.0, converts_to: "°F" },
+    Unit { symbols: &["°f", "f", "fahrenheit"], dimension: Dimension::Temperature, factor: 1.0, converts_to: "°C" },
+    Unit { symbols: &["kelvin"], dimension: Dimension::Temperature, factor: 1.0, converts_to: "°C" },
+];
+
+fn quantity_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"^(-?\d[\d,]*(?:\.\d+)?)\s*(°\s*[CcFf]|[A-Za-z/ ]+?|["'])$"#).unwrap())
+}
+
+fn find_unit(symbol: &str) -> Option<&'static Unit> {
+    let symbol = symbol.to_lowercase().replace("° ", "°");
+    UNITS.iter().find(|unit| unit.symbols.contains(&symbol.as_str()))
+}
+
+/// Converts a single quantity such as `12 mi` or `37°C` into its usual
+/// counterpart in the other measurement system.
+pub fn convert(text: &str) -> Option<String> {
+    let captures = quantity_pattern().captures(text)?;
+    let value: f64 = captures[1].replace(',', "").parse().ok()?;
+    let from = find_unit(captures[2].trim())?;
+    let to = find_unit(from.converts_to)?;
+
+    let converted = if from.dimension == Dimension::Temperature {
+        from_kelvin(to_kelvin(value, from.symbols[0]), to.symbols[0])
+    } else {
+        value * from.factor / to.factor
+    };
+
+    Some(format!("{} = {} {}", text, format_number(converted), from.converts_to))
+}
+
+fn to_kelvin(value: f64, symbol: &str) -> f64 {
+    match symbol {
+        "°c" => value + 273.15,
+        "°f" => (value - 32.0) * 5.0 / 9.0 + 273.15,
+        _ => value,
+    }
+}
+
+fn from_kelvin(value: f64, symbol: &str) -> f64 {
+    match symbol {
+        "°c" => value - 273.15,
+        "°f" => (value - 273.15) * 9.0 / 5.0 + 32.0,
+        _ => value,
+    }
+}
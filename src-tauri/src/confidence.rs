@@ -0,0 +1,87 @@
+//! How much to trust a capture: whether it was a genuine selection or a
+//! fallback, adjusted for text that looks like garbage. Rules that do
+//! something destructive can require a minimum level.
+
+use serde::{Deserialize, Serialize};
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::text_utils;
+
+/// Longer than this, in characters, is more likely a whole document than
+/// a selection.
+const SUSPICIOUSLY_LONG: usize = 20_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureMethod {
+    /// The app reported a selection (e.g. `AXSelectedText`).
+    Selection,
+    /// Nothing was selected; this is the focused element's value or title.
+    FocusedValue,
+    Hovered,
+    /// Text recognised in an image.
+    Ocr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfidenceLevel {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Confidence {
+    pub method: CaptureMethod,
+    /// 0 to 1.
+    pub score: f32,
+    pub level: ConfidenceLevel,
+}
+
+fn method(selection_type: &SelectionType) -> CaptureMethod {
+    match selection_type {
+        SelectionType::Selected => CaptureMethod::Selection,
+        SelectionType::Focused => CaptureMethod::FocusedValue,
+        SelectionType::Hovered => CaptureMethod::Hovered,
+        SelectionType::Image => CaptureMethod::Ocr,
+    }
+}
+
+pub fn assess(event: &SelectionEvent) -> Confidence {
+    let method = method(&event.selection_type);
+    let mut score: f32 = match method {
+        CaptureMethod::Selection => 1.0,
+        CaptureMethod::FocusedValue => 0.6,
+        CaptureMethod::Ocr => 0.5,
+        CaptureMethod::Hovered => 0.4,
+    };
+
+    let text = event.text.trim();
+    let chars = text.chars().count().max(1);
+    // Control characters or U+FFFD mean binary junk or a bad decode
+    let junk = text
+        .chars()
+        .filter(|c| (c.is_control() && !c.is_whitespace()) || *c == char::REPLACEMENT_CHARACTER)
+        .count();
+    if junk > 0 {
+        score -= 0.2 + junk as f32 / chars as f32;
+    }
+    // A lone ideograph can be a whole word; a lone letter rarely is
+    if chars < 2 && !text.chars().next().is_some_and(text_utils::is_ideographic) {
+        score -= 0.3;
+    }
+    if chars > SUSPICIOUSLY_LONG {
+        score -= 0.2;
+    }
+
+    let score = score.clamp(0.0, 1.0);
+    let level = if score >= 0.8 {
+        ConfidenceLevel::High
+    } else if score >= 0.5 {
+        ConfidenceLevel::Medium
+    } else {
+        ConfidenceLevel::Low
+    };
+    Confidence { method, score, level }
+}
@@ -0,0 +1,76 @@
+//! One shared answer to "are we online?", so integrations don't each find
+//! out by timing out. A TCP connect to a well-known address is tried every
+//! so often; changes are announced as `network-status-changed`, and coming
+//! back online flushes the delivery queue and refreshes stale exchange
+//! rates straight away.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::net::TcpStream;
+
+use crate::compute::currency;
+use crate::{deliveries, settings};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectivitySettings {
+    /// `host:port` to connect to; an IP address so DNS outages count too
+    /// without a lookup hanging the probe.
+    pub probe_address: String,
+    pub interval_secs: u64,
+}
+
+impl Default for ConnectivitySettings {
+    fn default() -> Self {
+        Self {
+            probe_address: "1.1.1.1:443".to_string(),
+            interval_secs: 15,
+        }
+    }
+}
+
+/// Assumed online until a probe says otherwise.
+pub struct NetworkStatus(AtomicBool);
+
+pub fn is_online(app: &AppHandle) -> bool {
+    app.try_state::<NetworkStatus>()
+        .is_none_or(|status| status.0.load(Ordering::SeqCst))
+}
+
+async fn probe(address: &str) -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(address)).await,
+        Ok(Ok(_))
+    )
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(NetworkStatus(AtomicBool::new(true)));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = settings::current(&app).connectivity;
+            let online = probe(&settings.probe_address).await;
+            let was_online = app.state::<NetworkStatus>().0.swap(online, Ordering::SeqCst);
+            if online != was_online {
+                println!("Network is {}", if online { "back" } else { "unreachable" });
+                let _ = app.emit("network-status-changed", online);
+                if online {
+                    deliveries::flush(&app, false).await;
+                    currency::refresh_if_stale(&app).await;
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(settings.interval_secs.max(1))).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_network_status(status: State<'_, NetworkStatus>) -> Result<bool, String> {
+    Ok(status.0.load(Ordering::SeqCst))
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::locations;
+
+/// Longer captures are prose, not a date the user wants converted.
+const MAX_LENGTH: usize = 80;
+const DEFAULT_EVENT_MINUTES: i64 = 60;
+
+/// A date/time found in a capture, normalized to ISO 8601.
+#[derive(Debug, Clone, Serialize)]
+pub struct DateMention {
+    pub original: String,
+    /// In the time zone named in the text (or the local zone).
+    pub iso: String,
+    pub utc: String,
+    pub local: String,
+    pub timezone: String,
+}
+
+/// Common abbreviations; anything else must be an IANA name.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("UTC", "UTC"),
+    ("GMT", "UTC"),
+    ("PST", "America/Los_Angeles"),
+    ("PDT", "America/Los_Angeles"),
+    ("PT", "America/Los_Angeles"),
+    ("MST", "America/Denver"),
+    ("MDT", "America/Denver"),
+    ("MT", "America/Denver"),
+    ("CST", "America/Chicago"),
+    ("CDT", "America/Chicago"),
+    ("CT", "America/Chicago"),
+    ("EST", "America/New_York"),
+    ("EDT", "America/New_York"),
+    ("ET", "America/New_York"),
+    ("BST", "Europe/London"),
+    ("CET", "Europe/Paris"),
+    ("CEST", "Europe/Paris"),
+    ("IST", "Asia/Kolkata"),
+    ("WIB", "Asia/Jakarta"),
+    ("SGT", "Asia/Singapore"),
+    ("JST", "Asia/Tokyo"),
+    ("AEST", "Australia/Sydney"),
+    ("AEDT", "Australia/Sydney"),
+];
+
+fn timezone_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b(?:[A-Z]{2,4}|[A-Z][A-Za-z]+/[A-Za-z_]+(?:/[A-Za-z_]+)?)\b").unwrap())
+}
+
+fn time_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?i)\b\d{1,2}(?:[:.]\d{2})?\s?(?:am|pm)\b|\b\d{1,2}:\d{2}(?::\d{2})?\b").unwrap())
+}
+
+fn lookup_timezone(token: &str) -> Option<Tz> {
+    let name = ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == token)
+        .map_or(token, |(_, name)| name);
+    name.parse().ok()
+}
+
+/// Parses phrases like "3pm PST Thursday" or "2024-06-01 14:30 Europe/Berlin".
+pub fn detect(text: &str) -> Option<DateMention> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > MAX_LENGTH {
+        return None;
+    }
+
+    // Pull the zone and time out so the rest reads as "<date> <time>",
+    // the order the natural-language parser expects.
+    let zone_match = timezone_pattern().find_iter(text).find(|m| lookup_timezone(m.as_str()).is_some());
+    let zone = zone_match.and_then(|m| lookup_timezone(m.as_str()));
+    let without_zone = match zone_match {
+        Some(m) => format!("{} {}", &text[..m.start()], &text[m.end()..]),
+        None => text.to_string(),
+    };
+
+    let time = time_pattern().find(&without_zone).map(|m| m.as_str().replace(' ', "").to_lowercase());
+    let date = time_pattern().replace(&without_zone, " ");
+    let date = date
+        .split_whitespace()
+        .filter(|word| !matches!(word.to_lowercase().as_str(), "at" | "on" | "@"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let phrase = format!("{} {}", date, time.unwrap_or_default());
+
+    match zone {
+        Some(tz) => parse_in(phrase.trim(), tz, text),
+        None => parse_in(phrase.trim(), Local, text),
+    }
+}
+
+fn parse_in<Z: TimeZone>(phrase: &str, tz: Z, original: &str) -> Option<DateMention>
+where
+    Z::Offset: std::fmt::Display,
+{
+    let now = Utc::now().with_timezone(&tz);
+    let parsed = interim::parse_date_string(phrase, now, interim::Dialect::Us).ok()?;
+    Some(DateMention {
+        original: original.to_string(),
+        iso: parsed.to_rfc3339(),
+        utc: parsed.with_timezone(&Utc).to_rfc3339(),
+        local: parsed.with_timezone(&Local).to_rfc3339(),
+        timezone: parsed.offset().to_string(),
+    })
+}
+
+/// Minimal single-event iCalendar document.
+fn to_ics(start: DateTime<Utc>, minutes: i64, summary: &str, description: &str) -> String {
+    let format = |dt: DateTime<Utc>| dt.format("%Y%m%dT%H%M%SZ").to_string();
+    let escape = |value: &str| {
+        value
+            .replace('\\', "\\\\")
+            .replace(';', "\\;")
+            .replace(',', "\\,")
+            .replace('\n', "\\n")
+    };
+
+    let lines = [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//ACMI Desktop//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@acmi-desktop", start.timestamp_millis()),
+        format!("DTSTAMP:{}", format(Utc::now())),
+        format!("DTSTART:{}", format(start)),
+        format!("DTEND:{}", format(start + Duration::minutes(minutes))),
+        format!("SUMMARY:{}", escape(summary)),
+        format!("DESCRIPTION:{}", escape(description)),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ];
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Writes an ICS file for an event at `mention` to the app cache dir and
+/// opens it with the default calendar app. Returns the file's path.
+pub fn open_event(
+    app: &AppHandle,
+    mention: &DateMention,
+    duration_minutes: Option<u32>,
+    summary: &str,
+    description: &str,
+) -> Result<PathBuf, String> {
+    let start = DateTime::parse_from_rfc3339(&mention.utc)
+        .map_err(|e| e.to_string())?
+        .with_timezone(&Utc);
+    let minutes = duration_minutes.map_or(DEFAULT_EVENT_MINUTES, i64::from);
+
+    let dir = locations::cache_dir(app).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("event-{}.ics", start.timestamp()));
+    std::fs::write(&path, to_ics(start, minutes, summary, description)).map_err(|e| e.to_string())?;
+
+    tauri_plugin_opener::open_path(&path, None::<&str>).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn convert_to_local(text: String) -> Result<DateMention, String> {
+    detect(&text).ok_or_else(|| format!("No date or time found in \"{}\"", text))
+}
+
+/// Writes an ICS file for the date in `text` and opens it with the default
+/// calendar app. Returns the file's path.
+#[tauri::command]
+pub async fn add_to_calendar(
+    app: AppHandle,
+    text: String,
+    title: Option<String>,
+    duration_minutes: Option<u32>,
+) -> Result<String, String> {
+    let mention = detect(&text).ok_or_else(|| format!("No date or time found in \"{}\"", text))?;
+    let summary = title.unwrap_or_else(|| text.clone());
+    let path = open_event(&app, &mention, duration_minutes, &summary, &text)?;
+    Ok(path.to_string_lossy().into_owned())
+}
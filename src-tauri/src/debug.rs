@@ -0,0 +1,32 @@
+//! Extra data for bug reports. With `element_dump` on, captures read from
+//! the screen carry what the focused element said about itself (role,
+//! subrole, identifiers), so a report of text captured from the wrong place
+//! in some app comes with what's needed to look into it.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use text_detection::{DetectorError, ElementDump};
+
+use crate::{settings, text_detector};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugSettings {
+    pub element_dump: bool,
+}
+
+/// The focused element's dump, if dumps are turned on. Call right after
+/// the selection was read, while its app still has focus.
+pub fn element_dump(app: &AppHandle) -> Option<ElementDump> {
+    if !settings::current(app).debug.element_dump {
+        return None;
+    }
+    match text_detector::detector(app).focused_element_dump() {
+        Ok(dump) => Some(dump),
+        Err(DetectorError::Unsupported) => None,
+        Err(e) => {
+            eprintln!("Failed to dump the focused element: {}", e);
+            None
+        }
+    }
+}
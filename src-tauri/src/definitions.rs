@@ -0,0 +1,246 @@
+//! App-specific definitions that can be updated without a new release: which
+//! capture adapter handles which app, and extra noise-filter roles and
+//! strings. They come from a remote feed signed with Ed25519, so a fix for
+//! "app X stopped working" can ship as data.
+//!
+//! The feed is `{"payload": "<definitions JSON>", "signature": "<base64>"}`,
+//! the signature being over the payload's bytes. The last accepted feed is
+//! kept in the data dir and verified again on every start. Noise entries are
+//! added to the user's own; adapter entries take precedence over the
+//! built-in mapping.
+
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::IgnoredRole;
+
+use crate::ide::Ide;
+use crate::terminal::Terminal;
+use crate::{connectivity, locations, settings};
+
+const DEFINITIONS_FILE_NAME: &str = "definitions.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DefinitionsSettings {
+    /// Where the signed feed is fetched from. No updates without one.
+    pub feed_url: Option<String>,
+    /// Base64 Ed25519 public key the feed must be signed with.
+    pub public_key: String,
+    /// Check the feed daily. Off means only [`update_definitions`] does.
+    pub auto_update: bool,
+    /// Stay on this feed version: nothing else is accepted, newer or not.
+    pub pinned_version: Option<u64>,
+}
+
+impl Default for DefinitionsSettings {
+    fn default() -> Self {
+        Self {
+            feed_url: None,
+            public_key: String::new(),
+            auto_update: true,
+            pinned_version: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Adapter {
+    /// Plain accessibility, even where a built-in adapter would apply.
+    None,
+    Vscode,
+    Jetbrains,
+    AppleTerminal,
+    Iterm2,
+    Kitty,
+}
+
+impl Adapter {
+    pub fn ide(self) -> Option<Ide> {
+        match self {
+            Adapter::Vscode => Some(Ide::VsCode),
+            Adapter::Jetbrains => Some(Ide::JetBrains),
+            _ => None,
+        }
+    }
+
+    pub fn terminal(self) -> Option<Terminal> {
+        match self {
+            Adapter::AppleTerminal => Some(Terminal::Apple),
+            Adapter::Iterm2 => Some(Terminal::ITerm2),
+            Adapter::Kitty => Some(Terminal::Kitty),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Definitions {
+    /// Increases with every published feed.
+    pub version: u64,
+    /// Bundle identifier to the adapter that handles it, taking precedence
+    /// over the built-in mapping.
+    pub adapters: HashMap<String, Adapter>,
+    /// Extra ignored roles, keyed by OS (`macos`, `windows`).
+    pub ignored_roles: HashMap<String, Vec<IgnoredRole>>,
+    /// Extra noise blocklist entries.
+    pub blocklist: Vec<String>,
+}
+
+impl Definitions {
+    /// The feed's adapter for `bundle_id`, if it names one.
+    pub fn adapter(&self, bundle_id: &str) -> Option<Adapter> {
+        self.adapters.get(bundle_id).copied()
+    }
+
+    pub fn ignored_roles(&self) -> &[IgnoredRole] {
+        self.ignored_roles
+            .get(std::env::consts::OS)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignedFeed {
+    payload: String,
+    signature: String,
+}
+
+impl SignedFeed {
+    fn verify(&self, public_key: &str) -> Result<Definitions, String> {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let key: [u8; 32] = engine
+            .decode(public_key.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("No valid public key configured for the definitions feed")?;
+        let key = VerifyingKey::from_bytes(&key).map_err(|e| format!("Invalid definitions public key: {}", e))?;
+        let signature = engine
+            .decode(self.signature.trim())
+            .map_err(|e| format!("Invalid feed signature: {}", e))?;
+        let signature = Signature::from_slice(&signature).map_err(|e| format!("Invalid feed signature: {}", e))?;
+        key.verify_strict(self.payload.as_bytes(), &signature)
+            .map_err(|_| "Definitions feed signature does not match".to_string())?;
+        serde_json::from_str(&self.payload).map_err(|e| format!("Invalid definitions: {}", e))
+    }
+}
+
+pub struct DefinitionsStore {
+    path: PathBuf,
+    current: Mutex<Definitions>,
+}
+
+impl DefinitionsStore {
+    /// The saved feed, if it still verifies against `public_key`.
+    fn load(dir: &Path, public_key: &str) -> Self {
+        let path = dir.join(DEFINITIONS_FILE_NAME);
+        let current = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<SignedFeed>(&contents).ok())
+            .and_then(|feed| match feed.verify(public_key) {
+                Ok(definitions) => Some(definitions),
+                Err(e) => {
+                    eprintln!("Ignoring saved definitions: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            current: Mutex::new(current),
+        }
+    }
+
+    fn get(&self) -> Definitions {
+        self.current.lock().unwrap().clone()
+    }
+}
+
+/// The definitions in effect; empty until a feed has been accepted.
+pub fn current(app: &AppHandle) -> Definitions {
+    app.try_state::<DefinitionsStore>()
+        .map(|store| store.get())
+        .unwrap_or_default()
+}
+
+/// Fetches and verifies the feed, replacing the current definitions if it
+/// is acceptable. Returns the version now in effect.
+async fn update(app: &AppHandle) -> Result<u64, String> {
+    let settings = settings::current(app).definitions;
+    let url = settings.feed_url.ok_or("No definitions feed configured")?;
+    let store = app.state::<DefinitionsStore>();
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Definitions request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Definitions feed returned {}", response.status()));
+    }
+    let feed: SignedFeed = response.json().await.map_err(|e| e.to_string())?;
+    let definitions = feed.verify(&settings.public_key)?;
+
+    let current = store.get().version;
+    match settings.pinned_version {
+        Some(pinned) if definitions.version != pinned => {
+            return Err(format!("Definitions are pinned to version {}; the feed has {}", pinned, definitions.version))
+        }
+        // Never roll back to an older (possibly replayed) feed
+        None if definitions.version <= current => return Ok(current),
+        _ => {}
+    }
+
+    let contents = serde_json::to_string(&feed).map_err(|e| e.to_string())?;
+    std::fs::write(&store.path, contents).map_err(|e| e.to_string())?;
+    let version = definitions.version;
+    *store.current.lock().unwrap() = definitions;
+    println!("Updated app definitions to version {}", version);
+    let _ = app.emit("definitions-updated", version);
+    Ok(version)
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = locations::data_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    app.manage(DefinitionsStore::load(&dir, &settings::current(app).definitions.public_key));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let settings = settings::current(&app).definitions;
+            if !settings.auto_update || settings.feed_url.is_none() || !connectivity::is_online(&app) {
+                continue;
+            }
+            if let Err(e) = update(&app).await {
+                eprintln!("Failed to update app definitions: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_definitions(store: State<'_, DefinitionsStore>) -> Result<Definitions, String> {
+    Ok(store.get())
+}
+
+/// Checks the feed now, regardless of `auto_update`.
+#[tauri::command]
+pub async fn update_definitions(app: AppHandle) -> Result<u64, String> {
+    update(&app).await
+}
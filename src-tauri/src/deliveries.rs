@@ -0,0 +1,262 @@
+//! Outgoing webhook requests. A request that fails for a reason that might
+//! pass (no network, a timeout, a 5xx or 429) is queued on disk and retried
+//! with exponential backoff until it goes through or gets too old, so
+//! captures made offline still arrive once the connection is back.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::sinks::{self, Sink};
+use crate::{connectivity, history, kiosk, pause, profiles, settings};
+
+const QUEUE_FILE_NAME: &str = "pending_deliveries.json";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// How often due retries are looked for.
+const RETRY_TICK: Duration = Duration::from_secs(30);
+const BASE_BACKOFF_SECS: u64 = 30;
+const MAX_BACKOFF_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliverySettings {
+    /// Queued deliveries older than this are dropped instead of retried.
+    pub max_age_hours: u64,
+}
+
+impl Default for DeliverySettings {
+    fn default() -> Self {
+        Self { max_age_hours: 24 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: u64,
+    pub url: String,
+    pub body: serde_json::Value,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub attempts: u32,
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+}
+
+struct Failure {
+    /// Worth trying again later.
+    retryable: bool,
+    message: String,
+}
+
+fn backoff_secs(attempts: u32) -> u64 {
+    BASE_BACKOFF_SECS
+        .saturating_mul(1 << attempts.min(16))
+        .min(MAX_BACKOFF_SECS)
+}
+
+async fn post(url: &str, body: &serde_json::Value) -> Result<(), Failure> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(body)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| Failure {
+            retryable: true,
+            message: format!("Request failed: {}", e),
+        })?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    Err(Failure {
+        retryable: status.is_server_error() || status.as_u16() == 429,
+        message: format!("Endpoint returned {}", status),
+    })
+}
+
+/// Deliveries waiting for another attempt, persisted per profile.
+pub struct DeliveryQueue {
+    path: Mutex<PathBuf>,
+    pending: Mutex<Vec<Delivery>>,
+}
+
+fn read(dir: &Path) -> (PathBuf, Vec<Delivery>) {
+    let path = dir.join(QUEUE_FILE_NAME);
+    let pending = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    (path, pending)
+}
+
+impl DeliveryQueue {
+    fn load(dir: &Path) -> Self {
+        let (path, pending) = read(dir);
+        Self {
+            path: Mutex::new(path),
+            pending: Mutex::new(pending),
+        }
+    }
+
+    /// Switches to the queue in `dir`, e.g. for another profile.
+    pub fn reload(&self, dir: &Path) {
+        let (path, pending) = read(dir);
+        *self.path.lock().unwrap() = path;
+        *self.pending.lock().unwrap() = pending;
+    }
+
+    fn save(&self, pending: &[Delivery]) {
+        let result = serde_json::to_string(pending)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| std::fs::write(&*self.path.lock().unwrap(), contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Failed to save pending deliveries: {}", e);
+        }
+    }
+
+    fn push(&self, url: String, body: serde_json::Value, error: String) {
+        let mut pending = self.pending.lock().unwrap();
+        let now = history::now_secs() as u64;
+        let id = pending.iter().map(|delivery| delivery.id).max().unwrap_or(0) + 1;
+        pending.push(Delivery {
+            id,
+            url,
+            body,
+            created_at: now,
+            attempts: 1,
+            next_attempt_at: now + backoff_secs(1),
+            last_error: Some(error),
+        });
+        self.save(&pending);
+    }
+
+    fn get(&self) -> Vec<Delivery> {
+        self.pending.lock().unwrap().clone()
+    }
+
+    /// Drops deliveries older than `max_age`, returning how many.
+    fn expire(&self, max_age: u64) -> usize {
+        let mut pending = self.pending.lock().unwrap();
+        let cutoff = (history::now_secs() as u64).saturating_sub(max_age);
+        let before = pending.len();
+        pending.retain(|delivery| delivery.created_at >= cutoff);
+        let expired = before - pending.len();
+        if expired > 0 {
+            self.save(&pending);
+        }
+        expired
+    }
+
+    /// Records the outcome of retrying delivery `id`.
+    fn finish(&self, id: u64, result: Result<(), Failure>) {
+        let mut pending = self.pending.lock().unwrap();
+        match result {
+            Ok(()) => pending.retain(|delivery| delivery.id != id),
+            Err(failure) if !failure.retryable => {
+                eprintln!("Dropping delivery {}: {}", id, failure.message);
+                pending.retain(|delivery| delivery.id != id);
+            }
+            Err(failure) => {
+                if let Some(delivery) = pending.iter_mut().find(|delivery| delivery.id == id) {
+                    delivery.attempts += 1;
+                    delivery.next_attempt_at = history::now_secs() as u64 + backoff_secs(delivery.attempts);
+                    delivery.last_error = Some(failure.message);
+                }
+            }
+        }
+        self.save(&pending);
+    }
+}
+
+/// Queues a delivery for a later attempt and tells the UI.
+fn enqueue(app: &AppHandle, url: String, body: serde_json::Value, error: String) {
+    if let Some(queue) = app.try_state::<DeliveryQueue>() {
+        queue.push(url, body, error);
+        let _ = app.emit("delivery-queued", queue.get().len());
+    }
+}
+
+/// POSTs `body` as JSON to `url` on the webhook sink, queueing it for retry
+/// if that fails for a transient reason or the sink is backed up. Nothing
+/// is sent in kiosk mode or while everything is paused.
+pub fn send(app: &AppHandle, url: String, body: serde_json::Value) {
+    if kiosk::is_locked(app) || pause::is_paused(app) {
+        return;
+    }
+    // Known to be offline: straight to the queue, without waiting on a timeout
+    if !connectivity::is_online(app) {
+        enqueue(app, url, body, "Offline".to_string());
+        return;
+    }
+    let (job_app, job_url, job_body) = (app.clone(), url.clone(), body.clone());
+    let submitted = sinks::submit(app, Sink::Webhook, move || {
+        let Err(failure) = tauri::async_runtime::block_on(post(&job_url, &job_body)) else {
+            return Ok(());
+        };
+        let message = format!("Webhook to {} failed: {}", job_url, failure.message);
+        if failure.retryable {
+            enqueue(&job_app, job_url, job_body, failure.message);
+        }
+        Err(message)
+    });
+    if let Err(e) = submitted {
+        enqueue(app, url, body, e);
+    }
+}
+
+/// Retries every queued delivery. With `only_due`, only those whose
+/// backoff has run out.
+pub async fn flush(app: &AppHandle, only_due: bool) {
+    let Some(queue) = app.try_state::<DeliveryQueue>() else {
+        return;
+    };
+    let max_age = settings::current(app).deliveries.max_age_hours * 60 * 60;
+    let expired = queue.expire(max_age);
+    if expired > 0 {
+        let _ = app.emit("deliveries-expired", expired);
+    }
+
+    let now = history::now_secs() as u64;
+    let due: Vec<Delivery> = queue
+        .get()
+        .into_iter()
+        .filter(|delivery| !only_due || delivery.next_attempt_at <= now)
+        .collect();
+    for delivery in due {
+        let result = post(&delivery.url, &delivery.body).await;
+        queue.finish(delivery.id, result);
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(DeliveryQueue::load(&profiles::data_dir(app)?));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(RETRY_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            if !kiosk::is_locked(&app) && !pause::is_paused(&app) && connectivity::is_online(&app) {
+                flush(&app, true).await;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Deliveries that failed and are waiting to be retried.
+#[tauri::command]
+pub async fn get_pending_deliveries(queue: State<'_, DeliveryQueue>) -> Result<Vec<Delivery>, String> {
+    Ok(queue.get())
+}
+
+/// Retries everything queued now, ignoring backoff.
+#[tauri::command]
+pub async fn retry_deliveries(app: AppHandle) -> Result<Vec<Delivery>, String> {
+    flush(&app, false).await;
+    Ok(app.state::<DeliveryQueue>().get())
+}
@@ -0,0 +1,333 @@
+//! The weekly digest: once a week, at a configured day and hour, the past
+//! seven days of captures summarised by app and by tag, written to a folder
+//! as Markdown or HTML and/or emailed over SMTP.
+//!
+//! Within each group the "top" captures are the ones the user did something
+//! with: copied, annotated or tagged, then the most recent.
+
+use chrono::{Datelike, Duration as ChronoDuration, Local, TimeZone};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::history::{self, HistoryEntry, HistoryStore};
+use crate::{kiosk, pause, profiles, secrets, settings, text_utils};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const PERIOD_SECS: u64 = 7 * 24 * 60 * 60;
+/// When the last scheduled digest went out, in the profile's data dir.
+const LAST_RUN_FILE_NAME: &str = "digest-last-run";
+const TOP_GROUPS: usize = 5;
+const CAPTURES_PER_GROUP: usize = 3;
+const PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFormat {
+    Markdown,
+    Html,
+}
+
+impl DigestFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DigestFormat::Markdown => "md",
+            DigestFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpSettings {
+    pub host: String,
+    /// Defaults to 587 with STARTTLS, 465 without.
+    pub port: Option<u16>,
+    /// Upgrade a plain connection with STARTTLS rather than connecting over
+    /// TLS from the start.
+    pub starttls: bool,
+    pub username: String,
+    /// The password, or a `keychain:` reference to it.
+    pub password: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: None,
+            starttls: true,
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestSettings {
+    pub enabled: bool,
+    /// Day of the week to send on, 0 = Sunday.
+    pub weekday: u8,
+    /// Local hour of the day to send at.
+    pub hour: u8,
+    pub format: DigestFormat,
+    /// Folder the digest is written to, if any.
+    pub folder: Option<String>,
+    /// Where to email the digest, if anywhere.
+    pub smtp: Option<SmtpSettings>,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: 1,
+            hour: 9,
+            format: DigestFormat::Markdown,
+            folder: None,
+            smtp: None,
+        }
+    }
+}
+
+/// What a digest run produced.
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestRun {
+    pub captures: usize,
+    /// The file written, if a folder is configured.
+    pub path: Option<String>,
+    pub emailed: bool,
+}
+
+/// Captures sharing an app or tag, with the ones worth showing.
+struct Group<'a> {
+    name: &'a str,
+    count: usize,
+    top: Vec<&'a HistoryEntry>,
+}
+
+/// Higher for captures the user did more with.
+fn score(entry: &HistoryEntry) -> (u32, u64) {
+    let engagement = entry.copied as u32 * 2 + entry.note.is_some() as u32 * 2 + entry.tags.len().min(3) as u32;
+    (engagement, entry.timestamp)
+}
+
+fn groups<'a>(entries: &'a [HistoryEntry], keys: impl Fn(&'a HistoryEntry) -> Vec<&'a str>) -> Vec<Group<'a>> {
+    let mut by_key: HashMap<&str, Vec<&HistoryEntry>> = HashMap::new();
+    for entry in entries {
+        for key in keys(entry) {
+            by_key.entry(key).or_default().push(entry);
+        }
+    }
+    let mut groups: Vec<Group> = by_key
+        .into_iter()
+        .map(|(name, mut members)| {
+            members.sort_by_key(|entry| std::cmp::Reverse(score(entry)));
+            Group {
+                name,
+                count: members.len(),
+                top: members.into_iter().take(CAPTURES_PER_GROUP).collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(b.name)));
+    groups.truncate(TOP_GROUPS);
+    groups
+}
+
+fn local_date(secs: u64) -> String {
+    Local
+        .timestamp_opt(secs as i64, 0)
+        .single()
+        .map(|dt| dt.format("%a %-d %b").to_string())
+        .unwrap_or_default()
+}
+
+fn title(since: u64, until: u64) -> String {
+    format!("Capture digest, {} – {}", local_date(since), local_date(until))
+}
+
+fn markdown(entries: &[HistoryEntry], since: u64, until: u64) -> String {
+    let mut out = format!("# {}\n\n{} captures this week.\n", title(since, until), entries.len());
+    let sections = [
+        ("By app", groups(entries, |entry| vec![entry.app_name.as_str()])),
+        ("By tag", groups(entries, |entry| entry.tags.iter().map(String::as_str).collect())),
+    ];
+    for (heading, groups) in sections.iter().filter(|(_, groups)| !groups.is_empty()) {
+        out.push_str(&format!("\n## {}\n", heading));
+        for group in groups {
+            out.push_str(&format!("\n### {} ({})\n\n", group.name, group.count));
+            for entry in &group.top {
+                let preview = text_utils::preview(&entry.text, PREVIEW_CHARS);
+                out.push_str(&format!("- {} — *{}*\n", preview, local_date(entry.timestamp)));
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(entries: &[HistoryEntry], since: u64, until: u64) -> String {
+    let title = escape_html(&title(since, until));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0}</title></head><body>\n<h1>{0}</h1>\n\
+         <p>{1} captures this week.</p>\n",
+        title,
+        entries.len()
+    );
+    let sections = [
+        ("By app", groups(entries, |entry| vec![entry.app_name.as_str()])),
+        ("By tag", groups(entries, |entry| entry.tags.iter().map(String::as_str).collect())),
+    ];
+    for (heading, groups) in sections.iter().filter(|(_, groups)| !groups.is_empty()) {
+        out.push_str(&format!("<h2>{}</h2>\n", heading));
+        for group in groups {
+            out.push_str(&format!("<h3>{} ({})</h3>\n<ul>\n", escape_html(group.name), group.count));
+            for entry in &group.top {
+                let preview = text_utils::preview(&entry.text, PREVIEW_CHARS);
+                out.push_str(&format!(
+                    "<li>{} — <em>{}</em></li>\n",
+                    escape_html(&preview),
+                    local_date(entry.timestamp)
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+async fn email(smtp: &SmtpSettings, subject: &str, body: String, format: DigestFormat) -> Result<(), String> {
+    let content_type = match format {
+        DigestFormat::Markdown => ContentType::TEXT_PLAIN,
+        DigestFormat::Html => ContentType::TEXT_HTML,
+    };
+    let message = Message::builder()
+        .from(smtp.from.parse().map_err(|e| format!("Invalid sender address: {}", e))?)
+        .to(smtp.to.parse().map_err(|e| format!("Invalid recipient address: {}", e))?)
+        .subject(subject)
+        .header(content_type)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+
+    let builder = if smtp.starttls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)
+    }
+    .map_err(|e| e.to_string())?;
+    let mut builder = builder.port(smtp.port.unwrap_or(if smtp.starttls { 587 } else { 465 }));
+    if !smtp.username.is_empty() {
+        let password = secrets::resolve(&smtp.password)?;
+        builder = builder.credentials(Credentials::new(smtp.username.clone(), password));
+    }
+    builder.build().send(message).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Builds the digest of the week up to now and sends it wherever it's
+/// configured to go.
+async fn run(app: &AppHandle) -> Result<DigestRun, String> {
+    let settings = settings::current(app).digest;
+    if settings.folder.is_none() && settings.smtp.is_none() {
+        return Err("Set a folder or SMTP server for the digest first".to_string());
+    }
+    let history = app.try_state::<HistoryStore>().ok_or("History is unavailable")?;
+    let until = history::now_secs() as u64;
+    let since = until - PERIOD_SECS;
+    let entries = history.between(since, until).map_err(|e| e.to_string())?;
+    let body = match settings.format {
+        DigestFormat::Markdown => markdown(&entries, since, until),
+        DigestFormat::Html => html(&entries, since, until),
+    };
+
+    let mut path = None;
+    if let Some(folder) = &settings.folder {
+        let folder = PathBuf::from(folder);
+        std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+        let name = format!("digest-{}.{}", Local::now().format("%Y-%m-%d"), settings.format.extension());
+        let file = folder.join(name);
+        std::fs::write(&file, &body).map_err(|e| e.to_string())?;
+        path = Some(file.to_string_lossy().into_owned());
+    }
+    if let Some(smtp) = &settings.smtp {
+        email(smtp, &title(since, until), body, settings.format).await?;
+    }
+    Ok(DigestRun {
+        captures: entries.len(),
+        path,
+        emailed: settings.smtp.is_some(),
+    })
+}
+
+/// The most recent scheduled send time at or before now, in Unix seconds.
+fn last_slot(settings: &DigestSettings) -> Option<u64> {
+    let now = Local::now();
+    let days_back = (now.weekday().num_days_from_sunday() + 7 - settings.weekday as u32 % 7) % 7;
+    let slot = (now.date_naive() - ChronoDuration::days(days_back as i64)).and_hms_opt(settings.hour as u32, 0, 0)?;
+    let slot = Local.from_local_datetime(&slot).earliest()?;
+    let slot = if slot > now { slot - ChronoDuration::days(7) } else { slot };
+    Some(slot.timestamp() as u64)
+}
+
+fn last_run_path(app: &AppHandle) -> Option<PathBuf> {
+    profiles::data_dir(app).ok().map(|dir| dir.join(LAST_RUN_FILE_NAME))
+}
+
+/// Sends the digest whenever a scheduled time has passed since the last
+/// one. Turning the digest on starts the schedule from then, rather than
+/// sending one straight away.
+pub fn schedule(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let settings = settings::current(&app).digest;
+            if !settings.enabled || kiosk::is_locked(&app) || pause::is_paused(&app) {
+                continue;
+            }
+            let Some(path) = last_run_path(&app) else {
+                continue;
+            };
+            let last_run = std::fs::read_to_string(&path).ok().and_then(|s| s.trim().parse::<u64>().ok());
+            let Some(last_run) = last_run else {
+                let _ = std::fs::write(&path, history::now_secs().to_string());
+                continue;
+            };
+            if last_slot(&settings).is_none_or(|slot| last_run >= slot) {
+                continue;
+            }
+            match run(&app).await {
+                Ok(digest) => println!("Sent the weekly digest of {} captures", digest.captures),
+                Err(e) => eprintln!("Failed to send the weekly digest: {}", e),
+            }
+            // A failure waits for next week rather than retrying every check
+            let _ = std::fs::write(&path, history::now_secs().to_string());
+        }
+    });
+}
+
+/// Builds and sends the digest now, without moving the schedule.
+#[tauri::command]
+pub async fn run_digest_now(app: AppHandle) -> Result<DigestRun, String> {
+    run(&app).await
+}
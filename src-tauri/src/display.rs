@@ -0,0 +1,135 @@
+//! Tracks the monitor layout so windows survive displays being unplugged or
+//! resized, and so selection bounds can be mapped to the right screen.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, WebviewWindow};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// Physical pixels, in the virtual desktop's coordinate space.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+}
+
+impl MonitorInfo {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && (x as i64) < self.x as i64 + self.width as i64
+            && (y as i64) < self.y as i64 + self.height as i64
+    }
+}
+
+/// The last monitor layout seen. Anything converting screen coordinates
+/// should read it through [`monitors`] / [`monitor_at`] rather than keeping
+/// its own copy, so a display change invalidates everything at once.
+#[derive(Default)]
+pub struct DisplayLayout(Mutex<Vec<MonitorInfo>>);
+
+fn query(app: &AppHandle) -> Vec<MonitorInfo> {
+    app.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect()
+}
+
+pub fn monitors(app: &AppHandle) -> Vec<MonitorInfo> {
+    app.try_state::<DisplayLayout>()
+        .map(|layout| layout.0.lock().unwrap().clone())
+        .unwrap_or_else(|| query(app))
+}
+
+/// The monitor showing the given physical point, e.g. a selection's origin.
+pub fn monitor_at(app: &AppHandle, x: i32, y: i32) -> Option<MonitorInfo> {
+    monitors(app).into_iter().find(|monitor| monitor.contains(x, y))
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(DisplayLayout(Mutex::new(query(app))));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let current = query(&app);
+            // An empty list is a transient state while displays reconfigure
+            if current.is_empty() {
+                continue;
+            }
+
+            let layout = app.state::<DisplayLayout>();
+            let changed = {
+                let mut cached = layout.0.lock().unwrap();
+                if *cached == current {
+                    false
+                } else {
+                    *cached = current.clone();
+                    true
+                }
+            };
+            if changed {
+                println!("Display configuration changed ({} monitors)", current.len());
+                for window in app.webview_windows().values() {
+                    keep_on_screen(window, &current);
+                }
+                let _ = app.emit("display-changed", &current);
+            }
+        }
+    });
+}
+
+/// Moves a visible window whose centre is no longer on any monitor back
+/// onto the first one.
+fn keep_on_screen(window: &WebviewWindow, monitors: &[MonitorInfo]) {
+    if !window.is_visible().unwrap_or(false) {
+        return;
+    }
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+
+    let center_x = position.x + size.width as i32 / 2;
+    let center_y = position.y + size.height as i32 / 2;
+    if monitors.iter().any(|monitor| monitor.contains(center_x, center_y)) {
+        return;
+    }
+    let Some(target) = monitors.first() else {
+        return;
+    };
+
+    let x = target.x + (target.width.saturating_sub(size.width) / 2) as i32;
+    let y = target.y + (target.height.saturating_sub(size.height) / 2) as i32;
+    if let Err(e) = window.set_position(PhysicalPosition::new(x, y)) {
+        eprintln!("Failed to move window {} back on screen: {}", window.label(), e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_displays(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    Ok(monitors(&app))
+}
+
+/// Which monitor a screen point is on, for placing popups next to a
+/// selection with the right scale factor.
+#[tauri::command]
+pub async fn get_display_at(app: AppHandle, x: i32, y: i32) -> Result<Option<MonitorInfo>, String> {
+    Ok(monitor_at(&app, x, y))
+}
@@ -0,0 +1,67 @@
+//! Whether the app has a Dock icon on macOS. As an accessory it's a
+//! menu-bar-only agent, never in the Dock or the app switcher; as a regular
+//! app it shows up there while the main window is open, and drops back to
+//! the menu bar when it's hidden or closed. Other platforms have no Dock,
+//! so this does nothing there.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::main_window;
+use crate::settings::{self, SettingsStore};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DockPolicy {
+    /// Menu bar only.
+    Accessory,
+    /// In the Dock while the main window is open.
+    #[default]
+    Regular,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockSettings {
+    pub policy: DockPolicy,
+}
+
+/// Applies the policy for the main window's current visibility. Called
+/// whenever the window is shown, hidden or closed.
+pub fn apply(app: &AppHandle) {
+    let window_open = app
+        .get_webview_window(main_window::LABEL)
+        .is_some_and(|window| window.is_visible().unwrap_or(false));
+    let in_dock = settings::current(app).dock.policy == DockPolicy::Regular && window_open;
+    if let Err(e) = platform::set_in_dock(app, in_dock) {
+        eprintln!("Failed to set the activation policy: {}", e);
+    }
+}
+
+/// Switches between a menu-bar-only agent and a Dock app, right away.
+#[tauri::command]
+pub async fn set_activation_policy(app: AppHandle, policy: DockPolicy) -> Result<(), String> {
+    let updated = app.state::<SettingsStore>().update(|s| s.dock.policy = policy)?;
+    let _ = app.emit("settings-changed", &updated.redacted());
+    apply(&app);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use tauri::{ActivationPolicy, AppHandle};
+
+    pub fn set_in_dock(app: &AppHandle, in_dock: bool) -> tauri::Result<()> {
+        let policy = if in_dock { ActivationPolicy::Regular } else { ActivationPolicy::Accessory };
+        app.set_activation_policy(policy)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use tauri::AppHandle;
+
+    pub fn set_in_dock(_app: &AppHandle, _in_dock: bool) -> tauri::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+//! Windows keeps unelevated processes from reading elevated (administrator)
+//! windows, so selections there fail with `ElevationRequired`. The user can
+//! choose to restart the app elevated, through the usual UAC prompt.
+
+use tauri::AppHandle;
+
+/// Relaunches the app as administrator and quits this instance. Fails,
+/// leaving this instance running, if the UAC prompt is declined.
+#[tauri::command]
+pub async fn restart_elevated(app: AppHandle) -> Result<(), String> {
+    platform::relaunch_elevated()?;
+    app.exit(0);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    pub fn relaunch_elevated() -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let args = std::env::args()
+            .skip(1)
+            .map(|arg| format!("\"{}\"", arg.replace('"', "\\\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (verb, exe, args) = (HSTRING::from("runas"), HSTRING::from(exe.as_os_str()), HSTRING::from(args));
+        let result = unsafe {
+            ShellExecuteW(
+                HWND::default(),
+                PCWSTR(verb.as_ptr()),
+                PCWSTR(exe.as_ptr()),
+                PCWSTR(args.as_ptr()),
+                PCWSTR::null(),
+                SW_SHOWNORMAL,
+            )
+        };
+        // Values up to 32 are errors, including the prompt being declined
+        if result.0 as isize <= 32 {
+            return Err("The app was not restarted as administrator".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    pub fn relaunch_elevated() -> Result<(), String> {
+        Err("Restarting elevated is only needed on Windows".to_string())
+    }
+}
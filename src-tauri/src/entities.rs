@@ -0,0 +1,43 @@
+//! Structured things recognised in captures (contacts, phone numbers,
+//! addresses, …) and the actions each one offers.
+
+use serde::{Deserialize, Serialize};
+
+use address::{Geocoder, MapProvider};
+use issues::IssueSettings;
+use securities::QuoteApi;
+
+pub mod address;
+pub mod contact;
+pub mod identifiers;
+pub mod issues;
+pub mod phone;
+pub mod securities;
+pub mod tracking;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntitySettings {
+    /// ISO country code that phone numbers without a country code are read
+    /// as belonging to.
+    pub phone_region: String,
+    /// Where "Open in Maps" goes.
+    pub map_provider: MapProvider,
+    /// Looks up coordinates for captured addresses; off when unset.
+    pub geocoder: Option<Geocoder>,
+    /// Looks up prices for captured tickers and ISINs; off when unset.
+    pub quotes: Option<QuoteApi>,
+    pub issues: IssueSettings,
+}
+
+impl Default for EntitySettings {
+    fn default() -> Self {
+        Self {
+            phone_region: "US".to_string(),
+            map_provider: MapProvider::default(),
+            geocoder: None,
+            quotes: None,
+            issues: IssueSettings::default(),
+        }
+    }
+}
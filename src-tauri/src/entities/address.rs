@@ -0,0 +1,206 @@
+//! Street addresses in captures, with an "Open in Maps" action and, when a
+//! geocoder is configured, coordinates looked up in the background.
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::{kiosk, secrets, settings};
+
+const MAX_LENGTH: usize = 300;
+const GEOCODE_TIMEOUT: Duration = Duration::from_secs(5);
+const GOOGLE_GEOCODE_URL: &str = "https://maps.googleapis.com/maps/api/geocode/json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Address {
+    /// The address as written, on one line.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapProvider {
+    Apple,
+    Google,
+}
+
+impl Default for MapProvider {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            MapProvider::Apple
+        } else {
+            MapProvider::Google
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Geocoder {
+    /// A Nominatim-compatible server; point it at a local instance to
+    /// geocode offline.
+    Nominatim { endpoint: String },
+    /// Google's Geocoding API.
+    Google { api_key: String },
+}
+
+/// A house number next to a street word, in either order: "221B Baker
+/// Street", "1600 Pennsylvania Ave NW", "Hauptstraße 5", "Jl. Sudirman 12".
+fn address_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?x)
+            \b\d{1,5}[a-zA-Z]?\s+(?:[\p{Lu}\d][\p{L}\d.'-]*\s+){1,4}
+                (?i:street|st|avenue|ave|road|rd|boulevard|blvd|lane|ln|drive|dr|way|court|ct|place|pl|square|sq|terrace|parkway|pkwy|highway|hwy)\b\.?
+            | \b[\p{L}.'-]*(?i:straße|strasse|str\.|weg|allee|platz|gasse|laan|straat|gracht)\s+\d{1,5}[a-zA-Z]?\b
+            | \b(?:Via|Viale|Calle|Avenida|Jalan|Jl\.)\s+[\p{L}\s.'-]{2,40}?\s*\d{1,5}[a-zA-Z]?\b
+            | \b\d{1,5}[a-zA-Z]?,?\s+(?i:rue|avenue|boulevard|via|calle)\s+[\p{L}\s.'-]{2,40}",
+        )
+        .unwrap()
+    })
+}
+
+/// The address in `text`, if it has one. The rest of the line (city,
+/// postcode) is kept since maps search better with it.
+pub fn detect(text: &str) -> Option<Address> {
+    let text = text.trim();
+    if text.len() > MAX_LENGTH {
+        return None;
+    }
+    let found = address_pattern().find(text)?;
+    let line_start = text[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+    // Addresses often wrap onto a second line for the city
+    let rest: Vec<&str> = text[line_start..].lines().take(2).collect();
+    Some(Address {
+        text: rest.join(", ").split_whitespace().collect::<Vec<_>>().join(" "),
+    })
+}
+
+fn maps_url(address: &str, provider: MapProvider) -> String {
+    let query = utf8_percent_encode(address, NON_ALPHANUMERIC);
+    match provider {
+        MapProvider::Apple => format!("https://maps.apple.com/?q={}", query),
+        MapProvider::Google => format!("https://www.google.com/maps/search/?api=1&query={}", query),
+    }
+}
+
+#[derive(Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleResponse {
+    results: Vec<GoogleResult>,
+}
+
+#[derive(Deserialize)]
+struct GoogleResult {
+    geometry: GoogleGeometry,
+}
+
+#[derive(Deserialize)]
+struct GoogleGeometry {
+    location: GoogleLocation,
+}
+
+#[derive(Deserialize)]
+struct GoogleLocation {
+    lat: f64,
+    lng: f64,
+}
+
+/// Looks `address` up with `geocoder`. `Ok(None)` means it wasn't found.
+pub async fn geocode(geocoder: &Geocoder, address: &str) -> Result<Option<Coordinates>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(GEOCODE_TIMEOUT)
+        .user_agent(concat!("acmi-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    match geocoder {
+        Geocoder::Nominatim { endpoint } => {
+            let places: Vec<NominatimPlace> = client
+                .get(format!("{}/search", endpoint.trim_end_matches('/')))
+                .query(&[("q", address), ("format", "jsonv2"), ("limit", "1")])
+                .send()
+                .await
+                .map_err(|e| format!("Geocoding request failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(places.into_iter().next().and_then(|place| {
+                Some(Coordinates {
+                    latitude: place.lat.parse().ok()?,
+                    longitude: place.lon.parse().ok()?,
+                })
+            }))
+        }
+        Geocoder::Google { api_key } => {
+            let response: GoogleResponse = client
+                .get(GOOGLE_GEOCODE_URL)
+                .query(&[("address", address), ("key", &secrets::resolve(api_key)?)])
+                .send()
+                .await
+                .map_err(|e| format!("Geocoding request failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(response.results.into_iter().next().map(|result| Coordinates {
+                latitude: result.geometry.location.lat,
+                longitude: result.geometry.location.lng,
+            }))
+        }
+    }
+}
+
+/// Geocodes the address of capture `id` in the background, if a geocoder
+/// is configured, and announces the result as `address-geocoded`.
+pub fn geocode_in_background(app: &AppHandle, id: Option<i64>, address: &Address) {
+    if kiosk::is_locked(app) {
+        return;
+    }
+    let Some(geocoder) = settings::current(app).entities.geocoder else {
+        return;
+    };
+
+    let app = app.clone();
+    let address = address.text.clone();
+    tauri::async_runtime::spawn(async move {
+        match geocode(&geocoder, &address).await {
+            Ok(Some(coordinates)) => {
+                let _ = app.emit("address-geocoded", (id, &address, coordinates));
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to geocode address: {}", e),
+        }
+    });
+}
+
+/// Opens `address` in Apple or Google Maps, or the configured default.
+#[tauri::command]
+pub async fn open_in_maps(app: AppHandle, address: String, provider: Option<MapProvider>) -> Result<(), String> {
+    let provider = provider.unwrap_or(settings::current(&app).entities.map_provider);
+    tauri_plugin_opener::open_url(maps_url(&address, provider), None::<&str>).map_err(|e| e.to_string())
+}
+
+/// Coordinates of `address` from the configured geocoder.
+#[tauri::command]
+pub async fn geocode_address(app: AppHandle, address: String) -> Result<Option<Coordinates>, String> {
+    let geocoder = settings::current(&app)
+        .entities
+        .geocoder
+        .ok_or("Configure a geocoder in settings first")?;
+    geocode(&geocoder, &address).await
+}
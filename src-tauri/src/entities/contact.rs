@@ -0,0 +1,153 @@
+//! Signature blocks: a name followed by some of a title, company, phone
+//! numbers and email addresses, one per line. Recognised ones can be saved
+//! as a vCard or added straight to the address book.
+
+use regex::Regex;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use super::phone;
+use crate::{actions, locations};
+
+const MAX_LINES: usize = 12;
+const MAX_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Contact {
+    pub name: String,
+    pub title: Option<String>,
+    pub organization: Option<String>,
+    pub phones: Vec<String>,
+    pub emails: Vec<String>,
+    pub urls: Vec<String>,
+}
+
+fn email_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap())
+}
+
+fn url_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?i)\b(?:https?://|www\.)[^\s<>]+").unwrap())
+}
+
+/// "Jane Doe", "Dr. Jane van der Berg": a few words, mostly capitalised,
+/// and no digits or punctuation beyond initials.
+fn looks_like_name(line: &str) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if !(2..=5).contains(&words.len()) || line.chars().any(|c| c.is_ascii_digit() || "@:/|,".contains(c)) {
+        return false;
+    }
+    let capitalised = words
+        .iter()
+        .filter(|word| word.chars().next().is_some_and(char::is_uppercase))
+        .count();
+    capitalised * 2 > words.len()
+}
+
+/// Parses `text` as a signature block. Needs a name plus at least one phone
+/// number or email address.
+pub fn detect(text: &str) -> Option<Contact> {
+    let text = text.trim();
+    if text.len() > MAX_LENGTH {
+        return None;
+    }
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    if !(2..=MAX_LINES).contains(&lines.len()) {
+        return None;
+    }
+
+    let mut contact = Contact::default();
+    let mut details = Vec::new();
+    for line in &lines {
+        let emails: Vec<String> = email_pattern().find_iter(line).map(|m| m.as_str().to_string()).collect();
+        let urls: Vec<String> = url_pattern()
+            .find_iter(line)
+            .map(|m| m.as_str().trim_end_matches(['.', ',', ')']).to_string())
+            .filter(|url| !emails.iter().any(|email| email.contains(url.as_str())))
+            .collect();
+        let phones: Vec<String> = phone::find_raw(line).map(str::to_string).collect();
+
+        if emails.is_empty() && urls.is_empty() && phones.is_empty() {
+            if contact.name.is_empty() && looks_like_name(line) {
+                contact.name = line.to_string();
+            } else if !contact.name.is_empty() {
+                details.push(line.to_string());
+            }
+        }
+        contact.emails.extend(emails);
+        contact.urls.extend(urls);
+        contact.phones.extend(phones);
+    }
+
+    if contact.name.is_empty() || (contact.emails.is_empty() && contact.phones.is_empty()) {
+        return None;
+    }
+    // Signatures put the title under the name, then the company
+    let mut details = details.into_iter();
+    contact.title = details.next();
+    contact.organization = details.next();
+    Some(contact)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// vCard 3.0, which every address book imports.
+pub fn to_vcard(contact: &Contact) -> String {
+    let mut names: Vec<&str> = contact.name.split_whitespace().collect();
+    let family = if names.len() > 1 { names.pop().unwrap_or_default() } else { "" };
+    let given = names.join(" ");
+
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("FN:{}", escape(&contact.name)),
+        format!("N:{};{};;;", escape(family), escape(&given)),
+    ];
+    if let Some(title) = &contact.title {
+        lines.push(format!("TITLE:{}", escape(title)));
+    }
+    if let Some(organization) = &contact.organization {
+        lines.push(format!("ORG:{}", escape(organization)));
+    }
+    lines.extend(contact.phones.iter().map(|phone| format!("TEL;TYPE=VOICE:{}", escape(phone))));
+    lines.extend(contact.emails.iter().map(|email| format!("EMAIL;TYPE=INTERNET:{}", escape(email))));
+    lines.extend(contact.urls.iter().map(|url| format!("URL:{}", escape(url))));
+    lines.push("END:VCARD".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Writes a vCard for history entry `id` to the app cache dir.
+fn write_vcard(app: &AppHandle, id: i64) -> Result<PathBuf, String> {
+    let input = actions::input_for_entry(app, id)?;
+    let contact = detect(&input.text).ok_or_else(|| format!("Entry {} doesn't look like a contact", id))?;
+
+    let dir = locations::cache_dir(app).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("contact-{}.vcf", id));
+    std::fs::write(&path, to_vcard(&contact)).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Saves history entry `id` as a vCard and returns the file's path.
+#[tauri::command]
+pub async fn create_vcard(app: AppHandle, id: i64) -> Result<String, String> {
+    write_vcard(&app, id).map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Opens the vCard for history entry `id` with the default address book,
+/// which offers to add it.
+#[tauri::command]
+pub async fn add_to_contacts(app: AppHandle, id: i64) -> Result<(), String> {
+    let path = write_vcard(&app, id)?;
+    tauri_plugin_opener::open_path(&path, None::<&str>).map_err(|e| e.to_string())
+}
@@ -0,0 +1,221 @@
+//! Identifiers developers select out of logs and terminals: UUIDs, git
+//! SHAs and digests, JWTs, API keys and crypto addresses. Checksums are
+//! verified where the format has one, and each can be copied in its
+//! normalised form.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+const MAX_LENGTH: usize = 10_000;
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum IdentifierKind {
+    Uuid,
+    /// 7–40 hex digits; a full SHA-1 is 40.
+    GitSha,
+    Md5,
+    Sha256,
+    Jwt,
+    GithubToken,
+    AwsAccessKey,
+    BitcoinAddress,
+    EthereumAddress,
+}
+
+impl IdentifierKind {
+    /// Credentials, which the UI should mask.
+    fn is_secret(self) -> bool {
+        matches!(self, IdentifierKind::Jwt | IdentifierKind::GithubToken | IdentifierKind::AwsAccessKey)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Identifier {
+    pub kind: IdentifierKind,
+    /// As written.
+    pub value: String,
+    /// Canonical form, e.g. a lowercase hyphenated UUID.
+    pub normalized: String,
+    /// Result of the format's checksum or structural check; `None` when it
+    /// has none (or none we can compute).
+    pub valid: Option<bool>,
+    pub secret: bool,
+}
+
+/// Patterns in priority order: a span matched by an earlier one isn't
+/// looked at again (so a JWT's segments aren't also reported as hex).
+fn patterns() -> &'static [(IdentifierKind, Regex)] {
+    static PATTERNS: OnceLock<Vec<(IdentifierKind, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (IdentifierKind::Jwt, r"\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*"),
+            (IdentifierKind::GithubToken, r"\b(?:ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{36}\b|\bgithub_pat_[A-Za-z0-9_]{82}\b"),
+            (IdentifierKind::AwsAccessKey, r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+            (
+                IdentifierKind::Uuid,
+                r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b",
+            ),
+            (IdentifierKind::EthereumAddress, r"\b0x[0-9a-fA-F]{40}\b"),
+            (IdentifierKind::BitcoinAddress, r"\b[13][1-9A-HJ-NP-Za-km-z]{25,34}\b"),
+            (IdentifierKind::Sha256, r"(?i)\b[0-9a-f]{64}\b"),
+            (IdentifierKind::GitSha, r"(?i)\b[0-9a-f]{40}\b"),
+            (IdentifierKind::Md5, r"(?i)\b[0-9a-f]{32}\b"),
+            (IdentifierKind::GitSha, r"(?i)\b[0-9a-f]{7,12}\b"),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).unwrap()))
+        .collect()
+    })
+}
+
+/// RFC 4122 variant bits, and a version from 1 to 8 (or the nil UUID).
+fn check_uuid(uuid: &str) -> bool {
+    let hex: Vec<char> = uuid.chars().filter(|c| *c != '-').collect();
+    if hex.iter().all(|c| *c == '0') {
+        return true;
+    }
+    let version = hex[12].to_digit(16).unwrap_or(0);
+    let variant = hex[16].to_digit(16).unwrap_or(0);
+    (1..=8).contains(&version) && (0x8..=0xb).contains(&variant)
+}
+
+/// Both the header and payload are base64url JSON, and the header names
+/// an algorithm.
+fn check_jwt(token: &str) -> bool {
+    let mut parts = token.split('.');
+    let decode = |part: Option<&str>| -> Option<serde_json::Value> {
+        let bytes = URL_SAFE_NO_PAD.decode(part?).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    };
+    let header = decode(parts.next());
+    let payload = decode(parts.next());
+    header.is_some_and(|header| header.get("alg").is_some()) && payload.is_some()
+}
+
+fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|a| *a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, (carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    let leading_zeros = text.bytes().take_while(|c| *c == b'1').count();
+    Some([vec![0; leading_zeros], bytes].concat())
+}
+
+/// Base58Check: the last four bytes are the start of the payload's double
+/// SHA-256.
+fn check_bitcoin(address: &str) -> bool {
+    let Some(bytes) = base58_decode(address).filter(|bytes| bytes.len() == 25) else {
+        return false;
+    };
+    let (payload, checksum) = bytes.split_at(21);
+    Sha256::digest(Sha256::digest(payload))[..4] == *checksum
+}
+
+fn identify(kind: IdentifierKind, value: &str) -> Option<Identifier> {
+    let (normalized, valid) = match kind {
+        IdentifierKind::Uuid => (value.to_lowercase(), Some(check_uuid(value))),
+        IdentifierKind::GitSha | IdentifierKind::Md5 | IdentifierKind::Sha256 => {
+            // Short runs are only SHAs if they mix digits and letters, or
+            // every hex word ("decade", "1234567") would count
+            let has_digit = value.chars().any(|c| c.is_ascii_digit());
+            let has_letter = value.chars().any(|c| c.is_ascii_alphabetic());
+            if value.len() <= 12 && !(has_digit && has_letter) {
+                return None;
+            }
+            (value.to_lowercase(), None)
+        }
+        IdentifierKind::Jwt => (value.to_string(), Some(check_jwt(value))),
+        IdentifierKind::BitcoinAddress => {
+            let valid = check_bitcoin(value);
+            // Base58 runs that fail the checksum are just random words
+            if !valid {
+                return None;
+            }
+            (value.to_string(), Some(valid))
+        }
+        // EIP-55's mixed-case checksum needs Keccak, which we don't ship
+        IdentifierKind::EthereumAddress => (value.to_lowercase(), None),
+        IdentifierKind::GithubToken | IdentifierKind::AwsAccessKey => (value.to_string(), None),
+    };
+    Some(Identifier {
+        kind,
+        value: value.to_string(),
+        normalized,
+        valid,
+        secret: kind.is_secret(),
+    })
+}
+
+/// Every identifier in `text`, in order of appearance.
+pub fn detect(text: &str) -> Vec<Identifier> {
+    if text.len() > MAX_LENGTH {
+        return Vec::new();
+    }
+    let mut found: Vec<(usize, usize, Identifier)> = Vec::new();
+    for (kind, pattern) in patterns() {
+        for m in pattern.find_iter(text) {
+            if found.iter().any(|(start, end, _)| m.start() < *end && *start < m.end()) {
+                continue;
+            }
+            if let Some(identifier) = identify(*kind, m.as_str()) {
+                found.push((m.start(), m.end(), identifier));
+            }
+        }
+    }
+    found.sort_by_key(|(start, _, _)| *start);
+    found.into_iter().map(|(_, _, identifier)| identifier).collect()
+}
+
+/// Copies the normalised form of the first identifier in `value`.
+#[tauri::command]
+pub async fn copy_identifier(app: AppHandle, value: String) -> Result<String, String> {
+    let identifier = detect(&value)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No identifier found in \"{}\"", value))?;
+    app.clipboard()
+        .write_text(identifier.normalized.clone())
+        .map_err(|e| e.to_string())?;
+    Ok(identifier.normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_version_and_variant() {
+        assert!(check_uuid("550e8400-e29b-41d4-a716-446655440000"));
+        assert!(check_uuid("00000000-0000-0000-0000-000000000000"));
+        assert!(!check_uuid("550e8400-e29b-01d4-a716-446655440000"));
+        assert!(!check_uuid("550e8400-e29b-41d4-c716-446655440000"));
+    }
+
+    #[test]
+    fn jwt_header_names_an_algorithm() {
+        assert!(check_jwt("eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0.c2ln"));
+        assert!(!check_jwt("eyJ0eXAiOiJKV1QifQ.eyJzdWIiOiIxMjM0NTY3ODkwIn0.c2ln"));
+    }
+
+    #[test]
+    fn bitcoin_base58_check() {
+        assert!(check_bitcoin("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"));
+        assert!(!check_bitcoin("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"));
+    }
+}
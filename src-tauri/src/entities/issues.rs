@@ -0,0 +1,242 @@
+//! Issue and pull request references: `owner/repo#567` for GitHub and
+//! `PROJ-1234` for Jira. With credentials configured, the title and status
+//! are fetched in the background.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::{kiosk, secrets, settings};
+
+const MAX_LENGTH: usize = 5000;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueSettings {
+    /// Personal access token; titles are only fetched when set.
+    pub github_token: Option<String>,
+    /// Jira keys are only recognised when Jira is configured.
+    pub jira: Option<JiraSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JiraSettings {
+    /// e.g. `https://example.atlassian.net`.
+    pub base_url: String,
+    pub email: String,
+    pub api_token: String,
+    /// Project keys to recognise; empty means any `ABC-123`.
+    #[serde(default)]
+    pub projects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tracker {
+    Github,
+    Jira,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueRef {
+    pub tracker: Tracker,
+    /// `owner/repo#567` or `PROJ-1234`.
+    pub key: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueDetails {
+    pub key: String,
+    pub title: String,
+    pub status: String,
+}
+
+fn github_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b([A-Za-z0-9][\w.-]*)/([\w.-]+)#(\d+)\b").unwrap())
+}
+
+fn jira_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b([A-Z][A-Z0-9]{1,9})-(\d{1,6})\b").unwrap())
+}
+
+/// Things shaped like Jira keys that never are.
+const NOT_JIRA: &[&str] = &["UTF", "SHA", "ISO", "RFC", "CVE", "COVID", "MP", "GPT"];
+
+pub fn detect(text: &str, settings: &IssueSettings) -> Vec<IssueRef> {
+    if text.len() > MAX_LENGTH {
+        return Vec::new();
+    }
+    let mut refs: Vec<IssueRef> = github_pattern()
+        .captures_iter(text)
+        .map(|captures| IssueRef {
+            tracker: Tracker::Github,
+            key: captures[0].to_string(),
+            url: format!("https://github.com/{}/{}/issues/{}", &captures[1], &captures[2], &captures[3]),
+        })
+        .collect();
+
+    if let Some(jira) = &settings.jira {
+        refs.extend(
+            jira_pattern()
+                .captures_iter(text)
+                .filter(|captures| {
+                    let project = &captures[1];
+                    if jira.projects.is_empty() {
+                        !NOT_JIRA.contains(&project)
+                    } else {
+                        jira.projects.iter().any(|key| key == project)
+                    }
+                })
+                .map(|captures| IssueRef {
+                    tracker: Tracker::Jira,
+                    key: captures[0].to_string(),
+                    url: format!("{}/browse/{}", jira.base_url.trim_end_matches('/'), &captures[0]),
+                }),
+        );
+    }
+    refs
+}
+
+#[derive(Deserialize)]
+struct GithubIssue {
+    title: String,
+    state: String,
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct JiraIssue {
+    fields: JiraFields,
+}
+
+#[derive(Deserialize)]
+struct JiraFields {
+    summary: String,
+    status: JiraStatus,
+}
+
+#[derive(Deserialize)]
+struct JiraStatus {
+    name: String,
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("acmi-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Title and status of `issue`. `Ok(None)` when there are no credentials
+/// for its tracker.
+pub async fn fetch(settings: &IssueSettings, issue: &IssueRef) -> Result<Option<IssueDetails>, String> {
+    match issue.tracker {
+        Tracker::Github => {
+            let Some(token) = &settings.github_token else {
+                return Ok(None);
+            };
+            let Some(captures) = github_pattern().captures(&issue.key) else {
+                return Ok(None);
+            };
+            let url = format!(
+                "https://api.github.com/repos/{}/{}/issues/{}",
+                &captures[1], &captures[2], &captures[3]
+            );
+            let response = client()?
+                .get(url)
+                .bearer_auth(secrets::resolve(token)?)
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await
+                .map_err(|e| format!("GitHub request failed: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("GitHub returned {}", response.status()));
+            }
+            let body: GithubIssue = response.json().await.map_err(|e| e.to_string())?;
+            let kind = if body.pull_request.is_some() { "pull request" } else { "issue" };
+            Ok(Some(IssueDetails {
+                key: issue.key.clone(),
+                title: body.title,
+                status: format!("{} {}", body.state, kind),
+            }))
+        }
+        Tracker::Jira => {
+            let Some(jira) = &settings.jira else {
+                return Ok(None);
+            };
+            let url = format!(
+                "{}/rest/api/2/issue/{}?fields=summary,status",
+                jira.base_url.trim_end_matches('/'),
+                issue.key
+            );
+            let response = client()?
+                .get(url)
+                .basic_auth(&jira.email, Some(secrets::resolve(&jira.api_token)?))
+                .send()
+                .await
+                .map_err(|e| format!("Jira request failed: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Jira returned {}", response.status()));
+            }
+            let body: JiraIssue = response.json().await.map_err(|e| e.to_string())?;
+            Ok(Some(IssueDetails {
+                key: issue.key.clone(),
+                title: body.fields.summary,
+                status: body.fields.status.name,
+            }))
+        }
+    }
+}
+
+/// Fetches details for the issue references of capture `id` in the
+/// background, announcing each as `issue-resolved`.
+pub fn fetch_in_background(app: &AppHandle, id: Option<i64>, issues: &[IssueRef]) {
+    if issues.is_empty() || kiosk::is_locked(app) {
+        return;
+    }
+    let settings = settings::current(app).entities.issues;
+    let app = app.clone();
+    let issues = issues.to_vec();
+    tauri::async_runtime::spawn(async move {
+        for issue in issues {
+            match fetch(&settings, &issue).await {
+                Ok(Some(details)) => {
+                    let _ = app.emit("issue-resolved", (id, details));
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to fetch {}: {}", issue.key, e),
+            }
+        }
+    });
+}
+
+fn find(app: &AppHandle, reference: &str) -> Result<(IssueSettings, IssueRef), String> {
+    let settings = settings::current(app).entities.issues;
+    let issue = detect(reference, &settings)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("\"{}\" isn't an issue reference", reference))?;
+    Ok((settings, issue))
+}
+
+/// Opens `reference` in the browser.
+#[tauri::command]
+pub async fn open_issue(app: AppHandle, reference: String) -> Result<(), String> {
+    let (_, issue) = find(&app, &reference)?;
+    tauri_plugin_opener::open_url(issue.url, None::<&str>).map_err(|e| e.to_string())
+}
+
+/// Title and status of `reference`, if credentials for its tracker are set.
+#[tauri::command]
+pub async fn resolve_issue(app: AppHandle, reference: String) -> Result<Option<IssueDetails>, String> {
+    let (settings, issue) = find(&app, &reference)?;
+    fetch(&settings, &issue).await
+}
@@ -0,0 +1,133 @@
+//! Phone numbers in captures, normalised to E.164 so they can be dialled,
+//! texted or opened in WhatsApp. Numbers written without a country code
+//! are read as belonging to the configured region.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::settings;
+
+const MAX_LENGTH: usize = 2000;
+
+/// Calling codes for `entities.phone_region`, and whether the region's
+/// national numbers start with a trunk `0` that is dropped internationally.
+const REGIONS: &[(&str, &str, bool)] = &[
+    ("US", "1", false),
+    ("CA", "1", false),
+    ("GB", "44", true),
+    ("IE", "353", true),
+    ("DE", "49", true),
+    ("FR", "33", true),
+    ("NL", "31", true),
+    ("BE", "32", true),
+    ("ES", "34", false),
+    ("IT", "39", false),
+    ("CH", "41", true),
+    ("AT", "43", true),
+    ("SE", "46", true),
+    ("PL", "48", false),
+    ("BR", "55", true),
+    ("MX", "52", false),
+    ("IN", "91", true),
+    ("ID", "62", true),
+    ("SG", "65", false),
+    ("MY", "60", true),
+    ("JP", "81", true),
+    ("KR", "82", true),
+    ("CN", "86", true),
+    ("AU", "61", true),
+    ("NZ", "64", true),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhoneNumber {
+    /// As written in the capture.
+    pub original: String,
+    /// `+` followed by the country code and number.
+    pub e164: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneAction {
+    Call,
+    Sms,
+    WhatsApp,
+}
+
+fn phone_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\+?\(?\d[\d\s().-]{5,}\d").unwrap())
+}
+
+/// Dates are digit runs with separators too.
+fn date_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\d{4}-\d{1,2}-\d{1,2}|\d{1,2}[./]\d{1,2}[./]\d{2,4}").unwrap())
+}
+
+/// Phone-number-looking runs in `text`, as written. E.164 allows 7 to 15
+/// digits, which rules out most other numbers.
+pub fn find_raw(text: &str) -> impl Iterator<Item = &str> {
+    phone_pattern()
+        .find_iter(text)
+        .map(|m| m.as_str().trim())
+        .filter(|candidate| (7..=15).contains(&candidate.chars().filter(char::is_ascii_digit).count()))
+        .filter(|candidate| !date_pattern().is_match(candidate))
+}
+
+/// `raw` in E.164, reading national numbers as being in `region`.
+pub fn normalize(raw: &str, region: &str) -> Option<String> {
+    let digits: String = raw.chars().filter(char::is_ascii_digit).collect();
+    let international = if raw.trim_start().starts_with('+') {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else {
+        let (_, code, trunk_zero) = REGIONS.iter().find(|(name, _, _)| name.eq_ignore_ascii_case(region))?;
+        let national = if *trunk_zero {
+            digits.strip_prefix('0').unwrap_or(&digits)
+        } else if *code == "1" && digits.len() == 11 {
+            // NANP numbers are often written with their leading 1
+            digits.strip_prefix('1').unwrap_or(&digits)
+        } else {
+            &digits
+        };
+        format!("{}{}", code, national)
+    };
+    (8..=15).contains(&international.len()).then(|| format!("+{}", international))
+}
+
+/// Every phone number in `text` that can be normalised.
+pub fn detect(text: &str, region: &str) -> Vec<PhoneNumber> {
+    if text.len() > MAX_LENGTH {
+        return Vec::new();
+    }
+    find_raw(text)
+        .filter_map(|raw| {
+            normalize(raw, region).map(|e164| PhoneNumber {
+                original: raw.to_string(),
+                e164,
+            })
+        })
+        .collect()
+}
+
+fn action_url(e164: &str, action: PhoneAction) -> String {
+    match action {
+        PhoneAction::Call => format!("tel:{}", e164),
+        PhoneAction::Sms => format!("sms:{}", e164),
+        PhoneAction::WhatsApp => format!("https://wa.me/{}", e164.trim_start_matches('+')),
+    }
+}
+
+/// Calls, texts or opens a WhatsApp chat with `number`, in whatever app
+/// handles the link.
+#[tauri::command]
+pub async fn open_phone_action(app: AppHandle, number: String, action: PhoneAction) -> Result<(), String> {
+    let region = settings::current(&app).entities.phone_region;
+    let e164 = normalize(&number, &region).ok_or_else(|| format!("\"{}\" isn't a phone number", number))?;
+    tauri_plugin_opener::open_url(action_url(&e164, action), None::<&str>).map_err(|e| e.to_string())
+}
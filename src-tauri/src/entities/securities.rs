@@ -0,0 +1,186 @@
+//! Stock tickers (`$AAPL`, `NASDAQ: MSFT`) and ISINs in captures. With a
+//! quote API configured, the latest price is looked up in the background
+//! for the popup to show.
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::{kiosk, secrets, settings};
+
+const MAX_LENGTH: usize = 2000;
+const QUOTE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityKind {
+    Ticker,
+    Isin,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Security {
+    pub kind: SecurityKind,
+    pub symbol: String,
+    /// For tickers written with one, e.g. `NYSE` in `NYSE: IBM`.
+    pub exchange: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub price: f64,
+    pub currency: Option<String>,
+}
+
+/// A user-supplied quote service. The response is JSON; the price (and
+/// optionally currency) are picked out with JSON pointers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteApi {
+    /// URL with `{symbol}` where the ticker or ISIN goes.
+    pub url: String,
+    /// Sent as a bearer token, if set.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// e.g. `/quoteResponse/result/0/regularMarketPrice`.
+    pub price_pointer: String,
+    #[serde(default)]
+    pub currency_pointer: Option<String>,
+}
+
+fn ticker_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|[^\w$])\$([A-Z]{1,5}(?:\.[A-Z])?)\b|\b(NASDAQ|NYSE|AMEX|LSE|TSX|IDX|XETRA)\s?:\s?([A-Z]{1,5}(?:\.[A-Z])?)\b")
+            .unwrap()
+    })
+}
+
+fn isin_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b[A-Z]{2}[A-Z0-9]{9}\d\b").unwrap())
+}
+
+/// ISIN check digit: letters become two digits (A = 10), then Luhn.
+fn check_isin(isin: &str) -> bool {
+    let digits: Vec<u32> = isin
+        .chars()
+        .flat_map(|c| {
+            let value = c.to_digit(36).unwrap_or(0);
+            if value >= 10 {
+                vec![value / 10, value % 10]
+            } else {
+                vec![value]
+            }
+        })
+        .collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+pub fn detect(text: &str) -> Vec<Security> {
+    if text.len() > MAX_LENGTH {
+        return Vec::new();
+    }
+    let tickers = ticker_pattern().captures_iter(text).filter_map(|captures| {
+        let (symbol, exchange) = match captures.get(1) {
+            Some(symbol) => (symbol, None),
+            None => (captures.get(3)?, captures.get(2)),
+        };
+        Some(Security {
+            kind: SecurityKind::Ticker,
+            symbol: symbol.as_str().to_string(),
+            exchange: exchange.map(|m| m.as_str().to_string()),
+        })
+    });
+    let isins = isin_pattern()
+        .find_iter(text)
+        .filter(|m| check_isin(m.as_str()))
+        .map(|m| Security {
+            kind: SecurityKind::Isin,
+            symbol: m.as_str().to_string(),
+            exchange: None,
+        });
+    tickers.chain(isins).collect()
+}
+
+/// The latest price of `symbol` from `api`.
+pub async fn quote(api: &QuoteApi, symbol: &str) -> Result<Quote, String> {
+    let url = api.url.replace("{symbol}", &utf8_percent_encode(symbol, NON_ALPHANUMERIC).to_string());
+    let mut request = reqwest::Client::new().get(&url).timeout(QUOTE_TIMEOUT);
+    if let Some(key) = &api.api_key {
+        request = request.bearer_auth(secrets::resolve(key)?);
+    }
+    let response = request.send().await.map_err(|e| format!("Quote request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Quote API returned {}", response.status()));
+    }
+    let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+
+    let price = body
+        .pointer(&api.price_pointer)
+        .and_then(|price| price.as_f64().or_else(|| price.as_str()?.parse().ok()))
+        .ok_or_else(|| format!("No price at {} in the quote response", api.price_pointer))?;
+    let currency = api
+        .currency_pointer
+        .as_deref()
+        .and_then(|pointer| body.pointer(pointer))
+        .and_then(|currency| currency.as_str())
+        .map(str::to_string);
+    Ok(Quote {
+        symbol: symbol.to_string(),
+        price,
+        currency,
+    })
+}
+
+/// Looks up quotes for the securities of capture `id` in the background,
+/// if a quote API is configured, announcing each as `security-quote`.
+pub fn quote_in_background(app: &AppHandle, id: Option<i64>, securities: &[Security]) {
+    if kiosk::is_locked(app) {
+        return;
+    }
+    let Some(api) = settings::current(app).entities.quotes else {
+        return;
+    };
+    let symbols: Vec<String> = securities.iter().map(|security| security.symbol.clone()).collect();
+    if symbols.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for symbol in symbols {
+            match quote(&api, &symbol).await {
+                Ok(quote) => {
+                    let _ = app.emit("security-quote", (id, quote));
+                }
+                Err(e) => eprintln!("Failed to fetch quote for {}: {}", symbol, e),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_quote(app: AppHandle, symbol: String) -> Result<Quote, String> {
+    let api = settings::current(&app)
+        .entities
+        .quotes
+        .ok_or("Configure a quote API in settings first")?;
+    quote(&api, &symbol).await
+}
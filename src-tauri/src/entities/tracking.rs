@@ -0,0 +1,207 @@
+//! Parcel tracking numbers from UPS, FedEx, USPS and DHL, recognised by
+//! format and check digit, with a "Track package" action for each.
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+const MAX_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Carrier {
+    Ups,
+    FedEx,
+    Usps,
+    Dhl,
+}
+
+impl Carrier {
+    fn name(self) -> &'static str {
+        match self {
+            Carrier::Ups => "UPS",
+            Carrier::FedEx => "FedEx",
+            Carrier::Usps => "USPS",
+            Carrier::Dhl => "DHL",
+        }
+    }
+
+    fn tracking_url(self, number: &str) -> String {
+        match self {
+            Carrier::Ups => format!("https://www.ups.com/track?tracknum={}", number),
+            Carrier::FedEx => format!("https://www.fedex.com/fedextrack/?trknbr={}", number),
+            Carrier::Usps => format!("https://tools.usps.com/go/TrackConfirmAction?tLabels={}", number),
+            Carrier::Dhl => format!(
+                "https://www.dhl.com/global-en/home/tracking/tracking-express.html?submit=1&tracking-id={}",
+                number
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackingNumber {
+    pub carrier: Carrier,
+    /// Without the spaces it is often printed with.
+    pub number: String,
+    pub url: String,
+}
+
+fn candidate_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)\b1Z\s?(?:[0-9A-Z]\s?){16}\b|\b[A-Z]{2}\d{9}US\b|\b\d(?:\s?\d){9,21}\b").unwrap()
+    })
+}
+
+fn digits(number: &str) -> Option<Vec<u32>> {
+    number.chars().map(|c| c.to_digit(10)).collect()
+}
+
+/// UPS: `1Z`, then 15 characters and a mod-10 check digit over them, with
+/// letters counting as `(code - 63) % 10`.
+fn is_ups(number: &str) -> bool {
+    let Some(body) = number.strip_prefix("1Z").filter(|body| body.len() == 16) else {
+        return false;
+    };
+    let values: Vec<u32> = body
+        .chars()
+        .map(|c| c.to_digit(10).unwrap_or_else(|| (c as u32).wrapping_sub(63) % 10))
+        .collect();
+    let sum: u32 = values[..15]
+        .iter()
+        .enumerate()
+        .map(|(i, value)| if i % 2 == 1 { value * 2 } else { *value })
+        .sum();
+    (10 - sum % 10) % 10 == values[15]
+}
+
+/// FedEx Express: 12 digits, the last a check over the first 11 weighted
+/// 3, 1, 7.
+fn is_fedex(number: &str) -> bool {
+    let Some(values) = digits(number).filter(|values| values.len() == 12) else {
+        return false;
+    };
+    let sum: u32 = values[..11].iter().zip([3, 1, 7].iter().cycle()).map(|(value, weight)| value * weight).sum();
+    sum % 11 % 10 == values[11]
+}
+
+/// USPS: 22-digit IMpb barcodes starting 91–95, with a mod-10 check digit
+/// weighting every other digit from the right by 3.
+fn is_usps(number: &str) -> bool {
+    let Some(values) = digits(number).filter(|values| values.len() == 22 && values[0] == 9 && (1..=5).contains(&values[1]))
+    else {
+        return false;
+    };
+    let sum: u32 = values[..21]
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, value)| if i % 2 == 0 { value * 3 } else { *value })
+        .sum();
+    (10 - sum % 10) % 10 == values[21]
+}
+
+/// USPS international (UPU S10): two letters, eight digits, a check digit
+/// and `US`.
+fn is_usps_international(number: &str) -> bool {
+    if number.len() != 13 || !number.is_ascii() || !number.ends_with("US") {
+        return false;
+    }
+    let Some(values) = digits(&number[2..11]) else {
+        return false;
+    };
+    let sum: u32 = values[..8].iter().zip([8, 6, 4, 2, 3, 5, 9, 7]).map(|(value, weight)| value * weight).sum();
+    let check = match 11 - sum % 11 {
+        10 => 0,
+        11 => 5,
+        check => check,
+    };
+    check == values[8]
+}
+
+/// DHL Express waybills: 10 digits, the last the first nine mod 7.
+fn is_dhl(number: &str) -> bool {
+    number.len() == 10
+        && number.chars().all(|c| c.is_ascii_digit())
+        && number[..9].parse::<u64>().is_ok_and(|serial| serial % 7 == number[9..].parse().unwrap_or(u64::MAX))
+}
+
+/// Tracking numbers in `text`. All-digit formats are only trusted when the
+/// capture is just the number or names the carrier, since any long number
+/// passes a single check digit now and then.
+pub fn detect(text: &str) -> Vec<TrackingNumber> {
+    if text.len() > MAX_LENGTH {
+        return Vec::new();
+    }
+    let lower = text.to_lowercase();
+    let mentions = |carrier: Carrier| lower.contains(&carrier.name().to_lowercase());
+
+    candidate_pattern()
+        .find_iter(text)
+        .filter_map(|m| {
+            let number: String = m.as_str().chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+            let alone = m.as_str().len() == text.trim().len();
+            let carrier = if is_ups(&number) {
+                Carrier::Ups
+            } else if is_usps(&number) || is_usps_international(&number) {
+                Carrier::Usps
+            } else if is_fedex(&number) && (alone || mentions(Carrier::FedEx)) {
+                Carrier::FedEx
+            } else if is_dhl(&number) && (alone || mentions(Carrier::Dhl)) {
+                Carrier::Dhl
+            } else {
+                return None;
+            };
+            Some(TrackingNumber {
+                carrier,
+                url: carrier.tracking_url(&number),
+                number,
+            })
+        })
+        .collect()
+}
+
+/// Opens the carrier's tracking page for `number`.
+#[tauri::command]
+pub async fn track_package(number: String) -> Result<(), String> {
+    let tracking = detect(&number)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("\"{}\" isn't a tracking number I recognise", number))?;
+    tauri_plugin_opener::open_url(tracking.url, None::<&str>).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ups_mod_10() {
+        assert!(is_ups("1Z999AA10123456784"));
+        assert!(!is_ups("1Z999AA10123456785"));
+    }
+
+    #[test]
+    fn fedex_mod_11() {
+        assert!(is_fedex("123456789012"));
+        assert!(!is_fedex("123456789013"));
+    }
+
+    #[test]
+    fn usps_mod_10() {
+        assert!(is_usps("9400111899223197428497"));
+        assert!(!is_usps("9400111899223197428498"));
+    }
+
+    #[test]
+    fn usps_international_mod_11() {
+        assert!(is_usps_international("EE123456785US"));
+        assert!(!is_usps_international("EE123456786US"));
+    }
+
+    #[test]
+    fn dhl_mod_7() {
+        assert!(is_dhl("3318810025"));
+        assert!(!is_dhl("3318810026"));
+    }
+}
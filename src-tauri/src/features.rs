@@ -0,0 +1,152 @@
+//! What can actually work on this machine right now, so the frontend can
+//! hide or grey out features up front instead of failing when they're used.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::plugin::PermissionState;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use text_detection::Detector;
+
+use crate::hotkey::taps;
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Availability {
+    Available,
+    /// Works once the user grants a permission.
+    Permission,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureStatus {
+    pub availability: Availability,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl FeatureStatus {
+    fn available() -> Self {
+        Self {
+            availability: Availability::Available,
+            reason: None,
+        }
+    }
+
+    fn needs_permission(reason: &str) -> Self {
+        Self {
+            availability: Availability::Permission,
+            reason: Some(reason.to_string()),
+        }
+    }
+
+    fn unavailable(reason: impl Into<String>) -> Self {
+        Self {
+            availability: Availability::Unavailable,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+fn selection() -> FeatureStatus {
+    let detector = Detector::new();
+    if !detector.is_supported() {
+        FeatureStatus::unavailable("Selection capture is not supported on this platform")
+    } else if !detector.has_permissions() {
+        FeatureStatus::needs_permission("Accessibility access has not been granted")
+    } else {
+        FeatureStatus::available()
+    }
+}
+
+/// Whether `command` is a path to a file or found on `PATH`.
+fn on_path(command: &str) -> bool {
+    if Path::new(command).components().count() > 1 {
+        return Path::new(command).is_file();
+    }
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| extensions.iter().any(|ext| dir.join(format!("{command}{ext}")).is_file()))
+    })
+}
+
+fn ocr(app: &AppHandle) -> FeatureStatus {
+    if !platform::SUPPORTS_IMAGES {
+        return FeatureStatus::unavailable("Image capture is not supported on this platform");
+    }
+    let command = settings::current(app).images.ocr_command;
+    if !on_path(&command) {
+        return FeatureStatus::unavailable(format!("OCR program {} was not found", command));
+    }
+    if !platform::can_record_screen() {
+        return FeatureStatus::needs_permission("Screen Recording access has not been granted");
+    }
+    FeatureStatus::available()
+}
+
+fn clipboard() -> FeatureStatus {
+    // Copy tracking falls back to reading the text every poll without a
+    // change count, but still works
+    FeatureStatus::available()
+}
+
+fn notifications(app: &AppHandle) -> FeatureStatus {
+    match app.notification().permission_state() {
+        Ok(PermissionState::Granted) => FeatureStatus::available(),
+        Ok(PermissionState::Denied) => FeatureStatus::unavailable("Notifications are turned off for this app"),
+        Ok(_) => FeatureStatus::needs_permission("Notifications have not been allowed yet"),
+        Err(e) => FeatureStatus::unavailable(e.to_string()),
+    }
+}
+
+fn modifier_taps() -> FeatureStatus {
+    match taps::availability() {
+        Ok(()) => FeatureStatus::available(),
+        Err(reason) if cfg!(target_os = "macos") => FeatureStatus::needs_permission(&reason),
+        Err(reason) => FeatureStatus::unavailable(reason),
+    }
+}
+
+/// Availability of each permission-dependent feature, keyed by `selection`,
+/// `ocr`, `clipboard`, `notifications` and `modifier_taps`.
+pub fn availability(app: &AppHandle) -> BTreeMap<&'static str, FeatureStatus> {
+    BTreeMap::from([
+        ("selection", selection()),
+        ("ocr", ocr(app)),
+        ("clipboard", clipboard()),
+        ("notifications", notifications(app)),
+        ("modifier_taps", modifier_taps()),
+    ])
+}
+
+#[tauri::command]
+pub async fn get_feature_availability(app: AppHandle) -> Result<BTreeMap<&'static str, FeatureStatus>, String> {
+    Ok(availability(&app))
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    /// The detector only reports image regions on macOS.
+    pub const SUPPORTS_IMAGES: bool = true;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+    }
+
+    pub fn can_record_screen() -> bool {
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub const SUPPORTS_IMAGES: bool = false;
+
+    pub fn can_record_screen() -> bool {
+        true
+    }
+}
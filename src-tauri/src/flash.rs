@@ -0,0 +1,99 @@
+//! A brief flash confirming a hotkey capture: a window that ignores the
+//! mouse, shown either as a small HUD by the pointer or over the whole
+//! screen, and hidden again after a moment. It's transparent except on
+//! macOS, where that would take the private API and rule out the App Store.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::geometry::{self, Rect};
+use crate::{display, settings};
+
+const WINDOW_LABEL: &str = "flash";
+const FLASH_DURATION: Duration = Duration::from_millis(300);
+/// Logical size of the HUD.
+const HUD_SIZE: (f64, f64) = (160.0, 48.0);
+/// Logical distance between the pointer and the HUD.
+const HUD_OFFSET: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashStyle {
+    Hud,
+    Screen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlashSettings {
+    pub enabled: bool,
+    pub style: FlashStyle,
+}
+
+impl Default for FlashSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            style: FlashStyle::Hud,
+        }
+    }
+}
+
+fn create(app: &AppHandle) -> tauri::Result<tauri::WebviewWindow> {
+    let builder = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("index.html#/flash".into()));
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+    let window = builder
+        .title("Captured")
+        .decorations(false)
+        .shadow(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .visible(false)
+        .build()?;
+    window.set_ignore_cursor_events(true)?;
+    Ok(window)
+}
+
+/// Flashes the overlay if it is turned on. Never takes focus.
+pub fn show(app: &AppHandle) {
+    let settings = settings::current(app).flash;
+    if !settings.enabled {
+        return;
+    }
+    if let Err(e) = flash(app, settings.style) {
+        eprintln!("Failed to show capture flash: {}", e);
+    }
+}
+
+fn flash(app: &AppHandle, style: FlashStyle) -> tauri::Result<()> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => window,
+        None => create(app)?,
+    };
+
+    let cursor = app.cursor_position()?;
+    let Some(monitor) = display::monitor_at(app, cursor.x as i32, cursor.y as i32) else {
+        return Ok(());
+    };
+    let rect = match style {
+        // Below and right of the pointer, on its monitor even right at the edge
+        FlashStyle::Hud => {
+            let pointer = Rect::new(cursor.x + HUD_OFFSET * monitor.scale_factor, cursor.y, 0.0, 0.0);
+            geometry::place_near(&monitor, pointer, HUD_SIZE, HUD_OFFSET)
+        }
+        FlashStyle::Screen => monitor.physical_bounds(),
+    };
+    geometry::apply(&window, &monitor, rect)?;
+    window.show()?;
+    let _ = app.emit_to(WINDOW_LABEL, "capture-flash", style);
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FLASH_DURATION).await;
+        let _ = window.hide();
+    });
+    Ok(())
+}
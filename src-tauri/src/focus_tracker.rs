@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use text_detection::{Detector, DetectorError};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Remembers the last application other than ourselves that was frontmost.
+///
+/// Showing the popup or main window moves focus into our process, after
+/// which the system-wide focused element is one of our own widgets. Captures
+/// triggered in that state should read from the app the user came from.
+#[derive(Default)]
+pub struct FocusTracker {
+    previous_app: Mutex<Option<i32>>,
+}
+
+impl FocusTracker {
+    pub fn previous_app(&self) -> Option<i32> {
+        *self.previous_app.lock().unwrap()
+    }
+
+    fn observe(&self, frontmost: Option<i32>) {
+        if let Some(pid) = frontmost.filter(|pid| !is_own_pid(*pid)) {
+            *self.previous_app.lock().unwrap() = Some(pid);
+        }
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(FocusTracker::default());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let detector = Detector::new();
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            app.state::<FocusTracker>().observe(detector.frontmost_app_pid());
+        }
+    });
+}
+
+/// The app a capture should target: `None` means "whatever is focused"
+/// (the system-wide element), `Some(pid)` means our own app is frontmost
+/// and the capture should be redirected to the previously focused app.
+pub fn capture_target(app: &AppHandle, detector: &Detector) -> Option<i32> {
+    let frontmost = detector.frontmost_app_pid()?;
+    if !is_own_pid(frontmost) {
+        return None;
+    }
+    app.try_state::<FocusTracker>()?.previous_app()
+}
+
+/// Reads the genuine selection, redirected away from our own windows.
+pub fn selected_text(app: &AppHandle, detector: &Detector) -> Result<Option<String>, DetectorError> {
+    match capture_target(app, detector) {
+        Some(pid) => detector.selected_text_in_app(pid),
+        None => detector.selected_text(),
+    }
+}
+
+fn is_own_pid(pid: i32) -> bool {
+    pid as u32 == std::process::id()
+}
@@ -0,0 +1,102 @@
+//! Captures that name a file or folder: metadata for the popup, plus the
+//! "Reveal in Finder/Explorer" and "Open" actions.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileInfo {
+    /// The path with `~` expanded.
+    pub path: String,
+    pub exists: bool,
+    /// `None` when nothing exists at the path.
+    pub kind: Option<FileKind>,
+    /// In bytes; zero for directories.
+    pub size: u64,
+    /// Seconds since the epoch.
+    pub modified: Option<u64>,
+}
+
+/// The capture as an absolute path, expanding a leading `~`, whether or not
+/// anything is there.
+fn as_path(text: &str) -> Option<PathBuf> {
+    let text = text.trim().trim_matches(|c| c == '"' || c == '\'');
+    if text.is_empty() || text.contains('\n') {
+        return None;
+    }
+
+    let path = match text.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME").map(|home| Path::new(&home).join(rest))?,
+        None => PathBuf::from(text),
+    };
+    path.is_absolute().then_some(path)
+}
+
+/// The capture as a path to something on disk.
+pub fn resolve_path(text: &str) -> Option<PathBuf> {
+    as_path(text).filter(|path| path.exists())
+}
+
+/// Pipeline annotation: metadata when the capture looks like a path.
+pub fn inspect(text: &str) -> Option<FileInfo> {
+    let path = as_path(text)?;
+    let path_string = path.to_string_lossy().into_owned();
+    let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+        return Some(FileInfo {
+            path: path_string,
+            exists: false,
+            kind: None,
+            size: 0,
+            modified: None,
+        });
+    };
+
+    let file_type = metadata.file_type();
+    let kind = if file_type.is_symlink() {
+        FileKind::Symlink
+    } else if file_type.is_dir() {
+        FileKind::Directory
+    } else if file_type.is_file() {
+        FileKind::File
+    } else {
+        FileKind::Other
+    };
+
+    Some(FileInfo {
+        path: path_string,
+        exists: true,
+        kind: Some(kind),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs()),
+    })
+}
+
+fn existing(path: &str) -> Result<PathBuf, String> {
+    resolve_path(path).ok_or_else(|| format!("{} does not exist", path))
+}
+
+/// Shows the item selected in Finder / Explorer / the file manager.
+#[tauri::command]
+pub async fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    tauri_plugin_opener::reveal_item_in_dir(existing(&path)?).map_err(|e| e.to_string())
+}
+
+/// Opens the item with its default application.
+#[tauri::command]
+pub async fn open_file(path: String) -> Result<(), String> {
+    tauri_plugin_opener::open_path(existing(&path)?, None::<&str>).map_err(|e| e.to_string())
+}
@@ -0,0 +1,195 @@
+//! Screen coordinates across monitors with different scale factors.
+//!
+//! Positions come in two spaces. Physical pixels are what Tauri reports for
+//! monitors and the cursor, and what UI Automation reports on Windows (the
+//! app is per-monitor DPI aware). Logical points are what macOS
+//! accessibility reports for selection bounds, and how macOS lays displays
+//! out. Every monitor has its own scale factor, so converting between the
+//! two always starts by finding the monitor a point is on.
+
+use serde::{Deserialize, Serialize};
+use tauri::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+use crate::display::MonitorInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self { x, y, width, height }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+}
+
+impl MonitorInfo {
+    pub fn physical_bounds(&self) -> Rect {
+        Rect::new(self.x as f64, self.y as f64, self.width as f64, self.height as f64)
+    }
+
+    /// Each monitor's physical origin is its logical origin times its own
+    /// scale factor, which is how Tauri derives it on macOS.
+    pub fn logical_bounds(&self) -> Rect {
+        let scale = self.scale_factor;
+        Rect::new(
+            self.x as f64 / scale,
+            self.y as f64 / scale,
+            self.width as f64 / scale,
+            self.height as f64 / scale,
+        )
+    }
+}
+
+/// Converts a logical rect to physical pixels using the monitor its origin
+/// is on.
+pub fn to_physical(monitors: &[MonitorInfo], rect: Rect) -> Option<Rect> {
+    let monitor = monitors.iter().find(|monitor| monitor.logical_bounds().contains(rect.x, rect.y))?;
+    let (logical, physical, scale) = (monitor.logical_bounds(), monitor.physical_bounds(), monitor.scale_factor);
+    Some(Rect::new(
+        physical.x + (rect.x - logical.x) * scale,
+        physical.y + (rect.y - logical.y) * scale,
+        rect.width * scale,
+        rect.height * scale,
+    ))
+}
+
+/// Converts a physical rect on `monitor` to logical points.
+fn to_logical(monitor: &MonitorInfo, rect: Rect) -> Rect {
+    let (logical, physical, scale) = (monitor.logical_bounds(), monitor.physical_bounds(), monitor.scale_factor);
+    Rect::new(
+        logical.x + (rect.x - physical.x) / scale,
+        logical.y + (rect.y - physical.y) / scale,
+        rect.width / scale,
+        rect.height / scale,
+    )
+}
+
+/// Selection bounds as the detector reports them, in physical pixels.
+pub fn bounds_to_physical(monitors: &[MonitorInfo], bounds: Rect) -> Option<Rect> {
+    if cfg!(target_os = "macos") {
+        to_physical(monitors, bounds)
+    } else {
+        Some(bounds)
+    }
+}
+
+/// Where a window of logical `size` goes next to physical `anchor` on
+/// `monitor`: below it if there's room, otherwise above, and always fully
+/// on the monitor. `gap` is logical too. Returns physical pixels.
+pub fn place_near(monitor: &MonitorInfo, anchor: Rect, size: (f64, f64), gap: f64) -> Rect {
+    let scale = monitor.scale_factor;
+    let (width, height, gap) = (size.0 * scale, size.1 * scale, gap * scale);
+    let screen = monitor.physical_bounds();
+
+    let below = anchor.bottom() + gap;
+    let above = anchor.y - gap - height;
+    let y = if below + height <= screen.bottom() || above < screen.y { below } else { above };
+    Rect::new(
+        anchor.x.min(screen.right() - width).max(screen.x),
+        y.min(screen.bottom() - height).max(screen.y),
+        width,
+        height,
+    )
+}
+
+/// Moves and sizes `window` to physical `rect` on `monitor`. macOS places
+/// windows in points and converts physical values with the scale of the
+/// screen the window is currently on, which lands it in the wrong place
+/// when moving between screens; convert with the target's scale instead.
+pub fn apply(window: &WebviewWindow, monitor: &MonitorInfo, rect: Rect) -> tauri::Result<()> {
+    if cfg!(target_os = "macos") {
+        let logical = to_logical(monitor, rect);
+        window.set_size(LogicalSize::new(logical.width, logical.height))?;
+        window.set_position(LogicalPosition::new(logical.x, logical.y))
+    } else {
+        window.set_size(PhysicalSize::new(rect.width.round() as u32, rect.height.round() as u32))?;
+        window.set_position(PhysicalPosition::new(rect.x.round() as i32, rect.y.round() as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32, scale_factor: f64) -> MonitorInfo {
+        MonitorInfo {
+            name: None,
+            x,
+            y,
+            width,
+            height,
+            scale_factor,
+        }
+    }
+
+    /// A 1440x900pt Retina laptop with a 1920x1080 1x display to its right.
+    fn mixed() -> Vec<MonitorInfo> {
+        vec![monitor(0, 0, 2880, 1800, 2.0), monitor(1440, 0, 1920, 1080, 1.0)]
+    }
+
+    #[test]
+    fn converts_on_a_retina_monitor() {
+        let physical = to_physical(&mixed(), Rect::new(100.0, 50.0, 200.0, 20.0)).unwrap();
+        assert_eq!(physical, Rect::new(200.0, 100.0, 400.0, 40.0));
+    }
+
+    #[test]
+    fn converts_on_a_secondary_monitor_with_its_own_scale() {
+        let physical = to_physical(&mixed(), Rect::new(1500.0, 10.0, 100.0, 10.0)).unwrap();
+        assert_eq!(physical, Rect::new(1500.0, 10.0, 100.0, 10.0));
+    }
+
+    #[test]
+    fn round_trips_through_physical() {
+        let monitors = vec![monitor(0, 0, 3840, 2160, 1.5), monitor(-1920, 0, 1920, 1080, 1.0)];
+        let rects = [Rect::new(30.0, 40.0, 120.0, 16.0), Rect::new(-900.0, 500.0, 50.0, 50.0)];
+        for (monitor, rect) in monitors.iter().zip(rects) {
+            let back = to_logical(monitor, to_physical(&monitors, rect).unwrap());
+            assert!((back.x - rect.x).abs() < 1e-9 && (back.y - rect.y).abs() < 1e-9);
+            assert!((back.width - rect.width).abs() < 1e-9 && (back.height - rect.height).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn points_off_every_monitor_do_not_convert() {
+        assert_eq!(to_physical(&mixed(), Rect::new(5000.0, 0.0, 1.0, 1.0)), None);
+        assert_eq!(to_physical(&mixed(), Rect::new(0.0, -10.0, 1.0, 1.0)), None);
+    }
+
+    #[test]
+    fn places_below_the_anchor_in_the_monitors_scale() {
+        let screen = monitor(0, 0, 2880, 1800, 2.0);
+        let placed = place_near(&screen, Rect::new(100.0, 100.0, 400.0, 40.0), (300.0, 200.0), 8.0);
+        assert_eq!(placed, Rect::new(100.0, 156.0, 600.0, 400.0));
+    }
+
+    #[test]
+    fn flips_above_near_the_bottom_edge() {
+        let screen = monitor(0, 0, 1920, 1080, 1.0);
+        let placed = place_near(&screen, Rect::new(100.0, 1000.0, 200.0, 20.0), (300.0, 200.0), 10.0);
+        assert_eq!(placed.y, 790.0);
+    }
+
+    #[test]
+    fn stays_on_the_anchors_monitor_at_the_right_edge() {
+        let screen = monitor(1440, 0, 1920, 1080, 1.0);
+        let placed = place_near(&screen, Rect::new(3300.0, 100.0, 50.0, 20.0), (300.0, 200.0), 0.0);
+        assert_eq!(placed.x, 3060.0);
+    }
+}
@@ -0,0 +1,381 @@
+//! Golden fixtures for everything that leaves the process serialized: the
+//! events the frontend listens to, the errors commands return, the settings
+//! file and history rows. Each type is compared against its fixture in
+//! `tests/golden`, so renaming or dropping a field shows up as a failing
+//! test instead of a broken frontend or an unreadable settings file.
+//!
+//! After an intended format change, rewrite the fixtures with
+//! `UPDATE_GOLDEN=1 cargo test golden` and review the diff.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::app_mute::AppMute;
+use crate::backends::{Backend, BackendSwitch};
+use crate::browser_bridge::PageContext;
+use crate::capture::{self, LargeCapture};
+use crate::capture_lengths::LengthAnomaly;
+use crate::display::MonitorInfo;
+use crate::entities::address::{Coordinates, MapProvider};
+use crate::entities::issues::IssueDetails;
+use crate::entities::securities::Quote;
+use crate::flash::FlashStyle;
+use crate::health::{HealthReport, Issue, Severity};
+use crate::history::diff::{DiffOutput, HistoryDiff, WordChange};
+use crate::history::reading_list::ReadingListItem;
+use crate::history::sources::{Recapture, RecaptureStatus};
+use crate::history::HistoryEntry;
+use crate::peers::PeerInfo;
+use crate::regex_tester::{self, LiveRegexResult};
+use crate::rules::DryRun;
+use crate::settings::Settings;
+use crate::spellcheck::TyposFlagged;
+use crate::text_detector::SelectionUpdate;
+use crate::translation::LanguagePair;
+use text_detection::{DetectorError, ImageRegion, SelectionEvent, SelectionType};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name)
+}
+
+fn updating() -> bool {
+    std::env::var_os("UPDATE_GOLDEN").is_some()
+}
+
+/// Checks `value` against `<name>.json` and returns the fixture.
+fn check<T: Serialize>(name: &str, value: &T) -> Value {
+    let path = fixture(&format!("{}.json", name));
+    let actual = serde_json::to_value(value).unwrap();
+    if updating() {
+        std::fs::write(&path, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap();
+    }
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}; run with UPDATE_GOLDEN=1 to create it", path.display(), e));
+    let expected: Value = serde_json::from_str(&contents).unwrap();
+    assert_eq!(actual, expected, "{} no longer matches {}", name, path.display());
+    expected
+}
+
+/// Checks `value` against its fixture, then that the fixture reads back
+/// into the same value.
+fn round_trip<T: Serialize + DeserializeOwned>(name: &str, value: &T) {
+    let expected = check(name, value);
+    let parsed: T = serde_json::from_value(expected.clone())
+        .unwrap_or_else(|e| panic!("{} no longer reads its own fixture: {}", name, e));
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), expected);
+}
+
+fn event(text: &str, selection_type: SelectionType) -> SelectionEvent {
+    SelectionEvent {
+        text: text.to_string(),
+        app_name: "TextEdit".to_string(),
+        timestamp: 1_700_000_000,
+        selection_type,
+    }
+}
+
+#[test]
+fn selection_events() {
+    let events = vec![
+        event("Selected text", SelectionType::Selected),
+        event("Hovered text", SelectionType::Hovered),
+        event("Focused text", SelectionType::Focused),
+        event("", SelectionType::Image),
+    ];
+    round_trip("selection_events", &events);
+}
+
+#[test]
+fn image_region() {
+    let region = ImageRegion {
+        x: 120.0,
+        y: 48.5,
+        width: 640.0,
+        height: 360.25,
+    };
+    round_trip("image_region", &region);
+}
+
+#[test]
+fn detector_errors() {
+    let errors = vec![
+        DetectorError::PermissionDenied,
+        DetectorError::Unsupported,
+        DetectorError::Timeout,
+        DetectorError::ElevationRequired,
+        DetectorError::Platform("AXError -25204".to_string()),
+    ];
+    check("detector_errors", &errors);
+    // Commands return the message, which the frontend shows as is
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    check("detector_error_messages", &messages);
+}
+
+/// The default settings with the parts that differ by OS pinned, so one
+/// fixture holds everywhere.
+fn default_settings() -> Settings {
+    let mut settings = Settings::default();
+    settings.noise.ignored_roles.clear();
+    settings.entities.map_provider = MapProvider::Google;
+    settings
+}
+
+#[test]
+fn settings() {
+    round_trip("settings_default", &default_settings());
+}
+
+/// The settings file on disk, which has to keep loading after an update.
+#[test]
+fn settings_file() {
+    let path = fixture("settings_default.toml");
+    let actual = toml::to_string_pretty(&default_settings()).unwrap();
+    if updating() {
+        std::fs::write(&path, &actual).unwrap();
+    }
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let expected: toml::Value = toml::from_str(&contents).unwrap();
+    assert_eq!(toml::from_str::<toml::Value>(&actual).unwrap(), expected);
+
+    let parsed: Settings = toml::from_str(&contents).unwrap();
+    assert_eq!(
+        serde_json::to_value(&parsed).unwrap(),
+        serde_json::to_value(default_settings()).unwrap()
+    );
+}
+
+#[test]
+fn history_entries() {
+    let entries = vec![
+        HistoryEntry {
+            id: 1,
+            text: "Selected text".to_string(),
+            app_name: "TextEdit".to_string(),
+            selection_type: SelectionType::Selected,
+            timestamp: 1_700_000_000,
+            title: "Untitled.txt".to_string(),
+            tags: Vec::new(),
+            copied: false,
+            note: None,
+            signature: None,
+        },
+        HistoryEntry {
+            id: 2,
+            text: "Focused text".to_string(),
+            app_name: "Safari".to_string(),
+            selection_type: SelectionType::Focused,
+            timestamp: 1_700_000_060,
+            title: "Example Domain".to_string(),
+            tags: vec!["work".to_string(), "later".to_string()],
+            copied: true,
+            note: Some("Check this".to_string()),
+            signature: Some("c2lnbmF0dXJl".to_string()),
+        },
+    ];
+    round_trip("history_entries", &entries);
+}
+
+/// Captures that set off each detector, as the frontend gets them in
+/// `text-selection-changed` and similar events, and sinks see them. Dates
+/// are left out since they're also given in the local time zone.
+#[test]
+fn captures() {
+    let texts = [
+        "Selected text",
+        "#1e90ff",
+        "Jane Doe\nHead of Research\nAcme Corp\njane@example.com\n+1 650-253-0000",
+        "1600 Amphitheatre Parkway, Mountain View, CA 94043",
+        "Your parcel 1Z999AA10123456784 is on its way",
+        "550e8400-e29b-41d4-a716-446655440000",
+        "NASDAQ: AAPL is up, see owner/repo#567",
+    ];
+    let captures: Vec<_> = texts
+        .iter()
+        .map(|text| capture::detect(&default_settings(), event(text, SelectionType::Selected)))
+        .collect();
+    check("captures", &captures);
+}
+
+/// Every structured event payload, keyed by event name.
+#[test]
+fn event_payloads() {
+    let selection = event("Selected text", SelectionType::Selected);
+    let regex = regex::Regex::new(r"(?<word>\w+) text").unwrap();
+    let address = "1600 Amphitheatre Parkway, Mountain View, CA 94043";
+    let payloads: BTreeMap<&str, Value> = BTreeMap::from([
+        (
+            "selection-updated",
+            json!(SelectionUpdate {
+                event: &selection,
+                revisions: 3,
+            }),
+        ),
+        (
+            "selection-large",
+            json!(LargeCapture {
+                body_id: 7,
+                source: "text-selection-changed",
+                id: Some(42),
+                app_name: "TextEdit",
+                selection_type: &SelectionType::Selected,
+                timestamp: 1_700_000_000,
+                size: 2_000_000,
+                preview: "Selected text".to_string(),
+            }),
+        ),
+        (
+            "capture-typos-flagged",
+            json!(TyposFlagged {
+                text: "Selectd text",
+                app_name: "TextEdit",
+                words: vec!["Selectd".to_string()],
+            }),
+        ),
+        (
+            "live-regex-matched",
+            json!(LiveRegexResult {
+                pattern: regex.as_str(),
+                text: &selection.text,
+                app_name: &selection.app_name,
+                matches: regex_tester::find_matches(&regex, &selection.text),
+            }),
+        ),
+        (
+            "rule-dry-run",
+            json!(DryRun {
+                rule_id: "hook".to_string(),
+                would: "POST the capture to http://localhost/hook".to_string(),
+            }),
+        ),
+        (
+            "capture-recaptured",
+            json!(Recapture {
+                id: 42,
+                status: RecaptureStatus::Changed,
+                text: Some("Selected text, edited".to_string()),
+            }),
+        ),
+        (
+            "history-diff",
+            json!(HistoryDiff {
+                old_id: 41,
+                new_id: 42,
+                output: DiffOutput::Words {
+                    changes: vec![
+                        WordChange { tag: "equal", value: "Selected ".to_string() },
+                        WordChange { tag: "insert", value: "text".to_string() },
+                    ],
+                },
+            }),
+        ),
+        (
+            "reading-list-updated",
+            json!(ReadingListItem {
+                id: 3,
+                url: "https://example.com/".to_string(),
+                title: "Example Domain".to_string(),
+                excerpt: "Selected text".to_string(),
+                history_id: Some(42),
+                added_at: 1_700_000_000,
+                updated_at: 1_700_000_060,
+                read_at: None,
+            }),
+        ),
+        (
+            "startup-health",
+            json!(HealthReport {
+                issues: vec![Issue {
+                    check: "permissions",
+                    severity: Severity::Error,
+                    message: "Accessibility access is off".to_string(),
+                    fix: "Turn it on in System Settings".to_string(),
+                }],
+            }),
+        ),
+        (
+            "backend-switched",
+            json!(BackendSwitch {
+                from: Backend::Accessibility,
+                to: Some(Backend::Clipboard),
+                reason: "Timed out".to_string(),
+                failures: 10,
+            }),
+        ),
+        (
+            "capture-length-anomaly",
+            json!(LengthAnomaly {
+                app_name: "TextEdit".to_string(),
+                length: 120_000,
+                typical: 64,
+            }),
+        ),
+        (
+            "display-changed",
+            json!([MonitorInfo {
+                name: Some("Built-in".to_string()),
+                x: 0,
+                y: 0,
+                width: 2880,
+                height: 1800,
+                scale_factor: 2.0,
+            }]),
+        ),
+        (
+            "browser-selection-received",
+            json!(PageContext {
+                url: "https://example.com/".to_string(),
+                title: "Example Domain".to_string(),
+                context: Some("This domain is for use in examples.".to_string()),
+                browser: Some("firefox".to_string()),
+            }),
+        ),
+        (
+            "peer-paired",
+            json!(PeerInfo {
+                id: "peer".to_string(),
+                name: "laptop".to_string(),
+                paired: true,
+                online: true,
+            }),
+        ),
+        (
+            "app-mutes-changed",
+            json!([AppMute { app_name: "Slack".to_string(), until: 1_700_003_600 }]),
+        ),
+        (
+            "translation-pair-changed",
+            json!(LanguagePair { source: "auto".to_string(), target: "en".to_string() }),
+        ),
+        ("capture-flash", json!(FlashStyle::Hud)),
+        (
+            "address-geocoded",
+            json!((Some(42), address, Coordinates { latitude: 37.422, longitude: -122.084 })),
+        ),
+        (
+            "issue-resolved",
+            json!((
+                Some(42),
+                IssueDetails {
+                    key: "owner/repo#567".to_string(),
+                    title: "Crash on start".to_string(),
+                    status: "open".to_string(),
+                }
+            )),
+        ),
+        (
+            "security-quote",
+            json!((
+                Some(42),
+                Quote {
+                    symbol: "AAPL".to_string(),
+                    price: 189.5,
+                    currency: Some("USD".to_string()),
+                }
+            )),
+        ),
+    ]);
+    check("event_payloads", &payloads);
+}
@@ -0,0 +1,234 @@
+//! A gRPC server for tools that embed the detector: start and stop it,
+//! stream captures as they happen and read the history, with the typed
+//! interface in `proto/detector.proto`. Off by default; when on, it listens
+//! on localhost only, and every call has to carry its token, which is
+//! made and kept in the keychain the first time the server starts.
+
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use text_detection::SelectionType;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::capture::Capture;
+use crate::history::{HistoryEntry, HistoryStore};
+use crate::settings::SettingsStore;
+use crate::text_detector::{self, TextDetector};
+use crate::trace::{self, Stage};
+use crate::{kiosk, pause, secrets, settings};
+
+mod proto {
+    tonic::include_proto!("acmi.detector.v1");
+}
+
+use proto::detector_server::{Detector, DetectorServer};
+
+/// Captures a slow subscriber can fall behind by before it misses some.
+const SUBSCRIBER_BACKLOG: usize = 64;
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+/// Keychain name of the token made when none is set.
+const TOKEN_NAME: &str = "grpc-token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Bearer token clients must send, or a `keychain:` reference to it.
+    /// Filled in with a new one on first start if empty.
+    pub token: Option<String>,
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 17844,
+            token: None,
+        }
+    }
+}
+
+/// Captures on their way to `Subscribe` streams.
+struct Subscribers(broadcast::Sender<proto::Capture>);
+
+fn selection_type(selection_type: &SelectionType) -> proto::SelectionType {
+    match selection_type {
+        SelectionType::Selected => proto::SelectionType::Selected,
+        SelectionType::Hovered => proto::SelectionType::Hovered,
+        SelectionType::Focused => proto::SelectionType::Focused,
+        SelectionType::Image => proto::SelectionType::Image,
+    }
+}
+
+fn entry(entry: HistoryEntry) -> proto::HistoryEntry {
+    proto::HistoryEntry {
+        id: entry.id,
+        selection_type: selection_type(&entry.selection_type).into(),
+        text: entry.text,
+        app_name: entry.app_name,
+        timestamp: entry.timestamp,
+        title: entry.title,
+        tags: entry.tags,
+        note: entry.note,
+    }
+}
+
+struct Service {
+    app: AppHandle,
+}
+
+impl Service {
+    fn state(&self) -> proto::DetectionState {
+        let detector = self.app.state::<Mutex<Option<TextDetector>>>();
+        let detecting = detector.lock().unwrap().is_some();
+        proto::DetectionState { detecting }
+    }
+}
+
+#[tonic::async_trait]
+impl Detector for Service {
+    async fn start(&self, _: Request<proto::StartRequest>) -> Result<Response<proto::DetectionState>, Status> {
+        if pause::is_paused(&self.app) {
+            return Err(Status::failed_precondition("Everything is paused"));
+        }
+        {
+            let detector_state = self.app.state::<Mutex<Option<TextDetector>>>();
+            let mut detector = detector_state.lock().unwrap();
+            if detector.is_none() {
+                let text_detector = TextDetector::new(self.app.clone());
+                text_detector.request_permissions().map_err(Status::failed_precondition)?;
+                text_detector.start().map_err(|e| Status::failed_precondition(e.to_string()))?;
+                *detector = Some(text_detector);
+            }
+        }
+        Ok(Response::new(self.state()))
+    }
+
+    async fn stop(&self, _: Request<proto::StopRequest>) -> Result<Response<proto::DetectionState>, Status> {
+        text_detector::pause(&self.app);
+        Ok(Response::new(self.state()))
+    }
+
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<proto::Capture, Status>> + Send>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let apps = request.into_inner().apps;
+        let receiver = self.app.state::<Subscribers>().0.subscribe();
+        // A subscriber that lagged just skips what it missed
+        let stream = BroadcastStream::new(receiver).filter_map(move |capture| {
+            capture
+                .ok()
+                .filter(|capture| apps.is_empty() || apps.contains(&capture.app_name))
+                .map(Ok)
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn history(
+        &self,
+        request: Request<proto::HistoryRequest>,
+    ) -> Result<Response<proto::HistoryResponse>, Status> {
+        if kiosk::is_locked(&self.app) {
+            return Err(Status::permission_denied("History is unavailable in kiosk mode"));
+        }
+        let history = self
+            .app
+            .try_state::<HistoryStore>()
+            .ok_or_else(|| Status::unavailable("History is not available"))?;
+        let request = request.into_inner();
+        let limit = if request.limit == 0 { DEFAULT_HISTORY_LIMIT } else { request.limit };
+        let entries = if request.query.trim().is_empty() {
+            history.recent(limit)
+        } else {
+            history.search(&request.query, limit)
+        }
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::HistoryResponse {
+            entries: entries.into_iter().map(entry).collect(),
+        }))
+    }
+}
+
+/// The token clients must send: the configured one, or a new one kept in
+/// the keychain and referred to from settings from then on.
+fn token(app: &AppHandle, configured: Option<&str>) -> Result<String, String> {
+    if let Some(configured) = configured.filter(|token| !token.trim().is_empty()) {
+        return secrets::resolve(configured);
+    }
+    let token = secrets::get_or_create(TOKEN_NAME, || {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+        Ok(hex::encode(bytes))
+    })?;
+    let store = app.try_state::<SettingsStore>().ok_or("Settings are not loaded")?;
+    store.update(|s| s.grpc.token = Some(secrets::reference(TOKEN_NAME)))?;
+    Ok(token)
+}
+
+/// Starts the server, if enabled.
+pub fn init(app: &AppHandle) {
+    let (sender, _) = broadcast::channel(SUBSCRIBER_BACKLOG);
+    app.manage(Subscribers(sender));
+
+    let settings = settings::current(app).grpc;
+    if !settings.enabled {
+        return;
+    }
+    let token = match token(app, settings.token.as_deref()) {
+        Ok(token) => format!("Bearer {}", token),
+        Err(e) => {
+            eprintln!("Failed to start gRPC server: {}", e);
+            return;
+        }
+    };
+
+    let service = Service { app: app.clone() };
+    let server = DetectorServer::with_interceptor(service, move |request: Request<()>| {
+        match request.metadata().get("authorization").and_then(|value| value.to_str().ok()) {
+            Some(given) if secrets::constant_time_eq(given, &token) => Ok(request),
+            _ => Err(Status::unauthenticated("Missing or wrong token")),
+        }
+    });
+    tauri::async_runtime::spawn(async move {
+        let address = ([127, 0, 0, 1], settings.port).into();
+        if let Err(e) = tonic::transport::Server::builder().add_service(server).serve(address).await {
+            eprintln!("gRPC server on port {} failed: {}", settings.port, e);
+        }
+    });
+}
+
+/// Sends `capture` to subscribers, unless kiosk mode is on.
+pub fn publish(app: &AppHandle, capture: &Capture) {
+    let Some(subscribers) = app.try_state::<Subscribers>() else {
+        return;
+    };
+    if subscribers.0.receiver_count() == 0 || kiosk::is_locked(app) {
+        return;
+    }
+    let sent = subscribers.0.send(proto::Capture {
+        id: capture.id.unwrap_or_default(),
+        text: capture.event.text.clone(),
+        app_name: capture.event.app_name.clone(),
+        selection_type: selection_type(&capture.event.selection_type).into(),
+        timestamp: capture.event.timestamp,
+        annotations_json: serde_json::to_string(capture).unwrap_or_default(),
+    });
+    let outcome = sent
+        .map(|receivers| Some(format!("{} subscriber(s)", receivers)))
+        .map_err(|_| "No subscribers left".to_string());
+    trace::step(app, capture.id, Stage::Sink, "grpc", outcome);
+}
+
+/// The token gRPC clients must send, for setting them up.
+#[tauri::command]
+pub async fn get_grpc_token(app: AppHandle) -> Result<String, String> {
+    token(&app, settings::current(&app).grpc.token.as_deref())
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::fs_actions;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Crc32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HashResult {
+    pub algorithm: HashAlgorithm,
+    /// Lowercase hex.
+    pub digest: String,
+    /// Set when the capture was a path and the file's contents were hashed.
+    pub file: Option<String>,
+}
+
+/// Incremental hasher over any of the supported algorithms.
+enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Self::Md5(md5::Md5::new()),
+            HashAlgorithm::Sha1 => Self::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Crc32 => Self::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Md5(h) => h.update(data),
+            Self::Sha1(h) => h.update(data),
+            Self::Sha256(h) => h.update(data),
+            Self::Crc32(h) => h.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Self::Md5(h) => hex::encode(h.finalize()),
+            Self::Sha1(h) => hex::encode(h.finalize()),
+            Self::Sha256(h) => hex::encode(h.finalize()),
+            Self::Crc32(h) => format!("{:08x}", h.finalize()),
+        }
+    }
+}
+
+fn hash_file(path: &Path, algorithm: HashAlgorithm) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+pub fn hash(text: &str, algorithm: HashAlgorithm) -> Result<HashResult, String> {
+    if let Some(path) = fs_actions::resolve_path(text).filter(|path| path.is_file()) {
+        return Ok(HashResult {
+            algorithm,
+            digest: hash_file(&path, algorithm).map_err(|e| e.to_string())?,
+            file: Some(path.to_string_lossy().into_owned()),
+        });
+    }
+
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(text.as_bytes());
+    Ok(HashResult {
+        algorithm,
+        digest: hasher.finish(),
+        file: None,
+    })
+}
+
+/// Hashes the captured text, or the file it names if it is a path.
+#[tauri::command]
+pub async fn hash_selection(text: String, algorithm: HashAlgorithm) -> Result<HashResult, String> {
+    tauri::async_runtime::spawn_blocking(move || hash(&text, algorithm))
+        .await
+        .map_err(|e| e.to_string())?
+}
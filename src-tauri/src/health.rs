@@ -0,0 +1,161 @@
+//! A quick check of everything that commonly breaks silently, run once at
+//! launch: permissions, global shortcuts, the settings file and the history
+//! database. The result goes out as `startup-health` and stays available
+//! from [`get_startup_health`], so the UI can say "3 issues need attention"
+//! right away.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::features::{self, Availability};
+use crate::history::HistoryStore;
+use crate::hotkey;
+use crate::settings::SettingsStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Something doesn't work and the user should act.
+    Error,
+    /// Works, but not fully.
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    /// What was checked: `permissions`, `hotkeys`, `settings` or `database`.
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// What the user can do about it.
+    pub fix: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Default)]
+pub struct StartupHealth(Mutex<Option<HealthReport>>);
+
+fn permissions(app: &AppHandle, issues: &mut Vec<Issue>) {
+    for (feature, status) in features::availability(app) {
+        let severity = match (feature, status.availability) {
+            (_, Availability::Available) => continue,
+            ("selection", _) => Severity::Error,
+            (_, Availability::Permission) => Severity::Warning,
+            // Not something the user can fix; the feature is simply hidden
+            (_, Availability::Unavailable) => continue,
+        };
+        issues.push(Issue {
+            check: "permissions",
+            severity,
+            message: status.reason.unwrap_or_else(|| format!("{} needs a permission", feature)),
+            fix: format!("Grant the permission {} needs in the system privacy settings", feature),
+        });
+    }
+}
+
+fn hotkeys(app: &AppHandle, issues: &mut Vec<Issue>) {
+    for (purpose, shortcut) in hotkey::configured(app) {
+        let message = match shortcut.parse::<Shortcut>() {
+            Ok(parsed) if app.global_shortcut().is_registered(parsed) => continue,
+            Ok(_) => format!("The {} shortcut {} couldn't be registered", purpose, shortcut),
+            Err(e) => format!("The {} shortcut \"{}\" isn't valid: {}", purpose, shortcut, e),
+        };
+        issues.push(Issue {
+            check: "hotkeys",
+            severity: Severity::Warning,
+            message,
+            fix: "Another app may be using it; pick a different shortcut in settings".to_string(),
+        });
+    }
+}
+
+fn settings_file(app: &AppHandle, issues: &mut Vec<Issue>) {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return;
+    };
+    let problems = match store.issues() {
+        Ok(problems) if problems.is_empty() => return,
+        Ok(problems) => problems,
+        Err(e) => {
+            issues.push(Issue {
+                check: "settings",
+                severity: Severity::Error,
+                message: format!("The settings file couldn't be read: {}", e),
+                fix: "Check the file's permissions".to_string(),
+            });
+            return;
+        }
+    };
+    let (message, severity) = match problems.iter().find(|problem| problem.is_fatal()) {
+        Some(fatal) => (format!("The settings file is invalid, so defaults are in use: {}", fatal), Severity::Error),
+        None => {
+            let message = format!("{} problem(s) in the settings file, first {}", problems.len(), problems[0]);
+            (message, Severity::Warning)
+        }
+    };
+    issues.push(Issue {
+        check: "settings",
+        severity,
+        message,
+        fix: "Fix the file at the lines shown, or save settings from the app to overwrite it".to_string(),
+    });
+}
+
+fn database(app: &AppHandle, issues: &mut Vec<Issue>) {
+    let Some(history) = app.try_state::<HistoryStore>() else {
+        issues.push(Issue {
+            check: "database",
+            severity: Severity::Error,
+            message: "The history database couldn't be opened; captures aren't being saved".to_string(),
+            fix: "Check that the app's data folder is writable, then restart".to_string(),
+        });
+        return;
+    };
+    let problems = match history.quick_check() {
+        Ok(problems) if problems.is_empty() => return,
+        Ok(problems) => problems.join("; "),
+        Err(e) => e.to_string(),
+    };
+    issues.push(Issue {
+        check: "database",
+        severity: Severity::Error,
+        message: format!("The history database is damaged: {}", problems),
+        fix: "Export what you can and restore from a backup".to_string(),
+    });
+}
+
+pub fn check(app: &AppHandle) -> HealthReport {
+    let mut issues = Vec::new();
+    permissions(app, &mut issues);
+    hotkeys(app, &mut issues);
+    settings_file(app, &mut issues);
+    database(app, &mut issues);
+    HealthReport { issues }
+}
+
+/// Runs the check in the background once everything is set up.
+pub fn init(app: &AppHandle) {
+    app.manage(StartupHealth::default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let report = check(&app);
+        if !report.issues.is_empty() {
+            eprintln!("{} issue(s) found at startup", report.issues.len());
+        }
+        *app.state::<StartupHealth>().0.lock().unwrap() = Some(report.clone());
+        let _ = app.emit("startup-health", report);
+    });
+}
+
+/// The report from launch, or a fresh one if it isn't ready yet.
+#[tauri::command]
+pub async fn get_startup_health(app: AppHandle, health: State<'_, StartupHealth>) -> Result<HealthReport, String> {
+    let report = health.0.lock().unwrap().clone();
+    Ok(report.unwrap_or_else(|| check(&app)))
+}
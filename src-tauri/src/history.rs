@@ -0,0 +1,428 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::{kiosk, profiles, system_tray};
+
+pub mod audit;
+pub mod clear;
+pub mod copies;
+pub mod diff;
+pub mod export;
+mod import;
+pub mod integrity;
+pub mod notes;
+pub mod provenance;
+pub mod reading_list;
+pub mod search;
+pub mod similarity;
+pub mod sources;
+pub mod stats;
+pub mod tags;
+pub mod thumbnails;
+mod titles;
+pub mod timeline;
+pub mod trash;
+
+pub use import::ImportSource;
+pub use titles::TitleModel;
+
+mod embedded {
+    use refinery::embed_migrations;
+    embed_migrations!("migrations");
+}
+
+pub(crate) const DB_FILE_NAME: &str = "history.db";
+const ENTRY_COLUMNS: &str = "id, text, app_name, selection_type, timestamp, title,
+    (SELECT group_concat(tag, char(31)) FROM tags WHERE tags.history_id = history.id), copied, note, signature";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Diffs the last two captures.
+    pub diff_shortcut: String,
+    /// Opens the search palette.
+    pub search_shortcut: String,
+    /// Writes entry titles with a model instead of using the first sentence.
+    pub title_model: Option<TitleModel>,
+    /// Deleted entries are purged from the trash after this many days.
+    pub trash_retention_days: u32,
+    /// Watch the clipboard and mark captures that were also copied.
+    pub track_copies: bool,
+    /// Sign each capture with a local key so exports can be verified.
+    pub sign_captures: bool,
+    /// Record which app and window had focus over time; see [`timeline`].
+    pub record_focus_timeline: bool,
+    /// Remember where each capture was read from so it can be recaptured;
+    /// see [`sources`].
+    pub record_sources: bool,
+    /// Screenshots of where text was selected; see [`thumbnails`].
+    pub thumbnails: thumbnails::ThumbnailSettings,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            diff_shortcut: "Alt+Shift+D".to_string(),
+            search_shortcut: "Alt+Shift+Space".to_string(),
+            title_model: None,
+            trash_retention_days: 30,
+            track_copies: false,
+            sign_captures: false,
+            record_focus_timeline: false,
+            record_sources: false,
+            thumbnails: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub text: String,
+    pub app_name: String,
+    pub selection_type: SelectionType,
+    pub timestamp: u64,
+    pub title: String,
+    pub tags: Vec<String>,
+    /// Also copied to the clipboard; see [`copies`].
+    pub copied: bool,
+    /// The user's note; see [`notes`].
+    pub note: Option<String>,
+    /// Set when signing was on; see [`provenance`].
+    pub signature: Option<String>,
+}
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// Opens (or creates) the database in `dir` and brings its schema up to date.
+    pub fn open(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            conn: Mutex::new(open_connection(dir)?),
+        })
+    }
+
+    /// Switches to the database in `dir`, e.g. for another profile.
+    pub fn reopen(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = open_connection(dir)?;
+        *self.conn.lock().unwrap() = conn;
+        Ok(())
+    }
+
+    /// Lets go of the database file, leaving an empty in-memory database
+    /// in its place until [`reopen`](Self::reopen).
+    pub fn close(&self) -> rusqlite::Result<()> {
+        *self.conn.lock().unwrap() = Connection::open_in_memory()?;
+        Ok(())
+    }
+
+    /// Writes a consistent copy of the database to `path`.
+    pub fn snapshot(&self, path: &Path) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+        Ok(())
+    }
+
+    pub fn insert(&self, event: &SelectionEvent) -> rusqlite::Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO history (text, app_name, selection_type, timestamp, title, simhash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                event.text,
+                event.app_name,
+                selection_type_to_str(&event.selection_type),
+                event.timestamp as i64,
+                titles::first_sentence(&event.text),
+                similarity::simhash(&event.text),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Inserts a batch in one transaction, skipping rows that already exist
+    /// with the same text and timestamp. Returns the position in `events`
+    /// and new id of each one added.
+    pub fn insert_many(&self, events: &[SelectionEvent]) -> rusqlite::Result<Vec<(usize, i64)>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut inserted = Vec::new();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO history (text, app_name, selection_type, timestamp, title, simhash)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6
+                 WHERE NOT EXISTS (SELECT 1 FROM history WHERE text = ?1 AND timestamp = ?4)",
+            )?;
+            for (index, event) in events.iter().enumerate() {
+                let added = stmt.execute(params![
+                    event.text,
+                    event.app_name,
+                    selection_type_to_str(&event.selection_type),
+                    event.timestamp as i64,
+                    titles::first_sentence(&event.text),
+                    similarity::simhash(&event.text),
+                ])?;
+                if added > 0 {
+                    inserted.push((index, tx.last_insert_rowid()));
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    pub fn recent(&self, limit: u32) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM history ORDER BY timestamp DESC, id DESC LIMIT ?1",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![limit], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Entries captured in `since..until` (Unix seconds), oldest first.
+    pub fn between(&self, since: u64, until: u64) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM history WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp, id",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![since as i64, until as i64], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Names of the apps captures came from, most recently used first.
+    pub fn recent_apps(&self, limit: u32) -> rusqlite::Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT app_name FROM history GROUP BY app_name ORDER BY MAX(timestamp) DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    pub fn get(&self, id: i64) -> rusqlite::Result<Option<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT {} FROM history WHERE id = ?1", ENTRY_COLUMNS),
+            params![id],
+            entry_from_row,
+        )
+        .optional()
+    }
+
+    pub fn set_title(&self, id: i64, title: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE history SET title = ?1 WHERE id = ?2", params![title, id])?;
+        Ok(())
+    }
+
+    /// SQLite's quick integrity check; empty when the database is sound.
+    pub fn quick_check(&self) -> rusqlite::Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA quick_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let problems = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(problems.into_iter().filter(|problem| problem != "ok").collect())
+    }
+
+    /// Highest migration version applied to the database, if any.
+    pub fn db_version(&self) -> Result<Option<i32>, refinery::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let last = embedded::migrations::runner().get_last_applied_migration(&mut *conn)?;
+        Ok(last.map(|m| m.version()))
+    }
+}
+
+fn open_connection(dir: &Path) -> Result<Connection, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(DB_FILE_NAME);
+    let mut conn = Connection::open(&path)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    search::register_segmenter(&conn)?;
+
+    migrate(&mut conn, &path)?;
+    Ok(conn)
+}
+
+/// Runs pending migrations, snapshotting the existing database first so a
+/// failed or buggy migration never destroys the only copy of a user's data.
+fn migrate(conn: &mut Connection, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let runner = embedded::migrations::runner();
+    let latest = runner.get_migrations().iter().map(|m| m.version()).max();
+    // Fails on a brand new database, before refinery has created its table
+    let applied = runner.get_last_applied_migration(conn).ok().flatten().map(|m| m.version());
+
+    if let (Some(applied), Some(latest)) = (applied, latest) {
+        if applied < latest {
+            let backup = path.with_extension(format!("db.bak-v{}", applied));
+            let _ = std::fs::remove_file(&backup);
+            conn.execute("VACUUM INTO ?1", params![backup.to_string_lossy()])?;
+            println!("Backed up history database to {}", backup.display());
+        }
+    }
+
+    let report = runner.run(conn)?;
+    for migration in report.applied_migrations() {
+        println!("Applied history migration {}", migration);
+    }
+    Ok(())
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let selection_type: String = row.get(3)?;
+    let text: String = row.get(1)?;
+    let title = row
+        .get::<_, Option<String>>(5)?
+        .unwrap_or_else(|| titles::first_sentence(&text));
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        text,
+        app_name: row.get(2)?,
+        selection_type: selection_type_from_str(&selection_type),
+        timestamp: row.get::<_, i64>(4)? as u64,
+        title,
+        tags: row
+            .get::<_, Option<String>>(6)?
+            .map(|tags| tags.split(tags::SEPARATOR).map(str::to_string).collect())
+            .unwrap_or_default(),
+        copied: row.get(7)?,
+        note: row.get(8)?,
+        signature: row.get(9)?,
+    })
+}
+
+pub(crate) fn selection_type_to_str(selection_type: &SelectionType) -> &'static str {
+    match selection_type {
+        SelectionType::Selected => "Selected",
+        SelectionType::Hovered => "Hovered",
+        SelectionType::Focused => "Focused",
+        SelectionType::Image => "Image",
+    }
+}
+
+pub(crate) fn selection_type_from_str(value: &str) -> SelectionType {
+    match value {
+        "Hovered" => SelectionType::Hovered,
+        "Focused" => SelectionType::Focused,
+        "Image" => SelectionType::Image,
+        _ => SelectionType::Selected,
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = profiles::data_dir(app)?;
+    let store = HistoryStore::open(&dir)?;
+    app.manage(store);
+    app.manage(clear::PendingClears::default());
+    integrity::check_on_open(app);
+    similarity::backfill_in_background(app);
+    trash::schedule_purge(app);
+    copies::watch(app);
+    timeline::record(app);
+    Ok(())
+}
+
+/// What every new entry gets once it's in the store, however it got there.
+fn stored(app: &AppHandle, store: &HistoryStore, id: i64, event: &SelectionEvent) {
+    provenance::sign_if_enabled(app, store, id, event);
+    titles::refine_in_background(app, id, &event.text);
+}
+
+/// Unix seconds, as the history tables store them.
+pub(crate) fn now_secs() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+/// Stores a capture if the history store is available and kiosk mode is
+/// off, returning its id.
+pub fn record(app: &AppHandle, event: &SelectionEvent) -> Option<i64> {
+    if kiosk::is_locked(app) {
+        return None;
+    }
+    let store = app.try_state::<HistoryStore>()?;
+    match store.insert(event) {
+        Ok(id) => {
+            stored(app, &store, id, event);
+            system_tray::note_app(app, &event.app_name);
+            Some(id)
+        }
+        Err(e) => {
+            eprintln!("Failed to store capture in history: {}", e);
+            integrity::note_error(app, &e);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_selection_history(
+    history: State<'_, HistoryStore>,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history.recent(limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+/// Concatenates captures in the order given into a new history entry and
+/// copies the result to the clipboard.
+#[tauri::command]
+pub async fn merge_captures(
+    app: AppHandle,
+    history: State<'_, HistoryStore>,
+    ids: Vec<i64>,
+    separator: Option<String>,
+) -> Result<HistoryEntry, String> {
+    if ids.is_empty() {
+        return Err("No captures selected".to_string());
+    }
+
+    let mut parts = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let entry = history
+            .get(*id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("No history entry with id {}", id))?;
+        parts.push(entry.text);
+    }
+    let merged = parts.join(separator.as_deref().unwrap_or("\n\n"));
+
+    let event = SelectionEvent::new(merged.clone(), "Merged", SelectionType::Selected);
+    let id = history.insert(&event).map_err(|e| e.to_string())?;
+    stored(&app, &history, id, &event);
+    app.clipboard().write_text(merged).map_err(|e| e.to_string())?;
+
+    history
+        .get(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Merged entry was not stored".to_string())
+}
+
+#[tauri::command]
+pub async fn import_history(
+    app: AppHandle,
+    history: State<'_, HistoryStore>,
+    source: ImportSource,
+    path: String,
+) -> Result<usize, String> {
+    let events = import::read(source, Path::new(&path))?;
+    let inserted = history.insert_many(&events).map_err(|e| e.to_string())?;
+    // Titles are the heuristic ones; a title model isn't worth a request per imported entry
+    for &(index, id) in &inserted {
+        provenance::sign_if_enabled(&app, &history, id, &events[index]);
+    }
+    Ok(inserted.len())
+}
+
+#[tauri::command]
+pub async fn get_db_version(history: State<'_, HistoryStore>) -> Result<Option<i32>, String> {
+    history.db_version().map_err(|e| e.to_string())
+}
@@ -0,0 +1,127 @@
+//! A record of every action automations carried out, so it's always
+//! possible to find out what copied, posted or opened something, and why.
+
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::{now_secs, HistoryStore};
+
+const DEFAULT_LIMIT: u32 = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionLogEntry {
+    pub id: i64,
+    /// Unix seconds.
+    pub timestamp: u64,
+    /// The action's type, e.g. `webhook`.
+    pub action: String,
+    /// The action with its parameters, as JSON.
+    pub detail: String,
+    /// What ran it: `rule:<id>` or `script`.
+    pub trigger: String,
+    /// The capture it ran for, if that was stored.
+    pub history_id: Option<i64>,
+    pub succeeded: bool,
+    pub error: Option<String>,
+}
+
+/// Narrows [`get_action_log`]; unset fields don't filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ActionLogFilter {
+    pub action: Option<String>,
+    pub trigger: Option<String>,
+    pub history_id: Option<i64>,
+    pub succeeded: Option<bool>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<ActionLogEntry> {
+    Ok(ActionLogEntry {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        action: row.get(2)?,
+        detail: row.get(3)?,
+        trigger: row.get(4)?,
+        history_id: row.get(5)?,
+        succeeded: row.get(6)?,
+        error: row.get(7)?,
+    })
+}
+
+impl HistoryStore {
+    pub fn log_action(
+        &self,
+        action: &str,
+        detail: &str,
+        trigger: &str,
+        history_id: Option<i64>,
+        result: &Result<(), String>,
+    ) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let timestamp = now_secs();
+        conn.execute(
+            "INSERT INTO action_log (timestamp, action, detail, trigger, history_id, succeeded, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![timestamp, action, detail, trigger, history_id, result.is_ok(), result.as_ref().err()],
+        )?;
+        Ok(())
+    }
+
+    /// Logged actions matching `filter`, newest first.
+    pub fn action_log(&self, filter: &ActionLogFilter) -> rusqlite::Result<Vec<ActionLogEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, action, detail, trigger, history_id, succeeded, error FROM action_log
+             WHERE (?1 IS NULL OR action = ?1)
+               AND (?2 IS NULL OR trigger = ?2)
+               AND (?3 IS NULL OR history_id = ?3)
+               AND (?4 IS NULL OR succeeded = ?4)
+               AND (?5 IS NULL OR timestamp >= ?5)
+               AND (?6 IS NULL OR timestamp <= ?6)
+             ORDER BY timestamp DESC, id DESC LIMIT ?7",
+        )?;
+        let rows = stmt.query_map(
+            params![
+                filter.action,
+                filter.trigger,
+                filter.history_id,
+                filter.succeeded,
+                filter.since.map(|since| since as i64),
+                filter.until.map(|until| until as i64),
+                filter.limit.unwrap_or(DEFAULT_LIMIT),
+            ],
+            entry_from_row,
+        )?;
+        rows.collect()
+    }
+}
+
+/// Logs that `trigger` ran `action` for capture `history_id`, with its
+/// result. `action` is the serialized action, its `type` field naming it.
+pub fn record(
+    app: &AppHandle,
+    trigger: &str,
+    action: &serde_json::Value,
+    history_id: Option<i64>,
+    result: &Result<(), String>,
+) {
+    let Some(history) = app.try_state::<HistoryStore>() else {
+        return;
+    };
+    let kind = action.get("type").and_then(|kind| kind.as_str()).unwrap_or("unknown");
+    if let Err(e) = history.log_action(kind, &action.to_string(), trigger, history_id, result) {
+        eprintln!("Failed to record action in the audit log: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_action_log(
+    history: State<'_, HistoryStore>,
+    filters: Option<ActionLogFilter>,
+) -> Result<Vec<ActionLogEntry>, String> {
+    history.action_log(&filters.unwrap_or_default()).map_err(|e| e.to_string())
+}
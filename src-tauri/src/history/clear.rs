@@ -0,0 +1,156 @@
+//! Clearing part of the history: one app, a date range, a tag, whatever
+//! matches a regex, or the last hour. Clearing takes two calls, so a single
+//! accidental click can't wipe anything: [`request_clear`] says how many
+//! entries a scope covers and hands out a single-use token for it, and
+//! [`clear_history`] only acts when given that token back, for the same
+//! scope, within a minute. Code running in the webview can make both calls,
+//! so this is no defence against scripts. Cleared entries go to the trash
+//! like any other deletion.
+
+use regex::Regex;
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use super::{now_secs, tags, trash, HistoryStore};
+
+const TOKEN_LIFETIME: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum ClearScope {
+    All,
+    App { app_name: String },
+    /// Unix seconds; an open end is unbounded.
+    DateRange { since: Option<u64>, until: Option<u64> },
+    Tag { tag: String },
+    /// Entries whose text matches `pattern`.
+    Matching { pattern: String },
+    LastHour,
+}
+
+/// Ids of the entries `scope` covers.
+fn select(conn: &Connection, scope: &ClearScope) -> Result<Vec<i64>, String> {
+    let (filter, values): (&str, Vec<rusqlite::types::Value>) = match scope {
+        ClearScope::All => ("1", vec![]),
+        ClearScope::App { app_name } => ("app_name = ?1", vec![app_name.clone().into()]),
+        ClearScope::DateRange { since, until } => (
+            "timestamp >= ?1 AND timestamp < ?2",
+            vec![
+                (since.unwrap_or(0) as i64).into(),
+                until.map_or(i64::MAX, |until| until as i64).into(),
+            ],
+        ),
+        ClearScope::Tag { tag } => {
+            let tag = tags::normalize(tag).ok_or("Empty tag")?;
+            ("id IN (SELECT history_id FROM tags WHERE tag = ?1)", vec![tag.into()])
+        }
+        ClearScope::LastHour => ("timestamp >= ?1", vec![(now_secs() - 60 * 60).into()]),
+        ClearScope::Matching { pattern } => {
+            let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+            let mut stmt = conn.prepare("SELECT id, text FROM history").map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| e.to_string())?;
+            let mut ids = Vec::new();
+            for row in rows {
+                let (id, text) = row.map_err(|e| e.to_string())?;
+                if regex.is_match(&text) {
+                    ids.push(id);
+                }
+            }
+            return Ok(ids);
+        }
+    };
+    let mut stmt = conn
+        .prepare(&format!("SELECT id FROM history WHERE {}", filter))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values), |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<_>>().map_err(|e| e.to_string())
+}
+
+impl HistoryStore {
+    pub fn count_scope(&self, scope: &ClearScope) -> Result<usize, String> {
+        select(&self.conn.lock().unwrap(), scope).map(|ids| ids.len())
+    }
+
+    /// Moves the entries `scope` covers to the trash.
+    pub fn trash_scope(&self, scope: &ClearScope) -> Result<usize, String> {
+        let mut conn = self.conn.lock().unwrap();
+        let ids = serde_json::to_string(&select(&conn, scope)?).map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let moved = trash::move_to_trash(&tx, "id IN (SELECT value FROM json_each(?1))", &[&ids])
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(moved)
+    }
+}
+
+/// Tokens handed out by [`request_clear`] and not used yet.
+#[derive(Default)]
+pub struct PendingClears(Mutex<HashMap<String, (ClearScope, Instant)>>);
+
+impl PendingClears {
+    fn issue(&self, scope: ClearScope) -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let mut pending = self.0.lock().unwrap();
+        pending.retain(|_, (_, issued)| issued.elapsed() < TOKEN_LIFETIME);
+        let token = hex::encode(Sha256::digest(format!("{}:{}:{:?}", nanos, pending.len(), scope)))[..24].to_string();
+        pending.insert(token.clone(), (scope, Instant::now()));
+        token
+    }
+
+    /// Uses up `token`, which must have been issued for `scope`.
+    fn redeem(&self, token: &str, scope: &ClearScope) -> Result<(), String> {
+        match self.0.lock().unwrap().remove(token) {
+            Some((issued_for, issued)) if issued_for == *scope && issued.elapsed() < TOKEN_LIFETIME => Ok(()),
+            Some((issued_for, _)) if issued_for != *scope => {
+                Err("The confirmation was for a different clear".to_string())
+            }
+            Some(_) => Err("The confirmation has expired; request it again".to_string()),
+            None => Err("Clearing needs a confirmation token from request_clear".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClearConfirmation {
+    pub token: String,
+    /// How many entries the clear would move to the trash right now.
+    pub count: usize,
+    pub expires_in_secs: u64,
+}
+
+/// First step of a clear: what `scope` covers, and the token that confirms it.
+#[tauri::command]
+pub async fn request_clear(
+    history: State<'_, HistoryStore>,
+    pending: State<'_, PendingClears>,
+    scope: ClearScope,
+) -> Result<ClearConfirmation, String> {
+    let count = history.count_scope(&scope)?;
+    Ok(ClearConfirmation {
+        token: pending.issue(scope),
+        count,
+        expires_in_secs: TOKEN_LIFETIME.as_secs(),
+    })
+}
+
+/// Moves the entries in `scope` to the trash; see [`trash::restore`].
+/// `token` comes from [`request_clear`] for the same scope.
+#[tauri::command]
+pub async fn clear_history(
+    history: State<'_, HistoryStore>,
+    pending: State<'_, PendingClears>,
+    scope: ClearScope,
+    token: String,
+) -> Result<usize, String> {
+    pending.redeem(&token, &scope)?;
+    history.trash_scope(&scope)
+}
@@ -0,0 +1,125 @@
+//! Correlates captures with the clipboard: when the user copies text they
+//! recently selected, the capture is marked `copied`, so selections that
+//! were never copied (quotes meant to be saved, say) can be found later.
+
+use rusqlite::params;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use super::{entry_from_row, now_secs, HistoryEntry, HistoryStore, ENTRY_COLUMNS};
+use crate::{rules, settings};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How far back a copy is matched against captures.
+const MATCH_WINDOW_SECS: i64 = 15 * 60;
+
+impl HistoryStore {
+    /// Marks captures of `text` stored since `since` as copied. Returns how
+    /// many were marked.
+    pub fn mark_copied(&self, text: &str, since: i64) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE history SET copied = 1 WHERE copied = 0 AND timestamp >= ?1 AND trim(text) = trim(?2)",
+            params![since, text],
+        )
+    }
+
+    /// Most recent captures that were (or weren't) copied.
+    pub fn by_copied(&self, copied: bool, limit: u32) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM history WHERE copied = ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![copied, limit], entry_from_row)?;
+        rows.collect()
+    }
+}
+
+/// Watches the clipboard while `history.track_copies` is on. Where the OS
+/// has a clipboard change count the text is only read when it moves.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_count: Option<i64> = None;
+        let mut last_hash: Option<u32> = None;
+        loop {
+            interval.tick().await;
+            if !settings::current(&app).history.track_copies {
+                last_count = None;
+                last_hash = None;
+                continue;
+            }
+
+            let count = platform::change_count();
+            if count.is_some() && count == last_count {
+                continue;
+            }
+            last_count = count;
+            let Ok(text) = app.clipboard().read_text() else {
+                continue;
+            };
+            let hash = crc32fast::hash(text.as_bytes());
+            // The first read is only a baseline: that copy predates tracking
+            let Some(previous) = last_hash.replace(hash) else {
+                continue;
+            };
+            if previous == hash || text.trim().is_empty() || rules::was_auto_copied(&text) {
+                continue;
+            }
+
+            let Some(history) = app.try_state::<HistoryStore>() else {
+                continue;
+            };
+            if let Err(e) = history.mark_copied(&text, now_secs() - MATCH_WINDOW_SECS) {
+                eprintln!("Failed to mark copied captures: {}", e);
+            }
+        }
+    });
+}
+
+/// Lists captures that were copied, or with `copied: false` the ones that
+/// never were.
+#[tauri::command]
+pub async fn get_history_by_copied(
+    history: State<'_, HistoryStore>,
+    copied: bool,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history.by_copied(copied, limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    /// `NSPasteboard.generalPasteboard.changeCount`.
+    pub fn change_count() -> Option<i64> {
+        unsafe {
+            let pasteboard: *mut Object = msg_send![class!(NSPasteboard), generalPasteboard];
+            let count: isize = msg_send![pasteboard, changeCount];
+            Some(count as i64)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::System::DataExchange::GetClipboardSequenceNumber;
+
+    pub fn change_count() -> Option<i64> {
+        Some(unsafe { GetClipboardSequenceNumber() } as i64)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    /// No change count here; the text is compared on every tick instead.
+    pub fn change_count() -> Option<i64> {
+        None
+    }
+}
@@ -0,0 +1,117 @@
+//! Comparing two captures, e.g. two versions of a paragraph selected in
+//! different places.
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::{HistoryEntry, HistoryStore};
+use crate::main_window;
+
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffMode {
+    #[default]
+    Unified,
+    Words,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WordChange {
+    /// "equal", "insert" or "delete".
+    pub tag: &'static str,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum DiffOutput {
+    Unified { patch: String },
+    Words { changes: Vec<WordChange> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryDiff {
+    pub old_id: i64,
+    pub new_id: i64,
+    #[serde(flatten)]
+    pub output: DiffOutput,
+}
+
+pub fn compare(old: &HistoryEntry, new: &HistoryEntry, mode: DiffMode) -> HistoryDiff {
+    let output = match mode {
+        DiffMode::Unified => DiffOutput::Unified {
+            patch: TextDiff::from_lines(&old.text, &new.text)
+                .unified_diff()
+                .context_radius(CONTEXT_LINES)
+                .header(&format!("#{}", old.id), &format!("#{}", new.id))
+                .to_string(),
+        },
+        DiffMode::Words => DiffOutput::Words {
+            changes: TextDiff::from_words(&old.text, &new.text)
+                .iter_all_changes()
+                .map(|change| WordChange {
+                    tag: match change.tag() {
+                        ChangeTag::Equal => "equal",
+                        ChangeTag::Insert => "insert",
+                        ChangeTag::Delete => "delete",
+                    },
+                    value: change.value().to_string(),
+                })
+                .collect(),
+        },
+    };
+
+    HistoryDiff {
+        old_id: old.id,
+        new_id: new.id,
+        output,
+    }
+}
+
+fn last_two(history: &HistoryStore, mode: DiffMode) -> Result<HistoryDiff, String> {
+    let entries = history.recent(2).map_err(|e| e.to_string())?;
+    match entries.as_slice() {
+        [new, old] => Ok(compare(old, new, mode)),
+        _ => Err("Need at least two captures to compare".to_string()),
+    }
+}
+
+/// Hotkey action: diffs the last two captures and shows the result.
+pub fn show_last_two(app: &AppHandle) {
+    let Some(history) = app.try_state::<HistoryStore>() else {
+        return;
+    };
+    match last_two(&history, DiffMode::Words) {
+        Ok(diff) => {
+            let _ = app.emit("history-diff", &diff);
+            if let Err(e) = main_window::show(app) {
+                eprintln!("Failed to show main window: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to diff captures: {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn diff_last_two(history: State<'_, HistoryStore>, mode: Option<DiffMode>) -> Result<HistoryDiff, String> {
+    last_two(&history, mode.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn diff(
+    history: State<'_, HistoryStore>,
+    id_a: i64,
+    id_b: i64,
+    mode: Option<DiffMode>,
+) -> Result<HistoryDiff, String> {
+    let fetch = |id| {
+        history
+            .get(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("No history entry with id {}", id))
+    };
+    Ok(compare(&fetch(id_a)?, &fetch(id_b)?, mode.unwrap_or_default()))
+}
@@ -0,0 +1,64 @@
+//! Writing history out to a file, optionally limited to some tags.
+
+use serde::Deserialize;
+use std::path::Path;
+use tauri::State;
+
+use super::{selection_type_to_str, HistoryEntry, HistoryStore};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+fn write(entries: &[HistoryEntry], format: ExportFormat, path: &Path) -> Result<(), String> {
+    match format {
+        ExportFormat::Json => {
+            let contents = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+            std::fs::write(path, contents).map_err(|e| e.to_string())
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+            writer
+                .write_record(["id", "timestamp", "app_name", "selection_type", "title", "tags", "note", "signature", "text"])
+                .map_err(|e| e.to_string())?;
+            for entry in entries {
+                writer
+                    .write_record([
+                        entry.id.to_string().as_str(),
+                        entry.timestamp.to_string().as_str(),
+                        &entry.app_name,
+                        selection_type_to_str(&entry.selection_type),
+                        &entry.title,
+                        &entry.tags.join(";"),
+                        entry.note.as_deref().unwrap_or_default(),
+                        entry.signature.as_deref().unwrap_or_default(),
+                        &entry.text,
+                    ])
+                    .map_err(|e| e.to_string())?;
+            }
+            writer.flush().map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Exports the whole history, or only entries carrying one of `tags`.
+/// Returns the number of entries written.
+#[tauri::command]
+pub async fn export_history(
+    history: State<'_, HistoryStore>,
+    path: String,
+    format: ExportFormat,
+    tags: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let entries = match tags.filter(|tags| !tags.is_empty()) {
+        Some(tags) => history.tagged(&tags),
+        None => history.recent(u32::MAX),
+    }
+    .map_err(|e| e.to_string())?;
+
+    write(&entries, format, Path::new(&path))?;
+    Ok(entries.len())
+}
@@ -0,0 +1,130 @@
+//! Importers for other clipboard managers' data, mapped onto history rows.
+//!
+//! * Maccy: its Core Data store (`~/Library/Containers/org.p0deje.Maccy/
+//!   Data/Library/Application Support/Maccy/Storage.sqlite`).
+//! * CopyQ: a JSON array of items, e.g. produced by
+//!   `copyq eval "print(JSON.stringify(...))"`, where each item carries
+//!   `text/plain` (or `text`) and optionally a timestamp.
+//! * Paste: its JSON export, an array of items with `text`/`content`,
+//!   `application` and `date`.
+
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+use text_detection::{SelectionEvent, SelectionType};
+
+/// Core Data stores dates as seconds since 2001-01-01.
+const CORE_DATA_EPOCH_OFFSET: f64 = 978_307_200.0;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Maccy,
+    Copyq,
+    Paste,
+}
+
+pub fn read(source: ImportSource, path: &Path) -> Result<Vec<SelectionEvent>, String> {
+    match source {
+        ImportSource::Maccy => read_maccy(path).map_err(|e| e.to_string()),
+        ImportSource::Copyq => read_json(
+            path,
+            &["text/plain", "text"],
+            &["application/x-copyq-owner-window-title"],
+            &["time", "timestamp"],
+        ),
+        ImportSource::Paste => read_json(
+            path,
+            &["text", "content", "string"],
+            &["application", "app", "source"],
+            &["date", "createdAt", "created_at"],
+        ),
+    }
+}
+
+fn read_maccy(path: &Path) -> rusqlite::Result<Vec<SelectionEvent>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(
+        "SELECT c.ZVALUE, i.ZAPPLICATION, i.ZLASTCOPIEDAT
+         FROM ZHISTORYITEMCONTENT c JOIN ZHISTORYITEM i ON c.ZITEM = i.Z_PK
+         WHERE c.ZTYPE = 'public.utf8-plain-text'",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        let value: Option<Vec<u8>> = row.get(0)?;
+        let app: Option<String> = row.get(1)?;
+        let copied_at: Option<f64> = row.get(2)?;
+        Ok((value, app, copied_at))
+    })?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        let (value, app, copied_at) = row?;
+        let Some(value) = value else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&value).into_owned();
+        if text.trim().is_empty() {
+            continue;
+        }
+        let mut event = SelectionEvent::new(text, app.unwrap_or_else(|| "Maccy".to_string()), SelectionType::Selected);
+        if let Some(copied_at) = copied_at {
+            event.timestamp = (copied_at + CORE_DATA_EPOCH_OFFSET).max(0.0) as u64;
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn read_json(path: &Path, text_keys: &[&str], app_keys: &[&str], time_keys: &[&str]) -> Result<Vec<SelectionEvent>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    // Accept either a bare array or an object wrapping it under "items"
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(obj) => obj
+            .get("items")
+            .and_then(Value::as_array)
+            .ok_or("Expected a JSON array of items")?,
+        _ => return Err("Expected a JSON array of items".to_string()),
+    };
+
+    let mut events = Vec::new();
+    for item in items {
+        let Some(text) = first_str(item, text_keys) else {
+            continue;
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        let app = first_str(item, app_keys).unwrap_or("Imported");
+        let mut event = SelectionEvent::new(text, app, SelectionType::Selected);
+        if let Some(timestamp) = time_keys.iter().find_map(|key| item.get(*key).and_then(parse_timestamp)) {
+            event.timestamp = timestamp;
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn first_str<'a>(item: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|key| item.get(*key).and_then(Value::as_str))
+}
+
+/// Accepts unix seconds, unix milliseconds, or an RFC 3339 string.
+fn parse_timestamp(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            // Anything past year ~5138 in seconds is really milliseconds
+            let secs = if n > 1e11 { n / 1000.0 } else { n };
+            Some(secs.max(0.0) as u64)
+        }
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp().max(0) as u64),
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+//! Checking and repairing the history database. A crash or power loss in
+//! the middle of a write can leave pages or the search index damaged; the
+//! first sign of that gets the file copied aside untouched, before anything
+//! (including a repair) writes to it again, so there's always something to
+//! recover months of captures from.
+
+use rusqlite::{Connection, ErrorCode};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::HistoryStore;
+
+/// One backup per run is enough; the damage only gets older.
+static BACKED_UP: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    /// What `PRAGMA integrity_check` found; empty when the database is sound.
+    pub problems: Vec<String>,
+    /// Whether the full-text index agrees with the history table.
+    pub search_index_ok: bool,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty() && self.search_index_ok
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairReport {
+    /// Copy of the database as it was before the repair.
+    pub backup: PathBuf,
+    pub before: IntegrityReport,
+    pub after: IntegrityReport,
+}
+
+fn check(conn: &Connection) -> rusqlite::Result<IntegrityReport> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let problems = rows
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|problem| problem != "ok")
+        .collect();
+    // Fails with SQLITE_CORRUPT_VTAB when the index is out of step; rank 1
+    // compares it against the history table too, not just itself
+    let search_index_ok = conn
+        .execute("INSERT INTO history_fts (history_fts, rank) VALUES ('integrity-check', 1)", [])
+        .is_ok();
+    Ok(IntegrityReport {
+        problems,
+        search_index_ok,
+    })
+}
+
+/// Copies the database file byte for byte, damage included, next to it.
+fn back_up(conn: &Connection) -> Result<PathBuf, String> {
+    let path = PathBuf::from(conn.path().ok_or("History database has no file")?);
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let backup = path.with_extension(format!("db.bak-corrupt-{}", now));
+    std::fs::copy(&path, &backup).map_err(|e| e.to_string())?;
+    println!("Backed up history database to {}", backup.display());
+    Ok(backup)
+}
+
+impl HistoryStore {
+    pub fn verify(&self) -> rusqlite::Result<IntegrityReport> {
+        check(&self.conn.lock().unwrap())
+    }
+
+    /// Backs the database up, then rebuilds the indexes and the search
+    /// index and compacts the file. Rows that SQLite itself can't read any
+    /// more stay lost, but everything else becomes usable again.
+    pub fn repair(&self) -> Result<RepairReport, String> {
+        let conn = self.conn.lock().unwrap();
+        let before = check(&conn).map_err(|e| e.to_string())?;
+        let backup = back_up(&conn)?;
+        conn.execute_batch(
+            "REINDEX;
+             INSERT INTO history_fts (history_fts) VALUES ('rebuild');
+             VACUUM;",
+        )
+        .map_err(|e| e.to_string())?;
+        let after = check(&conn).map_err(|e| e.to_string())?;
+        if !after.is_ok() {
+            eprintln!("History database is still damaged after repair: {:?}", after.problems);
+        }
+        Ok(RepairReport { backup, before, after })
+    }
+}
+
+/// Backs the database up the first time it looks damaged, and tells the UI
+/// so it can offer [`repair_database`].
+fn handle_corruption(app: &AppHandle, store: &HistoryStore, reason: &str) {
+    eprintln!("History database is damaged: {}", reason);
+    if BACKED_UP.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let backup = back_up(&store.conn.lock().unwrap());
+    if let Err(e) = &backup {
+        eprintln!("Failed to back up damaged history database: {}", e);
+    }
+    let _ = app.emit("history-corrupted", backup.ok());
+}
+
+/// Call with any error from a history write.
+pub fn note_error(app: &AppHandle, error: &rusqlite::Error) {
+    if error.sqlite_error_code() != Some(ErrorCode::DatabaseCorrupt) {
+        return;
+    }
+    if let Some(store) = app.try_state::<HistoryStore>() {
+        handle_corruption(app, &store, &error.to_string());
+    }
+}
+
+/// Checks the database in the background after it's opened.
+pub fn check_on_open(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(store) = app.try_state::<HistoryStore>() else {
+            return;
+        };
+        match store.quick_check() {
+            Ok(problems) if problems.is_empty() => {}
+            Ok(problems) => handle_corruption(&app, &store, &problems.join("; ")),
+            Err(e) => note_error(&app, &e),
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn verify_database(history: State<'_, HistoryStore>) -> Result<IntegrityReport, String> {
+    history.verify().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn repair_database(history: State<'_, HistoryStore>) -> Result<RepairReport, String> {
+    history.repair()
+}
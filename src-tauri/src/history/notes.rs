@@ -0,0 +1,31 @@
+//! User notes on history entries, e.g. why a snippet was worth keeping.
+//! Notes are part of the full-text index, so search finds them too.
+
+use rusqlite::params;
+use tauri::State;
+
+use super::HistoryStore;
+
+impl HistoryStore {
+    /// Sets or, with `None`, removes the note on entry `id`. Returns whether
+    /// the entry exists.
+    pub fn set_note(&self, id: i64, note: Option<&str>) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute("UPDATE history SET note = ?1 WHERE id = ?2", params![note, id])?;
+        Ok(updated > 0)
+    }
+}
+
+/// Attaches `note` to capture `id`, replacing any earlier note. An empty
+/// note removes it.
+#[tauri::command]
+pub async fn annotate_capture(history: State<'_, HistoryStore>, id: i64, note: String) -> Result<(), String> {
+    let note = note.trim();
+    let found = history
+        .set_note(id, (!note.is_empty()).then_some(note))
+        .map_err(|e| e.to_string())?;
+    if !found {
+        return Err(format!("No history entry with id {}", id));
+    }
+    Ok(())
+}
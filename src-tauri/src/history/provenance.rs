@@ -0,0 +1,145 @@
+//! Provenance signatures. With `sign_captures` on, each capture is stored
+//! with an HMAC-SHA256 over its text, timestamp and source app, keyed with
+//! a random key kept in the OS keychain. An export carries the signatures,
+//! so [`verify_export`] can later show that a research trail hasn't been
+//! edited since it was captured, on this machine.
+
+use hmac::{Hmac, Mac};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::Path;
+use tauri::{AppHandle, State};
+use text_detection::SelectionEvent;
+
+use super::{HistoryEntry, HistoryStore};
+use crate::{secrets, settings};
+
+pub const KEY_NAME: &str = "provenance-key";
+
+fn key() -> Result<Vec<u8>, String> {
+    let key = secrets::get_or_create(KEY_NAME, || {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).map_err(|e| e.to_string())?;
+        Ok(hex::encode(key))
+    })?;
+    hex::decode(key).map_err(|e| format!("Invalid provenance key: {}", e))
+}
+
+fn mac(key: &[u8], timestamp: u64, app_name: &str, text: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    // JSON keeps the fields apart, so text can't be shifted into app_name
+    let message = serde_json::json!([timestamp, app_name, text]).to_string();
+    mac.update(message.as_bytes());
+    mac
+}
+
+fn sign(key: &[u8], timestamp: u64, app_name: &str, text: &str) -> String {
+    hex::encode(mac(key, timestamp, app_name, text).finalize().into_bytes())
+}
+
+fn verify(key: &[u8], timestamp: u64, app_name: &str, text: &str, signature: &str) -> bool {
+    hex::decode(signature).is_ok_and(|signature| mac(key, timestamp, app_name, text).verify_slice(&signature).is_ok())
+}
+
+impl HistoryStore {
+    fn set_signature(&self, id: i64, signature: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE history SET signature = ?1 WHERE id = ?2", params![signature, id])?;
+        Ok(())
+    }
+}
+
+/// Signs entry `id`, just stored from `event`, if signing is on.
+pub fn sign_if_enabled(app: &AppHandle, store: &HistoryStore, id: i64, event: &SelectionEvent) {
+    if !settings::current(app).history.sign_captures {
+        return;
+    }
+    let result = key().and_then(|key| {
+        let signature = sign(&key, event.timestamp, &event.app_name, &event.text);
+        store.set_signature(id, &signature).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to sign capture {}: {}", id, e);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Signed, and unchanged since.
+    Intact,
+    /// Signed, but the text, timestamp or app no longer match.
+    Modified,
+    Unsigned,
+}
+
+fn verdict(key: &[u8], timestamp: u64, app_name: &str, text: &str, signature: Option<&str>) -> Verdict {
+    match signature.filter(|signature| !signature.is_empty()) {
+        None => Verdict::Unsigned,
+        Some(signature) if verify(key, timestamp, app_name, text, signature) => Verdict::Intact,
+        Some(_) => Verdict::Modified,
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportVerification {
+    pub intact: usize,
+    pub unsigned: usize,
+    /// Ids of entries whose signature doesn't match.
+    pub modified: Vec<i64>,
+}
+
+/// The columns of a CSV export that signatures cover.
+#[derive(Debug, Deserialize)]
+struct CsvRow {
+    id: i64,
+    timestamp: u64,
+    app_name: String,
+    #[serde(default)]
+    signature: String,
+    text: String,
+}
+
+/// Whether capture `id` is unchanged since it was signed.
+#[tauri::command]
+pub async fn verify_capture(history: State<'_, HistoryStore>, id: i64) -> Result<Verdict, String> {
+    let entry = history
+        .get(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No history entry with id {}", id))?;
+    Ok(verdict(&key()?, entry.timestamp, &entry.app_name, &entry.text, entry.signature.as_deref()))
+}
+
+/// Checks every entry in a file written by `export_history`, JSON or CSV.
+#[tauri::command]
+pub async fn verify_export(path: String) -> Result<ExportVerification, String> {
+    let path = Path::new(&path);
+    let rows: Vec<(i64, u64, String, String, Option<String>)> =
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            let mut reader = csv::Reader::from_path(path).map_err(|e| e.to_string())?;
+            reader
+                .deserialize::<CsvRow>()
+                .map(|row| row.map(|row| (row.id, row.timestamp, row.app_name, row.text, Some(row.signature))))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?
+        } else {
+            let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let entries: Vec<HistoryEntry> = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+            entries
+                .into_iter()
+                .map(|entry| (entry.id, entry.timestamp, entry.app_name, entry.text, entry.signature))
+                .collect()
+        };
+
+    let key = key()?;
+    let mut verification = ExportVerification::default();
+    for (id, timestamp, app_name, text, signature) in rows {
+        match verdict(&key, timestamp, &app_name, &text, signature.as_deref()) {
+            Verdict::Intact => verification.intact += 1,
+            Verdict::Unsigned => verification.unsigned += 1,
+            Verdict::Modified => verification.modified.push(id),
+        }
+    }
+    Ok(verification)
+}
@@ -0,0 +1,160 @@
+//! Reading list mode: with `browser_bridge.reading_list` on, every selection
+//! the browser extension sends also queues its page, with the selection as
+//! the excerpt. Selecting on the same page again refreshes the item instead
+//! of adding another, so the list stays one entry per URL.
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::{now_secs, HistoryStore};
+use crate::capture::Capture;
+use crate::{kiosk, settings};
+
+const ITEM_COLUMNS: &str = "id, url, title, excerpt, history_id, added_at, updated_at, read_at";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadingListItem {
+    pub id: i64,
+    pub url: String,
+    pub title: String,
+    /// The latest selection made on the page.
+    pub excerpt: String,
+    /// The capture the excerpt came from, if it was stored.
+    pub history_id: Option<i64>,
+    /// Unix seconds.
+    pub added_at: u64,
+    pub updated_at: u64,
+    pub read_at: Option<u64>,
+}
+
+/// The URL without its fragment, so jumping between headings on a page
+/// doesn't make it a different item.
+fn normalize_url(url: &str) -> &str {
+    let url = url.trim();
+    url.split_once('#').map_or(url, |(page, _)| page)
+}
+
+fn item_from_row(row: &Row) -> rusqlite::Result<ReadingListItem> {
+    Ok(ReadingListItem {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        excerpt: row.get(3)?,
+        history_id: row.get(4)?,
+        added_at: row.get::<_, i64>(5)? as u64,
+        updated_at: row.get::<_, i64>(6)? as u64,
+        read_at: row.get::<_, Option<i64>>(7)?.map(|at| at as u64),
+    })
+}
+
+impl HistoryStore {
+    /// Adds `url` to the reading list, or refreshes its title and excerpt if
+    /// it's already there. Read items stay read.
+    pub fn add_to_reading_list(
+        &self,
+        url: &str,
+        title: &str,
+        excerpt: &str,
+        history_id: Option<i64>,
+    ) -> rusqlite::Result<ReadingListItem> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!(
+                "INSERT INTO reading_list (url, title, excerpt, history_id, added_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+                 ON CONFLICT (url) DO UPDATE SET
+                     title = CASE WHEN excluded.title = '' THEN title ELSE excluded.title END,
+                     excerpt = excluded.excerpt,
+                     history_id = coalesce(excluded.history_id, history_id),
+                     updated_at = excluded.updated_at
+                 RETURNING {}",
+                ITEM_COLUMNS
+            ),
+            params![normalize_url(url), title.trim(), excerpt, history_id, now_secs()],
+            item_from_row,
+        )
+    }
+
+    /// Items most recently added to or refreshed first.
+    pub fn reading_list(&self, include_read: bool) -> rusqlite::Result<Vec<ReadingListItem>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM reading_list WHERE ?1 OR read_at IS NULL ORDER BY updated_at DESC, id DESC",
+            ITEM_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![include_read], item_from_row)?;
+        rows.collect()
+    }
+
+    /// Marks item `id` read, or unread again. Returns the item, if it exists.
+    pub fn set_reading_list_read(&self, id: i64, read: bool) -> rusqlite::Result<Option<ReadingListItem>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!(
+                "UPDATE reading_list SET read_at = CASE WHEN ?2 THEN coalesce(read_at, ?3) END
+                 WHERE id = ?1 RETURNING {}",
+                ITEM_COLUMNS
+            ),
+            params![id, read, now_secs()],
+            item_from_row,
+        )
+        .optional()
+    }
+
+    pub fn remove_from_reading_list(&self, id: i64) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute("DELETE FROM reading_list WHERE id = ?1", params![id])?;
+        Ok(removed > 0)
+    }
+}
+
+/// Queues the page `capture` came from, if reading list mode is on. Like
+/// history, nothing is queued in kiosk mode.
+pub fn add_if_enabled(app: &AppHandle, capture: &Capture) {
+    let Some(page) = &capture.page else {
+        return;
+    };
+    if !settings::current(app).browser_bridge.reading_list || page.url.trim().is_empty() || kiosk::is_locked(app) {
+        return;
+    }
+    let Some(history) = app.try_state::<HistoryStore>() else {
+        return;
+    };
+    match history.add_to_reading_list(&page.url, &page.title, capture.event.text.trim(), capture.id) {
+        Ok(item) => {
+            let _ = app.emit("reading-list-updated", &item);
+        }
+        Err(e) => eprintln!("Failed to add {} to the reading list: {}", page.url, e),
+    }
+}
+
+/// Unread items, or every item with `include_read`.
+#[tauri::command]
+pub async fn get_reading_list(
+    history: State<'_, HistoryStore>,
+    include_read: Option<bool>,
+) -> Result<Vec<ReadingListItem>, String> {
+    history.reading_list(include_read.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Marks item `id` read, or unread again with `read: false`.
+#[tauri::command]
+pub async fn mark_reading_list_item_read(
+    history: State<'_, HistoryStore>,
+    id: i64,
+    read: Option<bool>,
+) -> Result<ReadingListItem, String> {
+    history
+        .set_reading_list_read(id, read.unwrap_or(true))
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No reading list item with id {}", id))
+}
+
+#[tauri::command]
+pub async fn remove_from_reading_list(history: State<'_, HistoryStore>, id: i64) -> Result<(), String> {
+    if !history.remove_from_reading_list(id).map_err(|e| e.to_string())? {
+        return Err(format!("No reading list item with id {}", id));
+    }
+    Ok(())
+}
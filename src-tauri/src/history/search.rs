@@ -0,0 +1,181 @@
+//! Full-text search over captures, backing the search palette.
+//!
+//! SQLite's tokenizer only splits on spaces and punctuation, which would
+//! leave a sentence of Chinese or Japanese as a single token. Text is run
+//! through [`text_utils::segment`] on its way into the index, and queries
+//! the same way, so any word of it can be searched for.
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::ipc::Channel;
+use tauri::State;
+
+use super::{entry_from_row, HistoryEntry, HistoryStore, ENTRY_COLUMNS};
+use crate::text_utils;
+
+const DEFAULT_LIMIT: u32 = 50;
+/// Results are streamed in batches so the first ones show up immediately.
+const BATCH_SIZE: usize = 10;
+
+/// Bumped on every search, so results of a query the user has since typed
+/// past stop streaming.
+#[derive(Default)]
+pub struct SearchState {
+    generation: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchBatch {
+    pub query: String,
+    pub entries: Vec<HistoryEntry>,
+    /// Set on the last batch of a query.
+    pub done: bool,
+}
+
+/// Registers `segment_words()`, which the index's triggers run text
+/// through. Needed on every connection that writes to history.
+pub(super) fn register_segmenter(conn: &Connection) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    conn.create_scalar_function("segment_words", 1, flags, |ctx| {
+        let text: Option<String> = ctx.get(0)?;
+        Ok(text.map(|text| text_utils::segment(&text)))
+    })
+}
+
+/// Turns what the user typed into an FTS5 query: every word must match,
+/// the last one as a prefix since it's probably still being typed. A word
+/// that segments into several, like a run of CJK, matches as a phrase.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", text_utils::segment(word).replace('"', "\"\"")))
+        .collect();
+    let last = words.len().checked_sub(1)?;
+    Some(
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| if i == last { format!("{}*", word) } else { word.clone() })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+impl HistoryStore {
+    /// Best matches for `query` first.
+    pub fn search(&self, query: &str, limit: u32) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let Some(expression) = match_expression(query) else {
+            return self.recent(limit);
+        };
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM (SELECT rowid AS hit, rank FROM history_fts WHERE history_fts MATCH ?1 ORDER BY rank LIMIT ?2) AS hits
+             JOIN history ON history.id = hits.hit
+             ORDER BY hits.rank",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![expression, limit], entry_from_row)?;
+        rows.collect()
+    }
+}
+
+/// Streams matches for `query` to `on_results` as they are ready. An empty
+/// query lists the most recent captures.
+#[tauri::command]
+pub async fn search_history_stream(
+    history: State<'_, HistoryStore>,
+    search: State<'_, SearchState>,
+    query: String,
+    limit: Option<u32>,
+    on_results: Channel<SearchBatch>,
+) -> Result<(), String> {
+    let generation = search.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let entries = history
+        .search(&query, limit.unwrap_or(DEFAULT_LIMIT))
+        .map_err(|e| e.to_string())?;
+
+    let batches: Vec<&[HistoryEntry]> = entries.chunks(BATCH_SIZE).collect();
+    let count = batches.len().max(1);
+    for i in 0..count {
+        if search.generation.load(Ordering::SeqCst) != generation {
+            break;
+        }
+        on_results
+            .send(SearchBatch {
+                query: query.clone(),
+                entries: batches.get(i).map(|batch| batch.to_vec()).unwrap_or_default(),
+                done: i + 1 == count,
+            })
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use text_detection::{SelectionEvent, SelectionType};
+
+    /// A fresh database in a directory of its own, removed on drop.
+    struct TempStore {
+        dir: PathBuf,
+        store: HistoryStore,
+    }
+
+    impl TempStore {
+        fn with(name: &str, texts: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!("acmi-search-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            let store = HistoryStore::open(&dir).unwrap();
+            for text in texts {
+                store.insert(&SelectionEvent::new(*text, "Test", SelectionType::Selected)).unwrap();
+            }
+            Self { dir, store }
+        }
+
+        fn texts(&self, query: &str) -> Vec<String> {
+            self.store.search(query, 10).unwrap().into_iter().map(|entry| entry.text).collect()
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = self.store.close();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn segments_queries() {
+        assert_eq!(match_expression("hello wor").as_deref(), Some("\"hello\" \"wor\"*"));
+        assert_eq!(match_expression("東京 タワ").as_deref(), Some("\"東 京\" \"タワ\"*"));
+        assert_eq!(match_expression("say \"hi\"").as_deref(), Some("\"say\" \"\"\" hi \"\"\"*"));
+        assert_eq!(match_expression("  "), None);
+    }
+
+    #[test]
+    fn finds_words_inside_cjk_sentences() {
+        let temp = TempStore::with("cjk", &["東京は日本の首都です", "これはペンです", "서울은 한국의 수도입니다"]);
+        assert_eq!(temp.texts("首都"), ["東京は日本の首都です"]);
+        assert_eq!(temp.texts("日本の"), ["東京は日本の首都です"]);
+        assert_eq!(temp.texts("ペン"), ["これはペンです"]);
+        assert_eq!(temp.texts("한국의"), ["서울은 한국의 수도입니다"]);
+        assert!(temp.texts("京都").is_empty());
+    }
+
+    #[test]
+    fn finds_rtl_words() {
+        let temp = TempStore::with("rtl", &["שלום עולם", "مرحبا بالعالم", "hello world"]);
+        assert_eq!(temp.texts("עולם"), ["שלום עולם"]);
+        assert_eq!(temp.texts("مرح"), ["مرحبا بالعالم"]);
+    }
+
+    #[test]
+    fn index_stays_consistent() {
+        let temp = TempStore::with("integrity", &["東京は日本の首都です", "plain text"]);
+        assert!(temp.store.verify().unwrap().search_index_ok);
+    }
+}
@@ -0,0 +1,150 @@
+//! "Have I captured this before?" Each entry is stored with a 64-bit
+//! simhash of its words, so near-duplicates (the same quote with different
+//! punctuation, line breaks or a word changed) have hashes a few bits apart
+//! and can be found without comparing texts.
+
+use rusqlite::params;
+use tauri::{AppHandle, Manager};
+
+use super::HistoryStore;
+use crate::text_utils;
+
+/// Most bits two hashes may differ in to count as the same text. A word
+/// changed in a sentence or two flips about three; unrelated texts differ
+/// in twelve or more.
+const MAX_DISTANCE: u32 = 6;
+/// Fewer words than this hash too coarsely to tell texts apart, so short
+/// captures are hashed but never looked up.
+const MIN_WORDS: usize = 8;
+const BACKFILL_BATCH: usize = 500;
+
+/// FNV-1a, mixed with splitmix64's finalizer so every bit is usable.
+/// Stored hashes depend on this, so it must never change.
+fn hash_word(word: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in word.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// Runs of letters and digits, with runs of CJK split further into words.
+/// Other text splits as it always has, so stored hashes stay comparable.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .flat_map(text_utils::words)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn simhash_words(words: &[String]) -> i64 {
+    let mut weights = [0i32; 64];
+    for word in words {
+        let hash = hash_word(word);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    let hash = weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |hash, (bit, _)| hash | 1 << bit);
+    // SQLite integers are signed
+    hash as i64
+}
+
+/// The simhash of `text`, as stored with each entry.
+pub fn simhash(text: &str) -> i64 {
+    simhash_words(&words(text))
+}
+
+impl HistoryStore {
+    /// The closest earlier entry to `text`, newest first among equals.
+    pub fn similar_to(&self, id: i64, text: &str) -> rusqlite::Result<Option<i64>> {
+        let words = words(text);
+        if words.len() < MIN_WORDS {
+            return Ok(None);
+        }
+        let hash = simhash_words(&words);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT id, simhash FROM history WHERE simhash IS NOT NULL AND id < ?1 ORDER BY id DESC")?;
+        let mut rows = stmt.query(params![id])?;
+        let mut best: Option<(u32, i64)> = None;
+        while let Some(row) = rows.next()? {
+            let distance = (row.get::<_, i64>(1)? ^ hash).count_ones();
+            if distance <= MAX_DISTANCE && best.is_none_or(|(closest, _)| distance < closest) {
+                best = Some((distance, row.get(0)?));
+            }
+        }
+        Ok(best.map(|(_, id)| id))
+    }
+
+    /// Hashes up to a batch of entries stored before hashing existed, or
+    /// restored from the trash. Returns how many were hashed.
+    fn backfill_simhashes(&self) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let missing: Vec<(i64, String)> = conn
+            .prepare("SELECT id, text FROM history WHERE simhash IS NULL LIMIT ?1")?
+            .query_map(params![BACKFILL_BATCH], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let tx = conn.unchecked_transaction()?;
+        for (id, text) in &missing {
+            tx.execute("UPDATE history SET simhash = ?1 WHERE id = ?2", params![simhash(text), id])?;
+        }
+        tx.commit()?;
+        Ok(missing.len())
+    }
+}
+
+/// The earlier entry that entry `id`, just stored with `text`, nearly
+/// repeats, if any.
+pub fn find(app: &AppHandle, id: i64, text: &str) -> Option<i64> {
+    let store = app.try_state::<HistoryStore>()?;
+    store.similar_to(id, text).unwrap_or_else(|e| {
+        eprintln!("Failed to look for similar captures: {}", e);
+        None
+    })
+}
+
+/// Hashes older entries in the background, a batch at a time so captures
+/// aren't held up on the database lock. Call after opening a database.
+pub fn backfill_in_background(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let Some(store) = app.try_state::<HistoryStore>() else {
+                return;
+            };
+            match store.backfill_simhashes() {
+                Ok(hashed) if hashed < BACKFILL_BATCH => return,
+                Ok(_) => tokio::task::yield_now().await,
+                Err(e) => {
+                    eprintln!("Failed to hash older captures: {}", e);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_cjk_runs_into_words() {
+        assert_eq!(words("東京は日本の首都です").len(), 10);
+        assert_eq!(words("Tokyo 東京"), ["tokyo", "東", "京"]);
+    }
+
+    #[test]
+    fn splits_other_text_as_before() {
+        assert_eq!(words("Don't stop—it's 3.14!"), ["don", "t", "stop", "it", "s", "3", "14"]);
+        assert_eq!(words("שלום, עולם"), ["שלום", "עולם"]);
+    }
+}
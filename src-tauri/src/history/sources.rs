@@ -0,0 +1,235 @@
+//! Where each capture was read from, so it can be read again: with
+//! `record_sources` on, the app, window and span of text behind a capture
+//! (or the page, for browser captures) are kept with the entry. Recapturing
+//! brings that app back to the front, reads the same span and marks the
+//! entry if the text there has changed since, which is handy for keeping
+//! an eye on documents that are still being edited.
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::{SelectionType, TextRange};
+
+use super::{now_secs, HistoryStore};
+use crate::capture::Capture;
+use crate::{normalize, settings, text_detector};
+
+/// How long an app gets to come to the front before it's read.
+const ACTIVATE_DELAY: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CaptureSource {
+    /// Bundle identifier where the platform has one.
+    pub app: Option<String>,
+    pub window_title: Option<String>,
+    /// The page, for captures sent by a browser extension.
+    pub url: Option<String>,
+    pub range: Option<TextRange>,
+    /// Unix seconds of the last recapture.
+    pub checked_at: Option<u64>,
+    /// The last recapture found different text, or none at all.
+    pub changed: bool,
+    /// What the last recapture found, when it differed.
+    pub current_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecaptureStatus {
+    Unchanged,
+    Changed,
+    /// The app is running but the span can't be read any more, e.g. the
+    /// document was closed or got shorter.
+    Missing,
+    /// The page was opened again; its text comes back as a new capture if
+    /// the user selects it.
+    Reopened,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recapture {
+    pub id: i64,
+    pub status: RecaptureStatus,
+    pub text: Option<String>,
+}
+
+fn source_from_row(row: &Row) -> rusqlite::Result<CaptureSource> {
+    let location: Option<i64> = row.get(3)?;
+    let length: Option<i64> = row.get(4)?;
+    Ok(CaptureSource {
+        app: row.get(0)?,
+        window_title: row.get(1)?,
+        url: row.get(2)?,
+        range: location.zip(length).map(|(location, length)| TextRange {
+            location: location as usize,
+            length: length as usize,
+        }),
+        checked_at: row.get::<_, Option<i64>>(5)?.map(|at| at as u64),
+        changed: row.get(6)?,
+        current_text: row.get(7)?,
+    })
+}
+
+impl HistoryStore {
+    pub fn set_source(&self, id: i64, source: &CaptureSource) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO capture_sources (history_id, app, window_title, url, range_location, range_length)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                source.app,
+                source.window_title,
+                source.url,
+                source.range.map(|range| range.location as i64),
+                source.range.map(|range| range.length as i64),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn source(&self, id: i64) -> rusqlite::Result<Option<CaptureSource>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT app, window_title, url, range_location, range_length, checked_at, changed, current_text
+             FROM capture_sources WHERE history_id = ?1",
+            params![id],
+            source_from_row,
+        )
+        .optional()
+    }
+
+    fn mark_recaptured(&self, id: i64, changed: bool, current_text: Option<&str>) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE capture_sources SET checked_at = ?1, changed = ?2, current_text = ?3 WHERE history_id = ?4",
+            params![now_secs(), changed, current_text, id],
+        )?;
+        Ok(())
+    }
+
+    /// Drops the sources of entries that are gone for good.
+    pub fn prune_sources(&self) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM capture_sources
+             WHERE history_id NOT IN (SELECT id FROM history) AND history_id NOT IN (SELECT id FROM trash)",
+            [],
+        )
+    }
+}
+
+/// Records where `capture` came from, if sources are recorded. Call right
+/// after the selection was read, while its app still has focus.
+pub fn remember(app: &AppHandle, capture: &Capture) {
+    let Some(id) = capture.id else {
+        return;
+    };
+    if !settings::current(app).history.record_sources {
+        return;
+    }
+    if let Some(page) = &capture.page {
+        let source = CaptureSource {
+            app: page.browser.clone(),
+            url: Some(page.url.clone()),
+            ..Default::default()
+        };
+        store(app, id, &source);
+        return;
+    }
+    if !matches!(capture.event.selection_type, SelectionType::Selected) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let detector = text_detector::detector(&app);
+        // Our own windows aren't a source worth going back to
+        let Some(pid) = detector.frontmost_app_pid().filter(|pid| *pid as u32 != std::process::id()) else {
+            return;
+        };
+        let source = CaptureSource {
+            app: detector.bundle_id(pid),
+            window_title: detector.window_title(pid),
+            range: detector.selected_range().ok().flatten(),
+            ..Default::default()
+        };
+        store(&app, id, &source);
+    });
+}
+
+fn store(app: &AppHandle, id: i64, source: &CaptureSource) {
+    let Some(history) = app.try_state::<HistoryStore>() else {
+        return;
+    };
+    if let Err(e) = history.set_source(id, source) {
+        eprintln!("Failed to record the source of capture {}: {}", id, e);
+    }
+}
+
+/// Where capture `id` came from, and what the last recapture found.
+#[tauri::command]
+pub async fn get_capture_source(history: State<'_, HistoryStore>, id: i64) -> Result<Option<CaptureSource>, String> {
+    history.source(id).map_err(|e| e.to_string())
+}
+
+/// Goes back to where capture `id` came from and reads the same span of
+/// text again, marking the entry if it has changed. Pages are only opened
+/// again, since they can't be read from here.
+#[tauri::command]
+pub async fn recapture(app: AppHandle, history: State<'_, HistoryStore>, id: i64) -> Result<Recapture, String> {
+    let entry = history
+        .get(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No history entry with id {}", id))?;
+    let source = history
+        .source(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No source was recorded for entry {}", id))?;
+
+    if let Some(url) = &source.url {
+        tauri_plugin_opener::open_url(url, None::<&str>).map_err(|e| e.to_string())?;
+        return Ok(Recapture {
+            id,
+            status: RecaptureStatus::Reopened,
+            text: None,
+        });
+    }
+    let (Some(bundle_id), Some(range)) = (&source.app, source.range) else {
+        return Err(format!("Entry {} can't be recaptured: its app didn't report where the selection was", id));
+    };
+
+    let detector = text_detector::detector(&app);
+    let pid = detector
+        .find_app_pid(bundle_id)
+        .ok_or_else(|| format!("Application {} is not running", bundle_id))?;
+    if !detector.activate_app(pid) {
+        return Err(format!("Failed to bring {} to the front", bundle_id));
+    }
+    tokio::time::sleep(ACTIVATE_DELAY).await;
+
+    // The same span of another document would be meaningless
+    let same_window = source.window_title.is_none() || detector.window_title(pid) == source.window_title;
+    let text = if same_window {
+        detector.text_in_range(pid, range).map_err(|e| e.to_string())?
+    } else {
+        None
+    };
+    // Compared as it would have been stored
+    let normalize = settings::current(&app).normalize;
+    let status = match &text {
+        None => RecaptureStatus::Missing,
+        Some(text) if normalize.enabled && normalize::clean(&normalize, text).trim() == entry.text.trim() => {
+            RecaptureStatus::Unchanged
+        }
+        Some(text) if text.trim() == entry.text.trim() => RecaptureStatus::Unchanged,
+        Some(_) => RecaptureStatus::Changed,
+    };
+    let changed = status != RecaptureStatus::Unchanged;
+    let current_text = text.as_deref().filter(|_| status == RecaptureStatus::Changed);
+    history.mark_recaptured(id, changed, current_text).map_err(|e| e.to_string())?;
+
+    let recapture = Recapture { id, status, text };
+    let _ = app.emit("capture-recaptured", &recapture);
+    Ok(recapture)
+}
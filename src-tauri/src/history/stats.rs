@@ -0,0 +1,177 @@
+//! Aggregations over the history table, computed in SQL so the frontend
+//! never has to pull the full history to draw a summary.
+
+use chrono::{Local, TimeZone};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::HistoryStore;
+use crate::text_utils;
+
+const PREVIEW_CHARS: usize = 120;
+const TOP_APPS: u32 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppCount {
+    pub app_name: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturePreview {
+    pub id: i64,
+    pub preview: String,
+    pub app_name: String,
+    pub timestamp: u64,
+}
+
+/// How far back the heatmap looks.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeatmapRange {
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl HeatmapRange {
+    fn days(self) -> Option<i64> {
+        match self {
+            HeatmapRange::Week => Some(7),
+            HeatmapRange::Month => Some(30),
+            HeatmapRange::Year => Some(365),
+            HeatmapRange::All => None,
+        }
+    }
+}
+
+/// Captures per local day of week (0 = Sunday) and hour of day.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityHeatmap {
+    pub counts: [[u32; 24]; 7],
+    pub by_hour: [u32; 24],
+    pub by_weekday: [u32; 7],
+    /// Largest cell, for scaling the colours.
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodayOverview {
+    pub captures_today: u32,
+    pub top_apps: Vec<AppCount>,
+    pub last_capture: Option<CapturePreview>,
+}
+
+impl HistoryStore {
+    pub fn today_overview(&self) -> rusqlite::Result<TodayOverview> {
+        let since = start_of_today();
+        let conn = self.conn.lock().unwrap();
+
+        let captures_today: u32 = conn.query_row(
+            "SELECT COUNT(*) FROM history WHERE timestamp >= ?1",
+            params![since],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT app_name, COUNT(*) AS n FROM history WHERE timestamp >= ?1
+             GROUP BY app_name ORDER BY n DESC, app_name LIMIT ?2",
+        )?;
+        let top_apps = stmt
+            .query_map(params![since, TOP_APPS], |row| {
+                Ok(AppCount {
+                    app_name: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Only the prefix is needed for the preview, so don't ship whole blobs
+        let mut stmt = conn.prepare(
+            "SELECT id, substr(text, 1, ?1), app_name, timestamp FROM history
+             ORDER BY timestamp DESC, id DESC LIMIT 1",
+        )?;
+        let last_capture = stmt
+            .query_map(params![PREVIEW_CHARS as u32 * 4], |row| {
+                let text: String = row.get(1)?;
+                Ok(CapturePreview {
+                    id: row.get(0)?,
+                    preview: text_utils::preview(&text, PREVIEW_CHARS),
+                    app_name: row.get(2)?,
+                    timestamp: row.get::<_, i64>(3)? as u64,
+                })
+            })?
+            .next()
+            .transpose()?;
+
+        Ok(TodayOverview {
+            captures_today,
+            top_apps,
+            last_capture,
+        })
+    }
+}
+
+impl HistoryStore {
+    pub fn activity_heatmap(&self, range: HeatmapRange) -> rusqlite::Result<ActivityHeatmap> {
+        let since = range
+            .days()
+            .map(|days| start_of_today() - (days - 1) * 24 * 60 * 60)
+            .unwrap_or(0);
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT CAST(strftime('%w', timestamp, 'unixepoch', 'localtime') AS INTEGER) AS day,
+                    CAST(strftime('%H', timestamp, 'unixepoch', 'localtime') AS INTEGER) AS hour,
+                    COUNT(*)
+             FROM history WHERE timestamp >= ?1
+             GROUP BY day, hour",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok((row.get::<_, usize>(0)?, row.get::<_, usize>(1)?, row.get::<_, u32>(2)?))
+        })?;
+
+        let mut heatmap = ActivityHeatmap {
+            counts: [[0; 24]; 7],
+            by_hour: [0; 24],
+            by_weekday: [0; 7],
+            max: 0,
+        };
+        for row in rows {
+            let (day, hour, count) = row?;
+            if day >= 7 || hour >= 24 {
+                continue;
+            }
+            heatmap.counts[day][hour] = count;
+            heatmap.by_hour[hour] += count;
+            heatmap.by_weekday[day] += count;
+            heatmap.max = heatmap.max.max(count);
+        }
+        Ok(heatmap)
+    }
+}
+
+/// Unix timestamp of local midnight today.
+fn start_of_today() -> i64 {
+    let midnight = Local::now().date_naive().and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or(0)
+}
+
+#[tauri::command]
+pub async fn get_today_overview(history: State<'_, HistoryStore>) -> Result<TodayOverview, String> {
+    history.today_overview().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_activity_heatmap(
+    history: State<'_, HistoryStore>,
+    range: HeatmapRange,
+) -> Result<ActivityHeatmap, String> {
+    history.activity_heatmap(range).map_err(|e| e.to_string())
+}
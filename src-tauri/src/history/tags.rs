@@ -0,0 +1,79 @@
+//! Free-form tags on history entries.
+
+use rusqlite::params;
+use tauri::State;
+
+use super::{entry_from_row, HistoryEntry, HistoryStore, ENTRY_COLUMNS};
+
+/// Separates tags in the aggregated `tags` column of entry queries.
+pub(super) const SEPARATOR: char = '\u{1f}';
+
+/// Tags are case-insensitive and stored trimmed and lowercased.
+pub fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+impl HistoryStore {
+    pub fn add_tags(&self, id: i64, tags: &[String]) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("INSERT OR IGNORE INTO tags (history_id, tag) VALUES (?1, ?2)")?;
+        let mut added = 0;
+        for tag in tags.iter().filter_map(|tag| normalize(tag)) {
+            added += stmt.execute(params![id, tag])?;
+        }
+        Ok(added)
+    }
+
+    pub fn remove_tags(&self, id: i64, tags: &[String]) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("DELETE FROM tags WHERE history_id = ?1 AND tag = ?2")?;
+        let mut removed = 0;
+        for tag in tags.iter().filter_map(|tag| normalize(tag)) {
+            removed += stmt.execute(params![id, tag])?;
+        }
+        Ok(removed)
+    }
+
+    /// Entries carrying any of `tags`, newest first.
+    pub fn tagged(&self, tags: &[String]) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let tags: Vec<String> = tags.iter().filter_map(|tag| normalize(tag)).collect();
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM history WHERE id IN (SELECT history_id FROM tags WHERE tag IN (SELECT value FROM json_each(?1)))
+             ORDER BY timestamp DESC, id DESC",
+            ENTRY_COLUMNS
+        ))?;
+        let tags_json = serde_json::to_string(&tags).unwrap_or_default();
+        let rows = stmt.query_map(params![tags_json], entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Every tag in use, with how many entries carry it.
+    pub fn all_tags(&self) -> rusqlite::Result<Vec<(String, u32)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT tag, COUNT(*) FROM tags GROUP BY tag ORDER BY tag")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+}
+
+#[tauri::command]
+pub async fn add_tags(history: State<'_, HistoryStore>, id: i64, tags: Vec<String>) -> Result<usize, String> {
+    history.add_tags(id, &tags).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_tags(history: State<'_, HistoryStore>, id: i64, tags: Vec<String>) -> Result<usize, String> {
+    history.remove_tags(id, &tags).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_by_tag(history: State<'_, HistoryStore>, tag: String) -> Result<Vec<HistoryEntry>, String> {
+    history.tagged(&[tag]).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_tags(history: State<'_, HistoryStore>) -> Result<Vec<(String, u32)>, String> {
+    history.all_tags().map_err(|e| e.to_string())
+}
@@ -0,0 +1,147 @@
+//! Small screenshots of where text was selected, kept with the history
+//! entry so the list can show it in context. Grabbing the screen needs the
+//! Screen Recording permission on macOS; without it captures are stored as
+//! usual, just without a thumbnail.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use text_detection::{DetectorError, SelectionType};
+
+use super::{now_secs, HistoryStore};
+use crate::capture::Capture;
+use crate::{image_selection, settings, text_detector};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailSettings {
+    pub enabled: bool,
+    /// Longest side of a thumbnail, in pixels.
+    pub max_side: u32,
+    /// Thumbnails larger than this are not kept.
+    pub max_bytes: usize,
+    /// Thumbnails are dropped after this many days; zero keeps them.
+    pub retention_days: u32,
+    /// The oldest thumbnails are dropped once all of them together take more.
+    pub max_total_mb: u32,
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_side: 320,
+            max_bytes: 256 * 1024,
+            retention_days: 30,
+            max_total_mb: 100,
+        }
+    }
+}
+
+impl HistoryStore {
+    pub fn set_thumbnail(&self, id: i64, png: &[u8]) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO thumbnails (history_id, png, created_at) VALUES (?1, ?2, ?3)",
+            params![id, png, now_secs()],
+        )?;
+        Ok(())
+    }
+
+    pub fn thumbnail(&self, id: i64) -> rusqlite::Result<Option<Vec<u8>>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT png FROM thumbnails WHERE history_id = ?1", params![id], |row| row.get(0))
+            .optional()
+    }
+
+    /// Drops thumbnails of entries that are gone for good, those older than
+    /// `days` (unless zero), then the oldest until the rest fit in
+    /// `max_total_bytes`. Returns how many were dropped.
+    pub fn prune_thumbnails(&self, days: u32, max_total_bytes: u64) -> rusqlite::Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut pruned = tx.execute(
+            "DELETE FROM thumbnails
+             WHERE history_id NOT IN (SELECT id FROM history) AND history_id NOT IN (SELECT id FROM trash)",
+            [],
+        )?;
+        if days > 0 {
+            let cutoff = now_secs() - i64::from(days) * 24 * 60 * 60;
+            pruned += tx.execute("DELETE FROM thumbnails WHERE created_at < ?1", params![cutoff])?;
+        }
+        // Keeps the newest ones whose running total still fits
+        pruned += tx.execute(
+            "DELETE FROM thumbnails WHERE history_id IN (
+                 SELECT history_id FROM (
+                     SELECT history_id,
+                            SUM(length(png)) OVER (ORDER BY created_at DESC, history_id DESC) AS total
+                     FROM thumbnails
+                 ) WHERE total > ?1
+             )",
+            params![max_total_bytes as i64],
+        )?;
+        tx.commit()?;
+        Ok(pruned)
+    }
+}
+
+/// Drops thumbnails past the configured limits.
+pub fn prune(app: &AppHandle, history: &HistoryStore) {
+    let settings = settings::current(app).history.thumbnails;
+    match history.prune_thumbnails(settings.retention_days, u64::from(settings.max_total_mb) * 1024 * 1024) {
+        Ok(0) => {}
+        Ok(pruned) => println!("Pruned {} thumbnails", pruned),
+        Err(e) => eprintln!("Failed to prune thumbnails: {}", e),
+    }
+}
+
+/// Screenshots the selection behind `capture` and stores it with its
+/// history entry, off the capture path. Only for genuine selections read
+/// from the screen, right after reading them, while they're still there.
+pub fn grab_in_background(app: &AppHandle, capture: &Capture) {
+    let settings = settings::current(app).history.thumbnails;
+    let Some(id) = capture.id else {
+        return;
+    };
+    if !settings.enabled || !matches!(capture.event.selection_type, SelectionType::Selected) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let region = match text_detector::detector(&app).selection_region() {
+            Ok(Some(region)) => region,
+            Ok(None) | Err(DetectorError::Unsupported) => return,
+            Err(e) => {
+                eprintln!("Failed to locate the selection for a thumbnail: {}", e);
+                return;
+            }
+        };
+        let png = match image_selection::thumbnail(&app, region, settings.max_side) {
+            Ok(png) => png,
+            Err(e) => {
+                eprintln!("Failed to grab a thumbnail: {}", e);
+                return;
+            }
+        };
+        if png.len() > settings.max_bytes {
+            return;
+        }
+        let Some(history) = app.try_state::<HistoryStore>() else {
+            return;
+        };
+        if let Err(e) = history.set_thumbnail(id, &png) {
+            eprintln!("Failed to store thumbnail: {}", e);
+            return;
+        }
+        prune(&app, &history);
+    });
+}
+
+/// The thumbnail of entry `id` as a data URL, if it has one.
+#[tauri::command]
+pub async fn get_capture_thumbnail(history: State<'_, HistoryStore>, id: i64) -> Result<Option<String>, String> {
+    let png = history.thumbnail(id).map_err(|e| e.to_string())?;
+    Ok(png.map(|png| format!("data:image/png;base64,{}", STANDARD.encode(png))))
+}
@@ -0,0 +1,243 @@
+//! The focus timeline: with `record_focus_timeline` on, which app and window
+//! had focus and for how long, with no text. Captures are tied to it by
+//! time, so "everything I captured while reading that PDF" is a query, and
+//! the spans add up to a time-tracking style report.
+
+use rusqlite::{params, Row};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use text_detection::Detector;
+
+use super::{now_secs, HistoryEntry, HistoryStore, ENTRY_COLUMNS};
+use crate::{profiles, settings};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How often a span that's still going has its end moved up.
+const REFRESH_SECS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusSpan {
+    pub id: i64,
+    /// Bundle identifier where the platform has one.
+    pub app: String,
+    pub window_title: Option<String>,
+    /// Unix seconds.
+    pub started_at: u64,
+    /// Last seen, for a span that's still going.
+    pub ended_at: u64,
+}
+
+/// Time spent in one app and window over a report's range.
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusTotal {
+    pub app: String,
+    pub window_title: Option<String>,
+    pub seconds: u64,
+    pub captures: u32,
+}
+
+fn span_from_row(row: &Row) -> rusqlite::Result<FocusSpan> {
+    Ok(FocusSpan {
+        id: row.get(0)?,
+        app: row.get(1)?,
+        window_title: row.get(2)?,
+        started_at: row.get::<_, i64>(3)? as u64,
+        ended_at: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+impl HistoryStore {
+    fn open_span(&self, app: &str, window_title: Option<&str>, at: i64) -> rusqlite::Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO focus_spans (app, window_title, started_at, ended_at) VALUES (?1, ?2, ?3, ?3)",
+            params![app, window_title, at],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn extend_span(&self, id: i64, at: i64) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE focus_spans SET ended_at = ?1 WHERE id = ?2", params![at, id])?;
+        Ok(())
+    }
+
+    /// Spans overlapping `since..until`, oldest first.
+    pub fn focus_spans(&self, since: i64, until: i64) -> rusqlite::Result<Vec<FocusSpan>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, app, window_title, started_at, ended_at FROM focus_spans
+             WHERE started_at < ?2 AND ended_at >= ?1
+             ORDER BY started_at",
+        )?;
+        let rows = stmt.query_map(params![since, until], span_from_row)?;
+        rows.collect()
+    }
+
+    /// Captures made while an app (and window title containing `window`)
+    /// matching the filters had focus, newest first.
+    pub fn captures_during(
+        &self,
+        app: Option<&str>,
+        window: Option<&str>,
+        limit: u32,
+    ) -> rusqlite::Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM history WHERE EXISTS (
+                 SELECT 1 FROM focus_spans
+                 WHERE history.timestamp BETWEEN started_at AND ended_at
+                   AND (?1 IS NULL OR app = ?1)
+                   AND (?2 IS NULL OR instr(lower(window_title), lower(?2)) > 0)
+             )
+             ORDER BY timestamp DESC, id DESC LIMIT ?3",
+            ENTRY_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![app, window, limit], super::entry_from_row)?;
+        rows.collect()
+    }
+
+    /// Focus time and captures per app and window within `since..until`,
+    /// most time first. Spans are clipped to the range.
+    pub fn focus_report(&self, since: i64, until: i64) -> rusqlite::Result<Vec<FocusTotal>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT app, window_title,
+                    SUM(min(ended_at, ?2) - max(started_at, ?1)) AS seconds,
+                    (SELECT COUNT(*) FROM history h
+                     WHERE h.timestamp >= ?1 AND h.timestamp < ?2 AND EXISTS (
+                         SELECT 1 FROM focus_spans s
+                         WHERE s.app = f.app AND s.window_title IS f.window_title
+                           AND h.timestamp BETWEEN s.started_at AND s.ended_at))
+             FROM focus_spans f
+             WHERE started_at < ?2 AND ended_at >= ?1
+             GROUP BY app, window_title
+             ORDER BY seconds DESC",
+        )?;
+        let rows = stmt.query_map(params![since, until], |row| {
+            Ok(FocusTotal {
+                app: row.get(0)?,
+                window_title: row.get(1)?,
+                seconds: row.get::<_, i64>(2)?.max(0) as u64,
+                captures: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+/// The span being recorded, and the profile whose database it's in.
+struct OpenSpan {
+    id: i64,
+    profile: String,
+    app: String,
+    window_title: Option<String>,
+    refreshed_at: i64,
+}
+
+/// Records focus changes while `history.record_focus_timeline` is on.
+pub fn record(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let detector = Detector::new();
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut open: Option<OpenSpan> = None;
+        loop {
+            interval.tick().await;
+            let Some(history) = app.try_state::<HistoryStore>() else {
+                continue;
+            };
+            let now = now_secs();
+            // Our own windows don't count as focus elsewhere
+            let focused = settings::current(&app)
+                .history
+                .record_focus_timeline
+                .then(|| detector.frontmost_app_pid())
+                .flatten()
+                .filter(|pid| *pid as u32 != std::process::id())
+                .map(|pid| {
+                    let app_id = detector.bundle_id(pid).unwrap_or_else(|| "Unknown".to_string());
+                    (app_id, detector.window_title(pid))
+                });
+
+            let profile = profiles::active(&app);
+            let unchanged = match (&open, &focused) {
+                (Some(span), Some((app_id, title))) => {
+                    span.profile == profile && span.app == *app_id && span.window_title == *title
+                }
+                (None, None) => true,
+                _ => false,
+            };
+            if unchanged {
+                if let Some(span) = open.as_mut().filter(|span| now - span.refreshed_at >= REFRESH_SECS) {
+                    span.refreshed_at = now;
+                    if let Err(e) = history.extend_span(span.id, now) {
+                        eprintln!("Failed to record focus: {}", e);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(span) = open.take().filter(|span| span.profile == profile) {
+                if let Err(e) = history.extend_span(span.id, now) {
+                    eprintln!("Failed to record focus change: {}", e);
+                }
+            }
+            if let Some((app_id, window_title)) = focused {
+                match history.open_span(&app_id, window_title.as_deref(), now) {
+                    Ok(id) => {
+                        open = Some(OpenSpan {
+                            id,
+                            profile,
+                            app: app_id,
+                            window_title,
+                            refreshed_at: now,
+                        })
+                    }
+                    Err(e) => eprintln!("Failed to record focus change: {}", e),
+                }
+            }
+        }
+    });
+}
+
+/// Focus spans between `since` and `until` (Unix seconds), defaulting to
+/// the last 24 hours.
+#[tauri::command]
+pub async fn get_focus_timeline(
+    history: State<'_, HistoryStore>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<FocusSpan>, String> {
+    let until = until.unwrap_or_else(now_secs);
+    let since = since.unwrap_or(until - 24 * 60 * 60);
+    history.focus_spans(since, until).map_err(|e| e.to_string())
+}
+
+/// Captures made while `app` had focus, optionally only in windows whose
+/// title contains `window`, e.g. a PDF's name.
+#[tauri::command]
+pub async fn get_captures_during_focus(
+    history: State<'_, HistoryStore>,
+    app: Option<String>,
+    window: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history
+        .captures_during(app.as_deref(), window.as_deref(), limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// Time per app and window, with how many captures were made in each.
+#[tauri::command]
+pub async fn get_focus_report(
+    history: State<'_, HistoryStore>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<FocusTotal>, String> {
+    let until = until.unwrap_or_else(now_secs);
+    let since = since.unwrap_or(until - 24 * 60 * 60);
+    history.focus_report(since, until).map_err(|e| e.to_string())
+}
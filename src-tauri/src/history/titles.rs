@@ -0,0 +1,120 @@
+//! Short titles that make the history list scannable.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::HistoryStore;
+use crate::quota::{self, Provider};
+use crate::{secrets, settings, text_utils};
+
+const MAX_TITLE_CHARS: usize = 60;
+
+/// An OpenAI-compatible chat completions endpoint used to write titles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TitleModel {
+    pub endpoint: String,
+    pub api_key: Option<String>,
+    pub model: String,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    max_tokens: u32,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'static str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatReply,
+}
+
+#[derive(Deserialize)]
+struct ChatReply {
+    content: String,
+}
+
+/// The first sentence (or line) of `text`, shortened for a list row.
+pub fn first_sentence(text: &str) -> String {
+    let text = text.trim_start();
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| c == '\n' || (matches!(c, '.' | '!' | '?') && text[i + 1..].starts_with(char::is_whitespace)))
+        .map_or(text.len(), |(i, c)| i + c.len_utf8());
+    text_utils::preview(text[..end].trim_end_matches('\n'), MAX_TITLE_CHARS)
+}
+
+async fn request_title(model: &TitleModel, text: &str) -> Result<String, String> {
+    let excerpt: String = text.chars().take(2000).collect();
+    let request = ChatRequest {
+        model: &model.model,
+        messages: [
+            ChatMessage {
+                role: "system",
+                content: "Write a title of at most eight words for the user's text. Reply with the title only.",
+            },
+            ChatMessage {
+                role: "user",
+                content: &excerpt,
+            },
+        ],
+        max_tokens: 32,
+    };
+
+    let mut builder = reqwest::Client::new().post(&model.endpoint).json(&request);
+    if let Some(key) = &model.api_key {
+        builder = builder.bearer_auth(secrets::resolve(key)?);
+    }
+    let response = builder.send().await.map_err(|e| format!("Title request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Title model returned {}", response.status()));
+    }
+
+    let body: ChatResponse = response.json().await.map_err(|e| e.to_string())?;
+    let title = body
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .ok_or("Title model returned no choices")?;
+    Ok(text_utils::preview(title.trim().trim_matches('"'), MAX_TITLE_CHARS))
+}
+
+/// Replaces the heuristic title of entry `id` with a model-written one in
+/// the background, if a title model is configured.
+pub fn refine_in_background(app: &AppHandle, id: i64, text: &str) {
+    let Some(model) = settings::current(app).history.title_model else {
+        return;
+    };
+
+    let app = app.clone();
+    let text = text.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = quota::charge(&app, Provider::Titles, &text) {
+            eprintln!("Skipping title: {}", e);
+            return;
+        }
+        match request_title(&model, &text).await {
+            Ok(title) if !title.is_empty() => {
+                if let Err(e) = app.state::<HistoryStore>().set_title(id, &title) {
+                    eprintln!("Failed to store title: {}", e);
+                    return;
+                }
+                let _ = app.emit("history-title-updated", (id, &title));
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to generate title: {}", e),
+        }
+    });
+}
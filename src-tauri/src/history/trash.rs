@@ -0,0 +1,153 @@
+//! Soft deletion: deleted entries move to the `trash` table and can be
+//! restored until they are purged.
+
+use rusqlite::{params, Transaction};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::{now_secs, tags, thumbnails, HistoryStore};
+use crate::settings;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub text: String,
+    pub app_name: String,
+    pub timestamp: u64,
+    pub title: Option<String>,
+    pub deleted_at: u64,
+}
+
+/// Moves the history rows selected by `filter` (a WHERE clause over
+/// `history`) into the trash.
+pub(super) fn move_to_trash(tx: &Transaction, filter: &str, params: &[&dyn rusqlite::ToSql]) -> rusqlite::Result<usize> {
+    let mut all_params: Vec<&dyn rusqlite::ToSql> = params.to_vec();
+    let now = now_secs();
+    all_params.push(&now);
+
+    tx.execute(
+        &format!(
+            "INSERT OR REPLACE INTO trash
+                    (id, text, app_name, selection_type, timestamp, title, copied, note, signature, tags, deleted_at)
+             SELECT id, text, app_name, selection_type, timestamp, title, copied, note, signature,
+                    (SELECT group_concat(tag, char(31)) FROM tags WHERE tags.history_id = history.id),
+                    ?{}
+             FROM history WHERE {}",
+            all_params.len(),
+            filter
+        ),
+        all_params.as_slice(),
+    )?;
+    tx.execute(&format!("DELETE FROM history WHERE {}", filter), params)
+}
+
+impl HistoryStore {
+    pub fn trash_entry(&self, id: i64) -> rusqlite::Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let moved = move_to_trash(&tx, "id = ?1", &[&id])?;
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    /// Puts a trashed entry back under its original id, with its tags.
+    pub fn restore(&self, id: i64) -> rusqlite::Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let restored = tx.execute(
+            "INSERT INTO history (id, text, app_name, selection_type, timestamp, title, copied, note, signature)
+             SELECT id, text, app_name, selection_type, timestamp, title, copied, note, signature FROM trash WHERE id = ?1",
+            params![id],
+        )?;
+        if restored == 0 {
+            return Ok(false);
+        }
+
+        let tag_list: Option<String> = tx.query_row("SELECT tags FROM trash WHERE id = ?1", params![id], |row| row.get(0))?;
+        for tag in tag_list.iter().flat_map(|tags| tags.split(tags::SEPARATOR)) {
+            tx.execute("INSERT OR IGNORE INTO tags (history_id, tag) VALUES (?1, ?2)", params![id, tag])?;
+        }
+        tx.execute("DELETE FROM trash WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn trashed(&self) -> rusqlite::Result<Vec<TrashEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, text, app_name, timestamp, title, deleted_at FROM trash ORDER BY deleted_at DESC, id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(TrashEntry {
+                id: row.get(0)?,
+                text: row.get(1)?,
+                app_name: row.get(2)?,
+                timestamp: row.get::<_, i64>(3)? as u64,
+                title: row.get(4)?,
+                deleted_at: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Permanently deletes entries trashed more than `days` days ago.
+    pub fn purge_trash(&self, days: u32) -> rusqlite::Result<usize> {
+        let cutoff = now_secs() - i64::from(days) * 24 * 60 * 60;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM trash WHERE deleted_at < ?1", params![cutoff])
+    }
+
+    pub fn empty_trash(&self) -> rusqlite::Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM trash", [])
+    }
+}
+
+/// Purges expired trash, and the thumbnails and sources of purged
+/// entries, now and then periodically.
+pub fn schedule_purge(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(history) = app.try_state::<HistoryStore>() else {
+                return;
+            };
+            let days = settings::current(&app).history.trash_retention_days;
+            match history.purge_trash(days) {
+                Ok(0) => {}
+                Ok(purged) => println!("Purged {} expired entries from the trash", purged),
+                Err(e) => eprintln!("Failed to purge trash: {}", e),
+            }
+            thumbnails::prune(&app, &history);
+            if let Err(e) = history.prune_sources() {
+                eprintln!("Failed to prune capture sources: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn delete_history_entry(history: State<'_, HistoryStore>, id: i64) -> Result<bool, String> {
+    history.trash_entry(id).map(|moved| moved > 0).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore(history: State<'_, HistoryStore>, id: i64) -> Result<bool, String> {
+    history.restore(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_trash(history: State<'_, HistoryStore>) -> Result<Vec<TrashEntry>, String> {
+    history.trashed().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn empty_trash(history: State<'_, HistoryStore>) -> Result<usize, String> {
+    history.empty_trash().map_err(|e| e.to_string())
+}
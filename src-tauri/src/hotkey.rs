@@ -1,52 +1,314 @@
-use tauri::{AppHandle, Manager};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
-use tauri::Emitter;
-
-#[cfg(target_os = "macos")]
-mod mac_a11y;
-
-pub fn register_hotkey(app: &AppHandle) {
-    let shortcut_str = if cfg!(target_os = "macos") { "Command+Shift+L" } else { "Ctrl+Shift+L" };
-    
-    // Try to get the main window
-    let window = match app.get_webview_window("main") {
-        Some(w) => w,
-        None => {
-            eprintln!("Main window not found");
-            return;
-        }
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use text_detection::{Detector, SelectionEvent, SelectionType};
+
+use crate::actions::chat;
+use crate::actions::ActionInput;
+use crate::capture::Capture;
+use crate::sounds::{self, Sound};
+use crate::{
+    announce, capture, debug, flash, focus_tracker, history, kiosk, pause, search_palette, settings, text_detector,
+    trace, translation,
+};
+
+pub mod defaults;
+pub mod display;
+pub mod taps;
+
+use taps::CaptureTrigger;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    /// Shortcuts that only act while one of the listed apps (by bundle
+    /// identifier) is frontmost, keyed by what they're for: `capture`,
+    /// `translation_pair`, `diff`, `search` or `share`. Unlisted ones act
+    /// everywhere.
+    pub scopes: HashMap<String, Vec<String>>,
+}
+
+/// Whether the `purpose` shortcut should act in the frontmost app. It's
+/// still registered everywhere, so outside its scope the keys are swallowed
+/// rather than passed on.
+fn in_scope(app: &AppHandle, purpose: &str) -> bool {
+    let Some(apps) = settings::current(app).hotkeys.scopes.remove(purpose) else {
+        return true;
     };
+    if apps.is_empty() {
+        return true;
+    }
+    let detector = Detector::new();
+    let bundle_id = detector.frontmost_app_pid().and_then(|pid| detector.bundle_id(pid));
+    bundle_id.is_some_and(|bundle_id| apps.iter().any(|app| app.eq_ignore_ascii_case(&bundle_id)))
+}
+
+/// The shortcut that captures the selection, picked on first run.
+pub fn capture_shortcut(app: &AppHandle) -> String {
+    defaults::ensure(app)
+}
+
+/// Every global shortcut that should be registered, with what it's for.
+pub fn configured(app: &AppHandle) -> Vec<(&'static str, String)> {
+    let settings = settings::current(app);
+    let mut shortcuts = vec![("pause", settings.pause.shortcut)];
+    if settings.pause.paused {
+        return shortcuts;
+    }
+    // Modifier taps aren't a registered shortcut
+    if settings.detection.capture_trigger == CaptureTrigger::Shortcut {
+        shortcuts.push(("capture", capture_shortcut(app)));
+    }
+    shortcuts.extend([
+        ("translation pair", settings.translation.cycle_shortcut),
+        ("diff", settings.history.diff_shortcut),
+        ("search", settings.history.search_shortcut),
+    ]);
+    if let Some(shortcut) = settings.chat.share_shortcut {
+        shortcuts.push(("share", shortcut));
+    }
+    shortcuts
+}
+
+/// Captures the current selection, as the capture shortcut or modifier
+/// taps do.
+fn capture_selection(app: &AppHandle) {
+    if !in_scope(app, "capture") {
+        return;
+    }
+    println!("Hotkey triggered!");
+
+    match focus_tracker::selected_text(app, &text_detector::detector(app)) {
+        Ok(Some(text)) => {
+            println!("Selected text via hotkey: {}", text);
+            let selection_event = SelectionEvent::new(text, "Hotkey", SelectionType::Selected);
+            match capture::process(app, selection_event) {
+                Some(capture) => {
+                    trace::source(app, capture.id, "hotkey");
+                    let capture = Capture {
+                        element: debug::element_dump(app),
+                        ..capture
+                    };
+                    capture::emit(app, "hotkey-selection-detected", &capture);
+                    sounds::play(app, Sound::Success);
+                    announce::captured(app, &capture);
+                    history::sources::remember(app, &capture);
+                    history::thumbnails::grab_in_background(app, &capture);
+                    flash::show(app);
+                }
+                None => sounds::play(app, Sound::Filtered),
+            }
+        }
+        Ok(None) => sounds::play(app, Sound::Filtered),
+        Err(_) => {
+            sounds::play(app, Sound::Error);
+            let _ = app.emit("hotkey-triggered", "Hotkey pressed");
+        }
+    }
+}
+
+/// Captures the current selection and sends it to the default chat, with
+/// a sound for how it went since there's nothing else to see.
+fn share_selection(app: &AppHandle) {
+    if !in_scope(app, "share") {
+        return;
+    }
+    match focus_tracker::selected_text(app, &text_detector::detector(app)) {
+        Ok(Some(text)) => {
+            let selection_event = SelectionEvent::new(text, "Hotkey", SelectionType::Selected);
+            match capture::process(app, selection_event) {
+                Some(capture) => {
+                    trace::source(app, capture.id, "share shortcut");
+                    capture::emit(app, "hotkey-selection-detected", &capture);
+                    share(app, &capture);
+                }
+                None => sounds::play(app, Sound::Filtered),
+            }
+        }
+        Ok(None) => sounds::play(app, Sound::Filtered),
+        Err(_) => sounds::play(app, Sound::Error),
+    }
+}
+
+fn share(app: &AppHandle, capture: &Capture) {
+    if kiosk::is_locked(app) {
+        sounds::play(app, Sound::Filtered);
+        return;
+    }
+    let settings = settings::current(app).chat;
+    let input = ActionInput::from_capture(capture);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match chat::send(&settings, None, None, &input).await {
+            Ok(()) => sounds::play(&app, Sound::Success),
+            Err(e) => {
+                eprintln!("Failed to share capture: {}", e);
+                sounds::play(&app, Sound::Error);
+            }
+        }
+    });
+}
+
+// Hotkey registration function
+pub fn register_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let shortcut_str = capture_shortcut(app);
+
+    let trigger = settings::current(app).detection.capture_trigger;
+    if let CaptureTrigger::ModifierTap { modifier, taps, within_ms } = trigger {
+        taps::arm(app, modifier, taps, within_ms);
+        println!("Capturing on {} taps of {:?}", taps, modifier);
+        return Ok(());
+    }
+    taps::disarm();
 
     let global_shortcut = app.global_shortcut();
-    
+    let app_clone = app.clone();
+
     match shortcut_str.parse::<Shortcut>() {
         Ok(parsed_shortcut) => {
-            match global_shortcut.register(parsed_shortcut) {
-                Ok(_) => {
-                    println!("Hotkey {} registered successfully", shortcut_str);
-                    
-                    let _ = global_shortcut.on_shortcut(parsed_shortcut, move |_app, _hotkey, _event| {
-                        println!("Hotkey triggered!");
-                        
-                        #[cfg(target_os = "macos")]
-                        if let Some(text) = mac_a11y::get_mac_selected_text() {
-                            println!("Selected text: {}", text);
-                            let _ = window.emit("hotkey-selection-detected", text);
-                        }
-                        
-                        #[cfg(not(target_os = "macos"))]
-                        {
-                            let _ = window.emit("hotkey-triggered", "Hotkey pressed");
-                        }
-                    });
-                }
-                Err(e) => {
-                    eprintln!("Failed to register hotkey {}: {}", shortcut_str, e);
+            global_shortcut.register(parsed_shortcut)?;
+            println!("Hotkey {} registered successfully", shortcut_str);
+
+            let _ = global_shortcut.on_shortcut(parsed_shortcut, move |_app, _hotkey, event| {
+                // Released fires too, which would capture (and beep) twice
+                if event.state() == ShortcutState::Pressed {
+                    capture_selection(&app_clone);
                 }
-            }
+            });
+
+            Ok(())
         }
         Err(e) => {
-            eprintln!("Failed to parse hotkey {}: {}", shortcut_str, e);
+            Err(format!("Failed to parse hotkey {}: {}", shortcut_str, e).into())
+        }
+    }
+}
+
+/// Registers the shortcut that cycles through the favourite translation pairs.
+pub fn register_translation_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let shortcut_str = settings::current(app).translation.cycle_shortcut;
+    let parsed_shortcut = shortcut_str
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut_str, e))?;
+
+    app.global_shortcut().on_shortcut(parsed_shortcut, |app, _hotkey, event| {
+        if event.state() != ShortcutState::Pressed || !in_scope(app, "translation_pair") {
+            return;
+        }
+        match translation::cycle(app) {
+            Ok(pair) => println!("Translation target switched to {}", pair),
+            Err(e) => eprintln!("Failed to cycle translation target: {}", e),
         }
+    })?;
+    println!("Hotkey {} registered successfully", shortcut_str);
+
+    Ok(())
+}
+
+/// Registers the shortcut that diffs the last two captures.
+pub fn register_diff_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let shortcut_str = settings::current(app).history.diff_shortcut;
+    let parsed_shortcut = shortcut_str
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut_str, e))?;
+
+    app.global_shortcut().on_shortcut(parsed_shortcut, |app, _hotkey, event| {
+        if event.state() == ShortcutState::Pressed && in_scope(app, "diff") {
+            history::diff::show_last_two(app);
+        }
+    })?;
+    println!("Hotkey {} registered successfully", shortcut_str);
+
+    Ok(())
+}
+
+/// Registers the shortcut that toggles the search palette.
+pub fn register_search_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let shortcut_str = settings::current(app).history.search_shortcut;
+    let parsed_shortcut = shortcut_str
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut_str, e))?;
+
+    app.global_shortcut().on_shortcut(parsed_shortcut, |app, _hotkey, event| {
+        if event.state() != ShortcutState::Pressed || !in_scope(app, "search") {
+            return;
+        }
+        if let Err(e) = search_palette::toggle(app) {
+            eprintln!("Failed to toggle search palette: {}", e);
+        }
+    })?;
+    println!("Hotkey {} registered successfully", shortcut_str);
+
+    Ok(())
+}
+
+/// Registers the shortcut that shares the selection to chat, if one is set.
+pub fn register_share_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(shortcut_str) = settings::current(app).chat.share_shortcut else {
+        return Ok(());
+    };
+    let parsed_shortcut = shortcut_str
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut_str, e))?;
+
+    app.global_shortcut().on_shortcut(parsed_shortcut, |app, _hotkey, event| {
+        if event.state() == ShortcutState::Pressed {
+            share_selection(app);
+        }
+    })?;
+    println!("Hotkey {} registered successfully", shortcut_str);
+
+    Ok(())
+}
+
+/// Registers the shortcut that pauses or resumes everything.
+pub fn register_pause_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let shortcut_str = settings::current(app).pause.shortcut;
+    let parsed_shortcut = shortcut_str
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut_str, e))?;
+
+    app.global_shortcut().on_shortcut(parsed_shortcut, |app, _hotkey, event| {
+        if event.state() != ShortcutState::Pressed {
+            return;
+        }
+        if let Err(e) = pause::toggle(app) {
+            eprintln!("Failed to toggle pause: {}", e);
+        }
+    })?;
+    println!("Hotkey {} registered successfully", shortcut_str);
+
+    Ok(())
+}
+
+/// Drops every registered shortcut and registers them again from the
+/// current settings, e.g. after switching profiles. While everything is
+/// paused, only the pause shortcut is.
+pub fn reregister_all(app: &AppHandle) {
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        eprintln!("Failed to unregister hotkeys: {}", e);
+    }
+    if let Err(e) = register_pause_hotkey(app) {
+        eprintln!("Failed to register pause hotkey: {}", e);
+    }
+    if pause::is_paused(app) {
+        taps::disarm();
+        return;
+    }
+    if let Err(e) = register_hotkey(app) {
+        eprintln!("Failed to register hotkey: {}", e);
+    }
+    if let Err(e) = register_translation_hotkey(app) {
+        eprintln!("Failed to register translation hotkey: {}", e);
+    }
+    if let Err(e) = register_diff_hotkey(app) {
+        eprintln!("Failed to register diff hotkey: {}", e);
+    }
+    if let Err(e) = register_search_hotkey(app) {
+        eprintln!("Failed to register search hotkey: {}", e);
+    }
+    if let Err(e) = register_share_hotkey(app) {
+        eprintln!("Failed to register share hotkey: {}", e);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,179 @@
+//! Picks the capture shortcut the first time the app runs. Each platform
+//! has candidates, best first; one is passed over if it's known to clash
+//! with something common for the user's platform and locale, or if another
+//! app already holds it. The pick is saved as `detection.capture_shortcut`,
+//! and what was passed over is kept for onboarding to explain.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use super::display;
+use crate::settings::{self, SettingsStore};
+
+#[cfg(target_os = "macos")]
+const CANDIDATES: &[&str] = &["Command+Shift+L", "Control+Option+L", "Command+Option+L", "Control+Shift+Space"];
+#[cfg(not(target_os = "macos"))]
+const CANDIDATES: &[&str] = &["Ctrl+Shift+L", "Ctrl+Alt+L", "Alt+Shift+L", "Ctrl+Shift+Space"];
+
+struct KnownConflict {
+    shortcut: &'static str,
+    /// Language codes the clash applies to; empty for every locale.
+    languages: &'static [&'static str],
+    with: &'static str,
+}
+
+#[cfg(target_os = "macos")]
+const KNOWN_CONFLICTS: &[KnownConflict] = &[
+    KnownConflict {
+        shortcut: "Command+Shift+L",
+        languages: &[],
+        with: "Search With Google in the Services menu",
+    },
+    KnownConflict {
+        shortcut: "Command+Option+L",
+        languages: &[],
+        with: "Finder's Downloads folder shortcut",
+    },
+];
+#[cfg(target_os = "windows")]
+const KNOWN_CONFLICTS: &[KnownConflict] = &[
+    KnownConflict {
+        shortcut: "Ctrl+Shift+L",
+        languages: &["ru", "uk", "be", "kk", "bg"],
+        with: "Ctrl+Shift, which is often set to switch keyboard layouts",
+    },
+    KnownConflict {
+        shortcut: "Ctrl+Alt+L",
+        languages: &["pl", "cs", "sk", "hu", "hr", "sl"],
+        with: "AltGr+L, which types a letter on this keyboard layout",
+    },
+    KnownConflict {
+        shortcut: "Alt+Shift+L",
+        languages: &["ru", "uk", "be", "kk", "bg", "el", "he", "ar"],
+        with: "Alt+Shift, which switches keyboard layouts",
+    },
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const KNOWN_CONFLICTS: &[KnownConflict] = &[
+    KnownConflict {
+        shortcut: "Ctrl+Alt+L",
+        languages: &[],
+        with: "locking the screen in KDE, Xfce and Cinnamon",
+    },
+    KnownConflict {
+        shortcut: "Ctrl+Shift+L",
+        languages: &["ru", "uk", "be", "kk", "bg"],
+        with: "Ctrl+Shift, which is often set to switch keyboard layouts",
+    },
+];
+
+/// A candidate that wasn't picked, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct Skipped {
+    pub shortcut: String,
+    pub reason: String,
+}
+
+/// What first run picked, for onboarding to show.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutChoice {
+    pub shortcut: String,
+    /// The shortcut as [`display::describe`] renders it.
+    pub display: String,
+    pub skipped: Vec<Skipped>,
+}
+
+/// Set only on the run that made the choice.
+#[derive(Default)]
+pub struct FirstRunChoice(Mutex<Option<ShortcutChoice>>);
+
+/// The user's language, e.g. `pl` for `pl-PL`.
+fn language() -> Option<String> {
+    let locale = sys_locale::get_locale()?;
+    let language = locale.split(['-', '_']).next()?;
+    Some(language.to_lowercase())
+}
+
+fn known_conflict(shortcut: &str, language: Option<&str>) -> Option<&'static KnownConflict> {
+    KNOWN_CONFLICTS.iter().find(|conflict| {
+        let applies = conflict.languages.is_empty()
+            || language.is_some_and(|language| conflict.languages.contains(&language));
+        conflict.shortcut == shortcut && applies
+    })
+}
+
+/// Whether another app holds `shortcut`. Registering fails if so; on
+/// platforms that can't tell, it always succeeds.
+fn taken(app: &AppHandle, shortcut: &str) -> bool {
+    let Ok(parsed) = shortcut.parse::<Shortcut>() else {
+        return true;
+    };
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.register(parsed).is_err() {
+        return true;
+    }
+    let _ = global_shortcut.unregister(parsed);
+    false
+}
+
+fn choose(app: &AppHandle) -> ShortcutChoice {
+    let language = language();
+    let mut skipped = Vec::new();
+    for candidate in CANDIDATES {
+        let reason = match known_conflict(candidate, language.as_deref()) {
+            Some(conflict) => format!("Clashes with {}", conflict.with),
+            None if taken(app, candidate) => "Already used by another app".to_string(),
+            None => {
+                return ShortcutChoice {
+                    shortcut: candidate.to_string(),
+                    display: display::describe(candidate).unwrap_or_else(|_| candidate.to_string()),
+                    skipped,
+                };
+            }
+        };
+        skipped.push(Skipped {
+            shortcut: candidate.to_string(),
+            reason,
+        });
+    }
+    // Everything clashes somewhere; the first is still the most familiar
+    ShortcutChoice {
+        shortcut: CANDIDATES[0].to_string(),
+        display: display::describe(CANDIDATES[0]).unwrap_or_else(|_| CANDIDATES[0].to_string()),
+        skipped,
+    }
+}
+
+/// The capture shortcut from settings, picking and saving one first if
+/// there isn't one yet.
+pub fn ensure(app: &AppHandle) -> String {
+    if let Some(shortcut) = settings::current(app).detection.capture_shortcut {
+        return shortcut;
+    }
+    let choice = choose(app);
+    println!("Picked {} as the capture shortcut", choice.shortcut);
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        match store.update(|s| s.detection.capture_shortcut = Some(choice.shortcut.clone())) {
+            Ok(updated) => {
+                let _ = app.emit("settings-changed", &updated.redacted());
+            }
+            Err(e) => eprintln!("Failed to save the capture shortcut: {}", e),
+        }
+    }
+    let shortcut = choice.shortcut.clone();
+    if let Some(first_run) = app.try_state::<FirstRunChoice>() {
+        *first_run.0.lock().unwrap() = Some(choice);
+    }
+    shortcut
+}
+
+/// The capture shortcut picked on this run, if this is the first run, with
+/// the candidates passed over.
+#[tauri::command]
+pub async fn get_capture_shortcut_choice(
+    first_run: tauri::State<'_, FirstRunChoice>,
+) -> Result<Option<ShortcutChoice>, String> {
+    Ok(first_run.0.lock().unwrap().clone())
+}
@@ -0,0 +1,357 @@
+//! Renders shortcuts the way the platform shows them: `⌘⇧L` on macOS,
+//! `Ctrl+Shift+L` elsewhere. Shortcuts name physical keys, so the letter
+//! on a key comes from the current keyboard layout: the key written `Y` is
+//! shown as `Z` on a German layout.
+
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+#[cfg(target_os = "macos")]
+const MODIFIERS: [(Modifiers, &str); 4] = [
+    (Modifiers::CONTROL, "⌃"),
+    (Modifiers::ALT, "⌥"),
+    (Modifiers::SHIFT, "⇧"),
+    (Modifiers::SUPER, "⌘"),
+];
+#[cfg(target_os = "windows")]
+const MODIFIERS: [(Modifiers, &str); 4] = [
+    (Modifiers::CONTROL, "Ctrl"),
+    (Modifiers::ALT, "Alt"),
+    (Modifiers::SHIFT, "Shift"),
+    (Modifiers::SUPER, "Win"),
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const MODIFIERS: [(Modifiers, &str); 4] = [
+    (Modifiers::CONTROL, "Ctrl"),
+    (Modifiers::ALT, "Alt"),
+    (Modifiers::SHIFT, "Shift"),
+    (Modifiers::SUPER, "Super"),
+];
+
+/// Keys whose name doesn't depend on the layout.
+fn key_name(key: Code) -> Option<&'static str> {
+    let mac = cfg!(target_os = "macos");
+    Some(match key {
+        Code::Enter | Code::NumpadEnter if mac => "↩",
+        Code::Enter | Code::NumpadEnter => "Enter",
+        Code::Escape if mac => "⎋",
+        Code::Escape => "Esc",
+        Code::Tab if mac => "⇥",
+        Code::Tab => "Tab",
+        Code::Backspace if mac => "⌫",
+        Code::Backspace => "Backspace",
+        Code::Delete if mac => "⌦",
+        Code::Delete => "Del",
+        Code::CapsLock if mac => "⇪",
+        Code::CapsLock => "Caps Lock",
+        Code::Home if mac => "↖",
+        Code::End if mac => "↘",
+        Code::PageUp if mac => "⇞",
+        Code::PageDown if mac => "⇟",
+        Code::Home => "Home",
+        Code::End => "End",
+        Code::PageUp => "PgUp",
+        Code::PageDown => "PgDn",
+        Code::Space => "Space",
+        Code::Insert => "Ins",
+        Code::ArrowUp => "↑",
+        Code::ArrowDown => "↓",
+        Code::ArrowLeft => "←",
+        Code::ArrowRight => "→",
+        Code::PrintScreen => "PrtSc",
+        Code::ScrollLock => "Scroll Lock",
+        Code::NumLock => "Num Lock",
+        Code::Pause => "Pause",
+        Code::F1 => "F1",
+        Code::F2 => "F2",
+        Code::F3 => "F3",
+        Code::F4 => "F4",
+        Code::F5 => "F5",
+        Code::F6 => "F6",
+        Code::F7 => "F7",
+        Code::F8 => "F8",
+        Code::F9 => "F9",
+        Code::F10 => "F10",
+        Code::F11 => "F11",
+        Code::F12 => "F12",
+        Code::Numpad0 => "Num 0",
+        Code::Numpad1 => "Num 1",
+        Code::Numpad2 => "Num 2",
+        Code::Numpad3 => "Num 3",
+        Code::Numpad4 => "Num 4",
+        Code::Numpad5 => "Num 5",
+        Code::Numpad6 => "Num 6",
+        Code::Numpad7 => "Num 7",
+        Code::Numpad8 => "Num 8",
+        Code::Numpad9 => "Num 9",
+        Code::NumpadAdd => "Num +",
+        Code::NumpadSubtract => "Num -",
+        Code::NumpadMultiply => "Num *",
+        Code::NumpadDivide => "Num /",
+        Code::NumpadDecimal => "Num .",
+        Code::NumpadEqual => "Num =",
+        _ => return None,
+    })
+}
+
+/// What the key prints on a US layout, for when the current layout can't
+/// be read.
+fn us_label(key: Code) -> Option<char> {
+    let text = key.to_string();
+    if let Some(letter) = text.strip_prefix("Key") {
+        return letter.chars().next();
+    }
+    if let Some(digit) = text.strip_prefix("Digit") {
+        return digit.chars().next();
+    }
+    Some(match key {
+        Code::Backquote => '`',
+        Code::Minus => '-',
+        Code::Equal => '=',
+        Code::BracketLeft => '[',
+        Code::BracketRight => ']',
+        Code::Backslash => '\\',
+        Code::Semicolon => ';',
+        Code::Quote => '\'',
+        Code::Comma => ',',
+        Code::Period => '.',
+        Code::Slash => '/',
+        _ => return None,
+    })
+}
+
+fn key_label(key: Code) -> String {
+    if let Some(name) = key_name(key) {
+        return name.to_string();
+    }
+    platform::layout_label(key)
+        .filter(|label| !label.is_control())
+        .or_else(|| us_label(key))
+        .map(|label| label.to_uppercase().collect::<String>())
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// `shortcut` as the user should see it.
+pub fn describe(shortcut: &str) -> Result<String, String> {
+    let shortcut = shortcut
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Failed to parse hotkey {}: {}", shortcut, e))?;
+    let mut parts = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| shortcut.mods.contains(*modifier))
+        .map(|(_, label)| label.to_string())
+        .collect::<Vec<_>>();
+    parts.push(key_label(shortcut.key));
+    let separator = if cfg!(target_os = "macos") { "" } else { "+" };
+    Ok(parts.join(separator))
+}
+
+/// Sync on purpose: sync commands run on the main thread, which macOS
+/// requires for reading the keyboard layout.
+#[tauri::command]
+pub fn describe_shortcut(shortcut: String) -> Result<String, String> {
+    describe(&shortcut)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+    use core_foundation::data::{CFData, CFDataRef};
+    use core_foundation::string::CFStringRef;
+    use std::ffi::c_void;
+    use tauri_plugin_global_shortcut::Code;
+
+    const KEY_ACTION_DISPLAY: u16 = 3;
+    const NO_DEAD_KEYS: u32 = 1;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+        fn TISCopyCurrentKeyboardLayoutInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFStringRef) -> *const c_void;
+        fn LMGetKbdType() -> u8;
+        #[allow(clippy::too_many_arguments)]
+        fn UCKeyTranslate(
+            layout: *const u8,
+            key_code: u16,
+            key_action: u16,
+            modifier_state: u32,
+            keyboard_type: u32,
+            options: u32,
+            dead_key_state: *mut u32,
+            max_length: usize,
+            actual_length: *mut usize,
+            unicode: *mut u16,
+        ) -> i32;
+    }
+
+    /// The `kVK_ANSI_*` virtual key code for `key`.
+    fn virtual_key(key: Code) -> Option<u16> {
+        Some(match key {
+            Code::KeyA => 0x00,
+            Code::KeyS => 0x01,
+            Code::KeyD => 0x02,
+            Code::KeyF => 0x03,
+            Code::KeyH => 0x04,
+            Code::KeyG => 0x05,
+            Code::KeyZ => 0x06,
+            Code::KeyX => 0x07,
+            Code::KeyC => 0x08,
+            Code::KeyV => 0x09,
+            Code::KeyB => 0x0B,
+            Code::KeyQ => 0x0C,
+            Code::KeyW => 0x0D,
+            Code::KeyE => 0x0E,
+            Code::KeyR => 0x0F,
+            Code::KeyY => 0x10,
+            Code::KeyT => 0x11,
+            Code::Digit1 => 0x12,
+            Code::Digit2 => 0x13,
+            Code::Digit3 => 0x14,
+            Code::Digit4 => 0x15,
+            Code::Digit6 => 0x16,
+            Code::Digit5 => 0x17,
+            Code::Equal => 0x18,
+            Code::Digit9 => 0x19,
+            Code::Digit7 => 0x1A,
+            Code::Minus => 0x1B,
+            Code::Digit8 => 0x1C,
+            Code::Digit0 => 0x1D,
+            Code::BracketRight => 0x1E,
+            Code::KeyO => 0x1F,
+            Code::KeyU => 0x20,
+            Code::BracketLeft => 0x21,
+            Code::KeyI => 0x22,
+            Code::KeyP => 0x23,
+            Code::KeyL => 0x25,
+            Code::KeyJ => 0x26,
+            Code::Quote => 0x27,
+            Code::KeyK => 0x28,
+            Code::Semicolon => 0x29,
+            Code::Backslash => 0x2A,
+            Code::Comma => 0x2B,
+            Code::Slash => 0x2C,
+            Code::KeyN => 0x2D,
+            Code::KeyM => 0x2E,
+            Code::Period => 0x2F,
+            Code::Backquote => 0x32,
+            _ => return None,
+        })
+    }
+
+    pub fn layout_label(key: Code) -> Option<char> {
+        let key_code = virtual_key(key)?;
+        unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData) as CFDataRef;
+            let label = if data.is_null() {
+                // Input methods such as Japanese have no layout data
+                None
+            } else {
+                let data = CFData::wrap_under_get_rule(data);
+                let mut dead_key_state = 0;
+                let mut unicode = [0u16; 4];
+                let mut length = 0;
+                let status = UCKeyTranslate(
+                    data.bytes().as_ptr(),
+                    key_code,
+                    KEY_ACTION_DISPLAY,
+                    0,
+                    LMGetKbdType() as u32,
+                    NO_DEAD_KEYS,
+                    &mut dead_key_state,
+                    unicode.len(),
+                    &mut length,
+                    unicode.as_mut_ptr(),
+                );
+                (status == 0)
+                    .then(|| char::decode_utf16(unicode[..length].iter().copied()).next()?.ok())
+                    .flatten()
+            };
+            CFRelease(source);
+            label
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use tauri_plugin_global_shortcut::Code;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VK_TO_CHAR, MAPVK_VSC_TO_VK_EX};
+
+    /// The set 1 scan code for `key`.
+    fn scan_code(key: Code) -> Option<u32> {
+        Some(match key {
+            Code::Backquote => 0x29,
+            Code::Digit1 => 0x02,
+            Code::Digit2 => 0x03,
+            Code::Digit3 => 0x04,
+            Code::Digit4 => 0x05,
+            Code::Digit5 => 0x06,
+            Code::Digit6 => 0x07,
+            Code::Digit7 => 0x08,
+            Code::Digit8 => 0x09,
+            Code::Digit9 => 0x0A,
+            Code::Digit0 => 0x0B,
+            Code::Minus => 0x0C,
+            Code::Equal => 0x0D,
+            Code::KeyQ => 0x10,
+            Code::KeyW => 0x11,
+            Code::KeyE => 0x12,
+            Code::KeyR => 0x13,
+            Code::KeyT => 0x14,
+            Code::KeyY => 0x15,
+            Code::KeyU => 0x16,
+            Code::KeyI => 0x17,
+            Code::KeyO => 0x18,
+            Code::KeyP => 0x19,
+            Code::BracketLeft => 0x1A,
+            Code::BracketRight => 0x1B,
+            Code::KeyA => 0x1E,
+            Code::KeyS => 0x1F,
+            Code::KeyD => 0x20,
+            Code::KeyF => 0x21,
+            Code::KeyG => 0x22,
+            Code::KeyH => 0x23,
+            Code::KeyJ => 0x24,
+            Code::KeyK => 0x25,
+            Code::KeyL => 0x26,
+            Code::Semicolon => 0x27,
+            Code::Quote => 0x28,
+            Code::Backslash => 0x2B,
+            Code::KeyZ => 0x2C,
+            Code::KeyX => 0x2D,
+            Code::KeyC => 0x2E,
+            Code::KeyV => 0x2F,
+            Code::KeyB => 0x30,
+            Code::KeyN => 0x31,
+            Code::KeyM => 0x32,
+            Code::Comma => 0x33,
+            Code::Period => 0x34,
+            Code::Slash => 0x35,
+            _ => return None,
+        })
+    }
+
+    pub fn layout_label(key: Code) -> Option<char> {
+        let virtual_key = unsafe { MapVirtualKeyW(scan_code(key)?, MAPVK_VSC_TO_VK_EX) };
+        if virtual_key == 0 {
+            return None;
+        }
+        // The top bit marks a dead key; the character is in the low word
+        let mapped = unsafe { MapVirtualKeyW(virtual_key, MAPVK_VK_TO_CHAR) } & 0xFFFF;
+        char::from_u32(mapped).filter(|label| *label != '\0')
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use tauri_plugin_global_shortcut::Code;
+
+    /// X11 and Wayland don't share a way to ask, so keys keep their US labels.
+    pub fn layout_label(_key: Code) -> Option<char> {
+        None
+    }
+}
@@ -0,0 +1,363 @@
+//! Capturing by tapping a modifier, e.g. Shift three times, for users who
+//! have no letter-key chords left. Global shortcuts can't be a bare
+//! modifier, so this listens to raw key events instead. Only modifier keys
+//! are looked at; any other key or click just breaks the sequence, so
+//! typing capitals never triggers it.
+//!
+//! On macOS this is a listen-only event tap for modifier changes on the
+//! main run loop; other keys are only noticed by when they were last
+//! pressed, never read. Elsewhere rdev listens on a thread of its own,
+//! which on Linux needs X11: under Wayland it only sees XWayland windows.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TapModifier {
+    Shift,
+    Control,
+    /// Option on macOS.
+    Alt,
+    /// Command on macOS, the Windows key elsewhere.
+    Meta,
+}
+
+/// What starts a capture.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptureTrigger {
+    /// The global shortcut in `detection.capture_shortcut`.
+    #[default]
+    Shortcut,
+    /// Tapping `modifier` `taps` times (at least 2), all within `within_ms`.
+    ModifierTap {
+        modifier: TapModifier,
+        #[serde(default = "default_taps")]
+        taps: u32,
+        #[serde(default = "default_within_ms")]
+        within_ms: u64,
+    },
+}
+
+fn default_taps() -> u32 {
+    3
+}
+
+fn default_within_ms() -> u64 {
+    600
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Armed {
+    modifier: TapModifier,
+    taps: u32,
+    within: Duration,
+}
+
+/// What the listener waits for; `None` while the shortcut is used instead.
+static ARMED: Mutex<Option<Armed>> = Mutex::new(None);
+/// The listener can't be stopped once started, so it's started at most
+/// once and disarmed instead.
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// What the listeners pass on: a modifier going down or up, or anything
+/// else that breaks the sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Input {
+    Press(TapModifier),
+    Release(TapModifier),
+    Other,
+}
+
+/// Taps seen so far.
+#[derive(Default)]
+struct Sequence {
+    started: Option<Instant>,
+    count: u32,
+    held: bool,
+}
+
+impl Sequence {
+    /// Feeds one event in; returns whether it completed the sequence.
+    fn feed(&mut self, armed: Armed, input: Input) -> bool {
+        match input {
+            Input::Press(modifier) if modifier == armed.modifier => {
+                // Holding the key repeats the press
+                if self.held {
+                    return false;
+                }
+                self.held = true;
+                if self.started.is_none_or(|started| started.elapsed() > armed.within) {
+                    self.started = Some(Instant::now());
+                    self.count = 0;
+                }
+                false
+            }
+            Input::Release(modifier) if modifier == armed.modifier => {
+                if !std::mem::take(&mut self.held) {
+                    return false;
+                }
+                self.count += 1;
+                let in_time = self.started.is_some_and(|started| started.elapsed() <= armed.within);
+                if in_time && self.count >= armed.taps {
+                    *self = Sequence::default();
+                    return true;
+                }
+                false
+            }
+            Input::Press(_) | Input::Other => {
+                *self = Sequence::default();
+                false
+            }
+            Input::Release(_) => false,
+        }
+    }
+}
+
+static SEQUENCE: Mutex<Sequence> = Mutex::new(Sequence {
+    started: None,
+    count: 0,
+    held: false,
+});
+
+/// Called by the platform listener for each input; captures once the
+/// armed sequence completes.
+fn feed(app: &AppHandle, input: Input) {
+    let Some(armed) = *ARMED.lock().unwrap() else {
+        return;
+    };
+    if SEQUENCE.lock().unwrap().feed(armed, input) {
+        // Off the listener's thread, which has to keep up with input
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || super::capture_selection(&app));
+    }
+}
+
+/// Whether modifier taps can be heard here, for the feature overview.
+pub fn availability() -> Result<(), String> {
+    platform::availability()
+}
+
+/// Captures on `taps` taps of `modifier`, starting the listener if it
+/// isn't running yet.
+pub fn arm(app: &AppHandle, modifier: TapModifier, taps: u32, within_ms: u64) {
+    *ARMED.lock().unwrap() = Some(Armed {
+        modifier,
+        taps: taps.max(2),
+        within: Duration::from_millis(within_ms),
+    });
+    if LISTENING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if let Err(e) = platform::listen(app) {
+        eprintln!("Failed to listen for modifier taps: {}", e);
+        LISTENING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Stops reacting to taps, e.g. after switching back to the shortcut.
+pub fn disarm() {
+    *ARMED.lock().unwrap() = None;
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{Input, TapModifier};
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Instant;
+    use tauri::AppHandle;
+
+    type CGEventRef = *mut c_void;
+    type CFMachPortRef = *mut c_void;
+    type CFRunLoopSourceRef = *mut c_void;
+    type CFRunLoopRef = *mut c_void;
+    type CFStringRef = *const c_void;
+    type CGEventTapCallBack = extern "C" fn(*mut c_void, u32, CGEventRef, *mut c_void) -> CGEventRef;
+
+    const SESSION_EVENT_TAP: u32 = 1;
+    const HEAD_INSERT_EVENT_TAP: u32 = 0;
+    const TAP_OPTION_LISTEN_ONLY: u32 = 1;
+    const EVENT_LEFT_MOUSE_DOWN: u32 = 1;
+    const EVENT_KEY_DOWN: u32 = 10;
+    const EVENT_FLAGS_CHANGED: u32 = 12;
+    const EVENT_TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
+    const KEYBOARD_EVENT_KEYCODE: u32 = 9;
+    const COMBINED_SESSION_STATE: i32 = 0;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventTapCreate(
+            tap: u32,
+            place: u32,
+            options: u32,
+            events_of_interest: u64,
+            callback: CGEventTapCallBack,
+            user_info: *mut c_void,
+        ) -> CFMachPortRef;
+        fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+        fn CGEventGetFlags(event: CGEventRef) -> u64;
+        fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
+        fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+        fn CGPreflightListenEventAccess() -> bool;
+        fn CGRequestListenEventAccess() -> bool;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopCommonModes: CFStringRef;
+        fn CFMachPortCreateRunLoopSource(
+            allocator: *const c_void,
+            port: CFMachPortRef,
+            order: isize,
+        ) -> CFRunLoopSourceRef;
+        fn CFRunLoopGetMain() -> CFRunLoopRef;
+        fn CFRunLoopAddSource(run_loop: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFStringRef);
+    }
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+    static TAP: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+    /// When the last modifier change was seen.
+    static LAST_CHANGE: Mutex<Option<Instant>> = Mutex::new(None);
+
+    /// The modifier `keycode` is, with the flag that's set while it's down.
+    fn modifier(keycode: i64) -> Option<(TapModifier, u64)> {
+        match keycode {
+            56 | 60 => Some((TapModifier::Shift, 0x0002_0000)),
+            59 | 62 => Some((TapModifier::Control, 0x0004_0000)),
+            58 | 61 => Some((TapModifier::Alt, 0x0008_0000)),
+            55 | 54 => Some((TapModifier::Meta, 0x0010_0000)),
+            _ => None,
+        }
+    }
+
+    /// Whether a key or click happened since `since`, from the system's
+    /// last-event times rather than the events themselves.
+    fn other_input_since(since: Instant) -> bool {
+        let elapsed = since.elapsed().as_secs_f64();
+        [EVENT_KEY_DOWN, EVENT_LEFT_MOUSE_DOWN].into_iter().any(|event_type| {
+            let since = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, event_type) };
+            since < elapsed
+        })
+    }
+
+    extern "C" fn on_event(_proxy: *mut c_void, event_type: u32, event: CGEventRef, _info: *mut c_void) -> CGEventRef {
+        if event_type == EVENT_TAP_DISABLED_BY_TIMEOUT {
+            unsafe { CGEventTapEnable(TAP.load(Ordering::SeqCst), true) };
+            return event;
+        }
+        let Some(app) = APP.get() else {
+            return event;
+        };
+        let previous = LAST_CHANGE.lock().unwrap().replace(Instant::now());
+        if previous.is_some_and(other_input_since) {
+            super::feed(app, Input::Other);
+        }
+        let keycode = unsafe { CGEventGetIntegerValueField(event, KEYBOARD_EVENT_KEYCODE) };
+        let input = match modifier(keycode) {
+            Some((modifier, flag)) if unsafe { CGEventGetFlags(event) } & flag != 0 => Input::Press(modifier),
+            Some((modifier, _)) => Input::Release(modifier),
+            None => Input::Other,
+        };
+        super::feed(app, input);
+        event
+    }
+
+    pub fn availability() -> Result<(), String> {
+        if unsafe { CGPreflightListenEventAccess() } {
+            Ok(())
+        } else {
+            Err("Input Monitoring access has not been granted".to_string())
+        }
+    }
+
+    /// Installs the tap on the main run loop, which macOS requires of
+    /// anything handling input.
+    pub fn listen(app: &AppHandle) -> Result<(), String> {
+        if !unsafe { CGPreflightListenEventAccess() } {
+            unsafe { CGRequestListenEventAccess() };
+            return Err("Input Monitoring access has not been granted".to_string());
+        }
+        let _ = APP.set(app.clone());
+        // Not waited for, since this may already be on the main thread
+        app.run_on_main_thread(|| unsafe {
+            let tap = CGEventTapCreate(
+                SESSION_EVENT_TAP,
+                HEAD_INSERT_EVENT_TAP,
+                TAP_OPTION_LISTEN_ONLY,
+                1 << EVENT_FLAGS_CHANGED,
+                on_event,
+                std::ptr::null_mut(),
+            );
+            if tap.is_null() {
+                eprintln!("Failed to listen for modifier taps: the event tap could not be created");
+                super::LISTENING.store(false, Ordering::SeqCst);
+                return;
+            }
+            TAP.store(tap, Ordering::SeqCst);
+            let source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
+            CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopCommonModes);
+            CGEventTapEnable(tap, true);
+        })
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::{Input, TapModifier};
+    use rdev::{EventType, Key};
+    use tauri::AppHandle;
+
+    fn modifier(key: Key) -> Option<TapModifier> {
+        match key {
+            Key::ShiftLeft | Key::ShiftRight => Some(TapModifier::Shift),
+            Key::ControlLeft | Key::ControlRight => Some(TapModifier::Control),
+            Key::Alt | Key::AltGr => Some(TapModifier::Alt),
+            Key::MetaLeft | Key::MetaRight => Some(TapModifier::Meta),
+            _ => None,
+        }
+    }
+
+    fn is_wayland() -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+            || std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland")
+    }
+
+    pub fn availability() -> Result<(), String> {
+        if cfg!(target_os = "linux") && is_wayland() {
+            return Err("Wayland doesn't let apps see keys pressed in other windows".to_string());
+        }
+        Ok(())
+    }
+
+    /// Listens on a thread of its own; rdev's listener never returns.
+    pub fn listen(app: &AppHandle) -> Result<(), String> {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let result = rdev::listen(move |event| {
+                let input = match event.event_type {
+                    EventType::KeyPress(key) => modifier(key).map_or(Input::Other, Input::Press),
+                    EventType::KeyRelease(key) => match modifier(key) {
+                        Some(modifier) => Input::Release(modifier),
+                        None => return,
+                    },
+                    EventType::ButtonPress(_) => Input::Other,
+                    _ => return,
+                };
+                super::feed(&app, input);
+            });
+            if let Err(e) = result {
+                eprintln!("Failed to listen for modifier taps: {:?}", e);
+                super::LISTENING.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+        });
+        Ok(())
+    }
+}
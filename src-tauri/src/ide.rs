@@ -0,0 +1,95 @@
+//! Capture adapter for editors whose accessibility support is unreliable
+//! (Electron and JVM apps). When one is frontmost, the selection is asked
+//! from a companion extension over localhost instead:
+//!
+//! - VS Code: an extension serving `GET /selection` on `vscode_port`.
+//! - JetBrains IDEs: a plugin registering `/api/acmi/selection` on the
+//!   IDE's built-in web server.
+//!
+//! Both answer `{"text": "...", "file": "...", "language": "..."}`, with
+//! `text` empty when nothing is selected.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use text_detection::{Detector, SelectionEvent, SelectionType};
+
+use crate::definitions::Definitions;
+
+/// Anything slower than this is worse than just using AX.
+const REQUEST_TIMEOUT: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdeSettings {
+    pub enabled: bool,
+    pub vscode_port: u16,
+    /// The JetBrains built-in server port (Settings > Build > Debugger).
+    pub jetbrains_port: u16,
+}
+
+impl Default for IdeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            vscode_port: 17841,
+            jetbrains_port: 63342,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ide {
+    VsCode,
+    JetBrains,
+}
+
+impl Ide {
+    fn from_bundle_id(bundle_id: &str) -> Option<Self> {
+        match bundle_id {
+            "com.microsoft.VSCode" | "com.microsoft.VSCodeInsiders" | "com.vscodium" => Some(Self::VsCode),
+            id if id.starts_with("com.jetbrains.") || id == "com.google.android.studio" => Some(Self::JetBrains),
+            _ => None,
+        }
+    }
+
+    fn selection_url(self, settings: &IdeSettings) -> String {
+        match self {
+            Self::VsCode => format!("http://127.0.0.1:{}/selection", settings.vscode_port),
+            Self::JetBrains => format!("http://127.0.0.1:{}/api/acmi/selection", settings.jetbrains_port),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BridgeSelection {
+    text: String,
+}
+
+/// The editor that currently has focus, if it is one we have a bridge for.
+/// `definitions` can map more apps to a bridge, or take one away.
+pub fn frontmost(detector: &Detector, definitions: &Definitions) -> Option<Ide> {
+    let pid = detector.frontmost_app_pid()?;
+    let bundle_id = detector.bundle_id(pid)?;
+    match definitions.adapter(&bundle_id) {
+        Some(adapter) => adapter.ide(),
+        None => Ide::from_bundle_id(&bundle_id),
+    }
+}
+
+/// The editor's selection from its bridge. `None` when nothing is selected
+/// or the bridge isn't installed, so callers fall back to AX.
+pub async fn selection(settings: &IdeSettings, ide: Ide) -> Option<SelectionEvent> {
+    let response = reqwest::Client::new()
+        .get(ide.selection_url(settings))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let selection: BridgeSelection = response.json().await.ok()?;
+    (!selection.text.trim().is_empty())
+        .then(|| SelectionEvent::new(selection.text, "Unknown", SelectionType::Selected))
+}
@@ -0,0 +1,186 @@
+//! Captures a selected image (e.g. a picture focused in Preview) as a
+//! screenshot of its region, with a thumbnail and optional OCR text.
+//! Grabbing the region needs the Screen Recording permission on macOS.
+//! Off by default; only the last [`KEPT_GRABS`] full-size grabs are kept.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use text_detection::{ImageRegion, SelectionEvent, SelectionType};
+
+use crate::locations;
+
+const THUMBNAIL_MAX_SIDE: u32 = 256;
+const KEPT_GRABS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageSelectionSettings {
+    pub enabled: bool,
+    /// Run OCR on captured images and use the result as the capture text.
+    pub ocr: bool,
+    /// OCR program called as `<command> <image> stdout`, tesseract-style.
+    pub ocr_command: String,
+}
+
+impl Default for ImageSelectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ocr: false,
+            ocr_command: "tesseract".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedImage {
+    pub region: ImageRegion,
+    /// Full-size PNG in the app cache dir.
+    pub path: String,
+    /// Small PNG as a data URL, ready for an `<img>`.
+    pub thumbnail: String,
+}
+
+/// Grabs the region and builds the event for it; the text is the OCR
+/// result, or empty when OCR is off or found nothing.
+pub fn capture(app: &AppHandle, region: ImageRegion) -> Result<(SelectionEvent, CapturedImage), String> {
+    let settings = crate::settings::current(app).images;
+
+    let (path, thumbnail_path) = grab_paths(app, "image")?;
+    platform::grab(region, &path)?;
+    if let Some(dir) = path.parent() {
+        prune_grabs(dir);
+    }
+    platform::thumbnail(&path, &thumbnail_path, THUMBNAIL_MAX_SIDE)?;
+    let thumbnail = std::fs::read(&thumbnail_path).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&thumbnail_path);
+
+    let text = if settings.ocr {
+        ocr(&settings.ocr_command, &path).unwrap_or_else(|e| {
+            eprintln!("OCR failed: {}", e);
+            String::new()
+        })
+    } else {
+        String::new()
+    };
+
+    Ok((
+        SelectionEvent::new(text, "Unknown", SelectionType::Image),
+        CapturedImage {
+            region,
+            path: path.to_string_lossy().into_owned(),
+            thumbnail: format!("data:image/png;base64,{}", STANDARD.encode(thumbnail)),
+        },
+    ))
+}
+
+/// Where a grab of kind `prefix` and its thumbnail go.
+fn grab_paths(app: &AppHandle, prefix: &str) -> Result<(PathBuf, PathBuf), String> {
+    let dir = locations::cache_dir(app).map_err(|e| e.to_string())?.join("selections");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    Ok((
+        dir.join(format!("{}-{}.png", prefix, millis)),
+        dir.join(format!("{}-{}-thumb.png", prefix, millis)),
+    ))
+}
+
+/// Deletes all but the newest [`KEPT_GRABS`] full-size image grabs in
+/// `dir`.
+fn prune_grabs(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut grabs: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let name = name.to_str()?;
+            if !name.starts_with("image-") || name.ends_with("-thumb.png") {
+                return None;
+            }
+            Some((entry.metadata().ok()?.modified().ok()?, entry.path()))
+        })
+        .collect();
+    grabs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in grabs.into_iter().skip(KEPT_GRABS) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// A PNG of `region` scaled to fit in `max_side` pixels, keeping nothing
+/// on disk.
+pub fn thumbnail(app: &AppHandle, region: ImageRegion, max_side: u32) -> Result<Vec<u8>, String> {
+    let (path, thumbnail_path) = grab_paths(app, "region")?;
+    let result = platform::grab(region, &path)
+        .and_then(|()| platform::thumbnail(&path, &thumbnail_path, max_side))
+        .and_then(|()| std::fs::read(&thumbnail_path).map_err(|e| e.to_string()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&thumbnail_path);
+    result
+}
+
+fn ocr(command: &str, image: &Path) -> Result<String, String> {
+    let output = Command::new(command)
+        .arg(image)
+        .arg("stdout")
+        .output()
+        .map_err(|e| format!("{}: {}", command, e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub fn grab(region: ImageRegion, out: &Path) -> Result<(), String> {
+        let rect = format!(
+            "-R{},{},{},{}",
+            region.x.round(),
+            region.y.round(),
+            region.width.round(),
+            region.height.round()
+        );
+        run(Command::new("screencapture").args(["-x", "-t", "png", &rect]).arg(out))
+    }
+
+    pub fn thumbnail(image: &Path, out: &Path, max_side: u32) -> Result<(), String> {
+        run(Command::new("sips")
+            .arg("-Z")
+            .arg(max_side.to_string())
+            .arg(image)
+            .arg("--out")
+            .arg(out))
+    }
+
+    fn run(command: &mut Command) -> Result<(), String> {
+        let output = command.output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::*;
+
+    // The detector only reports image regions on macOS
+    pub fn grab(_region: ImageRegion, _out: &Path) -> Result<(), String> {
+        Err("Image capture is not supported on this platform".to_string())
+    }
+
+    pub fn thumbnail(_image: &Path, _out: &Path, _max_side: u32) -> Result<(), String> {
+        Err("Image capture is not supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,236 @@
+//! Read-only mode for shared machines: detection keeps working, but nothing
+//! is stored or sent anywhere and settings are frozen until unlocked.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::secrets;
+use crate::settings::{self, SettingsStore};
+
+/// Commands refused while kiosk mode is on.
+const BLOCKED_COMMANDS: &[&str] = &[
+    "update_settings",
+    "import_preset",
+    "set_secret",
+    "delete_secret",
+    "update_definitions",
+    "reload_plugins",
+    "save_script",
+    "delete_script",
+    "import_glossary",
+    "get_selection_history",
+    "request_clear",
+    "clear_history",
+    "merge_captures",
+    "get_history_by_copied",
+    "get_pending_deliveries",
+    "retry_deliveries",
+    "create_task",
+    "send_as_email",
+    "push_to_phone",
+    "send_to_chat",
+    "list_slack_channels",
+    "start_peer_pairing",
+    "pair_with_peer",
+    "unpair_peer",
+    "send_capture_to_peer",
+    "create_calendar_event",
+    "create_vcard",
+    "add_to_contacts",
+    "import_history",
+    "get_today_overview",
+    "get_activity_heatmap",
+    "diff_last_two",
+    "diff",
+    "search_history_stream",
+    "add_tags",
+    "remove_tags",
+    "get_by_tag",
+    "list_tags",
+    "get_action_log",
+    "annotate_capture",
+    "repair_database",
+    "get_focus_timeline",
+    "get_captures_during_focus",
+    "get_focus_report",
+    "run_digest_now",
+    "get_reading_list",
+    "mark_reading_list_item_read",
+    "remove_from_reading_list",
+    "export_history",
+    "delete_history_entry",
+    "restore",
+    "get_trash",
+    "empty_trash",
+    "switch_profile",
+    "export_all_user_data",
+    "wipe_all_user_data",
+    "install_browser_bridge",
+    "recapture",
+    "get_capture_source",
+    "get_capture_thumbnail",
+    "verify_capture",
+    "trace_capture",
+    "fetch_capture_body",
+    "open_file",
+    "reveal_in_file_manager",
+    "restart_elevated",
+    "export_preset",
+    "set_translation_target",
+    "cycle_translation_target",
+    "get_grpc_token",
+    "translate_text",
+    "add_to_calendar",
+    "geocode_address",
+    "get_quote",
+    "resolve_issue",
+    "open_issue",
+    "open_in_maps",
+    "open_phone_action",
+    "track_package",
+];
+
+const PBKDF2_PREFIX: &str = "pbkdf2-sha256";
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// Wrong passcodes allowed before unlocking starts to back off.
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+struct Failures {
+    count: u32,
+    last: Option<Instant>,
+}
+
+static FAILURES: Mutex<Failures> = Mutex::new(Failures { count: 0, last: None });
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskSettings {
+    pub enabled: bool,
+    /// `pbkdf2-sha256$<rounds>$<hex>` of the passcode with `salt`; no
+    /// passcode means anyone can unlock. Never sent to the frontend.
+    pub passcode_hash: Option<String>,
+    pub salt: String,
+}
+
+fn hash_passcode(salt: &str, passcode: &str, rounds: u32) -> String {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passcode.as_bytes(), salt.as_bytes(), rounds, &mut key);
+    format!("{}${}${}", PBKDF2_PREFIX, rounds, hex::encode(key))
+}
+
+/// Checks `passcode` against `expected`, including hashes saved before
+/// PBKDF2 (a bare hex SHA-256 of salt + passcode).
+fn verify_passcode(salt: &str, passcode: &str, expected: &str) -> bool {
+    let given = match expected.split('$').collect::<Vec<_>>()[..] {
+        [PBKDF2_PREFIX, rounds, _] => match rounds.parse() {
+            Ok(rounds) => hash_passcode(salt, passcode, rounds),
+            Err(_) => return false,
+        },
+        _ => hex::encode(Sha256::digest(format!("{}{}", salt, passcode))),
+    };
+    secrets::constant_time_eq(&given, expected)
+}
+
+fn new_salt() -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| e.to_string())?;
+    Ok(hex::encode(salt))
+}
+
+fn backoff(failures: u32) -> Duration {
+    match failures.checked_sub(FREE_ATTEMPTS) {
+        Some(over) => Duration::from_secs(1 << over.min(16)).min(MAX_BACKOFF),
+        None => Duration::ZERO,
+    }
+}
+
+/// Counts an unlock attempt as failed until it succeeds, so guesses made in
+/// parallel can't get around the backoff.
+fn begin_attempt() -> Result<(), String> {
+    let mut failures = FAILURES.lock().unwrap();
+    if let Some(last) = failures.last {
+        let wait = backoff(failures.count).saturating_sub(last.elapsed());
+        if !wait.is_zero() {
+            return Err(format!("Too many incorrect passcodes, try again in {}s", wait.as_secs() + 1));
+        }
+    }
+    failures.count += 1;
+    failures.last = Some(Instant::now());
+    Ok(())
+}
+
+pub fn is_locked(app: &AppHandle) -> bool {
+    settings::current(app).kiosk.enabled
+}
+
+/// Refuses blocked commands while locked, with the reason.
+pub fn check(invoke: &Invoke) -> Result<(), String> {
+    let command = invoke.message.command();
+    if BLOCKED_COMMANDS.contains(&command) && is_locked(invoke.message.webview().app_handle()) {
+        return Err(format!("{} is disabled in kiosk mode", command));
+    }
+    Ok(())
+}
+
+async fn set_enabled(app: &AppHandle, enabled: bool, passcode: Option<String>) -> Result<(), String> {
+    let store = app.try_state::<SettingsStore>().ok_or("Settings are not loaded")?;
+    let secret = match (enabled, passcode) {
+        (true, Some(code)) => {
+            let salt = new_salt()?;
+            let hash = tauri::async_runtime::spawn_blocking({
+                let salt = salt.clone();
+                move || hash_passcode(&salt, &code, PBKDF2_ROUNDS)
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            Some((salt, hash))
+        }
+        _ => None,
+    };
+    store.update(|s| {
+        s.kiosk.enabled = enabled;
+        if enabled {
+            let (salt, hash) = secret.unzip();
+            s.kiosk.salt = salt.unwrap_or_default();
+            s.kiosk.passcode_hash = hash;
+        }
+    })?;
+    let _ = app.emit("kiosk-mode-changed", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn enable_kiosk(app: AppHandle, passcode: Option<String>) -> Result<(), String> {
+    if is_locked(&app) {
+        return Err("Kiosk mode is already on".to_string());
+    }
+    set_enabled(&app, true, passcode.filter(|code| !code.is_empty())).await
+}
+
+#[tauri::command]
+pub async fn disable_kiosk(app: AppHandle, passcode: Option<String>) -> Result<(), String> {
+    let kiosk = settings::current(&app).kiosk;
+    if let Some(expected) = kiosk.passcode_hash {
+        begin_attempt()?;
+        let given = passcode.unwrap_or_default();
+        let matches = tauri::async_runtime::spawn_blocking(move || verify_passcode(&kiosk.salt, &given, &expected))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !matches {
+            return Err("Incorrect passcode".to_string());
+        }
+        *FAILURES.lock().unwrap() = Failures { count: 0, last: None };
+    }
+    set_enabled(&app, false, None).await
+}
+
+#[tauri::command]
+pub async fn is_kiosk_mode(app: AppHandle) -> Result<bool, String> {
+    Ok(is_locked(&app))
+}
@@ -0,0 +1,149 @@
+//! Where settings, history and caches are kept. Normally that's the OS's
+//! app directories, but they can be moved for running from a USB stick or
+//! on a machine where those are synced or locked down:
+//!
+//! - `--data-dir <path>` keeps everything under `path`.
+//! - Portable mode, turned on by `--portable` or a file named `portable`
+//!   next to the executable, keeps everything in a `data` folder beside it.
+//!
+//! If the chosen directory can't be created or written to, the app falls
+//! back to the usual directories rather than fail to start. The webview's
+//! own storage stays where the OS puts it.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+const DATA_DIR_FLAG: &str = "--data-dir";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DIR_NAME: &str = "data";
+const CACHE_DIR_NAME: &str = "cache";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// The OS's app directories.
+    Default,
+    /// Set with `--data-dir`.
+    Custom,
+    Portable,
+}
+
+/// The root everything is kept under instead of the OS's directories.
+static OVERRIDE: OnceLock<Option<(Mode, PathBuf)>> = OnceLock::new();
+
+/// The folder a portable install keeps its data in. On macOS that's beside
+/// the `.app` bundle rather than inside it.
+fn portable_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let mut dir = exe.parent()?;
+    if cfg!(target_os = "macos") {
+        if let Some(bundle) = dir.ancestors().find(|dir| dir.extension().is_some_and(|ext| ext == "app")) {
+            dir = bundle.parent()?;
+        }
+    }
+    Some(dir.join(PORTABLE_DIR_NAME))
+}
+
+fn is_portable(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == PORTABLE_FLAG)
+        || portable_dir()
+            .and_then(|dir| dir.parent().map(|parent| parent.join(PORTABLE_MARKER)))
+            .is_some_and(|marker| marker.exists())
+}
+
+/// The value of `--data-dir <path>` or `--data-dir=<path>`.
+fn data_dir_arg(args: &[String]) -> Option<PathBuf> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == DATA_DIR_FLAG {
+            args.get(i + 1).map(PathBuf::from)
+        } else {
+            arg.strip_prefix(DATA_DIR_FLAG)?.strip_prefix('=').map(PathBuf::from)
+        }
+    })
+}
+
+/// Creates `dir` if needed and checks that files can be written in it.
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(probe)
+}
+
+/// Reads the command line and the portable marker. Runs once, before
+/// anything looks for its files.
+pub fn init() {
+    OVERRIDE.get_or_init(|| {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let (mode, dir) = match data_dir_arg(&args) {
+            Some(dir) => (Mode::Custom, std::path::absolute(&dir).unwrap_or(dir)),
+            None if is_portable(&args) => (Mode::Portable, portable_dir()?),
+            None => return None,
+        };
+        match check_writable(&dir) {
+            Ok(()) => {
+                println!("Keeping data in {}", dir.display());
+                Some((mode, dir))
+            }
+            Err(e) => {
+                eprintln!("Can't use {} for data ({}), using the default directories", dir.display(), e);
+                None
+            }
+        }
+    });
+}
+
+fn root() -> Option<&'static PathBuf> {
+    OVERRIDE.get().and_then(|root| root.as_ref()).map(|(_, dir)| dir)
+}
+
+pub fn mode() -> Mode {
+    OVERRIDE.get().and_then(|root| root.as_ref()).map_or(Mode::Default, |(mode, _)| *mode)
+}
+
+/// Where settings live.
+pub fn config_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    match root() {
+        Some(root) => Ok(root.clone()),
+        None => app.path().app_config_dir(),
+    }
+}
+
+/// Where history and other data live. The same as [`config_dir`] when
+/// overridden, as it is on macOS anyway.
+pub fn data_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    match root() {
+        Some(root) => Ok(root.clone()),
+        None => app.path().app_data_dir(),
+    }
+}
+
+/// Where things that can be recreated live.
+pub fn cache_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    match root() {
+        Some(root) => Ok(root.join(CACHE_DIR_NAME)),
+        None => app.path().app_cache_dir(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataLocations {
+    pub mode: Mode,
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+/// Where the app keeps its files, and why there.
+#[tauri::command]
+pub async fn get_data_locations(app: AppHandle) -> Result<DataLocations, String> {
+    Ok(DataLocations {
+        mode: mode(),
+        config_dir: config_dir(&app).map_err(|e| e.to_string())?,
+        data_dir: data_dir(&app).map_err(|e| e.to_string())?,
+        cache_dir: cache_dir(&app).map_err(|e| e.to_string())?,
+    })
+}
@@ -1,304 +1,90 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
-use tauri::{AppHandle, State, Manager, Emitter};
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use serde::{Deserialize, Serialize};
-
+use tauri::{AppHandle, Manager, RunEvent, State, WindowEvent};
+use std::sync::Mutex;
+use text_detection::{Detector, DetectorError, SelectionEvent, SelectionType};
+
+mod actions;
+mod announce;
+mod app_mute;
+mod backends;
+#[cfg(feature = "bench")]
+mod bench;
+mod browser_bridge;
+mod capture;
+mod capture_lengths;
+mod coalesce;
+mod color;
+mod compute;
+mod confidence;
+mod connectivity;
+mod dates;
+mod debug;
+mod definitions;
+mod deliveries;
+mod digest;
+mod display;
+mod dock;
+mod elevation;
+mod entities;
+mod features;
+mod flash;
+mod focus_tracker;
+mod fs_actions;
+mod geometry;
+#[cfg(test)]
+mod golden;
+mod grpc;
+mod hashing;
+mod health;
+mod history;
+mod hotkey;
+mod ide;
+mod image_selection;
+mod kiosk;
+mod locations;
+mod main_window;
+mod mqtt;
+mod noise;
+mod normalize;
+mod pause;
+mod peers;
+mod plugins;
+mod presets;
+mod profiles;
+#[cfg(test)]
+mod properties;
+mod quota;
+mod readability;
+mod regex_tester;
+mod rules;
+mod safe_mode;
+mod scripts;
+mod search_palette;
+mod secrets;
+mod services;
+mod session;
+mod settings;
+mod sinks;
+mod sounds;
+mod spellcheck;
+mod state_broadcast;
 mod system_tray;
+mod terminal;
+mod text_detector;
+mod text_utils;
+mod trace;
+mod transform;
+mod translation;
+mod url_scheme;
+mod user_data;
+mod webhook_templates;
 
-// Data structures for text detection
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SelectionEvent {
-    pub text: String,
-    pub app_name: String,
-    pub timestamp: u64,
-    pub selection_type: SelectionType,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SelectionType {
-    Selected,
-    Hovered,
-    Focused,
-}
-
-// Text detector structure
-pub struct TextDetector {
-    app_handle: AppHandle,
-    is_running: Arc<Mutex<bool>>,
-    last_selection: Arc<Mutex<Option<String>>>,
-}
-
-impl TextDetector {
-    pub fn new(app_handle: AppHandle) -> Self {
-        Self {
-            app_handle,
-            is_running: Arc::new(Mutex::new(false)),
-            last_selection: Arc::new(Mutex::new(None)),
-        }
-    }
-
-    pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut is_running = self.is_running.lock().unwrap();
-        if *is_running {
-            return Ok(());
-        }
-        *is_running = true;
-        drop(is_running);
-
-        let app_handle = self.app_handle.clone();
-        let is_running_clone = Arc::clone(&self.is_running);
-        let last_selection_clone = Arc::clone(&self.last_selection);
-
-        // Check for accessibility permissions first
-        #[cfg(target_os = "macos")]
-        if !self.check_accessibility_permissions() {
-            return Err("Accessibility permissions not granted".into());
-        }
-
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_millis(500));
-            
-            loop {
-                interval.tick().await;
-                
-                let is_running = {
-                    let guard = is_running_clone.lock().unwrap();
-                    *guard
-                };
-                
-                if !is_running {
-                    break;
-                }
-
-                if let Some(selection) = Self::get_current_selection().await {
-                    let mut last = last_selection_clone.lock().unwrap();
-                    
-                    // Only emit if the selection has changed
-                    if last.as_ref() != Some(&selection.text) {
-                        *last = Some(selection.text.clone());
-                        let _ = app_handle.emit("text-selection-changed", &selection);
-                    }
-                }
-            }
-        });
-
-        Ok(())
-    }
+use text_detector::TextDetector;
 
-    pub fn stop(&self) {
-        let mut is_running = self.is_running.lock().unwrap();
-        *is_running = false;
-    }
-
-    async fn get_current_selection() -> Option<SelectionEvent> {
-        #[cfg(target_os = "macos")]
-        return macos::get_selection().await;
-        
-        #[cfg(not(target_os = "macos"))]
-        None
-    }
-
-    #[cfg(target_os = "macos")]
-    fn check_accessibility_permissions(&self) -> bool {
-        macos::check_accessibility_permissions()
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    fn check_accessibility_permissions(&self) -> bool {
-        true // Assume permissions are OK on other platforms
-    }
-
-    pub fn request_permissions(&self) -> Result<(), String> {
-        #[cfg(target_os = "macos")]
-        return macos::request_accessibility_permissions();
-        
-        #[cfg(not(target_os = "macos"))]
-        Ok(())
-    }
-}
-
-// Platform-specific implementations
-#[cfg(target_os = "macos")]
-pub mod macos {
-    use super::*;
-    use accessibility_sys::*;
-    use core_foundation::string::{CFStringRef, CFString};
-    use core_foundation::base::{CFTypeRef, TCFType};
-    
-    pub async fn get_selection() -> Option<SelectionEvent> {
-        unsafe {
-            let system_wide = AXUIElementCreateSystemWide();
-            let mut focused: AXUIElementRef = std::ptr::null_mut();
-            
-            // Create CFString for the attribute
-            let focused_attr = CFString::new(kAXFocusedUIElementAttribute);
-            let result = AXUIElementCopyAttributeValue(
-                system_wide,
-                focused_attr.as_concrete_TypeRef(),
-                &mut focused as *mut _ as *mut CFTypeRef,
-            );
-
-            if result != kAXErrorSuccess || focused.is_null() {
-                return None;
-            }
-
-            // Try to get selected text first
-            if let Some(text) = get_selected_text(focused) {
-                return Some(SelectionEvent {
-                    text,
-                    app_name: "Unknown".to_string(),
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    selection_type: SelectionType::Selected,
-                });
-            }
-
-            // If no selected text, try to get focused text or value
-            if let Some(text) = get_focused_text(focused) {
-                return Some(SelectionEvent {
-                    text,
-                    app_name: "Unknown".to_string(),
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    selection_type: SelectionType::Focused,
-                });
-            }
-
-            None
-        }
-    }
-
-    unsafe fn get_selected_text(element: AXUIElementRef) -> Option<String> {
-        let mut selected_text_ref: CFTypeRef = std::ptr::null_mut();
-        let selected_attr = CFString::new(kAXSelectedTextAttribute);
-        let result = AXUIElementCopyAttributeValue(
-            element,
-            selected_attr.as_concrete_TypeRef(),
-            &mut selected_text_ref,
-        );
-
-        if result == kAXErrorSuccess && !selected_text_ref.is_null() {
-            let cf_string: CFString = TCFType::wrap_under_create_rule(selected_text_ref as CFStringRef);
-            let text = cf_string.to_string();
-            if !text.trim().is_empty() {
-                return Some(text);
-            }
-        }
-        None
-    }
-
-    unsafe fn get_focused_text(element: AXUIElementRef) -> Option<String> {
-        // Try different attributes that might contain text
-        let attributes = [
-            kAXValueAttribute,
-            kAXTitleAttribute,
-            kAXDescriptionAttribute,
-            kAXHelpAttribute,
-        ];
-
-        for attr_name in &attributes {
-            let mut text_ref: CFTypeRef = std::ptr::null_mut();
-            let attr = CFString::new(attr_name);
-            let result = AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut text_ref);
-
-            if result == kAXErrorSuccess && !text_ref.is_null() {
-                let cf_string: CFString = TCFType::wrap_under_create_rule(text_ref as CFStringRef);
-                let text = cf_string.to_string();
-                if !text.trim().is_empty() && text.len() > 2 {
-                    return Some(text);
-                }
-            }
-        }
-        None
-    }
-
-    pub fn check_accessibility_permissions() -> bool {
-        unsafe {
-            AXIsProcessTrusted()
-        }
-    }
-
-    pub fn request_accessibility_permissions() -> Result<(), String> {
-        unsafe {
-            // Use a null pointer instead of creating a dictionary to avoid type conflicts
-            AXIsProcessTrustedWithOptions(std::ptr::null());
-        }
-        Ok(())
-    }
-
-    // Hotkey-specific function to get currently selected text
-    pub fn get_mac_selected_text() -> Option<String> {
-        unsafe {
-            let system_wide = AXUIElementCreateSystemWide();
-            let mut focused: AXUIElementRef = std::ptr::null_mut();
-            
-            let focused_attr = CFString::new(kAXFocusedUIElementAttribute);
-            let result = AXUIElementCopyAttributeValue(
-                system_wide,
-                focused_attr.as_concrete_TypeRef(),
-                &mut focused as *mut _ as *mut CFTypeRef,
-            );
-
-            if result != kAXErrorSuccess || focused.is_null() {
-                return None;
-            }
-
-            get_selected_text(focused)
-        }
-    }
-}
-
-// Hotkey registration function
-fn register_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let shortcut_str = if cfg!(target_os = "macos") { "Command+Shift+L" } else { "Ctrl+Shift+L" };
-    
-    let global_shortcut = app.global_shortcut();
-    let app_clone = app.clone();
-    
-    match shortcut_str.parse::<Shortcut>() {
-        Ok(parsed_shortcut) => {
-            global_shortcut.register(parsed_shortcut)?;
-            println!("Hotkey {} registered successfully", shortcut_str);
-            
-            let _ = global_shortcut.on_shortcut(parsed_shortcut, move |_app, _hotkey, _event| {
-                println!("Hotkey triggered!");
-                
-                #[cfg(target_os = "macos")]
-                if let Some(text) = macos::get_mac_selected_text() {
-                    println!("Selected text via hotkey: {}", text);
-                    let selection_event = SelectionEvent {
-                        text: text.clone(),
-                        app_name: "Hotkey".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        selection_type: SelectionType::Selected,
-                    };
-                    let _ = app_clone.emit("hotkey-selection-detected", &selection_event);
-                }
-                
-                #[cfg(not(target_os = "macos"))]
-                {
-                    let _ = app_clone.emit("hotkey-triggered", "Hotkey pressed");
-                }
-            });
-            
-            Ok(())
-        }
-        Err(e) => {
-            Err(format!("Failed to parse hotkey {}: {}", shortcut_str, e).into())
-        }
-    }
-}
+/// Logical distance between a selection and the popup shown next to it.
+const POPUP_GAP: f64 = 8.0;
 
 // Commands that can be called from the frontend
 #[tauri::command]
@@ -306,6 +92,9 @@ async fn start_text_detection(
     app_handle: AppHandle,
     detector_state: State<'_, Mutex<Option<TextDetector>>>,
 ) -> Result<String, String> {
+    if pause::is_paused(&app_handle) {
+        return Err("Everything is paused; resume it first".to_string());
+    }
     let mut detector = detector_state.lock().unwrap();
     
     if detector.is_none() {
@@ -345,85 +134,342 @@ async fn stop_text_detection(
 
 #[tauri::command]
 async fn check_permissions() -> Result<bool, String> {
-    #[cfg(target_os = "macos")]
-    {
-        Ok(macos::check_accessibility_permissions())
-    }
-    #[cfg(not(target_os = "macos"))]
-    {
-        Ok(true)
-    }
+    Ok(Detector::new().has_permissions())
 }
 
 #[tauri::command]
 async fn show_main_window(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
-    }
-    Ok(())
+    main_window::show(&app)
+}
+
+/// Shows the popup next to a selection's bounds, as the detector reports
+/// them, keeping it on the selection's monitor.
+#[tauri::command]
+async fn show_main_window_near(app: AppHandle, bounds: geometry::Rect) -> Result<(), String> {
+    let window = main_window::ensure(&app)?;
+    let monitors = display::monitors(&app);
+    let anchor = geometry::bounds_to_physical(&monitors, bounds).ok_or("Selection is not on any display")?;
+    let monitor = display::monitor_at(&app, anchor.x as i32, anchor.y as i32).ok_or("Selection is not on any display")?;
+
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let size = (size.width as f64 / scale, size.height as f64 / scale);
+    let rect = geometry::place_near(&monitor, anchor, size, POPUP_GAP);
+    geometry::apply(&window, &monitor, rect).map_err(|e| e.to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    dock::apply(&app);
+    window.set_focus().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 async fn hide_main_window(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
+    if let Some(window) = app.get_webview_window(main_window::LABEL) {
         window.hide().map_err(|e| e.to_string())?;
     }
+    dock::apply(&app);
     Ok(())
 }
 
 #[tauri::command]
-async fn get_current_selection() -> Result<Option<SelectionEvent>, String> {
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(text) = macos::get_mac_selected_text() {
-            Ok(Some(SelectionEvent {
-                text,
-                app_name: "Manual".to_string(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                selection_type: SelectionType::Selected,
-            }))
-        } else {
-            Ok(None)
-        }
-    }
-    #[cfg(not(target_os = "macos"))]
-    {
-        Ok(None)
+async fn get_current_selection(app: AppHandle) -> Result<Option<SelectionEvent>, String> {
+    match focus_tracker::selected_text(&app, &text_detector::detector(&app)) {
+        Ok(text) => Ok(text.map(|text| SelectionEvent::new(text, "Manual", SelectionType::Selected))),
+        Err(DetectorError::Unsupported) => Ok(None),
+        Err(e) => Err(e.to_string()),
     }
 }
 
+/// Captures from a specific application (by bundle identifier) by reading
+/// its own focused element, so it works while our window has focus.
+#[tauri::command]
+async fn capture_from_app(app: AppHandle, bundle_id: String) -> Result<Option<capture::Capture>, String> {
+    let detector = text_detector::detector(&app);
+    let pid = detector
+        .find_app_pid(&bundle_id)
+        .ok_or_else(|| format!("Application {} is not running", bundle_id))?;
+
+    let selection = detector.selection_in_app(pid).map_err(|e| e.to_string())?;
+    Ok(selection.and_then(|mut event| {
+        event.app_name = bundle_id;
+        let capture = capture::process(&app, event)?;
+        trace::source(&app, capture.id, "accessibility");
+        Some(capture)
+    }))
+}
+
 fn main() {
+    // Started by a browser as the extensions' native messaging host
+    if browser_bridge::is_host_invocation() {
+        browser_bridge::run_host();
+        return;
+    }
+    // Started by `cargo run --release --features bench -- --bench`
+    #[cfg(feature = "bench")]
+    if std::env::args().any(|arg| arg == "--bench") {
+        bench::run();
+        return;
+    }
+    // Before anything looks for its files
+    locations::init();
+
+    let handler = tauri::generate_handler![
+        start_text_detection,
+        stop_text_detection,
+        check_permissions,
+        elevation::restart_elevated,
+        features::get_feature_availability,
+        grpc::get_grpc_token,
+        show_main_window,
+        show_main_window_near,
+        main_window::ensure_main_window,
+        dock::set_activation_policy,
+        pause::set_paused_all,
+        pause::is_paused_all,
+        hide_main_window,
+        get_current_selection,
+        capture_from_app,
+        capture::fetch_capture_body,
+        trace::trace_capture,
+        text_detector::boost_detection,
+        actions::calendar::create_calendar_event,
+        actions::email::send_as_email,
+        actions::push::push_to_phone,
+        actions::chat::send_to_chat,
+        actions::chat::list_slack_channels,
+        actions::tasks::create_task,
+        browser_bridge::install_browser_bridge,
+        peers::list_peers,
+        peers::start_peer_pairing,
+        peers::pair_with_peer,
+        peers::unpair_peer,
+        peers::send_capture_to_peer,
+        fs_actions::reveal_in_file_manager,
+        fs_actions::open_file,
+        hashing::hash_selection,
+        history::get_selection_history,
+        history::clear::request_clear,
+        history::clear::clear_history,
+        history::merge_captures,
+        history::copies::get_history_by_copied,
+        history::trash::delete_history_entry,
+        history::trash::restore,
+        history::trash::get_trash,
+        history::trash::empty_trash,
+        history::tags::add_tags,
+        history::tags::remove_tags,
+        history::tags::get_by_tag,
+        history::tags::list_tags,
+        history::audit::get_action_log,
+        history::notes::annotate_capture,
+        history::provenance::verify_capture,
+        history::provenance::verify_export,
+        history::timeline::get_focus_timeline,
+        history::timeline::get_captures_during_focus,
+        history::timeline::get_focus_report,
+        history::reading_list::get_reading_list,
+        history::reading_list::mark_reading_list_item_read,
+        history::reading_list::remove_from_reading_list,
+        history::integrity::verify_database,
+        history::integrity::repair_database,
+        history::export::export_history,
+        history::import_history,
+        history::get_db_version,
+        history::diff::diff_last_two,
+        history::diff::diff,
+        history::search::search_history_stream,
+        history::thumbnails::get_capture_thumbnail,
+        history::sources::get_capture_source,
+        history::sources::recapture,
+        history::stats::get_today_overview,
+        history::stats::get_activity_heatmap,
+        digest::run_digest_now,
+        color::preview_color,
+        compute::currency::refresh_rates,
+        dates::convert_to_local,
+        dates::add_to_calendar,
+        display::get_displays,
+        display::get_display_at,
+        entities::contact::create_vcard,
+        entities::contact::add_to_contacts,
+        entities::phone::open_phone_action,
+        entities::address::open_in_maps,
+        entities::address::geocode_address,
+        entities::tracking::track_package,
+        entities::identifiers::copy_identifier,
+        entities::securities::get_quote,
+        entities::issues::open_issue,
+        entities::issues::resolve_issue,
+        kiosk::enable_kiosk,
+        kiosk::disable_kiosk,
+        kiosk::is_kiosk_mode,
+        profiles::list_profiles,
+        profiles::get_active_profile,
+        profiles::switch_profile,
+        user_data::export_all_user_data,
+        user_data::wipe_all_user_data,
+        locations::get_data_locations,
+        readability::analyze_readability,
+        regex_tester::set_live_regex,
+        regex_tester::get_live_regex,
+        search_palette::toggle_search_palette,
+        hotkey::display::describe_shortcut,
+        hotkey::defaults::get_capture_shortcut_choice,
+        session::is_session_locked,
+        settings::get_settings,
+        settings::update_settings,
+        settings::get_config_issues,
+        presets::export_preset,
+        presets::import_preset,
+        secrets::set_secret,
+        secrets::delete_secret,
+        sounds::preview_sound,
+        spellcheck::spellcheck_selection,
+        transform::pretty_print_json,
+        transform::decode_base64,
+        transform::url_decode,
+        transform::unescape_string,
+        translation::translate_text,
+        quota::get_quota_usage,
+        deliveries::get_pending_deliveries,
+        deliveries::retry_deliveries,
+        sinks::get_sink_status,
+        connectivity::get_network_status,
+        definitions::get_definitions,
+        definitions::update_definitions,
+        plugins::list_plugins,
+        plugins::reload_plugins,
+        scripts::list_scripts,
+        scripts::get_script,
+        scripts::save_script,
+        scripts::delete_script,
+        rules::test_rule,
+        webhook_templates::preview_webhook_template,
+        app_mute::mute_app,
+        app_mute::unmute_app,
+        app_mute::get_app_mutes,
+        capture_lengths::get_capture_lengths,
+        health::get_startup_health,
+        safe_mode::get_safe_mode,
+        translation::cycle_translation_target,
+        translation::set_translation_target,
+        translation::import_glossary
+    ];
+
     tauri::Builder::default()
+        // First, so a second launch hands its link over before starting anything
+        .plugin(tauri_plugin_single_instance::init(url_scheme::second_instance))
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .on_window_event(|window, event| {
+            // A closed main window leaves nothing for the Dock icon to show
+            if window.label() == main_window::LABEL && matches!(event, WindowEvent::Destroyed) {
+                dock::apply(window.app_handle());
+            }
+        })
         .manage(Mutex::new(None::<TextDetector>))
-        .invoke_handler(tauri::generate_handler![
-            start_text_detection,
-            stop_text_detection,
-            check_permissions,
-            show_main_window,
-            hide_main_window,
-            get_current_selection
-        ])
+        .invoke_handler(move |invoke| match kiosk::check(&invoke) {
+            Ok(()) => handler(invoke),
+            Err(message) => {
+                invoke.resolver.reject(message);
+                true
+            }
+        })
         .setup(|app| {
-            // Register global hotkey with proper error handling
-            if let Err(e) = register_hotkey(&app.handle()) {
-                eprintln!("Failed to register hotkey: {}", e);
+            // Before anything that could be what keeps the app from starting
+            safe_mode::init(app.handle());
+            let safe = safe_mode::is_active();
+
+            // Settings and history are per profile, so pick the profile first
+            if let Err(e) = profiles::init(app.handle()) {
+                eprintln!("Failed to restore active profile: {}", e);
+            }
+            if let Err(e) = settings::init(app.handle()) {
+                eprintln!("Failed to load settings: {}", e);
+            }
+
+            // Open the history database before anything can capture
+            if let Err(e) = history::init(app.handle()) {
+                eprintln!("Failed to open history database: {}", e);
             }
+            digest::schedule(app.handle());
+
+            if !safe {
+                sinks::init(app.handle());
+                if let Err(e) = deliveries::init(app.handle()) {
+                    eprintln!("Failed to load pending deliveries: {}", e);
+                }
+            }
+            connectivity::init(app.handle());
+            if !safe {
+                mqtt::init(app.handle());
+                grpc::init(app.handle());
+            }
+
+            app.manage(capture::LargeCaptures::default());
+            app.manage(trace::Traces::default());
+            app.manage(app_mute::AppMutes::default());
+            app.manage(capture_lengths::CaptureLengths::default());
+            app.manage(spellcheck::SpellChecker::default());
+            app.manage(regex_tester::RegexTester::default());
+            app.manage(history::search::SearchState::default());
+            if let Err(e) = compute::currency::init(app.handle()) {
+                eprintln!("Failed to load exchange rates: {}", e);
+            }
+            if let Err(e) = quota::init(app.handle()) {
+                eprintln!("Failed to load quota usage: {}", e);
+            }
+            if let Err(e) = definitions::init(app.handle()) {
+                eprintln!("Failed to load app definitions: {}", e);
+            }
+            if !safe {
+                if let Err(e) = plugins::init(app.handle()) {
+                    eprintln!("Failed to load plugins: {}", e);
+                }
+                if let Err(e) = scripts::init(app.handle()) {
+                    eprintln!("Failed to set up scripts: {}", e);
+                }
+            }
+
+            state_broadcast::init(app.handle());
+
+            // Start tracking the frontmost app before any capture path needs it
+            focus_tracker::init(app.handle());
+            session::init(app.handle());
+            display::init(app.handle());
+            browser_bridge::init(app.handle());
+            peers::init(app.handle());
+            services::init(app.handle());
+            url_scheme::init(app.handle());
+
+            // Register global hotkeys, or just the pause one if everything
+            // was left paused
+            app.manage(hotkey::defaults::FirstRunChoice::default());
+            hotkey::reregister_all(app.handle());
             
             // Create system tray
             system_tray::create_system_tray(&app.handle())?;
+
+            // Last, so it sees the outcome of everything above
+            health::init(app.handle());
+            safe_mode::finish_startup(app.handle());
             
             // Hide the main window on startup to start as menu bar app
-            if let Some(window) = app.get_webview_window("main") {
+            if let Some(window) = app.get_webview_window(main_window::LABEL) {
                 let _ = window.hide();
             }
+            dock::apply(app.handle());
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            // Closing the last window shouldn't quit: the tray, detector and
+            // sinks keep going, and the window comes back on demand
+            if let RunEvent::ExitRequested { code: None, api, .. } = event {
+                api.prevent_exit();
+            }
+        });
 }
@@ -0,0 +1,43 @@
+//! The main window can be closed for good, leaving no webview at all. The
+//! app keeps running from the tray, with detection, history and deliveries
+//! unaffected; events emitted meanwhile just have no listener. Anything
+//! that wants the window goes through [`ensure`], which recreates it from
+//! its `tauri.conf.json` entry.
+
+use tauri::{AppHandle, Manager, WebviewWindow, WebviewWindowBuilder};
+
+use crate::dock;
+
+pub const LABEL: &str = "main";
+
+/// The main window, created again if it was closed. A recreated window
+/// starts hidden, like the original.
+pub fn ensure(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        return Ok(window);
+    }
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|window| window.label == LABEL)
+        .ok_or("The main window is missing from the app configuration")?;
+    WebviewWindowBuilder::from_config(app, config)
+        .and_then(|builder| builder.build())
+        .map_err(|e| e.to_string())
+}
+
+/// Shows and focuses the main window, recreating it if needed.
+pub fn show(app: &AppHandle) -> Result<(), String> {
+    let window = ensure(app)?;
+    window.show().map_err(|e| e.to_string())?;
+    dock::apply(app);
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Recreates the main window if it was closed, without showing it.
+#[tauri::command]
+pub async fn ensure_main_window(app: AppHandle) -> Result<(), String> {
+    ensure(&app).map(|_| ())
+}
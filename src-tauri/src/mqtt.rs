@@ -0,0 +1,130 @@
+//! Publishes each capture to an MQTT broker, for home-automation setups:
+//! Home Assistant or Node-RED can subscribe to the topic and, say, start a
+//! kitchen timer when a "12 minutes" string is selected.
+//!
+//! The payload is the capture as the UI sees it, as JSON. The connection is
+//! made when the app starts and kept up by the client, which reconnects on
+//! its own; captures made while it's down are dropped, not queued.
+
+use rumqttc::{AsyncClient, MqttOptions, QoS, Transport};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::capture::Capture;
+use crate::trace::{self, Stage};
+use crate::{kiosk, secrets, settings};
+
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Captures waiting to go out before new ones are dropped.
+const QUEUE_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Connect over TLS, checked against the system's root certificates.
+    pub tls: bool,
+    pub client_id: String,
+    pub username: Option<String>,
+    /// The password, or a `keychain:` reference to it.
+    pub password: Option<String>,
+    pub topic: String,
+    /// 0 (at most once), 1 (at least once) or 2 (exactly once).
+    pub qos: u8,
+    /// Keep the last capture on the broker for new subscribers.
+    pub retain: bool,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            tls: false,
+            client_id: "acmi-desktop".to_string(),
+            username: None,
+            password: None,
+            topic: "acmi-desktop/capture".to_string(),
+            qos: 0,
+            retain: false,
+        }
+    }
+}
+
+struct Publisher {
+    client: AsyncClient,
+    topic: String,
+    qos: QoS,
+    retain: bool,
+}
+
+fn options(settings: &MqttSettings) -> Result<MqttOptions, String> {
+    let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+    options.set_keep_alive(KEEP_ALIVE);
+    if settings.tls {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+    if let Some(username) = &settings.username {
+        let password = settings.password.as_deref().map(secrets::resolve).transpose()?;
+        options.set_credentials(username, password.unwrap_or_default());
+    }
+    Ok(options)
+}
+
+/// Connects to the broker, if publishing is enabled.
+pub fn init(app: &AppHandle) {
+    let settings = settings::current(app).mqtt;
+    if !settings.enabled {
+        return;
+    }
+    let result = rumqttc::qos(settings.qos)
+        .map_err(|_| format!("QoS must be 0, 1 or 2, not {}", settings.qos))
+        .and_then(|qos| Ok((qos, options(&settings)?)));
+    let (qos, options) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to set up MQTT publishing: {}", e);
+            return;
+        }
+    };
+
+    let (client, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+    app.manage(Publisher {
+        client,
+        topic: settings.topic,
+        qos,
+        retain: settings.retain,
+    });
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // Polling drives the connection; after an error the next poll
+            // reconnects
+            if let Err(e) = eventloop.poll().await {
+                eprintln!("MQTT connection to {} failed: {}", settings.host, e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    });
+}
+
+/// Publishes `capture`, unless publishing is off or kiosk mode is on.
+pub fn publish(app: &AppHandle, capture: &Capture) -> Result<(), String> {
+    let Some(publisher) = app.try_state::<Publisher>() else {
+        return Ok(());
+    };
+    if kiosk::is_locked(app) {
+        return Ok(());
+    }
+    let payload = serde_json::to_vec(capture).map_err(|e| format!("Failed to encode capture for MQTT: {}", e))?;
+    let result = publisher
+        .client
+        .try_publish(publisher.topic.as_str(), publisher.qos, publisher.retain, payload)
+        .map_err(|e| format!("Failed to publish capture over MQTT: {}", e));
+    trace::step(app, capture.id, Stage::Sink, "mqtt", result.clone().map(|()| None));
+    result
+}
@@ -0,0 +1,88 @@
+//! Keeps UI chrome out of the focused-text fallback. With nothing selected,
+//! the focused element is often a button or menu item, and its title is
+//! captured as if it were content. Elements are skipped by accessibility
+//! role before their text is read, and whatever slips through is checked
+//! against a list of common UI strings.
+
+use serde::{Deserialize, Serialize};
+use text_detection::{IgnoredRole, SelectionEvent, SelectionType};
+
+use crate::definitions::Definitions;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseFilterSettings {
+    pub enabled: bool,
+    /// Roles whose focused value/title is never captured.
+    pub ignored_roles: Vec<IgnoredRole>,
+    /// Focused or hovered text equal to one of these, ignoring case and
+    /// surrounding whitespace, is dropped. Genuine selections always pass.
+    pub blocklist: Vec<String>,
+}
+
+impl Default for NoiseFilterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ignored_roles: default_roles(),
+            blocklist: DEFAULT_BLOCKLIST.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+const DEFAULT_BLOCKLIST: &[&str] = &[
+    "OK", "Cancel", "Close", "Save", "Save As…", "Open", "New", "File", "Edit", "View", "Window", "Help", "Back",
+    "Forward", "Reload", "Search", "Settings", "Preferences", "Share", "Copy", "Cut", "Paste", "Undo", "Redo",
+    "Delete", "Done", "Apply", "Yes", "No", "Next", "Previous", "More", "Menu", "Minimize", "Maximize", "Zoom",
+    "Print", "Sign in", "Sign out", "Log in", "Log out", "Submit", "Send", "Reply", "Refresh",
+];
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn role(role: &str, within: Option<&str>) -> IgnoredRole {
+    IgnoredRole {
+        role: role.to_string(),
+        within: within.map(str::to_string),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn default_roles() -> Vec<IgnoredRole> {
+    vec![
+        role("AXButton", None),
+        role("AXMenuItem", None),
+        role("AXMenuBarItem", None),
+        role("AXHelpTag", None),
+        role("AXStaticText", Some("AXToolbar")),
+    ]
+}
+
+#[cfg(target_os = "windows")]
+fn default_roles() -> Vec<IgnoredRole> {
+    vec![
+        role("Button", None),
+        role("MenuItem", None),
+        role("ToolTip", None),
+        role("Text", Some("ToolBar")),
+    ]
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn default_roles() -> Vec<IgnoredRole> {
+    Vec::new()
+}
+
+/// Whether `event` is fallback text that matches the blocklist.
+pub fn is_chrome(settings: &NoiseFilterSettings, event: &SelectionEvent) -> bool {
+    if !settings.enabled || !matches!(event.selection_type, SelectionType::Focused | SelectionType::Hovered) {
+        return false;
+    }
+    let text = event.text.trim();
+    settings.blocklist.iter().any(|entry| entry.trim().eq_ignore_ascii_case(text))
+}
+
+/// `settings` with the roles and strings from the definitions feed added.
+pub fn with_definitions(mut settings: NoiseFilterSettings, definitions: &Definitions) -> NoiseFilterSettings {
+    settings.ignored_roles.extend_from_slice(definitions.ignored_roles());
+    settings.blocklist.extend(definitions.blocklist.iter().cloned());
+    settings
+}
@@ -0,0 +1,81 @@
+//! Cleans up text copied out of PDFs and web pages before it's stored or
+//! sent anywhere. Such text is full of things nobody sees: decomposed
+//! accents, zero-width spaces, soft hyphens and bidi marks, which break
+//! search, diffing and anything matching on the text. Off by default; each
+//! cleanup can be switched off on its own once it's on.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use text_detection::SelectionEvent;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::settings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizeSettings {
+    pub enabled: bool,
+    /// Composes accents with their letters (NFC), so `é` is one character
+    /// however it was typed.
+    pub nfc: bool,
+    /// Removes zero-width spaces, byte order marks, word joiners and soft
+    /// hyphens. Zero-width joiners stay, since emoji and some scripts need
+    /// them.
+    pub strip_invisible: bool,
+    /// Removes bidi embedding, override, isolate and mark characters.
+    pub strip_bidi_controls: bool,
+    /// Turns curly quotes and apostrophes into straight ones.
+    pub straighten_quotes: bool,
+}
+
+impl Default for NormalizeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            nfc: true,
+            strip_invisible: true,
+            strip_bidi_controls: true,
+            straighten_quotes: true,
+        }
+    }
+}
+
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{FEFF}' | '\u{2060}' | '\u{00AD}' | '\u{180E}')
+}
+
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{061C}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+fn straighten(c: char) -> char {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+        c => c,
+    }
+}
+
+/// `text` with the cleanups `settings` asks for, whether or not they're
+/// enabled as a whole.
+pub fn clean(settings: &NormalizeSettings, text: &str) -> String {
+    let cleaned = text
+        .chars()
+        .filter(|&c| !(settings.strip_invisible && is_invisible(c)))
+        .filter(|&c| !(settings.strip_bidi_controls && is_bidi_control(c)))
+        .map(|c| if settings.straighten_quotes { straighten(c) } else { c });
+    if settings.nfc {
+        cleaned.nfc().collect()
+    } else {
+        cleaned.collect()
+    }
+}
+
+/// Cleans up a fresh capture's text, if normalization is on.
+pub fn run(app: &AppHandle, mut event: SelectionEvent) -> SelectionEvent {
+    let settings = settings::current(app).normalize;
+    if settings.enabled {
+        event.text = clean(&settings, &event.text);
+    }
+    event
+}
@@ -0,0 +1,82 @@
+//! The master switch: one toggle that pauses everything at once. While
+//! paused the detector is stopped, every shortcut but the one that resumes
+//! is unregistered, and no capture is made, so nothing reaches history,
+//! sinks or integrations. The tray icon is dimmed meanwhile. The switch is
+//! saved, so a paused app stays paused across restarts.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::{self, SettingsStore};
+use crate::{hotkey, kiosk, system_tray, text_detector};
+
+/// Whether detection was running when everything was paused, so resuming
+/// only restarts it if it was.
+static DETECTION_WAS_ON: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PauseSettings {
+    pub paused: bool,
+    /// Toggles the switch; the only shortcut left registered while paused.
+    pub shortcut: String,
+}
+
+impl Default for PauseSettings {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            shortcut: "CommandOrControl+Alt+Shift+P".to_string(),
+        }
+    }
+}
+
+pub fn is_paused(app: &AppHandle) -> bool {
+    settings::current(app).pause.paused
+}
+
+/// Brings the rest of the app in line with the switch.
+fn apply(app: &AppHandle, paused: bool) {
+    if paused {
+        DETECTION_WAS_ON.store(text_detector::pause(app), Ordering::SeqCst);
+    } else if DETECTION_WAS_ON.swap(false, Ordering::SeqCst) {
+        if let Err(e) = text_detector::resume(app) {
+            eprintln!("Failed to resume text detection: {}", e);
+        }
+    }
+    hotkey::reregister_all(app);
+    system_tray::show_paused(app, paused);
+}
+
+/// Pauses or resumes everything. Refused in kiosk mode, whether it comes
+/// from the frontend, the tray or the shortcut.
+pub fn set_paused(app: &AppHandle, paused: bool) -> Result<(), String> {
+    if kiosk::is_locked(app) {
+        return Err("Pausing is disabled in kiosk mode".to_string());
+    }
+    if is_paused(app) == paused {
+        return Ok(());
+    }
+    let store = app.try_state::<SettingsStore>().ok_or("Settings are not loaded")?;
+    let updated = store.update(|s| s.pause.paused = paused)?;
+    let _ = app.emit("settings-changed", &updated.redacted());
+    apply(app, paused);
+    let _ = app.emit("paused-changed", paused);
+    println!("{}", if paused { "Everything paused" } else { "Everything resumed" });
+    Ok(())
+}
+
+pub fn toggle(app: &AppHandle) -> Result<(), String> {
+    set_paused(app, !is_paused(app))
+}
+
+#[tauri::command]
+pub async fn set_paused_all(app: AppHandle, paused: bool) -> Result<(), String> {
+    set_paused(&app, paused)
+}
+
+#[tauri::command]
+pub async fn is_paused_all(app: AppHandle) -> Result<bool, String> {
+    Ok(is_paused(&app))
+}
@@ -0,0 +1,638 @@
+//! Sending a capture straight to another machine on the LAN running the
+//! app, with nothing going through a server. Peers find each other over
+//! mDNS and pair once with a six-digit code shown on the receiving side;
+//! after that, captures travel encrypted with a key only the two of them
+//! hold, in the keychain.
+//!
+//! The protocol is a line of JSON per message over TCP:
+//!
+//! - Pairing: `pair` (X25519 key) → `pair_accept` (X25519 key) →
+//!   `pair_commit` → `pair_confirm` → `pair_reveal` → `paired`. Both sides
+//!   derive keys from the shared secret salted with the code, and each
+//!   proves it used the same code with an HMAC over the exchange. The
+//!   initiator only commits to its HMAC (hashed with a random nonce) until
+//!   the responder has shown its own, so neither side hands out something
+//!   that could be checked against every code offline before the other has
+//!   committed to a guess. A code is single-use, so a wrong guess ends the
+//!   pairing.
+//! - Sending: `hello` (nonce) → `challenge` (nonce) → `capture` → `received`.
+//!   The capture is sealed with a key derived from the pair's secret and
+//!   both nonces, which authenticates the sender and rules out replays.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::SelectionEvent;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream};
+use tokio::net::{TcpListener, TcpStream};
+use x25519_dalek::{EphemeralSecret, PublicKey};
+
+use crate::history::HistoryStore;
+use crate::settings::SettingsStore;
+use crate::{history, kiosk, pause, secrets, settings};
+
+const SERVICE_TYPE: &str = "_acmi-peer._tcp.local.";
+const DEVICE_ID_NAME: &str = "peer-device-id";
+const PAIRING_LIFETIME: Duration = Duration::from_secs(120);
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_MESSAGE_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerSettings {
+    pub enabled: bool,
+    /// How this machine shows up to others; the host name if unset.
+    pub name: Option<String>,
+    pub port: u16,
+    /// Peers paired with; their keys are in the keychain.
+    pub paired: Vec<PairedPeer>,
+}
+
+impl Default for PeerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: None,
+            port: 17843,
+            paired: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedPeer {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerInfo {
+    pub id: String,
+    pub name: String,
+    pub paired: bool,
+    /// Currently seen on the network.
+    pub online: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_in_secs: u64,
+}
+
+/// A peer as last announced over mDNS.
+struct Discovered {
+    name: String,
+    fullname: String,
+    addresses: Vec<IpAddr>,
+    port: u16,
+}
+
+/// Peers on the network and the pairing code on offer, if any.
+#[derive(Default)]
+pub struct Peers {
+    discovered: Mutex<HashMap<String, Discovered>>,
+    pairing: Mutex<Option<(String, Instant)>>,
+    daemon: Mutex<Option<ServiceDaemon>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Pair { id: String, name: String, public_key: String },
+    PairAccept { id: String, name: String, public_key: String },
+    PairCommit { commitment: String },
+    PairConfirm { mac: String },
+    PairReveal { nonce: String, mac: String },
+    Paired,
+    Hello { id: String, nonce: String },
+    Challenge { nonce: String },
+    Capture { ciphertext: String },
+    Received,
+    Refused { reason: String },
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+/// This machine's id among peers, made up the first time it's needed.
+fn device_id() -> Result<String, String> {
+    secrets::get_or_create(DEVICE_ID_NAME, || Ok(hex::encode(random_bytes::<8>()?)))
+}
+
+fn device_name(settings: &PeerSettings) -> String {
+    settings
+        .name
+        .clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| gethostname::gethostname().to_string_lossy().into_owned())
+}
+
+fn link_secret_name(peer_id: &str) -> String {
+    format!("peer-link-{}", peer_id)
+}
+
+/// The confirmation key and the pair's lasting secret, from a pairing.
+fn pairing_keys(shared: &[u8], code: &str) -> ([u8; 32], [u8; 32]) {
+    let hkdf = Hkdf::<Sha256>::new(Some(code.as_bytes()), shared);
+    let mut confirm = [0u8; 32];
+    let mut link = [0u8; 32];
+    hkdf.expand(b"acmi-peer-confirm", &mut confirm).expect("32 bytes is a valid length");
+    hkdf.expand(b"acmi-peer-link", &mut link).expect("32 bytes is a valid length");
+    (confirm, link)
+}
+
+fn confirmation(key: &[u8], role: &str, transcript: &str) -> Hmac<Sha256> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(role.as_bytes());
+    mac.update(transcript.as_bytes());
+    mac
+}
+
+fn confirms(key: &[u8], role: &str, transcript: &str, mac: &str) -> bool {
+    hex::decode(mac).is_ok_and(|mac| confirmation(key, role, transcript).verify_slice(&mac).is_ok())
+}
+
+/// Binds the initiator to `mac` without revealing it; the nonce keeps the
+/// code from being brute-forced out of the commitment.
+fn commitment(nonce: &[u8], mac: &[u8]) -> String {
+    let mut hash = Sha256::new();
+    hash.update(nonce);
+    hash.update(mac);
+    hex::encode(hash.finalize())
+}
+
+/// The cipher for one transfer; each gets its own key, so the nonce can
+/// stay fixed.
+fn transfer_cipher(link: &[u8], sender_nonce: &str, receiver_nonce: &str) -> ChaCha20Poly1305 {
+    let salt = format!("{}{}", sender_nonce, receiver_nonce);
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt.as_bytes()), link)
+        .expand(b"acmi-peer-capture", &mut key)
+        .expect("32 bytes is a valid length");
+    ChaCha20Poly1305::new(&key.into())
+}
+
+async fn send(stream: &mut BufStream<TcpStream>, message: &Message) -> Result<(), String> {
+    let mut line = serde_json::to_vec(message).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+    stream.write_all(&line).await.map_err(|e| e.to_string())?;
+    stream.flush().await.map_err(|e| e.to_string())
+}
+
+async fn receive(stream: &mut BufStream<TcpStream>) -> Result<Message, String> {
+    let mut line = String::new();
+    let read = tokio::time::timeout(MESSAGE_TIMEOUT, (&mut *stream).take(MAX_MESSAGE_BYTES).read_line(&mut line))
+        .await
+        .map_err(|_| "The peer stopped responding".to_string())?
+        .map_err(|e| e.to_string())?;
+    if read == 0 {
+        return Err("The peer closed the connection".to_string());
+    }
+    match serde_json::from_str(&line).map_err(|e| format!("Malformed message from peer: {}", e))? {
+        Message::Refused { reason } => Err(reason),
+        message => Ok(message),
+    }
+}
+
+async fn refuse(stream: &mut BufStream<TcpStream>, reason: &str) -> Result<(), String> {
+    send(stream, &Message::Refused { reason: reason.to_string() }).await?;
+    Err(reason.to_string())
+}
+
+/// Records a completed pairing on this side.
+fn remember(app: &AppHandle, id: &str, name: &str, link: &[u8]) -> Result<PeerInfo, String> {
+    secrets::set(&link_secret_name(id), &hex::encode(link))?;
+    let updated = app.state::<SettingsStore>().update(|s| {
+        s.peers.paired.retain(|peer| peer.id != id);
+        s.peers.paired.push(PairedPeer {
+            id: id.to_string(),
+            name: name.to_string(),
+        });
+    })?;
+    let _ = app.emit("settings-changed", &updated.redacted());
+    let info = PeerInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        paired: true,
+        online: true,
+    };
+    let _ = app.emit("peer-paired", &info);
+    Ok(info)
+}
+
+/// Responder side of a pairing, after the `pair` message.
+async fn accept_pairing(
+    app: &AppHandle,
+    stream: &mut BufStream<TcpStream>,
+    peer_id: String,
+    peer_name: String,
+    peer_key: String,
+) -> Result<(), String> {
+    // Whatever happens next, the code has been used
+    let code = app.state::<Peers>().pairing.lock().unwrap().take();
+    let Some((code, _)) = code.filter(|(_, issued)| issued.elapsed() < PAIRING_LIFETIME) else {
+        return refuse(stream, "This machine isn't waiting to pair").await;
+    };
+    let peer_public = decode_public_key(&peer_key)?;
+    let secret = EphemeralSecret::random();
+    let public_key = hex::encode(PublicKey::from(&secret).as_bytes());
+    let settings = settings::current(app).peers;
+    let id = device_id()?;
+    let transcript = format!("{}|{}|{}|{}", peer_id, peer_key, id, public_key);
+    send(
+        stream,
+        &Message::PairAccept {
+            id,
+            name: device_name(&settings),
+            public_key,
+        },
+    )
+    .await?;
+
+    let (confirm, link) = pairing_keys(secret.diffie_hellman(&peer_public).as_bytes(), &code);
+    // The initiator is bound to its guess before it sees anything that
+    // depends on the code
+    let Message::PairCommit { commitment: committed } = receive(stream).await? else {
+        return refuse(stream, "Unexpected message while pairing").await;
+    };
+    let mac = hex::encode(confirmation(&confirm, "responder", &transcript).finalize().into_bytes());
+    send(stream, &Message::PairConfirm { mac }).await?;
+
+    let Message::PairReveal { nonce, mac } = receive(stream).await? else {
+        return refuse(stream, "Unexpected message while pairing").await;
+    };
+    let opens = match (hex::decode(&nonce), hex::decode(&mac)) {
+        (Ok(nonce), Ok(mac)) => commitment(&nonce, &mac) == committed,
+        _ => false,
+    };
+    if !opens || !confirms(&confirm, "initiator", &transcript, &mac) {
+        return refuse(stream, "Wrong pairing code").await;
+    }
+    send(stream, &Message::Paired).await?;
+    remember(app, &peer_id, &peer_name, &link).map(|_| ())
+}
+
+/// Receiving side of a transfer, after the `hello` message.
+async fn accept_capture(
+    app: &AppHandle,
+    stream: &mut BufStream<TcpStream>,
+    peer_id: String,
+    sender_nonce: String,
+) -> Result<(), String> {
+    let settings = settings::current(app).peers;
+    let Some(peer) = settings.paired.iter().find(|peer| peer.id == peer_id) else {
+        return refuse(stream, "Not paired with this machine").await;
+    };
+    if kiosk::is_locked(app) {
+        return refuse(stream, "This machine is in kiosk mode").await;
+    }
+    if pause::is_paused(app) {
+        return refuse(stream, "This machine is paused").await;
+    }
+    let link = hex::decode(secrets::get(&link_secret_name(&peer.id))?).map_err(|e| e.to_string())?;
+    let nonce = hex::encode(random_bytes::<16>()?);
+    send(stream, &Message::Challenge { nonce: nonce.clone() }).await?;
+
+    let Message::Capture { ciphertext } = receive(stream).await? else {
+        return refuse(stream, "Unexpected message while receiving").await;
+    };
+    let ciphertext = BASE64.decode(ciphertext).map_err(|e| e.to_string())?;
+    let Ok(plaintext) = transfer_cipher(&link, &sender_nonce, &nonce).decrypt(&Nonce::default(), ciphertext.as_slice())
+    else {
+        return refuse(stream, "The capture couldn't be authenticated").await;
+    };
+    let event: SelectionEvent = serde_json::from_slice(&plaintext).map_err(|e| e.to_string())?;
+    let id = history::record(app, &event);
+    send(stream, &Message::Received).await?;
+    let _ = app.emit(
+        "peer-capture-received",
+        serde_json::json!({ "from": peer.name, "id": id, "event": event }),
+    );
+    Ok(())
+}
+
+async fn handle(app: AppHandle, stream: TcpStream) -> Result<(), String> {
+    let mut stream = BufStream::new(stream);
+    match receive(&mut stream).await? {
+        Message::Pair { id, name, public_key } => accept_pairing(&app, &mut stream, id, name, public_key).await,
+        Message::Hello { id, nonce } => accept_capture(&app, &mut stream, id, nonce).await,
+        _ => refuse(&mut stream, "Unexpected message").await,
+    }
+}
+
+fn decode_public_key(key: &str) -> Result<PublicKey, String> {
+    let bytes: [u8; 32] = hex::decode(key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("Invalid key from peer")?;
+    Ok(PublicKey::from(bytes))
+}
+
+/// Opens a connection to a discovered peer, trying each address it
+/// announced.
+async fn connect(peers: &Peers, peer_id: &str) -> Result<BufStream<TcpStream>, String> {
+    let addresses: Vec<SocketAddr> = {
+        let discovered = peers.discovered.lock().unwrap();
+        let peer = discovered.get(peer_id).ok_or("That peer isn't on the network right now")?;
+        peer.addresses.iter().map(|address| SocketAddr::new(*address, peer.port)).collect()
+    };
+    let mut last_error = "The peer announced no addresses".to_string();
+    for address in addresses {
+        match tokio::time::timeout(MESSAGE_TIMEOUT, TcpStream::connect(address)).await {
+            Ok(Ok(stream)) => return Ok(BufStream::new(stream)),
+            Ok(Err(e)) => last_error = e.to_string(),
+            Err(_) => last_error = format!("Timed out connecting to {}", address),
+        }
+    }
+    Err(last_error)
+}
+
+fn advertise(daemon: &ServiceDaemon, settings: &PeerSettings, id: &str) -> Result<(), String> {
+    let name = device_name(settings);
+    let host: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let info = ServiceInfo::new(
+        SERVICE_TYPE,
+        &name,
+        &format!("{}.local.", host),
+        "",
+        settings.port,
+        &[("id", id)][..],
+    )
+    .map_err(|e| e.to_string())?
+    .enable_addr_auto();
+    daemon.register(info).map_err(|e| e.to_string())
+}
+
+/// Keeps [`Peers::discovered`] up to date from mDNS announcements.
+fn browse(app: AppHandle, daemon: &ServiceDaemon, own_id: String) -> Result<(), String> {
+    let events = daemon.browse(SERVICE_TYPE).map_err(|e| e.to_string())?;
+    std::thread::spawn(move || {
+        while let Ok(event) = events.recv() {
+            let peers = app.state::<Peers>();
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(id) = info.get_property_val_str("id").filter(|id| *id != own_id) else {
+                        continue;
+                    };
+                    let fullname = info.get_fullname().to_string();
+                    let name = fullname.strip_suffix(&format!(".{}", SERVICE_TYPE)).unwrap_or(&fullname);
+                    let peer = Discovered {
+                        name: name.to_string(),
+                        fullname: fullname.clone(),
+                        addresses: info.get_addresses().iter().copied().collect(),
+                        port: info.get_port(),
+                    };
+                    peers.discovered.lock().unwrap().insert(id.to_string(), peer);
+                    let _ = app.emit("peers-changed", ());
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    peers.discovered.lock().unwrap().retain(|_, peer| peer.fullname != fullname);
+                    let _ = app.emit("peers-changed", ());
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Starts listening for peers and announcing this machine, if enabled.
+pub fn init(app: &AppHandle) {
+    app.manage(Peers::default());
+    let settings = settings::current(app).peers;
+    if !settings.enabled {
+        return;
+    }
+    let id = match device_id() {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("Failed to set up peer transfers: {}", e);
+            return;
+        }
+    };
+
+    let listener_app = app.clone();
+    let port = settings.port;
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to listen for peers on port {}: {}", port, e);
+                return;
+            }
+        };
+        loop {
+            let Ok((stream, address)) = listener.accept().await else {
+                continue;
+            };
+            let app = listener_app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle(app, stream).await {
+                    eprintln!("Peer exchange with {} failed: {}", address, e);
+                }
+            });
+        }
+    });
+
+    let result = ServiceDaemon::new().map_err(|e| e.to_string()).and_then(|daemon| {
+        advertise(&daemon, &settings, &id)?;
+        browse(app.clone(), &daemon, id)?;
+        *app.state::<Peers>().daemon.lock().unwrap() = Some(daemon);
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to start peer discovery: {}", e);
+    }
+}
+
+/// Peers seen on the network and peers paired with, which may be offline.
+#[tauri::command]
+pub async fn list_peers(app: AppHandle, peers: State<'_, Peers>) -> Result<Vec<PeerInfo>, String> {
+    let paired = settings::current(&app).peers.paired;
+    let discovered = peers.discovered.lock().unwrap();
+    let mut list: Vec<PeerInfo> = discovered
+        .iter()
+        .map(|(id, peer)| PeerInfo {
+            id: id.clone(),
+            name: peer.name.clone(),
+            paired: paired.iter().any(|paired| paired.id == *id),
+            online: true,
+        })
+        .collect();
+    list.extend(
+        paired
+            .iter()
+            .filter(|peer| !discovered.contains_key(&peer.id))
+            .map(|peer| PeerInfo {
+                id: peer.id.clone(),
+                name: peer.name.clone(),
+                paired: true,
+                online: false,
+            }),
+    );
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(list)
+}
+
+/// Lets one other machine pair with this one, using the returned code,
+/// within the next two minutes.
+#[tauri::command]
+pub async fn start_peer_pairing(peers: State<'_, Peers>) -> Result<PairingCode, String> {
+    let code = format!("{:06}", u32::from_le_bytes(random_bytes::<4>()?) % 1_000_000);
+    *peers.pairing.lock().unwrap() = Some((code.clone(), Instant::now()));
+    Ok(PairingCode {
+        code,
+        expires_in_secs: PAIRING_LIFETIME.as_secs(),
+    })
+}
+
+/// Pairs with `peer` using the code it's showing.
+#[tauri::command]
+pub async fn pair_with_peer(
+    app: AppHandle,
+    peers: State<'_, Peers>,
+    peer: String,
+    code: String,
+) -> Result<PeerInfo, String> {
+    let mut stream = connect(&peers, &peer).await?;
+    let secret = EphemeralSecret::random();
+    let public_key = hex::encode(PublicKey::from(&secret).as_bytes());
+    let id = device_id()?;
+    send(
+        &mut stream,
+        &Message::Pair {
+            id: id.clone(),
+            name: device_name(&settings::current(&app).peers),
+            public_key: public_key.clone(),
+        },
+    )
+    .await?;
+
+    let Message::PairAccept {
+        id: peer_id,
+        name,
+        public_key: peer_key,
+    } = receive(&mut stream).await?
+    else {
+        return Err("Unexpected reply from peer".to_string());
+    };
+    if peer_id != peer {
+        return Err("A different machine answered".to_string());
+    }
+    let transcript = format!("{}|{}|{}|{}", id, public_key, peer_id, peer_key);
+    let peer_public = decode_public_key(&peer_key)?;
+    let (confirm, link) = pairing_keys(secret.diffie_hellman(&peer_public).as_bytes(), code.trim());
+    let mac = confirmation(&confirm, "initiator", &transcript).finalize().into_bytes();
+    let nonce = random_bytes::<32>()?;
+    send(
+        &mut stream,
+        &Message::PairCommit {
+            commitment: commitment(&nonce, &mac),
+        },
+    )
+    .await?;
+
+    // The peer proves it has the code before ours is revealed, so a machine
+    // posing as the peer gets one guess rather than something to test
+    // every code against
+    let Message::PairConfirm { mac: peer_mac } = receive(&mut stream).await? else {
+        return Err("Unexpected reply from peer".to_string());
+    };
+    if !confirms(&confirm, "responder", &transcript, &peer_mac) {
+        return Err("The peer couldn't prove it has the same code".to_string());
+    }
+    send(
+        &mut stream,
+        &Message::PairReveal {
+            nonce: hex::encode(nonce),
+            mac: hex::encode(mac),
+        },
+    )
+    .await?;
+    let Message::Paired = receive(&mut stream).await? else {
+        return Err("Unexpected reply from peer".to_string());
+    };
+    remember(&app, &peer_id, &name, &link)
+}
+
+/// Forgets `peer`; pairing again needs a new code.
+#[tauri::command]
+pub async fn unpair_peer(app: AppHandle, peer: String) -> Result<(), String> {
+    secrets::delete(&link_secret_name(&peer))?;
+    let updated = app.state::<SettingsStore>().update(|s| {
+        s.peers.paired.retain(|paired| paired.id != peer);
+    })?;
+    let _ = app.emit("settings-changed", &updated.redacted());
+    Ok(())
+}
+
+/// Sends history entry `id` to the paired `peer`, which stores it in its
+/// own history.
+#[tauri::command]
+pub async fn send_capture_to_peer(
+    history: State<'_, HistoryStore>,
+    peers: State<'_, Peers>,
+    id: i64,
+    peer: String,
+) -> Result<(), String> {
+    let entry = history
+        .get(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No history entry with id {}", id))?;
+    let link = hex::decode(secrets::get(&link_secret_name(&peer)).map_err(|_| "Not paired with that peer")?)
+        .map_err(|e| e.to_string())?;
+
+    let mut stream = connect(&peers, &peer).await?;
+    let nonce = hex::encode(random_bytes::<16>()?);
+    send(
+        &mut stream,
+        &Message::Hello {
+            id: device_id()?,
+            nonce: nonce.clone(),
+        },
+    )
+    .await?;
+    let Message::Challenge { nonce: peer_nonce } = receive(&mut stream).await? else {
+        return Err("Unexpected reply from peer".to_string());
+    };
+
+    let event = SelectionEvent {
+        text: entry.text,
+        app_name: entry.app_name,
+        timestamp: entry.timestamp,
+        selection_type: entry.selection_type,
+    };
+    let plaintext = serde_json::to_vec(&event).map_err(|e| e.to_string())?;
+    let ciphertext = transfer_cipher(&link, &nonce, &peer_nonce)
+        .encrypt(&Nonce::default(), plaintext.as_slice())
+        .map_err(|e| e.to_string())?;
+    send(
+        &mut stream,
+        &Message::Capture {
+            ciphertext: BASE64.encode(ciphertext),
+        },
+    )
+    .await?;
+    match receive(&mut stream).await? {
+        Message::Received => Ok(()),
+        _ => Err("Unexpected reply from peer".to_string()),
+    }
+}
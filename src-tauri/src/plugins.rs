@@ -0,0 +1,261 @@
+//! Third-party capture processors compiled to WebAssembly. Each plugin is a
+//! directory under `plugins/` in the app data dir holding a `plugin.toml`
+//! manifest and the module it names:
+//!
+//! ```toml
+//! name = "strip-tracking-params"
+//! version = "1.0.0"
+//! description = "Removes utm_* parameters from captured links"
+//! module = "plugin.wasm"
+//! capabilities = ["transform"]
+//! ```
+//!
+//! Every selection is passed through the enabled plugins in name order
+//! before the rest of the pipeline sees it. A module exports `memory`,
+//! `alloc(len: i32) -> i32` and `process(ptr: i32, len: i32) -> i64`. The
+//! host writes the `SelectionEvent` as JSON into a buffer from `alloc` and
+//! calls `process`, which returns `(ptr << 32) | len` of a JSON reply:
+//!
+//! ```json
+//! {"veto": false, "text": "replacement text", "annotation": {"any": "json"}}
+//! ```
+//!
+//! All fields are optional, and each is only honoured if the manifest
+//! declares the matching capability (`veto`, `transform`, `annotate`).
+//! Modules get no imports, so they can't touch files or the network, and
+//! each call runs on a fresh instance with a fuel limit and a memory cap.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use text_detection::SelectionEvent;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::{locations, settings};
+
+const PLUGINS_DIR_NAME: &str = "plugins";
+const MANIFEST_FILE_NAME: &str = "plugin.toml";
+/// Roughly a few hundred milliseconds of work; a plugin that runs out is
+/// skipped for that capture.
+const FUEL_PER_CALL: u64 = 50_000_000;
+/// Linear memory a plugin may grow to; growing past it fails, as does
+/// instantiating a module that asks for more up front.
+const MEMORY_PER_CALL: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginSettings {
+    /// Plugins that are installed but shouldn't run, by name.
+    pub disabled: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// Attach data to the capture, under the plugin's name.
+    Annotate,
+    /// Replace the captured text.
+    Transform,
+    /// Drop the capture altogether.
+    Veto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub module: PathBuf,
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Reply {
+    veto: bool,
+    text: Option<String>,
+    annotation: Option<serde_json::Value>,
+}
+
+struct Plugin {
+    manifest: Manifest,
+    module: Module,
+}
+
+impl Plugin {
+    fn allows(&self, capability: Capability) -> bool {
+        self.manifest.capabilities.contains(&capability)
+    }
+
+    fn call(&self, engine: &Engine, event: &SelectionEvent) -> Result<Reply, String> {
+        let input = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_PER_CALL).instances(1).build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Module doesn't export its memory")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(|e| e.to_string())?;
+        let process = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "process")
+            .map_err(|e| e.to_string())?;
+
+        let len = i32::try_from(input.len()).map_err(|_| "Capture is too large for a plugin")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory.write(&mut store, ptr as u32 as usize, &input).map_err(|e| e.to_string())?;
+        let packed = process.call(&mut store, (ptr, len)).map_err(|e| e.to_string())? as u64;
+
+        let (start, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let output = memory
+            .data(&store)
+            .get(start..start + len)
+            .ok_or("Plugin replied out of bounds")?;
+        serde_json::from_slice(output).map_err(|e| format!("Invalid reply: {}", e))
+    }
+}
+
+fn load_plugin(engine: &Engine, dir: &Path) -> Result<Plugin, String> {
+    let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).map_err(|e| e.to_string())?;
+    let manifest: Manifest = toml::from_str(&manifest).map_err(|e| format!("Invalid manifest: {}", e))?;
+    let module = Module::from_file(engine, dir.join(&manifest.module)).map_err(|e| e.to_string())?;
+    Ok(Plugin { manifest, module })
+}
+
+/// Installed plugin as reported to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    /// Directory name, standing in for the name when the manifest is bad.
+    pub id: String,
+    pub manifest: Option<Manifest>,
+    pub enabled: bool,
+    /// Why the plugin couldn't be loaded.
+    pub error: Option<String>,
+}
+
+pub struct PluginHost {
+    engine: Engine,
+    dir: PathBuf,
+    plugins: Mutex<Vec<Plugin>>,
+    /// Directories that failed to load, with the reason.
+    failed: Mutex<Vec<(String, String)>>,
+}
+
+impl PluginHost {
+    fn new(dir: PathBuf) -> Result<Self, String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+        let host = Self {
+            engine,
+            dir,
+            plugins: Mutex::new(Vec::new()),
+            failed: Mutex::new(Vec::new()),
+        };
+        host.reload();
+        Ok(host)
+    }
+
+    /// Loads (or loads again) every plugin directory.
+    fn reload(&self) {
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+            .unwrap_or_default();
+        dirs.sort();
+
+        let (mut plugins, mut failed) = (Vec::new(), Vec::new());
+        for dir in dirs {
+            let id = dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            match load_plugin(&self.engine, &dir) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => {
+                    eprintln!("Failed to load plugin {}: {}", id, e);
+                    failed.push((id, e));
+                }
+            }
+        }
+        plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+        *self.plugins.lock().unwrap() = plugins;
+        *self.failed.lock().unwrap() = failed;
+    }
+
+    fn info(&self, settings: &PluginSettings) -> Vec<PluginInfo> {
+        let plugins = self.plugins.lock().unwrap();
+        let loaded = plugins.iter().map(|plugin| PluginInfo {
+            id: plugin.manifest.name.clone(),
+            enabled: !settings.disabled.contains(&plugin.manifest.name),
+            manifest: Some(plugin.manifest.clone()),
+            error: None,
+        });
+        let failed = self.failed.lock().unwrap();
+        let failed = failed.iter().map(|(id, error)| PluginInfo {
+            id: id.clone(),
+            manifest: None,
+            enabled: false,
+            error: Some(error.clone()),
+        });
+        loaded.chain(failed).collect()
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = locations::data_dir(app)?.join(PLUGINS_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    app.manage(PluginHost::new(dir)?);
+    Ok(())
+}
+
+/// Passes `event` through the enabled plugins. Returns the event as they
+/// left it, with their annotations by plugin name, or `None` if one vetoed
+/// it. A plugin that fails is skipped.
+pub fn run(app: &AppHandle, mut event: SelectionEvent) -> Option<(SelectionEvent, BTreeMap<String, serde_json::Value>)> {
+    let mut annotations = BTreeMap::new();
+    let Some(host) = app.try_state::<PluginHost>() else {
+        return Some((event, annotations));
+    };
+    let settings = settings::current(app).plugins;
+
+    for plugin in host.plugins.lock().unwrap().iter() {
+        let name = &plugin.manifest.name;
+        if settings.disabled.contains(name) {
+            continue;
+        }
+        let reply = match plugin.call(&host.engine, &event) {
+            Ok(reply) => reply,
+            Err(e) => {
+                eprintln!("Plugin {} failed: {}", name, e);
+                continue;
+            }
+        };
+        if reply.veto && plugin.allows(Capability::Veto) {
+            println!("Capture vetoed by plugin {}", name);
+            return None;
+        }
+        if let Some(text) = reply.text.filter(|_| plugin.allows(Capability::Transform)) {
+            event.text = text;
+        }
+        if let Some(annotation) = reply.annotation.filter(|_| plugin.allows(Capability::Annotate)) {
+            annotations.insert(name.clone(), annotation);
+        }
+    }
+    Some((event, annotations))
+}
+
+#[tauri::command]
+pub async fn list_plugins(app: AppHandle, host: State<'_, PluginHost>) -> Result<Vec<PluginInfo>, String> {
+    Ok(host.info(&settings::current(&app).plugins))
+}
+
+/// Picks up plugins added, changed or removed since startup.
+#[tauri::command]
+pub async fn reload_plugins(app: AppHandle, host: State<'_, PluginHost>) -> Result<Vec<PluginInfo>, String> {
+    host.reload();
+    Ok(host.info(&settings::current(&app).plugins))
+}
@@ -0,0 +1,225 @@
+//! Settings bundles a team can pass around: hotkeys, rules, filters and
+//! adapter configuration, without anyone's credentials. Importing one
+//! keeps the local secrets, webhook URLs, paired peers and the kiosk lock as
+//! they are.
+
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+use toml::value::Table;
+use toml::Value;
+
+use crate::settings::{Settings, SettingsStore};
+
+/// Keys holding credentials, stripped wherever they appear. A new
+/// credential setting needs adding here; the tests below catch ones named
+/// like a credential that aren't.
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "api_token",
+    "token",
+    "password",
+    "todoist_token",
+    "github_token",
+    "slack_token",
+    "discord_webhook_url",
+    "ntfy_token",
+    "pushover_token",
+    "pushover_user",
+    "pushbullet_token",
+    "passcode_hash",
+    "salt",
+];
+/// Sections that describe this machine rather than a shared setup.
+const LOCAL_SECTIONS: &[&str] = &["kiosk", "peers"];
+
+fn strip_secrets(value: &mut Value) {
+    match value {
+        Value::Table(table) => {
+            table.retain(|key, _| !SECRET_KEYS.contains(&key));
+            table.iter_mut().for_each(|(_, value)| strip_secrets(value));
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// The actions of webhook rules, by rule id. Their URLs often carry a
+/// token, so they're treated as credentials too.
+fn webhook_actions(value: &mut Value) -> Vec<(String, &mut Table)> {
+    let Some(Value::Array(rules)) = value.get_mut("rules") else {
+        return Vec::new();
+    };
+    rules
+        .iter_mut()
+        .filter_map(|rule| {
+            let rule = rule.as_table_mut()?;
+            let id = rule.get("id")?.as_str()?.to_string();
+            let action = rule.get_mut("action")?.as_table_mut()?;
+            (action.get("type")?.as_str()? == "webhook").then_some((id, action))
+        })
+        .collect()
+}
+
+/// Reduces a settings document to what is safe to share.
+fn make_shareable(value: &mut Value) {
+    if let Value::Table(table) = value {
+        table.retain(|key, _| !LOCAL_SECTIONS.contains(&key));
+    }
+    strip_secrets(value);
+    for (_, action) in webhook_actions(value) {
+        action.remove("url");
+    }
+}
+
+/// `settings` as a preset document.
+fn export(settings: Settings) -> Result<String, String> {
+    let mut value = Value::try_from(settings).map_err(|e| e.to_string())?;
+    make_shareable(&mut value);
+    toml::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// Overlays `preset` on `base`, merging tables so keys the preset leaves
+/// out (its stripped secrets included) keep their current values.
+fn merge(base: &mut Value, preset: Value) {
+    match (base, preset) {
+        (Value::Table(base), Value::Table(preset)) => {
+            for (key, value) in preset {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, preset) => *base = preset,
+    }
+}
+
+/// Applies `preset` over `current`. Rules arrays are replaced as a whole,
+/// so webhook rules get back the URLs the same rules have here first.
+fn apply(current: Settings, mut preset: Value) -> Result<Settings, String> {
+    // Never let a preset carry credentials or unlock kiosk mode
+    make_shareable(&mut preset);
+
+    let mut merged = Value::try_from(current).map_err(|e| e.to_string())?;
+    let urls: Vec<(String, Value)> = webhook_actions(&mut merged)
+        .into_iter()
+        .filter_map(|(id, action)| Some((id, action.get("url")?.clone())))
+        .collect();
+    for (id, action) in webhook_actions(&mut preset) {
+        if let Some((_, url)) = urls.iter().find(|(local, _)| *local == id) {
+            action.insert("url".to_string(), url.clone());
+        }
+    }
+    merge(&mut merged, preset);
+    merged
+        .try_into()
+        .map_err(|e| format!("Preset doesn't fit these settings (is a credential missing?): {}", e))
+}
+
+/// Writes the current settings to `path` as a preset.
+#[tauri::command]
+pub async fn export_preset(settings: State<'_, SettingsStore>, path: String) -> Result<(), String> {
+    let contents = export(settings.get())?;
+    std::fs::write(Path::new(&path), contents).map_err(|e| e.to_string())
+}
+
+/// Applies the preset at `path` over the current settings.
+#[tauri::command]
+pub async fn import_preset(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    path: String,
+) -> Result<Settings, String> {
+    let contents = std::fs::read_to_string(Path::new(&path)).map_err(|e| e.to_string())?;
+    let preset: Value = toml::from_str(&contents).map_err(|e| format!("Not a valid preset: {}", e))?;
+    let imported = apply(settings.get(), preset)?;
+    let updated = settings.update(|current| *current = imported)?.redacted();
+    let _ = app.emit("settings-changed", &updated);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "hunter2-sentinel";
+
+    /// Settings with every credential filled in, including those in
+    /// sections that are off by default.
+    fn with_credentials() -> Settings {
+        serde_json::from_value(serde_json::json!({
+            "digest": { "smtp": { "username": "me", "password": SECRET } },
+            "mqtt": { "username": "me", "password": SECRET },
+            "grpc": { "token": SECRET },
+            "push": {
+                "ntfy_token": SECRET,
+                "pushover_token": SECRET,
+                "pushover_user": SECRET,
+                "pushbullet_token": SECRET,
+            },
+            "chat": { "slack_token": SECRET, "discord_webhook_url": SECRET },
+            "tasks": { "todoist_token": SECRET },
+            "translation": { "api_key": SECRET },
+            "history": { "title_model": { "endpoint": "http://localhost", "api_key": SECRET, "model": "m" } },
+            "entities": {
+                "geocoder": { "type": "google", "api_key": SECRET },
+                "quotes": { "url": "http://localhost/{symbol}", "api_key": SECRET, "price_pointer": "/price" },
+                "issues": {
+                    "github_token": SECRET,
+                    "jira": { "base_url": "http://localhost", "email": "me@example.com", "api_token": SECRET },
+                },
+            },
+            "kiosk": { "passcode_hash": SECRET, "salt": SECRET },
+            "rules": [{ "id": "hook", "action": { "type": "webhook", "url": format!("http://localhost/{}", SECRET) } }],
+            "peers": { "paired": [{ "id": SECRET, "name": "laptop" }] },
+        }))
+        .unwrap()
+    }
+
+    fn keys(value: &serde_json::Value, found: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    found.push(key.clone());
+                    keys(value, found);
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|item| keys(item, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn exported_presets_hold_no_credentials() {
+        let preset = export(with_credentials()).unwrap();
+        assert!(!preset.contains(SECRET), "a credential was exported:\n{}", preset);
+    }
+
+    #[test]
+    fn every_credential_setting_is_stripped() {
+        let mut found = Vec::new();
+        keys(&serde_json::to_value(with_credentials()).unwrap(), &mut found);
+        for key in found {
+            let looks_secret = ["token", "password", "secret", "api_key", "webhook_url", "passcode"]
+                .iter()
+                .any(|word| key.contains(word));
+            assert!(!looks_secret || SECRET_KEYS.contains(&key.as_str()), "{} isn't in SECRET_KEYS", key);
+        }
+    }
+
+    #[test]
+    fn local_settings_survive_a_preset_round_trip() {
+        let settings = with_credentials();
+        let preset = export(settings.clone()).unwrap();
+        assert!(!preset.contains("laptop"), "paired peers were exported:\n{}", preset);
+
+        let imported = apply(settings, toml::from_str(&preset).unwrap()).unwrap();
+        match &imported.rules[0].action {
+            crate::rules::RuleAction::Webhook { url, .. } => assert!(url.contains(SECRET)),
+            other => panic!("the webhook rule became {:?}", other),
+        }
+        assert_eq!(imported.peers.paired[0].id, SECRET);
+    }
+}
@@ -0,0 +1,161 @@
+//! Named profiles (e.g. work/personal), each with its own settings file and
+//! history database. The "default" profile uses the original locations so
+//! existing installs keep their data.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::deliveries::DeliveryQueue;
+use crate::history::{similarity, HistoryStore};
+use crate::scripts::ScriptHost;
+use crate::settings::SettingsStore;
+use crate::{hotkey, kiosk, locations, system_tray};
+
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+
+/// The profile currently in use.
+pub struct ActiveProfile(Mutex<String>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+}
+
+fn profile_dir(root: PathBuf, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        root
+    } else {
+        root.join(PROFILES_DIR).join(name)
+    }
+}
+
+pub fn active(app: &AppHandle) -> String {
+    app.try_state::<ActiveProfile>()
+        .map(|profile| profile.0.lock().unwrap().clone())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Where the active profile's settings live.
+pub fn config_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(profile_dir(locations::config_dir(app)?, &active(app)))
+}
+
+/// Where the active profile's history database lives.
+pub fn data_dir(app: &AppHandle) -> tauri::Result<PathBuf> {
+    Ok(profile_dir(locations::data_dir(app)?, &active(app)))
+}
+
+/// Restores the last active profile. Must run before settings and history
+/// are loaded.
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let marker = locations::config_dir(app)?.join(ACTIVE_PROFILE_FILE);
+    let name = std::fs::read_to_string(marker)
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| is_valid_name(name))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    app.manage(ActiveProfile(Mutex::new(name)));
+    Ok(())
+}
+
+/// Every profile that has settings on disk, plus the default one.
+pub fn list(app: &AppHandle) -> Vec<ProfileInfo> {
+    let active = active(app);
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    if let Some(entries) = locations::config_dir(app)
+        .ok()
+        .and_then(|dir| std::fs::read_dir(dir.join(PROFILES_DIR)).ok())
+    {
+        names.extend(
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| name != DEFAULT_PROFILE && is_valid_name(name)),
+        );
+    }
+    if !names.contains(&active) {
+        names.push(active.clone());
+    }
+    names.sort_by(|a, b| (a != DEFAULT_PROFILE).cmp(&(b != DEFAULT_PROFILE)).then(a.cmp(b)));
+
+    names
+        .into_iter()
+        .map(|name| ProfileInfo {
+            active: name == active,
+            name,
+        })
+        .collect()
+}
+
+/// Makes `name` the active profile, creating it if needed, and reloads
+/// everything that depends on per-profile data.
+pub fn switch(app: &AppHandle, name: &str) -> Result<(), String> {
+    if !is_valid_name(name) {
+        return Err(format!("\"{}\" is not a valid profile name", name));
+    }
+    if kiosk::is_locked(app) {
+        return Err("Profiles can't be switched in kiosk mode".to_string());
+    }
+    if active(app) == name {
+        return Ok(());
+    }
+    load(app, name)
+}
+
+/// Loads profile `name`'s settings and data in place of the current ones.
+pub fn load(app: &AppHandle, name: &str) -> Result<(), String> {
+    let config_root = locations::config_dir(app).map_err(|e| e.to_string())?;
+    let data_root = locations::data_dir(app).map_err(|e| e.to_string())?;
+
+    let settings = app
+        .state::<SettingsStore>()
+        .reload(&profile_dir(config_root.clone(), name))
+        .map_err(|e| e.to_string())?;
+    let data_dir = profile_dir(data_root, name);
+    if let Some(history) = app.try_state::<HistoryStore>() {
+        history.reopen(&data_dir).map_err(|e| e.to_string())?;
+        similarity::backfill_in_background(app);
+    }
+    if let Some(queue) = app.try_state::<DeliveryQueue>() {
+        queue.reload(&data_dir);
+    }
+    if let Some(scripts) = app.try_state::<ScriptHost>() {
+        scripts.reload(&data_dir);
+    }
+
+    *app.state::<ActiveProfile>().0.lock().unwrap() = name.to_string();
+    std::fs::write(config_root.join(ACTIVE_PROFILE_FILE), name).map_err(|e| e.to_string())?;
+
+    hotkey::reregister_all(app);
+    system_tray::refresh_menu(app);
+    let _ = app.emit("profile-changed", name);
+    let _ = app.emit("settings-changed", &settings.redacted());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    Ok(list(&app))
+}
+
+#[tauri::command]
+pub async fn get_active_profile(profile: State<'_, ActiveProfile>) -> Result<String, String> {
+    Ok(profile.0.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<(), String> {
+    switch(&app, name.trim())
+}
@@ -0,0 +1,147 @@
+//! Property tests for the code that handles selected text before anyone
+//! has looked at it: previews, transforms, normalization and entity
+//! extraction. Selections come from arbitrary apps, so inputs mix scripts,
+//! right-to-left runs, emoji sequences, combining marks and invisible
+//! characters. None of it may panic, and truncation must never split a
+//! user-perceived character.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::entities::issues::IssueSettings;
+use crate::entities::{address, contact, identifiers, issues, phone, securities, tracking};
+use crate::normalize::{self, NormalizeSettings};
+use crate::{text_utils, transform};
+
+/// Pieces that tend to break text handling, plus enough ASCII for entity
+/// patterns to match now and then.
+const PIECES: &[&str] = &[
+    "e\u{301}",
+    "\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}",
+    "\u{1F1EF}\u{1F1F5}",
+    "\u{1F44D}\u{1F3FD}",
+    "\u{200B}",
+    "\u{200D}",
+    "\u{FEFF}",
+    "\u{202E}",
+    "\u{2066}",
+    "שלום",
+    "مرحبا",
+    "漢字かな",
+    "한국어",
+    "Z\u{351}\u{34A}\u{35B}",
+    "\r\n",
+    "\t",
+    " ",
+    "  ",
+    "@",
+    "#42",
+    "+1 (415) 555-0100",
+    "ABC-123",
+    "rust-lang/rust#1",
+    "1Z999AA10123456784",
+    "US0378331005",
+    "$AAPL",
+    "1600 Pennsylvania Ave NW, Washington, DC 20500",
+    "jane@example.com",
+    "https://example.com/a?b=c",
+    "123e4567-e89b-12d3-a456-426614174000",
+];
+
+fn exotic_text() -> impl Strategy<Value = String> {
+    let piece = prop_oneof![
+        3 => proptest::sample::select(PIECES).prop_map(str::to_string),
+        2 => any::<char>().prop_map(String::from),
+        1 => "[a-zA-Z0-9 .,:/-]{1,12}",
+    ];
+    prop::collection::vec(piece, 0..48).prop_map(|pieces| pieces.concat())
+}
+
+fn graphemes(text: &str) -> Vec<&str> {
+    text.graphemes(true).collect()
+}
+
+proptest! {
+    #[test]
+    fn truncate_keeps_whole_graphemes(text in exotic_text(), max in 0usize..64) {
+        let cut = text_utils::truncate(&text, max);
+        let all = graphemes(&text);
+        let kept = graphemes(cut);
+        prop_assert_eq!(kept.len(), all.len().min(max));
+        prop_assert_eq!(&all[..kept.len()], &kept[..]);
+    }
+
+    #[test]
+    fn preview_fits_and_cuts_cleanly(text in exotic_text(), max in 1usize..64) {
+        let preview = text_utils::preview(&text, max);
+        let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match preview.strip_suffix('…') {
+            Some(kept) if preview != collapsed => {
+                let kept = kept.trim_end_matches(['\u{202C}', '\u{2069}']);
+                prop_assert!(graphemes(kept).len() < max);
+                prop_assert!(text_utils::truncate(&collapsed, max - 1).starts_with(kept));
+            }
+            _ => prop_assert_eq!(&preview, &collapsed),
+        }
+    }
+
+    #[test]
+    fn transforms_never_panic(text in exotic_text()) {
+        let _ = transform::pretty_json(&text);
+        let _ = transform::base64_decode(&text);
+        let _ = transform::percent_decode(&text);
+        let _ = transform::unescape(&text);
+    }
+
+    #[test]
+    fn unescape_reverses_escaping(text in exotic_text()) {
+        let quoted = format!("\"{}\"", text.escape_default());
+        prop_assert_eq!(transform::unescape(&quoted).unwrap(), text);
+    }
+
+    #[test]
+    fn base64_decode_reverses_encoding(text in exotic_text()) {
+        prop_assert_eq!(transform::base64_decode(&STANDARD.encode(&text)).unwrap(), text);
+    }
+
+    #[test]
+    fn percent_decode_reverses_encoding(text in exotic_text()) {
+        let encoded = percent_encoding::utf8_percent_encode(&text, percent_encoding::NON_ALPHANUMERIC).to_string();
+        prop_assert_eq!(transform::percent_decode(&encoded).unwrap(), text);
+    }
+
+    #[test]
+    fn pretty_json_keeps_strings_intact(text in exotic_text()) {
+        let json = serde_json::to_string(&serde_json::json!({ "text": text })).unwrap();
+        let pretty = transform::pretty_json(&json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        prop_assert_eq!(value["text"].as_str(), Some(text.as_str()));
+    }
+
+    #[test]
+    fn normalizing_is_idempotent(text in exotic_text()) {
+        let settings = NormalizeSettings { enabled: true, ..Default::default() };
+        let once = normalize::clean(&settings, &text);
+        prop_assert_eq!(normalize::clean(&settings, &once), once.clone());
+        let stripped = ['\u{200B}', '\u{FEFF}', '\u{202E}', '\u{2066}', '\u{2019}'];
+        prop_assert!(!once.contains(stripped), "{:?} kept a character it should strip", once);
+    }
+
+    #[test]
+    fn entities_are_taken_from_the_text(text in exotic_text()) {
+        let _ = address::detect(&text);
+        let _ = contact::detect(&text);
+        let _ = securities::detect(&text);
+        let _ = tracking::detect(&text);
+        let _ = issues::detect(&text, &IssueSettings::default());
+        for identifier in identifiers::detect(&text) {
+            prop_assert!(text.contains(&identifier.value));
+        }
+        for number in phone::detect(&text, "US") {
+            prop_assert!(text.contains(&number.original));
+            prop_assert!(number.e164.starts_with('+'));
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Limits on paid API calls (translation, the title model), so something
+//! like translating every selection can't run up a surprise bill. Each
+//! provider has an optional per-minute rate, kept as a token bucket, and
+//! daily request and character budgets that reset at local midnight. Once a
+//! limit is hit, calls fail with an error saying which one.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{locations, settings};
+
+const USAGE_FILE_NAME: &str = "quota_usage.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Translation,
+    Titles,
+}
+
+impl Provider {
+    const ALL: [Provider; 2] = [Provider::Translation, Provider::Titles];
+
+    fn label(self) -> &'static str {
+        match self {
+            Provider::Translation => "translation",
+            Provider::Titles => "title model",
+        }
+    }
+}
+
+/// No limit where a field is unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaLimit {
+    pub requests_per_minute: Option<u32>,
+    pub requests_per_day: Option<u32>,
+    pub chars_per_day: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaSettings {
+    pub translation: QuotaLimit,
+    pub titles: QuotaLimit,
+}
+
+impl Default for QuotaSettings {
+    fn default() -> Self {
+        let limit = QuotaLimit {
+            requests_per_minute: Some(30),
+            ..QuotaLimit::default()
+        };
+        Self {
+            translation: limit.clone(),
+            titles: limit,
+        }
+    }
+}
+
+impl QuotaSettings {
+    fn limit(&self, provider: Provider) -> &QuotaLimit {
+        match provider {
+            Provider::Translation => &self.translation,
+            Provider::Titles => &self.titles,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub requests: u32,
+    pub chars: u64,
+}
+
+/// Today's usage, persisted so restarting doesn't reset the budget.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DailyUsage {
+    /// Local date, `YYYY-MM-DD`.
+    day: String,
+    usage: HashMap<Provider, Usage>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Bucket {
+    /// Takes a token if one is left, refilling at `per_minute` per minute up
+    /// to a burst of `per_minute`.
+    fn take(&mut self, per_minute: u32) -> bool {
+        let capacity = per_minute as f64;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.refilled = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+pub struct QuotaTracker {
+    path: PathBuf,
+    daily: Mutex<DailyUsage>,
+    buckets: Mutex<HashMap<Provider, Bucket>>,
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+impl QuotaTracker {
+    fn load(dir: &Path) -> Self {
+        let path = dir.join(USAGE_FILE_NAME);
+        let daily = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            daily: Mutex::new(daily),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Today's usage, starting over when the date has changed.
+    fn current(daily: &mut DailyUsage) -> &mut HashMap<Provider, Usage> {
+        let today = today();
+        if daily.day != today {
+            daily.day = today;
+            daily.usage.clear();
+        }
+        &mut daily.usage
+    }
+
+    /// Records a call of `chars` characters to `provider`, or refuses it if
+    /// it would go over a limit.
+    fn charge(&self, limits: &QuotaSettings, provider: Provider, chars: u64) -> Result<(), String> {
+        let limit = limits.limit(provider);
+        let mut daily = self.daily.lock().unwrap();
+        let usage = Self::current(&mut daily).entry(provider).or_default();
+
+        if let Some(max) = limit.requests_per_day.filter(|max| usage.requests >= *max) {
+            return Err(format!(
+                "Daily {} quota of {} requests reached; it resets at midnight",
+                provider.label(),
+                max
+            ));
+        }
+        if let Some(max) = limit.chars_per_day.filter(|max| usage.chars + chars > *max) {
+            return Err(format!(
+                "Daily {} quota of {} characters reached; it resets at midnight",
+                provider.label(),
+                max
+            ));
+        }
+        if let Some(per_minute) = limit.requests_per_minute {
+            let mut buckets = self.buckets.lock().unwrap();
+            let bucket = buckets.entry(provider).or_insert_with(|| Bucket {
+                tokens: per_minute as f64,
+                refilled: Instant::now(),
+            });
+            if !bucket.take(per_minute) {
+                return Err(format!(
+                    "Too many {} requests; the limit is {} a minute",
+                    provider.label(),
+                    per_minute
+                ));
+            }
+        }
+
+        usage.requests += 1;
+        usage.chars += chars;
+        let contents = serde_json::to_string(&*daily).map_err(|e| e.to_string())?;
+        if let Err(e) = std::fs::write(&self.path, contents) {
+            eprintln!("Failed to save quota usage: {}", e);
+        }
+        Ok(())
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = locations::data_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    app.manage(QuotaTracker::load(&dir));
+    Ok(())
+}
+
+/// Call before each paid request with the size of the text being sent.
+pub fn charge(app: &AppHandle, provider: Provider, text: &str) -> Result<(), String> {
+    let Some(tracker) = app.try_state::<QuotaTracker>() else {
+        return Ok(());
+    };
+    tracker.charge(&settings::current(app).quota, provider, text.chars().count() as u64)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaUsage {
+    pub provider: Provider,
+    pub today: Usage,
+    pub limit: QuotaLimit,
+}
+
+#[tauri::command]
+pub async fn get_quota_usage(app: AppHandle, tracker: State<'_, QuotaTracker>) -> Result<Vec<QuotaUsage>, String> {
+    let limits = settings::current(&app).quota;
+    let mut daily = tracker.daily.lock().unwrap();
+    let usage = QuotaTracker::current(&mut daily);
+    Ok(Provider::ALL
+        .into_iter()
+        .map(|provider| QuotaUsage {
+            provider,
+            today: usage.get(&provider).copied().unwrap_or_default(),
+            limit: limits.limit(provider).clone(),
+        })
+        .collect())
+}
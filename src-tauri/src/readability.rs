@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::text_utils;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadabilitySettings {
+    /// Attach a `readability` annotation to captures.
+    pub annotate_captures: bool,
+    /// Shorter captures are skipped; the formulas are meaningless on a few words.
+    pub min_words: usize,
+}
+
+impl Default for ReadabilitySettings {
+    fn default() -> Self {
+        Self {
+            annotate_captures: false,
+            min_words: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readability {
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub smog_index: f64,
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+}
+
+/// Scores `text`, or `None` if it has no words to score.
+pub fn analyze(text: &str) -> Option<Readability> {
+    let words: Vec<&str> = text_utils::words(text)
+        .filter(|word| word.chars().any(char::is_alphabetic))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let syllable_counts: Vec<usize> = words.iter().map(|word| syllables(word)).collect();
+    let syllable_total: usize = syllable_counts.iter().sum();
+    let polysyllables = syllable_counts.iter().filter(|&&n| n >= 3).count();
+    let sentences = count_sentences(text);
+
+    let words_per_sentence = words.len() as f64 / sentences as f64;
+    let syllables_per_word = syllable_total as f64 / words.len() as f64;
+
+    Some(Readability {
+        flesch_reading_ease: round(206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word),
+        flesch_kincaid_grade: round(0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59),
+        smog_index: round(1.043 * (polysyllables as f64 * 30.0 / sentences as f64).sqrt() + 3.1291),
+        sentences,
+        words: words.len(),
+        syllables: syllable_total,
+    })
+}
+
+/// Annotation for a capture, if enabled and the capture is long enough.
+pub fn annotate(settings: &ReadabilitySettings, text: &str) -> Option<Readability> {
+    if !settings.annotate_captures {
+        return None;
+    }
+    analyze(text).filter(|r| r.words >= settings.min_words)
+}
+
+/// Runs of terminal punctuation end a sentence; unterminated trailing text
+/// counts as one more.
+fn count_sentences(text: &str) -> usize {
+    let mut sentences = 0;
+    let mut in_sentence = false;
+    for c in text.chars() {
+        if matches!(c, '.' | '!' | '?' | '。' | '！' | '？') {
+            if in_sentence {
+                sentences += 1;
+                in_sentence = false;
+            }
+        } else if c.is_alphanumeric() {
+            in_sentence = true;
+        }
+    }
+    (sentences + in_sentence as usize).max(1)
+}
+
+/// English syllable estimate: vowel groups, minus a silent trailing "e".
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[tauri::command]
+pub async fn analyze_readability(text: String) -> Result<Readability, String> {
+    analyze(&text).ok_or_else(|| "Text has no words to score".to_string())
+}
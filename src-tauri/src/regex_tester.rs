@@ -0,0 +1,99 @@
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::SelectionEvent;
+
+/// Emitted for every capture while a live pattern is registered.
+pub const MATCHED_EVENT: &str = "live-regex-matched";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegexMatch {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Numbered groups, `None` for groups that didn't participate.
+    pub groups: Vec<Option<String>>,
+    pub named: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveRegexResult<'a> {
+    pub pattern: &'a str,
+    pub text: &'a str,
+    pub app_name: &'a str,
+    pub matches: Vec<RegexMatch>,
+}
+
+/// The pattern the regex tester window has registered, if any.
+#[derive(Default)]
+pub struct RegexTester {
+    pattern: Mutex<Option<Regex>>,
+}
+
+pub fn find_matches(regex: &Regex, text: &str) -> Vec<RegexMatch> {
+    regex
+        .captures_iter(text)
+        .map(|captures| {
+            let whole = captures.get(0).expect("group 0 always matches");
+            RegexMatch {
+                start: whole.start(),
+                end: whole.end(),
+                text: whole.as_str().to_string(),
+                groups: captures.iter().skip(1).map(|g| g.map(|m| m.as_str().to_string())).collect(),
+                named: regex
+                    .capture_names()
+                    .flatten()
+                    .filter_map(|name| Some((name.to_string(), captures.name(name)?.as_str().to_string())))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Pipeline hook: matches the capture against the live pattern.
+pub fn run(app: &AppHandle, event: &SelectionEvent) {
+    let Some(tester) = app.try_state::<RegexTester>() else {
+        return;
+    };
+    let Some(regex) = tester.pattern.lock().unwrap().clone() else {
+        return;
+    };
+
+    let _ = app.emit(
+        MATCHED_EVENT,
+        LiveRegexResult {
+            pattern: regex.as_str(),
+            text: &event.text,
+            app_name: &event.app_name,
+            matches: find_matches(&regex, &event.text),
+        },
+    );
+}
+
+/// Registers (or with `None`, clears) the pattern every new capture is
+/// matched against.
+#[tauri::command]
+pub async fn set_live_regex(
+    tester: State<'_, RegexTester>,
+    pattern: Option<String>,
+    case_insensitive: Option<bool>,
+) -> Result<(), String> {
+    let regex = match pattern {
+        Some(pattern) => Some(
+            RegexBuilder::new(&pattern)
+                .case_insensitive(case_insensitive.unwrap_or(false))
+                .build()
+                .map_err(|e| e.to_string())?,
+        ),
+        None => None,
+    };
+    *tester.pattern.lock().unwrap() = regex;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_live_regex(tester: State<'_, RegexTester>) -> Result<Option<String>, String> {
+    Ok(tester.pattern.lock().unwrap().as_ref().map(|regex| regex.as_str().to_string()))
+}
@@ -0,0 +1,420 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::actions::chat::{self, ChatTarget};
+use crate::actions::push::{self, PushProvider};
+use crate::actions::tasks::{self, TaskTarget};
+use crate::actions::{calendar, email, ActionInput};
+use crate::capture::{self, Capture};
+use crate::confidence::ConfidenceLevel;
+use crate::deliveries;
+use crate::history::{audit, HistoryStore};
+use crate::normalize::{self, NormalizeSettings};
+use crate::trace::{self, Stage};
+use crate::{kiosk, settings, webhook_templates};
+
+/// A user-defined automation: when a capture matches, run `action`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Only fire for captures from these apps; empty means any app.
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Only fire when the capture text matches this regex.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Cleans the text this way before matching `pattern`, on top of the
+    /// global [`crate::normalize`] stage. The action still gets the capture
+    /// as stored.
+    #[serde(default)]
+    pub normalize: Option<NormalizeSettings>,
+    /// Skip captures less trustworthy than this, e.g. for actions that
+    /// send or overwrite something.
+    #[serde(default)]
+    pub min_confidence: Option<ConfidenceLevel>,
+    /// Log and announce what the action would do instead of doing it, while
+    /// the rule is being worked on.
+    #[serde(default)]
+    pub dry_run: bool,
+    pub action: RuleAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_copy_interval() -> u64 {
+    5
+}
+
+fn default_open_interval() -> u64 {
+    30
+}
+
+/// When [`RuleAction::CopyToClipboard`] last wrote the clipboard, and a
+/// hash of what it wrote.
+static LAST_AUTO_COPY: Mutex<Option<(Instant, u32)>> = Mutex::new(None);
+
+/// Whether `text` on the clipboard was put there by a rule rather than
+/// copied by the user.
+pub fn was_auto_copied(text: &str) -> bool {
+    LAST_AUTO_COPY
+        .lock()
+        .unwrap()
+        .is_some_and(|(_, hash)| hash == crc32fast::hash(text.as_bytes()))
+}
+
+/// When [`RuleAction::SendEmail`] last opened a draft.
+static LAST_EMAIL: Mutex<Option<Instant>> = Mutex::new(None);
+/// When [`RuleAction::CreateCalendarEvent`] last opened an event.
+static LAST_CALENDAR_EVENT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Whether `last` is at least `min_interval_secs` ago, and if so moves it
+/// to now.
+fn take_turn(last: &Mutex<Option<Instant>>, min_interval_secs: u64) -> bool {
+    let mut last = last.lock().unwrap();
+    if last.is_some_and(|at| at.elapsed() < Duration::from_secs(min_interval_secs)) {
+        return false;
+    }
+    *last = Some(Instant::now());
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Show the calculator/unit conversion result in a notification.
+    NotifyComputed,
+    /// Tag the stored history entry.
+    AddTags { tags: Vec<String> },
+    /// Copy the capture, for apps that block Cmd+C (e.g. e-readers). Skipped
+    /// if any rule already copied within `min_interval_secs`, so a string of
+    /// selections doesn't keep clobbering the clipboard.
+    CopyToClipboard {
+        #[serde(default = "default_copy_interval")]
+        min_interval_secs: u64,
+    },
+    /// Create a task from the capture.
+    CreateTask { target: TaskTarget },
+    /// Open a draft email of the capture, to `to` or the default address.
+    /// Skipped if any rule opened one within `min_interval_secs`, so a run
+    /// of selections doesn't open a window for each.
+    SendEmail {
+        #[serde(default)]
+        to: Option<String>,
+        #[serde(default = "default_open_interval")]
+        min_interval_secs: u64,
+    },
+    /// Open a calendar event at the date the capture mentions, if any.
+    /// Rate-limited like [`RuleAction::SendEmail`].
+    CreateCalendarEvent {
+        #[serde(default)]
+        duration_minutes: Option<u32>,
+        #[serde(default = "default_open_interval")]
+        min_interval_secs: u64,
+    },
+    /// POST the capture as JSON to `url`, retrying later if offline. With
+    /// `template`, the payload is that webhook template filled in instead;
+    /// see [`crate::webhook_templates`].
+    Webhook {
+        url: String,
+        #[serde(default)]
+        template: Option<String>,
+    },
+    /// Send the capture to Slack or Discord, through `target` or the
+    /// default one, and for Slack to `channel` or the configured channel.
+    SendToChat {
+        #[serde(default)]
+        target: Option<ChatTarget>,
+        #[serde(default)]
+        channel: Option<String>,
+    },
+    /// Push the capture to the phone, through `provider` or the default one
+    /// and to `to` (a topic, device or address) or everywhere.
+    PushToPhone {
+        #[serde(default)]
+        provider: Option<PushProvider>,
+        #[serde(default)]
+        to: Option<String>,
+    },
+}
+
+impl RuleAction {
+    /// Whether the action hands the capture to another app or service.
+    fn is_outgoing(&self) -> bool {
+        matches!(
+            self,
+            RuleAction::CreateTask { .. }
+                | RuleAction::SendEmail { .. }
+                | RuleAction::CreateCalendarEvent { .. }
+                | RuleAction::Webhook { .. }
+                | RuleAction::SendToChat { .. }
+                | RuleAction::PushToPhone { .. }
+        )
+    }
+
+    /// What running the action for `capture` would do, in words.
+    fn describe(&self, capture: &Capture) -> String {
+        match self {
+            RuleAction::NotifyComputed => match &capture.computed {
+                Some(computed) => format!("Show a notification with \"{}\"", computed),
+                None => "Nothing: the capture has no computed result".to_string(),
+            },
+            RuleAction::AddTags { tags } => format!("Tag the history entry with {}", tags.join(", ")),
+            RuleAction::CopyToClipboard { .. } => "Copy the capture to the clipboard".to_string(),
+            RuleAction::CreateTask { target } => format!("Create a task in {:?}", target),
+            RuleAction::SendEmail { to, .. } => match to {
+                Some(to) => format!("Open an email draft to {}", to),
+                None => "Open an email draft to the default address".to_string(),
+            },
+            RuleAction::CreateCalendarEvent { .. } => match &capture.date {
+                Some(date) => format!("Open a calendar event at {}", date.local),
+                None => "Nothing: the capture mentions no date".to_string(),
+            },
+            RuleAction::Webhook { url, template } => match template {
+                Some(template) => format!("POST the capture to {} using the \"{}\" template", url, template),
+                None => format!("POST the capture as JSON to {}", url),
+            },
+            RuleAction::SendToChat { target, channel } => {
+                let target = match target {
+                    Some(target) => format!("{:?}", target),
+                    None => "the default chat".to_string(),
+                };
+                match channel {
+                    Some(channel) => format!("Send the capture to {} on {}", channel, target),
+                    None => format!("Send the capture to {}", target),
+                }
+            }
+            RuleAction::PushToPhone { provider, to } => {
+                let provider = match provider {
+                    Some(provider) => format!("{:?}", provider),
+                    None => "the default provider".to_string(),
+                };
+                match to {
+                    Some(to) => format!("Push the capture to {} through {}", to, provider),
+                    None => format!("Push the capture to the phone through {}", provider),
+                }
+            }
+        }
+    }
+}
+
+impl Rule {
+    /// Why `capture` doesn't satisfy the rule's conditions, if it doesn't.
+    /// Whether the rule is enabled isn't considered.
+    fn mismatch(&self, capture: &Capture) -> Option<String> {
+        if !self.apps.is_empty() && !self.apps.iter().any(|app| app == &capture.event.app_name) {
+            return Some(format!("Captures from {} aren't covered", capture.event.app_name));
+        }
+        if let Some(min) = self.min_confidence.filter(|min| capture.confidence.level < *min) {
+            return Some(format!("Confidence is {:?}, below {:?}", capture.confidence.level, min));
+        }
+        let pattern = self.pattern.as_ref()?;
+        let text = match self.normalize.as_ref().filter(|normalize| normalize.enabled) {
+            Some(normalize) => Cow::Owned(normalize::clean(normalize, &capture.event.text)),
+            None => Cow::Borrowed(capture.event.text.as_str()),
+        };
+        match Regex::new(pattern) {
+            Ok(regex) if regex.is_match(&text) => None,
+            Ok(_) => Some("The text doesn't match the pattern".to_string()),
+            Err(e) => {
+                eprintln!("Rule {} has an invalid pattern: {}", self.id, e);
+                Some(format!("The pattern is invalid: {}", e))
+            }
+        }
+    }
+
+    fn matches(&self, capture: &Capture) -> bool {
+        self.enabled && self.mismatch(capture).is_none()
+    }
+}
+
+/// What the trace and audit log show for an action kiosk mode held back.
+const SKIPPED_IN_KIOSK: &str = "skipped: kiosk mode";
+
+/// Sent as `rule-dry-run` instead of running a dry-run rule's action.
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRun {
+    pub rule_id: String,
+    pub would: String,
+}
+
+/// Runs every matching rule's action for `capture`, failing with the last
+/// rule that failed. One failing rule doesn't stop the others.
+pub fn run(app: &AppHandle, capture: &Capture) -> Result<(), String> {
+    let mut result = Ok(());
+    for rule in settings::current(app).rules.iter().filter(|rule| rule.matches(capture)) {
+        if rule.dry_run {
+            let would = rule.action.describe(capture);
+            trace::step(app, capture.id, Stage::Rule, &rule.id, Ok(Some(format!("Dry run, would: {}", would))));
+            println!("Rule {} (dry run) would: {}", rule.id, would);
+            let _ = app.emit("rule-dry-run", DryRun { rule_id: rule.id.clone(), would });
+            continue;
+        }
+        trace::step(app, capture.id, Stage::Rule, &rule.id, Ok(None));
+        if let Err(e) = perform(app, &format!("rule:{}", rule.id), &rule.action, capture) {
+            result = Err(format!("Rule {} failed: {}", rule.id, e));
+        }
+    }
+    result
+}
+
+/// Carries out `action` for `capture` on behalf of `trigger`, recording it
+/// in the audit log. Outgoing actions are skipped in kiosk mode, which is
+/// recorded as such but isn't a failure.
+pub fn perform(app: &AppHandle, trigger: &str, action: &RuleAction, capture: &Capture) -> Result<(), String> {
+    let skipped = action.is_outgoing() && kiosk::is_locked(app);
+    let result = if skipped {
+        Err(SKIPPED_IN_KIOSK.to_string())
+    } else {
+        execute(app, action, capture)
+    };
+    let action = serde_json::to_value(action).unwrap_or_default();
+    let kind = action.get("type").and_then(|kind| kind.as_str()).unwrap_or("action");
+    let outcome = result.clone().map(|()| Some(trigger.to_string()));
+    trace::step(app, capture.id, Stage::Action, kind, outcome);
+    audit::record(app, trigger, &action, capture.id, &result);
+    if skipped {
+        return Ok(());
+    }
+    result
+}
+
+fn execute(app: &AppHandle, action: &RuleAction, capture: &Capture) -> Result<(), String> {
+    match action {
+        RuleAction::NotifyComputed => {
+            let Some(computed) = &capture.computed else {
+                return Ok(());
+            };
+            app.notification()
+                .builder()
+                .title("ACMI Desktop")
+                .body(computed)
+                .show()
+                .map_err(|e| e.to_string())
+        }
+        RuleAction::AddTags { tags } => {
+            let (Some(id), Some(history)) = (capture.id, app.try_state::<HistoryStore>()) else {
+                return Ok(());
+            };
+            history.add_tags(id, tags).map(|_| ()).map_err(|e| e.to_string())
+        }
+        RuleAction::CopyToClipboard { min_interval_secs } => {
+            if capture.event.text.trim().is_empty() {
+                return Ok(());
+            }
+            let mut last = LAST_AUTO_COPY.lock().unwrap();
+            let min_interval = Duration::from_secs(*min_interval_secs);
+            if last.is_some_and(|(at, _)| at.elapsed() < min_interval) {
+                return Ok(());
+            }
+            app.clipboard()
+                .write_text(capture.event.text.clone())
+                .map_err(|e| e.to_string())?;
+            *last = Some((Instant::now(), crc32fast::hash(capture.event.text.as_bytes())));
+            Ok(())
+        }
+        RuleAction::CreateTask { target } => {
+            let settings = settings::current(app).tasks;
+            let target = *target;
+            let input = ActionInput::from_capture(capture);
+            // Todoist is a network round trip; don't hold up the capture
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = tasks::create(&settings, target, &input).await {
+                    eprintln!("Failed to create task: {}", e);
+                }
+            });
+            Ok(())
+        }
+        RuleAction::SendEmail { to, min_interval_secs } => {
+            if !take_turn(&LAST_EMAIL, *min_interval_secs) {
+                return Ok(());
+            }
+            email::compose(&settings::current(app).email, to.as_deref(), &ActionInput::from_capture(capture))
+        }
+        RuleAction::CreateCalendarEvent { duration_minutes, min_interval_secs } => {
+            let Some(date) = &capture.date else {
+                return Ok(());
+            };
+            if !take_turn(&LAST_CALENDAR_EVENT, *min_interval_secs) {
+                return Ok(());
+            }
+            calendar::create(app, date, *duration_minutes, &ActionInput::from_capture(capture))
+        }
+        RuleAction::Webhook { url, template } => {
+            let body = webhook_templates::payload(app, template.as_deref(), capture)?;
+            deliveries::send(app, url.clone(), body);
+            Ok(())
+        }
+        RuleAction::SendToChat { target, channel } => {
+            let settings = settings::current(app).chat;
+            let (target, channel) = (*target, channel.clone());
+            let input = ActionInput::from_capture(capture);
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = chat::send(&settings, target, channel.as_deref(), &input).await {
+                    eprintln!("Failed to send capture to chat: {}", e);
+                }
+            });
+            Ok(())
+        }
+        RuleAction::PushToPhone { provider, to } => {
+            let settings = settings::current(app).push;
+            let (provider, to) = (*provider, to.clone());
+            let input = ActionInput::from_capture(capture);
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = push::push(&settings, provider, to.as_deref(), &input).await {
+                    eprintln!("Failed to push capture to phone: {}", e);
+                }
+            });
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleTest {
+    pub matched: bool,
+    /// Why it didn't match.
+    pub reason: Option<String>,
+    /// What the action would have done, had it matched.
+    pub would: Option<String>,
+    /// The capture the sample text turned into, as the rule saw it.
+    pub capture: Capture,
+}
+
+/// Checks rule `rule_id` against `sample_text` as if it had been selected
+/// in `app_name`, without storing anything or running the action.
+#[tauri::command]
+pub async fn test_rule(
+    app: AppHandle,
+    rule_id: String,
+    sample_text: String,
+    app_name: Option<String>,
+) -> Result<RuleTest, String> {
+    let rules = settings::current(&app).rules;
+    let rule = rules
+        .iter()
+        .find(|rule| rule.id == rule_id)
+        .ok_or_else(|| format!("No rule with id {}", rule_id))?;
+
+    let event = SelectionEvent::new(sample_text, app_name.unwrap_or_else(|| "Test".to_string()), SelectionType::Selected);
+    let capture = capture::annotate(&app, event);
+    let reason = rule.mismatch(&capture);
+    Ok(RuleTest {
+        matched: reason.is_none(),
+        would: reason.is_none().then(|| rule.action.describe(&capture)),
+        reason,
+        capture,
+    })
+}
@@ -0,0 +1,111 @@
+//! Safe mode, so a bad rule, plugin or settings file can't keep the app from
+//! starting. Every launch is counted until the app has stayed up for a
+//! while; after [`MAX_FAILED_STARTS`] launches in a row that didn't, or with
+//! `--safe-mode` on the command line, the app starts with default settings
+//! (paused, so nothing is detected), without plugins, scripts, rules or
+//! sinks, and says so in a `safe-mode` event. The user's files are left as
+//! they are, and the next launch is a normal one again.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::locations;
+use crate::settings::Settings;
+
+/// Launches in a row that didn't last before the next one is safe.
+const MAX_FAILED_STARTS: u32 = 3;
+/// How long the app has to stay up for a launch to count as a good one.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+const ATTEMPTS_FILE_NAME: &str = "startup-attempts";
+
+/// What was turned off, for the user.
+const DISABLED: &[&str] = &[
+    "settings (defaults are in use and changes aren't saved)",
+    "detection (everything is paused)",
+    "plugins",
+    "scripts",
+    "rules",
+    "sinks and pending webhook deliveries",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeMode {
+    /// Launches in a row that didn't last; zero when asked for with
+    /// `--safe-mode`.
+    pub failed_starts: u32,
+    pub disabled: Vec<&'static str>,
+}
+
+static ACTIVE: OnceLock<Option<SafeMode>> = OnceLock::new();
+
+fn attempts_path(app: &AppHandle) -> Option<PathBuf> {
+    Some(locations::data_dir(app).ok()?.join(ATTEMPTS_FILE_NAME))
+}
+
+/// Counts this launch and decides whether it's a safe one. Runs first
+/// thing in setup, before anything that could be what keeps failing.
+pub fn init(app: &AppHandle) {
+    ACTIVE.get_or_init(|| {
+        let path = attempts_path(app);
+        let failed_starts = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| contents.trim().parse::<u32>().ok())
+            .unwrap_or(0);
+        let forced = std::env::args().any(|arg| arg == "--safe-mode");
+        let safe = forced || failed_starts >= MAX_FAILED_STARTS;
+
+        // A safe launch starts the count over, so the next one is normal
+        let attempts = if safe { 1 } else { failed_starts + 1 };
+        if let Some(path) = &path {
+            let _ = path.parent().map(std::fs::create_dir_all);
+            if let Err(e) = std::fs::write(path, attempts.to_string()) {
+                eprintln!("Failed to record startup attempt: {}", e);
+            }
+        }
+        safe.then(|| SafeMode {
+            failed_starts: if forced { 0 } else { failed_starts },
+            disabled: DISABLED.to_vec(),
+        })
+    });
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.get().is_some_and(|safe_mode| safe_mode.is_some())
+}
+
+/// What settings are in use in safe mode.
+pub fn settings() -> Settings {
+    let mut settings = Settings::default();
+    settings.pause.paused = true;
+    settings
+}
+
+/// Announces safe mode if this is a safe launch, and marks the launch as
+/// a good one once the app has stayed up for a while. Runs last in setup.
+pub fn finish_startup(app: &AppHandle) {
+    if let Some(Some(safe_mode)) = ACTIVE.get() {
+        eprintln!("Started in safe mode after {} failed launches", safe_mode.failed_starts);
+        let _ = app.emit("safe-mode", safe_mode);
+    }
+    let Some(path) = attempts_path(app) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        std::thread::sleep(STABLE_AFTER);
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Failed to clear startup attempts: {}", e);
+            }
+        }
+    });
+}
+
+/// Why this launch is a safe one, if it is, for a UI that missed the event.
+#[tauri::command]
+pub async fn get_safe_mode() -> Result<Option<SafeMode>, String> {
+    Ok(ACTIVE.get().cloned().flatten())
+}
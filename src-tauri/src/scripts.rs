@@ -0,0 +1,265 @@
+//! User scripts in Rhai, run on every capture. Scripts are `.rhai` files in
+//! the profile's `scripts` directory, edited from the settings window through
+//! [`save_script`], and picked up again whenever a file changes.
+//!
+//! A script sees:
+//!
+//! - `text`: the captured text; assigning to it changes the capture.
+//! - `app`: the app it came from (read-only).
+//! - `kind`: `"Selected"`, `"Focused"`, `"Hovered"` or `"Image"` (read-only).
+//! - `tags`: an array; strings pushed onto it tag the history entry.
+//! - `action(#{ type: "...", ... })`: runs a rule action, in the same form
+//!   as in `rules`, e.g. `action(#{ type: "webhook", url: "https://..." })`.
+//!
+//! A script that evaluates to `false` drops the capture. Scripts run in name
+//! order, each on what the previous one left. They can't reach files, the
+//! network or the rest of the app except through `action`, and each run is
+//! capped in operations so a runaway loop can't stall capturing.
+
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, State};
+use text_detection::SelectionEvent;
+
+use crate::capture::Capture;
+use crate::profiles;
+use crate::rules::{self, RuleAction};
+
+const SCRIPTS_DIR_NAME: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "rhai";
+const MAX_OPERATIONS: u64 = 100_000;
+
+struct Script {
+    name: String,
+    modified: Option<SystemTime>,
+    /// Compile errors keep the script listed but not run.
+    ast: Result<AST, String>,
+}
+
+/// Script as listed in the settings window.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptInfo {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+/// What scripts asked for, carried out once the capture is stored.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    actions: Vec<RuleAction>,
+}
+
+impl Outcome {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Runs the actions like a rule's, failing with the last that failed.
+    /// Meant to run on the rules sink, as rules do.
+    pub fn apply(self, app: &AppHandle, capture: &Capture) -> Result<(), String> {
+        let mut result = Ok(());
+        for action in &self.actions {
+            if let Err(e) = rules::perform(app, "script", action, capture) {
+                result = Err(format!("Script action failed: {}", e));
+            }
+        }
+        result
+    }
+}
+
+pub struct ScriptHost {
+    dir: Mutex<PathBuf>,
+    engine: Engine,
+    /// Filled by `action()` during a run.
+    requested: Arc<Mutex<Vec<RuleAction>>>,
+    scripts: Mutex<Vec<Script>>,
+}
+
+fn engine(requested: Arc<Mutex<Vec<RuleAction>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_string_size(1024 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.on_print(|message| println!("[script] {}", message));
+    engine.on_debug(|message, _, position| println!("[script] {} ({})", message, position));
+    engine.register_fn("action", move |action: Map| -> Result<(), Box<rhai::EvalAltResult>> {
+        let action: RuleAction = rhai::serde::from_dynamic(&Dynamic::from_map(action))?;
+        requested.lock().unwrap().push(action);
+        Ok(())
+    });
+    engine
+}
+
+fn script_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("\"{}\" is not a valid script name", name));
+    }
+    Ok(dir.join(name).with_extension(SCRIPT_EXTENSION))
+}
+
+impl ScriptHost {
+    fn new(dir: PathBuf) -> Self {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        Self {
+            dir: Mutex::new(dir),
+            engine: engine(Arc::clone(&requested)),
+            requested,
+            scripts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Switches to the scripts in `dir`, e.g. for another profile.
+    pub fn reload(&self, dir: &Path) {
+        *self.dir.lock().unwrap() = dir.join(SCRIPTS_DIR_NAME);
+        self.scripts.lock().unwrap().clear();
+    }
+
+    /// Compiles whatever was added or changed since the last look, and
+    /// forgets deleted scripts.
+    fn refresh(&self) {
+        let dir = self.dir.lock().unwrap().clone();
+        let mut files: Vec<(String, Option<SystemTime>, PathBuf)> = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+                    .filter_map(|path| {
+                        let name = path.file_stem()?.to_string_lossy().into_owned();
+                        let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+                        Some((name, modified, path))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut scripts = self.scripts.lock().unwrap();
+        let mut previous = std::mem::take(&mut *scripts);
+        for (name, modified, path) in files {
+            match previous.iter().position(|script| script.name == name && script.modified == modified) {
+                Some(index) => scripts.push(previous.swap_remove(index)),
+                None => {
+                    let ast = std::fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|source| self.engine.compile(source).map_err(|e| e.to_string()));
+                    if let Err(e) = &ast {
+                        eprintln!("Script {} doesn't compile: {}", name, e);
+                    }
+                    scripts.push(Script { name, modified, ast });
+                }
+            }
+        }
+    }
+
+    fn info(&self) -> Vec<ScriptInfo> {
+        self.refresh();
+        self.scripts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|script| ScriptInfo {
+                name: script.name.clone(),
+                error: script.ast.as_ref().err().cloned(),
+            })
+            .collect()
+    }
+
+    fn run(&self, mut event: SelectionEvent) -> Option<(SelectionEvent, Outcome)> {
+        self.refresh();
+        let scripts = self.scripts.lock().unwrap();
+        let mut outcome = Outcome::default();
+        let mut tags = Vec::new();
+
+        for script in scripts.iter() {
+            let Ok(ast) = &script.ast else {
+                continue;
+            };
+            let mut scope = Scope::new();
+            scope.push("text", event.text.clone());
+            scope.push_constant("app", event.app_name.clone());
+            scope.push_constant("kind", format!("{:?}", event.selection_type));
+            scope.push("tags", Array::new());
+
+            self.requested.lock().unwrap().clear();
+            let result = self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, ast);
+            let requested = std::mem::take(&mut *self.requested.lock().unwrap());
+            match result {
+                Ok(value) if value.as_bool() == Ok(false) => {
+                    println!("Capture dropped by script {}", script.name);
+                    return None;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("Script {} failed: {}", script.name, e);
+                    continue;
+                }
+            }
+
+            if let Some(text) = scope.get_value::<String>("text") {
+                event.text = text;
+            }
+            if let Some(added) = scope.get_value::<Array>("tags") {
+                tags.extend(added.into_iter().filter_map(|tag| tag.into_string().ok()));
+            }
+            outcome.actions.extend(requested);
+        }
+
+        if !tags.is_empty() {
+            outcome.actions.insert(0, RuleAction::AddTags { tags });
+        }
+        Some((event, outcome))
+    }
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = profiles::data_dir(app)?.join(SCRIPTS_DIR_NAME);
+    app.manage(ScriptHost::new(dir));
+    Ok(())
+}
+
+/// Runs the user's scripts over `event`. `None` if one dropped it.
+pub fn run(app: &AppHandle, event: SelectionEvent) -> Option<(SelectionEvent, Outcome)> {
+    match app.try_state::<ScriptHost>() {
+        Some(host) => host.run(event),
+        None => Some((event, Outcome::default())),
+    }
+}
+
+#[tauri::command]
+pub async fn list_scripts(host: State<'_, ScriptHost>) -> Result<Vec<ScriptInfo>, String> {
+    Ok(host.info())
+}
+
+#[tauri::command]
+pub async fn get_script(host: State<'_, ScriptHost>, name: String) -> Result<String, String> {
+    let path = script_path(&host.dir.lock().unwrap(), &name)?;
+    std::fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+/// Saves `source` as script `name`. A script that doesn't compile is
+/// refused, with the error and its position, and the old version kept.
+#[tauri::command]
+pub async fn save_script(host: State<'_, ScriptHost>, name: String, source: String) -> Result<(), String> {
+    host.engine.compile(&source).map_err(|e| e.to_string())?;
+    let dir = host.dir.lock().unwrap().clone();
+    let path = script_path(&dir, &name)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    std::fs::write(path, source).map_err(|e| e.to_string())?;
+    host.refresh();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_script(host: State<'_, ScriptHost>, name: String) -> Result<(), String> {
+    let path = script_path(&host.dir.lock().unwrap(), &name)?;
+    std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    host.refresh();
+    Ok(())
+}
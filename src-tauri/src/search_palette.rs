@@ -0,0 +1,48 @@
+//! Spotlight-style palette for finding old captures: a small frameless
+//! window, toggled by hotkey, that hides again as soon as it loses focus.
+
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+const WINDOW_LABEL: &str = "search";
+
+fn create(app: &AppHandle) -> tauri::Result<tauri::WebviewWindow> {
+    let window = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("index.html#/search".into()))
+        .title("Search history")
+        .inner_size(640.0, 420.0)
+        .center()
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()?;
+
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            let _ = handle.hide();
+        }
+    });
+    Ok(window)
+}
+
+/// Shows the palette, or hides it if it is already showing.
+pub fn toggle(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => window,
+        None => create(app).map_err(|e| e.to_string())?,
+    };
+
+    if window.is_visible().unwrap_or(false) {
+        window.hide().map_err(|e| e.to_string())
+    } else {
+        window.center().map_err(|e| e.to_string())?;
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn toggle_search_palette(app: AppHandle) -> Result<(), String> {
+    toggle(&app)
+}
@@ -0,0 +1,155 @@
+//! Credentials kept in the OS keychain (the macOS Keychain, Windows
+//! Credential Manager, the Secret Service on Linux) instead of the
+//! plaintext settings file. A setting refers to one by name, written as
+//! `keychain:<name>` wherever it would otherwise hold the token itself.
+//!
+//! The keychain can't list what the app stored, so the names are also kept
+//! in an index entry of their own, for [`delete_all`].
+
+use std::sync::Mutex;
+
+const SERVICE: &str = "com.acmi-desktop.app";
+const REFERENCE_PREFIX: &str = "keychain:";
+const INDEX_NAME: &str = "secret-index";
+
+/// Held while the index is read and written back.
+static INDEX: Mutex<()> = Mutex::new(());
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+/// The value of a credential setting: looked up when it is a `keychain:`
+/// reference, otherwise used as written.
+pub fn resolve(value: &str) -> Result<String, String> {
+    match value.strip_prefix(REFERENCE_PREFIX) {
+        Some(name) => get(name),
+        None => Ok(value.to_string()),
+    }
+}
+
+fn read_index() -> Result<Vec<String>, String> {
+    match entry(INDEX_NAME)?.get_password() {
+        Ok(index) => Ok(index.lines().map(str::to_string).collect()),
+        Err(keyring::Error::NoEntry) => Ok(Vec::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn change_index(change: impl FnOnce(&mut Vec<String>)) -> Result<(), String> {
+    let _guard = INDEX.lock().unwrap();
+    let mut names = read_index()?;
+    let before = names.clone();
+    change(&mut names);
+    if names != before {
+        entry(INDEX_NAME)?.set_password(&names.join("\n")).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn remember(name: &str) -> Result<(), String> {
+    change_index(|names| {
+        if !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    })
+}
+
+/// The names of the secrets `text` refers to, e.g. a settings file.
+pub fn references(text: &str) -> Vec<String> {
+    text.match_indices(REFERENCE_PREFIX)
+        .filter_map(|(start, _)| {
+            let name: String = text[start + REFERENCE_PREFIX.len()..]
+                .chars()
+                .take_while(|&c| c != '"' && c != '\'' && !c.is_whitespace())
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+pub fn get(name: &str) -> Result<String, String> {
+    entry(name)?.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => format!("No secret named {} in the keychain", name),
+        e => e.to_string(),
+    })
+}
+
+pub fn set(name: &str, value: &str) -> Result<(), String> {
+    entry(name)?.set_password(value).map_err(|e| e.to_string())?;
+    remember(name)
+}
+
+/// The secret `name`, stored as `generate` makes it the first time it's
+/// asked for.
+pub fn get_or_create(name: &str, generate: impl FnOnce() -> Result<String, String>) -> Result<String, String> {
+    let entry = entry(name)?;
+    match entry.get_password() {
+        Ok(value) => Ok(value),
+        Err(keyring::Error::NoEntry) => {
+            let value = generate()?;
+            entry.set_password(&value).map_err(|e| e.to_string())?;
+            remember(name)?;
+            Ok(value)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The setting value that refers to secret `name`.
+pub fn reference(name: &str) -> String {
+    format!("{}{}", REFERENCE_PREFIX, name)
+}
+
+/// Whether `a` and `b` are equal, taking as long whichever byte differs,
+/// for checking tokens presented by other processes.
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Stores `value` in the keychain as `name`, replacing any previous value.
+/// Returns the reference to put in settings.
+#[tauri::command]
+pub async fn set_secret(name: String, value: String) -> Result<String, String> {
+    if name.trim().is_empty() {
+        return Err("A secret needs a name".to_string());
+    }
+    set(&name, &value)?;
+    Ok(reference(&name))
+}
+
+fn delete_entry(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    delete_entry(name)?;
+    change_index(|names| names.retain(|known| known != name))
+}
+
+/// Deletes every secret the app stored, plus those in `also`, e.g. ones
+/// referred to from settings but stored before the index was kept.
+/// Returns what couldn't be deleted.
+pub fn delete_all(also: &[String]) -> Vec<String> {
+    let _guard = INDEX.lock().unwrap();
+    let mut names = read_index().unwrap_or_else(|e| {
+        eprintln!("Failed to read the secret index: {}", e);
+        Vec::new()
+    });
+    names.extend(also.iter().cloned());
+    names.push(INDEX_NAME.to_string());
+    names.sort();
+    names.dedup();
+    names
+        .iter()
+        .filter_map(|name| delete_entry(name).err().map(|e| format!("{}: {}", name, e)))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<(), String> {
+    delete(&name)
+}
@@ -0,0 +1,93 @@
+//! "Capture with ACMI" in the macOS Services menu, so text can be sent into
+//! the pipeline from any app's context menu. The menu entry is declared
+//! under `NSServices` in Info.plist; this registers the object that
+//! receives the pasteboard.
+
+use tauri::AppHandle;
+
+/// Registers the services provider. Must run on the main thread.
+pub fn init(app: &AppHandle) {
+    platform::register(app);
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_void};
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+    use text_detection::{SelectionEvent, SelectionType};
+
+    use crate::{capture, trace};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        fn NSUpdateDynamicServices();
+    }
+
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+
+    /// Handles text sent through the service.
+    fn deliver(app: &AppHandle, text: String) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let event = SelectionEvent::new(text, "Services", SelectionType::Selected);
+        if let Some(capture) = capture::process(app, event) {
+            trace::source(app, capture.id, "services");
+            capture::emit(app, "text-selection-changed", &capture);
+        }
+    }
+
+    /// `- (void)captureWithAcmi:(NSPasteboard *)pboard userData:(NSString *)data error:(NSString **)error`,
+    /// matching `NSMessage` in Info.plist.
+    extern "C" fn capture_with_acmi(_this: &Object, _cmd: Sel, pboard: *mut Object, _data: *mut Object, _error: *mut c_void) {
+        let text = unsafe {
+            let utf8_type: *mut Object =
+                msg_send![class!(NSString), stringWithUTF8String: c"public.utf8-plain-text".as_ptr()];
+            let string: *mut Object = msg_send![pboard, stringForType: utf8_type];
+            if string.is_null() {
+                return;
+            }
+            let utf8: *const c_char = msg_send![string, UTF8String];
+            if utf8.is_null() {
+                return;
+            }
+            CStr::from_ptr(utf8).to_string_lossy().into_owned()
+        };
+        if let Some(app) = APP.get() {
+            deliver(app, text);
+        }
+    }
+
+    pub fn register(app: &AppHandle) {
+        if APP.set(app.clone()).is_err() {
+            return;
+        }
+        let Some(mut decl) = ClassDecl::new("AcmiServicesProvider", class!(NSObject)) else {
+            eprintln!("Failed to declare the Services provider class");
+            return;
+        };
+        unsafe {
+            decl.add_method(
+                sel!(captureWithAcmi:userData:error:),
+                capture_with_acmi as extern "C" fn(&Object, Sel, *mut Object, *mut Object, *mut c_void),
+            );
+            let provider_class = decl.register();
+            let provider: *mut Object = msg_send![provider_class, new];
+            let ns_app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: () = msg_send![ns_app, setServicesProvider: provider];
+            NSUpdateDynamicServices();
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use tauri::AppHandle;
+
+    pub fn register(_app: &AppHandle) {}
+}
@@ -0,0 +1,162 @@
+//! Follows the OS user session so nothing polls the login screen: detection
+//! pauses while the screen is locked and resumes after unlocking. On Windows
+//! the same goes for UAC prompts, which show on the same secure desktop. Also
+//! notices system sleep and puts timers and hotkeys back in order on wake.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use text_detection::Detector;
+
+use crate::{hotkey, text_detector};
+
+/// UAC prompts come and go quickly, so Windows checks more often.
+const POLL_INTERVAL: Duration = if cfg!(target_os = "windows") {
+    Duration::from_millis(500)
+} else {
+    Duration::from_secs(2)
+};
+/// A gap this much longer than the poll interval means the machine slept;
+/// tokio's clock doesn't advance during sleep, the wall clock does.
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub struct SessionState {
+    locked: AtomicBool,
+    /// Detection was running when the screen locked.
+    paused_detection: AtomicBool,
+}
+
+impl SessionState {
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+}
+
+pub fn init(app: &AppHandle) {
+    app.manage(SessionState::default());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last_tick = SystemTime::now();
+        loop {
+            interval.tick().await;
+
+            let now = SystemTime::now();
+            let gap = now.duration_since(last_tick).unwrap_or_default();
+            last_tick = now;
+            if gap > POLL_INTERVAL + SLEEP_THRESHOLD {
+                handle_wake(&app, gap);
+            }
+
+            if let Some(locked) = platform::screen_locked() {
+                observe(&app, locked);
+            }
+        }
+    });
+}
+
+fn observe(app: &AppHandle, locked: bool) {
+    let state = app.state::<SessionState>();
+    if state.locked.swap(locked, Ordering::SeqCst) == locked {
+        return;
+    }
+
+    if locked {
+        state.paused_detection.store(text_detector::pause(app), Ordering::SeqCst);
+        println!("Screen locked, detection paused");
+        let _ = app.emit("session-locked", ());
+    } else {
+        if state.paused_detection.swap(false, Ordering::SeqCst) {
+            if let Err(e) = text_detector::resume(app) {
+                eprintln!("Failed to resume detection after unlock: {}", e);
+            }
+        }
+        println!("Screen unlocked");
+        let _ = app.emit("session-unlocked", ());
+    }
+}
+
+/// Restarts the polling task (so its interval doesn't fire a burst of
+/// catch-up ticks), re-registers hotkeys and re-checks permissions.
+fn handle_wake(app: &AppHandle, slept_for: Duration) {
+    println!("Resumed from sleep after {}s", slept_for.as_secs());
+
+    if text_detector::pause(app) {
+        if let Err(e) = text_detector::resume(app) {
+            eprintln!("Failed to restart detection after sleep: {}", e);
+        }
+    }
+    hotkey::reregister_all(app);
+
+    let has_permissions = Detector::new().has_permissions();
+    if !has_permissions {
+        eprintln!("Accessibility permissions were lost while asleep");
+    }
+    let _ = app.emit(
+        "resumed-from-sleep",
+        serde_json::json!({ "slept_secs": slept_for.as_secs(), "has_permissions": has_permissions }),
+    );
+}
+
+#[tauri::command]
+pub async fn is_session_locked(app: AppHandle) -> Result<bool, String> {
+    Ok(app.state::<SessionState>().is_locked())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::CFString;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    }
+
+    /// `CGSSessionScreenIsLocked` is only present while locked.
+    pub fn screen_locked() -> Option<bool> {
+        unsafe {
+            let dict = CGSessionCopyCurrentDictionary();
+            if dict.is_null() {
+                return None;
+            }
+            let dict: CFDictionary<CFString, CFBoolean> = CFDictionary::wrap_under_create_rule(dict);
+            let key = CFString::from_static_string("CGSSessionScreenIsLocked");
+            Some(dict.find(&key).is_some_and(|value| bool::from((*value).clone())))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::System::StationsAndDesktops::{
+        CloseDesktop, OpenInputDesktop, DESKTOP_CONTROL_FLAGS, DESKTOP_READOBJECTS,
+    };
+
+    /// The input desktop can't be opened while the secure desktop is
+    /// active, whether for the lock screen or a UAC prompt.
+    pub fn screen_locked() -> Option<bool> {
+        unsafe {
+            match OpenInputDesktop(DESKTOP_CONTROL_FLAGS(0), BOOL::from(false), DESKTOP_READOBJECTS) {
+                Ok(desktop) => {
+                    let _ = CloseDesktop(desktop);
+                    Some(false)
+                }
+                Err(_) => Some(true),
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn screen_locked() -> Option<bool> {
+        None
+    }
+}
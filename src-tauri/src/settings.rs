@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::actions::chat::ChatSettings;
+use crate::actions::email::EmailSettings;
+use crate::actions::push::PushSettings;
+use crate::actions::tasks::TaskSettings;
+use crate::announce::AnnounceSettings;
+use crate::browser_bridge::BrowserBridgeSettings;
+use crate::compute::currency::CurrencySettings;
+use crate::connectivity::ConnectivitySettings;
+use crate::definitions::DefinitionsSettings;
+use crate::debug::DebugSettings;
+use crate::deliveries::DeliverySettings;
+use crate::digest::DigestSettings;
+use crate::dock::DockSettings;
+use crate::entities::EntitySettings;
+use crate::flash::FlashSettings;
+use crate::grpc::GrpcSettings;
+use crate::history::HistorySettings;
+use crate::hotkey::HotkeySettings;
+use crate::ide::IdeSettings;
+use crate::image_selection::ImageSelectionSettings;
+use crate::kiosk::KioskSettings;
+use crate::mqtt::MqttSettings;
+use crate::noise::NoiseFilterSettings;
+use crate::normalize::NormalizeSettings;
+use crate::pause::PauseSettings;
+use crate::peers::PeerSettings;
+use crate::plugins::PluginSettings;
+use crate::profiles;
+use crate::quota::QuotaSettings;
+use crate::readability::ReadabilitySettings;
+use crate::rules::Rule;
+use crate::safe_mode;
+use crate::sinks::SinkSettings;
+use crate::sounds::SoundSettings;
+use crate::spellcheck::SpellcheckSettings;
+use crate::state_broadcast::StateBroadcastSettings;
+use crate::text_detector::DetectionSettings;
+use crate::translation::TranslationSettings;
+use crate::webhook_templates::WebhookTemplate;
+
+mod validation;
+
+pub use validation::ConfigIssue;
+
+pub const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub pause: PauseSettings,
+    pub detection: DetectionSettings,
+    pub hotkeys: HotkeySettings,
+    pub noise: NoiseFilterSettings,
+    pub normalize: NormalizeSettings,
+    pub definitions: DefinitionsSettings,
+    pub plugins: PluginSettings,
+    pub images: ImageSelectionSettings,
+    pub ide: IdeSettings,
+    pub browser_bridge: BrowserBridgeSettings,
+    pub peers: PeerSettings,
+    pub translation: TranslationSettings,
+    pub quota: QuotaSettings,
+    pub spellcheck: SpellcheckSettings,
+    pub readability: ReadabilitySettings,
+    pub rules: Vec<Rule>,
+    pub webhook_templates: Vec<WebhookTemplate>,
+    pub deliveries: DeliverySettings,
+    pub sinks: SinkSettings,
+    pub mqtt: MqttSettings,
+    pub grpc: GrpcSettings,
+    pub connectivity: ConnectivitySettings,
+    pub tasks: TaskSettings,
+    pub email: EmailSettings,
+    pub push: PushSettings,
+    pub chat: ChatSettings,
+    pub entities: EntitySettings,
+    pub currency: CurrencySettings,
+    pub history: HistorySettings,
+    pub digest: DigestSettings,
+    pub sounds: SoundSettings,
+    pub announce: AnnounceSettings,
+    pub flash: FlashSettings,
+    pub dock: DockSettings,
+    pub state_broadcast: StateBroadcastSettings,
+    pub kiosk: KioskSettings,
+    pub debug: DebugSettings,
+}
+
+impl Settings {
+    /// What the frontend gets to see: everything but the kiosk passcode hash
+    /// and salt.
+    pub fn redacted(mut self) -> Settings {
+        self.kiosk.passcode_hash = None;
+        self.kiosk.salt.clear();
+        self
+    }
+}
+
+/// The user's settings, loaded from `settings.toml` in the app config dir
+/// and written back whenever they change.
+pub struct SettingsStore {
+    path: Mutex<PathBuf>,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let (path, settings) = read(dir)?;
+        Ok(Self {
+            path: Mutex::new(path),
+            settings: Mutex::new(settings),
+        })
+    }
+
+    /// Switches to the settings file in `dir`, e.g. for another profile.
+    pub fn reload(&self, dir: &Path) -> Result<Settings, Box<dyn std::error::Error>> {
+        let (path, settings) = read(dir)?;
+        *self.path.lock().unwrap() = path;
+        *self.settings.lock().unwrap() = settings.clone();
+        Ok(settings)
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Applies `change` and persists the result.
+    pub fn update<F: FnOnce(&mut Settings)>(&self, change: F) -> Result<Settings, String> {
+        let mut settings = self.settings.lock().unwrap();
+        change(&mut settings);
+        self.save(&settings)?;
+        Ok(settings.clone())
+    }
+
+    /// Problems with the settings file as it is on disk now, which may
+    /// have been edited since it was loaded.
+    pub fn issues(&self) -> Result<Vec<ConfigIssue>, String> {
+        let path = self.path.lock().unwrap().clone();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(validation::validate(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    fn save(&self, settings: &Settings) -> Result<(), String> {
+        // The file is set aside in safe mode; changes last until restart
+        if safe_mode::is_active() {
+            return Ok(());
+        }
+        let contents = toml::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        std::fs::write(&*self.path.lock().unwrap(), contents).map_err(|e| e.to_string())
+    }
+}
+
+fn read(dir: &Path) -> Result<(PathBuf, Settings), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(SETTINGS_FILE_NAME);
+    // Whatever is in the file may be what keeps the app from starting
+    if safe_mode::is_active() {
+        return Ok((path, safe_mode::settings()));
+    }
+
+    let settings = match std::fs::read_to_string(&path) {
+        Ok(contents) => {
+            for issue in validation::validate(&contents) {
+                eprintln!("{}, {}", path.display(), issue);
+            }
+            toml::from_str(&contents).unwrap_or_else(|_| {
+                eprintln!("Failed to parse {}, using defaults", path.display());
+                Settings::default()
+            })
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+        Err(e) => return Err(e.into()),
+    };
+    Ok((path, settings))
+}
+
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = profiles::config_dir(app)?;
+    app.manage(SettingsStore::load(&dir)?);
+    Ok(())
+}
+
+/// Current settings, or the defaults if the store failed to load.
+pub fn current(app: &AppHandle) -> Settings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.get())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_settings(settings: State<'_, SettingsStore>) -> Result<Settings, String> {
+    Ok(settings.get().redacted())
+}
+
+/// What's wrong with the settings file, with line numbers, so hand edits
+/// that were ignored don't go unnoticed.
+#[tauri::command]
+pub async fn get_config_issues(settings: State<'_, SettingsStore>) -> Result<Vec<ConfigIssue>, String> {
+    settings.issues()
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    new_settings: Settings,
+) -> Result<Settings, String> {
+    let updated = settings.update(|current| {
+        // The frontend only ever sees these redacted, so keep what's stored.
+        let kiosk = std::mem::take(&mut current.kiosk);
+        *current = new_settings;
+        current.kiosk.passcode_hash = kiosk.passcode_hash;
+        current.kiosk.salt = kiosk.salt;
+    })?;
+    let updated = updated.redacted();
+    let _ = app.emit("settings-changed", &updated);
+    Ok(updated)
+}
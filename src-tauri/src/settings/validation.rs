@@ -0,0 +1,235 @@
+//! Checks a hand-edited `settings.toml` and says exactly what's wrong with
+//! it and where, rather than leaving the user to wonder why their change
+//! did nothing. A file that doesn't parse is still replaced by defaults at
+//! load; this is how they find out why.
+
+use regex::Regex;
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+use tauri_plugin_global_shortcut::Shortcut;
+use toml_edit::ImDocument;
+
+use super::Settings;
+use crate::rules::RuleAction;
+use crate::webhook_templates;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueKind {
+    /// Not valid TOML, or a value of the wrong type. The whole file is
+    /// ignored and defaults are used instead.
+    Unreadable,
+    /// A key no setting uses, typically a typo. Ignored.
+    UnknownKey,
+    /// A shortcut that can't be parsed. It won't be registered.
+    InvalidShortcut,
+    /// A regex that doesn't compile. What uses it never matches.
+    InvalidRegex,
+    /// A webhook template that isn't JSON, or a rule naming one that
+    /// doesn't exist. Rules using it fail.
+    InvalidTemplate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub kind: IssueKind,
+    /// Where the setting lives, e.g. `rules[2].pattern`.
+    pub key: Option<String>,
+    /// 1-based, when the problem can be pinned to a place in the file.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// Whether the file was thrown out over it.
+    pub fn is_fatal(&self) -> bool {
+        self.kind == IssueKind::Unreadable
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, "{}: ", key)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn segments(path: &serde_ignored::Path, out: &mut Vec<Segment>) {
+    match path {
+        serde_ignored::Path::Root => {}
+        serde_ignored::Path::Seq { parent, index } => {
+            segments(parent, out);
+            out.push(Segment::Index(*index));
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            segments(parent, out);
+            out.push(Segment::Key(key.clone()));
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => segments(parent, out),
+    }
+}
+
+fn display(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if out.is_empty() => out.push_str(key),
+            Segment::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
+/// Span of the deepest part of `path` present in the file.
+fn locate(doc: &ImDocument<&str>, path: &[Segment]) -> Option<Range<usize>> {
+    let mut item = doc.as_item();
+    let mut span = None;
+    for segment in path {
+        let next = match segment {
+            Segment::Key(key) => item.get(key.as_str()),
+            Segment::Index(index) => item.get(*index),
+        };
+        let Some(next) = next else {
+            break;
+        };
+        item = next;
+        span = item.span().or(span);
+    }
+    span
+}
+
+struct Checker<'a> {
+    contents: &'a str,
+    doc: Option<ImDocument<&'a str>>,
+    issues: Vec<ConfigIssue>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, kind: IssueKind, path: Option<&[Segment]>, span: Option<Range<usize>>, message: String) {
+        let span = span.or_else(|| Some(locate(self.doc.as_ref()?, path?)).flatten());
+        let (line, column) = match span {
+            Some(span) => {
+                let before = &self.contents[..span.start.min(self.contents.len())];
+                let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+                (Some(before.matches('\n').count() + 1), Some(before[line_start..].chars().count() + 1))
+            }
+            None => (None, None),
+        };
+        self.issues.push(ConfigIssue {
+            kind,
+            key: path.map(display),
+            line,
+            column,
+            message,
+        });
+    }
+
+    fn shortcut(&mut self, path: &[Segment], shortcut: &str) {
+        if let Err(e) = shortcut.parse::<Shortcut>() {
+            let message = format!("\"{}\" isn't a valid shortcut: {}", shortcut, e);
+            self.push(IssueKind::InvalidShortcut, Some(path), None, message);
+        }
+    }
+
+    fn regex(&mut self, path: &[Segment], pattern: &str) {
+        if let Err(e) = Regex::new(pattern) {
+            self.push(IssueKind::InvalidRegex, Some(path), None, e.to_string());
+        }
+    }
+}
+
+fn key(path: &[&str]) -> Vec<Segment> {
+    path.iter().map(|key| Segment::Key(key.to_string())).collect()
+}
+
+/// Everything wrong with `contents` as a settings file, in file order.
+pub fn validate(contents: &str) -> Vec<ConfigIssue> {
+    let mut checker = Checker {
+        contents,
+        doc: None,
+        issues: Vec::new(),
+    };
+    match ImDocument::parse(contents) {
+        Ok(doc) => checker.doc = Some(doc),
+        Err(e) => {
+            checker.push(IssueKind::Unreadable, None, e.span(), e.message().trim().to_string());
+            return checker.issues;
+        }
+    }
+
+    let mut unknown = Vec::new();
+    let result: Result<Settings, _> = serde_ignored::deserialize(toml::Deserializer::new(contents), |path| {
+        let mut path_segments = Vec::new();
+        segments(&path, &mut path_segments);
+        unknown.push(path_segments);
+    });
+    for path in unknown {
+        checker.push(IssueKind::UnknownKey, Some(&path), None, "Unknown setting".to_string());
+    }
+
+    match result {
+        Ok(settings) => {
+            checker.shortcut(&key(&["translation", "cycle_shortcut"]), &settings.translation.cycle_shortcut);
+            checker.shortcut(&key(&["history", "diff_shortcut"]), &settings.history.diff_shortcut);
+            checker.shortcut(&key(&["history", "search_shortcut"]), &settings.history.search_shortcut);
+            if let Some(shortcut) = &settings.chat.share_shortcut {
+                checker.shortcut(&key(&["chat", "share_shortcut"]), shortcut);
+            }
+            for (index, rule) in settings.rules.iter().enumerate() {
+                if let Some(pattern) = &rule.pattern {
+                    let path = [
+                        Segment::Key("rules".to_string()),
+                        Segment::Index(index),
+                        Segment::Key("pattern".to_string()),
+                    ];
+                    checker.regex(&path, pattern);
+                }
+                if let RuleAction::Webhook { template: Some(name), .. } = &rule.action {
+                    if !settings.webhook_templates.iter().any(|template| template.name == *name) {
+                        let path = [
+                            Segment::Key("rules".to_string()),
+                            Segment::Index(index),
+                            Segment::Key("action".to_string()),
+                            Segment::Key("template".to_string()),
+                        ];
+                        let message = format!("No webhook template named \"{}\"", name);
+                        checker.push(IssueKind::InvalidTemplate, Some(&path), None, message);
+                    }
+                }
+            }
+            for (index, template) in settings.webhook_templates.iter().enumerate() {
+                if let Err(e) = webhook_templates::parse(&template.body) {
+                    let path = [
+                        Segment::Key("webhook_templates".to_string()),
+                        Segment::Index(index),
+                        Segment::Key("body".to_string()),
+                    ];
+                    checker.push(IssueKind::InvalidTemplate, Some(&path), None, e);
+                }
+            }
+        }
+        Err(e) => checker.push(IssueKind::Unreadable, None, e.span(), e.message().trim().to_string()),
+    }
+
+    checker.issues.sort_by_key(|issue| issue.line);
+    checker.issues
+}
@@ -0,0 +1,220 @@
+//! Where captures go after the UI: rule actions, webhooks, MQTT and gRPC
+//! subscribers. Each sink has its own bounded queue and worker threads, so
+//! a slow webhook or a stalled broker only backs up its own queue and the
+//! capture is emitted to the UI without waiting on any of them. A full
+//! queue drops new jobs rather than block.
+//!
+//! The history insert isn't a sink: the UI needs the row id it returns.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sink {
+    /// Rule actions: tags, files, the clipboard, tasks and the audit log.
+    Rules,
+    Webhook,
+    Mqtt,
+    Grpc,
+}
+
+impl Sink {
+    const ALL: [Sink; 4] = [Sink::Rules, Sink::Webhook, Sink::Mqtt, Sink::Grpc];
+
+    fn name(self) -> &'static str {
+        match self {
+            Sink::Rules => "rules",
+            Sink::Webhook => "webhook",
+            Sink::Mqtt => "mqtt",
+            Sink::Grpc => "grpc",
+        }
+    }
+}
+
+/// Read at startup; changes apply after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SinkSettings {
+    /// Jobs each sink may have waiting before new ones are dropped.
+    pub queue_capacity: usize,
+    /// Worker threads per sink. One keeps a sink's jobs in order; more let
+    /// them overlap. Zero runs the sink's jobs on the capture path, as
+    /// before there were queues. Sinks not listed get one.
+    pub workers: HashMap<Sink, usize>,
+}
+
+impl Default for SinkSettings {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 256,
+            workers: HashMap::from([(Sink::Webhook, 4)]),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() -> Result<(), String> + Send>;
+
+#[derive(Default)]
+struct Counters {
+    queued: AtomicUsize,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    /// Unix seconds and message.
+    last_error: Mutex<Option<(u64, String)>>,
+}
+
+impl Counters {
+    fn record(&self, sink: Sink, result: Result<(), String>) {
+        match result {
+            Ok(()) => {
+                self.completed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                eprintln!("{} sink failed: {}", sink.name(), e);
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                *self.last_error.lock().unwrap() = Some((now, e));
+            }
+        }
+    }
+}
+
+struct Queue {
+    /// `None` when the sink runs inline.
+    sender: Option<SyncSender<Job>>,
+    workers: usize,
+    counters: Arc<Counters>,
+}
+
+impl Queue {
+    fn start(sink: Sink, workers: usize, capacity: usize) -> Self {
+        let counters = Arc::new(Counters::default());
+        if workers == 0 {
+            return Self {
+                sender: None,
+                workers,
+                counters,
+            };
+        }
+        let (sender, receiver) = mpsc::sync_channel::<Job>(capacity.max(1));
+        let receiver = Arc::new(Mutex::new(receiver));
+        for worker in 0..workers {
+            let (receiver, counters) = (receiver.clone(), counters.clone());
+            let spawned = std::thread::Builder::new()
+                .name(format!("sink-{}-{}", sink.name(), worker))
+                .spawn(move || work(sink, &receiver, &counters));
+            if let Err(e) = spawned {
+                eprintln!("Failed to start {} sink worker: {}", sink.name(), e);
+            }
+        }
+        Self {
+            sender: Some(sender),
+            workers,
+            counters,
+        }
+    }
+}
+
+fn work(sink: Sink, receiver: &Mutex<Receiver<Job>>, counters: &Counters) {
+    loop {
+        // Held only while waiting, so the other workers take the next job
+        let job = receiver.lock().unwrap().recv();
+        let Ok(job) = job else {
+            return;
+        };
+        counters.queued.fetch_sub(1, Ordering::Relaxed);
+        counters.record(sink, job());
+    }
+}
+
+pub struct SinkPool(HashMap<Sink, Queue>);
+
+/// Hands `job` to `sink`'s workers. Fails, without running it, if the
+/// sink's queue is full.
+pub fn submit(
+    app: &AppHandle,
+    sink: Sink,
+    job: impl FnOnce() -> Result<(), String> + Send + 'static,
+) -> Result<(), String> {
+    let pool = app.try_state::<SinkPool>();
+    let Some(queue) = pool.as_ref().and_then(|pool| pool.0.get(&sink)) else {
+        // Not started yet
+        if let Err(e) = job() {
+            eprintln!("{} sink failed: {}", sink.name(), e);
+        }
+        return Ok(());
+    };
+    let counters = &queue.counters;
+    let Some(sender) = &queue.sender else {
+        counters.record(sink, job());
+        return Ok(());
+    };
+    counters.queued.fetch_add(1, Ordering::Relaxed);
+    let reason = match sender.try_send(Box::new(job)) {
+        Ok(()) => return Ok(()),
+        Err(TrySendError::Full(_)) => "queue is full",
+        Err(TrySendError::Disconnected(_)) => "workers aren't running",
+    };
+    counters.queued.fetch_sub(1, Ordering::Relaxed);
+    counters.dropped.fetch_add(1, Ordering::Relaxed);
+    Err(format!("The {} sink's {}", sink.name(), reason))
+}
+
+pub fn init(app: &AppHandle) {
+    let settings = settings::current(app).sinks;
+    let queues = Sink::ALL
+        .into_iter()
+        .map(|sink| {
+            let workers = settings.workers.get(&sink).copied().unwrap_or(1);
+            (sink, Queue::start(sink, workers, settings.queue_capacity))
+        })
+        .collect();
+    app.manage(SinkPool(queues));
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SinkStatus {
+    pub sink: Sink,
+    pub workers: usize,
+    /// Jobs waiting for a worker.
+    pub queued: usize,
+    pub completed: u64,
+    pub failed: u64,
+    /// Jobs turned away because the queue was full.
+    pub dropped: u64,
+    pub last_error: Option<String>,
+    /// Unix seconds.
+    pub last_error_at: Option<u64>,
+}
+
+/// Queue depths, counts and the last error of every sink.
+#[tauri::command]
+pub async fn get_sink_status(pool: State<'_, SinkPool>) -> Result<Vec<SinkStatus>, String> {
+    Ok(Sink::ALL
+        .into_iter()
+        .filter_map(|sink| {
+            let queue = pool.0.get(&sink)?;
+            let counters = &queue.counters;
+            let last_error = counters.last_error.lock().unwrap().clone();
+            Some(SinkStatus {
+                sink,
+                workers: queue.workers,
+                queued: counters.queued.load(Ordering::Relaxed),
+                completed: counters.completed.load(Ordering::Relaxed),
+                failed: counters.failed.load(Ordering::Relaxed),
+                dropped: counters.dropped.load(Ordering::Relaxed),
+                last_error_at: last_error.as_ref().map(|(at, _)| *at),
+                last_error: last_error.map(|(_, message)| message),
+            })
+        })
+        .collect())
+}
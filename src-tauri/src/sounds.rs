@@ -0,0 +1,128 @@
+//! Short system sounds confirming what a hotkey capture did, for when the
+//! popup is hidden and there is otherwise no feedback at all.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::settings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    pub success: bool,
+    pub filtered: bool,
+    pub error: bool,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            success: true,
+            filtered: true,
+            error: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sound {
+    /// Something was captured.
+    Success,
+    /// The hotkey fired but there was nothing to capture.
+    Filtered,
+    /// Reading the selection failed.
+    Error,
+}
+
+impl SoundSettings {
+    fn allows(&self, sound: Sound) -> bool {
+        self.enabled
+            && match sound {
+                Sound::Success => self.success,
+                Sound::Filtered => self.filtered,
+                Sound::Error => self.error,
+            }
+    }
+}
+
+/// Plays `sound` if it is turned on. Doesn't block.
+pub fn play(app: &AppHandle, sound: Sound) {
+    if settings::current(app).sounds.allows(sound) {
+        std::thread::spawn(move || platform::play(sound));
+    }
+}
+
+/// Plays `sound` regardless of the settings, so it can be previewed.
+#[tauri::command]
+pub async fn preview_sound(sound: Sound) -> Result<(), String> {
+    platform::play(sound);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Sound;
+    use objc::runtime::{Object, BOOL};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    pub fn play(sound: Sound) {
+        let name = match sound {
+            Sound::Success => c"Tink",
+            Sound::Filtered => c"Pop",
+            Sound::Error => c"Basso",
+        };
+        unsafe {
+            let name: *mut Object = msg_send![class!(NSString), stringWithUTF8String: name.as_ptr()];
+            let sound: *mut Object = msg_send![class!(NSSound), soundNamed: name];
+            if !sound.is_null() {
+                let _: BOOL = msg_send![sound, play];
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Sound;
+    use windows::Win32::System::Diagnostics::Debug::MessageBeep;
+    use windows::Win32::UI::WindowsAndMessaging::{MB_ICONASTERISK, MB_ICONEXCLAMATION, MB_ICONHAND};
+
+    pub fn play(sound: Sound) {
+        let style = match sound {
+            Sound::Success => MB_ICONASTERISK,
+            Sound::Filtered => MB_ICONEXCLAMATION,
+            Sound::Error => MB_ICONHAND,
+        };
+        unsafe {
+            let _ = MessageBeep(style);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Sound;
+    use std::process::Command;
+
+    /// Freedesktop sound theme names, played through libcanberra.
+    pub fn play(sound: Sound) {
+        let id = match sound {
+            Sound::Success => "complete",
+            Sound::Filtered => "message",
+            Sound::Error => "dialog-error",
+        };
+        if let Err(e) = Command::new("canberra-gtk-play").args(["-i", id]).status() {
+            eprintln!("Failed to play sound: {}", e);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::Sound;
+
+    pub fn play(_sound: Sound) {}
+}
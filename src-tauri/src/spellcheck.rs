@@ -0,0 +1,153 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use spellbook::Dictionary;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter, Manager, State};
+use text_detection::SelectionEvent;
+
+use crate::{locations, settings};
+
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpellcheckSettings {
+    /// Check every capture and emit `capture-typos-flagged` when it has typos.
+    pub flag_captures: bool,
+    /// e.g. `en_US`. No dictionaries ship with the app, so none is picked
+    /// until one has been installed; see [`find_dictionary`].
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Misspelling {
+    pub word: String,
+    /// Byte offset of the word in the checked text.
+    pub offset: usize,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TyposFlagged<'a> {
+    pub text: &'a str,
+    pub app_name: &'a str,
+    pub words: Vec<String>,
+}
+
+/// Loaded Hunspell dictionaries, keyed by language code.
+#[derive(Default)]
+pub struct SpellChecker {
+    dictionaries: Mutex<HashMap<String, Arc<Dictionary>>>,
+}
+
+impl SpellChecker {
+    fn dictionary(&self, app: &AppHandle, lang: &str) -> Result<Arc<Dictionary>, String> {
+        let lang = lang.replace('-', "_");
+        if let Some(dict) = self.dictionaries.lock().unwrap().get(&lang) {
+            return Ok(Arc::clone(dict));
+        }
+
+        let (aff, dic) = find_dictionary(app, &lang).ok_or_else(|| {
+            format!("No dictionary installed for {}; add {0}.aff and {0}.dic to the app's dictionaries folder", lang)
+        })?;
+        let aff = std::fs::read_to_string(aff).map_err(|e| e.to_string())?;
+        let dic = std::fs::read_to_string(dic).map_err(|e| e.to_string())?;
+        let dict = Arc::new(Dictionary::new(&aff, &dic).map_err(|e| format!("Invalid dictionary {}: {}", lang, e))?);
+
+        self.dictionaries.lock().unwrap().insert(lang, Arc::clone(&dict));
+        Ok(dict)
+    }
+}
+
+/// User-installed dictionaries in `<app data dir>/dictionaries`, then any a
+/// packager bundled as resources. The app itself bundles none.
+fn find_dictionary(app: &AppHandle, lang: &str) -> Option<(PathBuf, PathBuf)> {
+    let dirs = [locations::data_dir(app), app.path().resource_dir()];
+    dirs.into_iter().flatten().find_map(|dir| {
+        let dir = dir.join("dictionaries");
+        let aff = dir.join(format!("{}.aff", lang));
+        let dic = dir.join(format!("{}.dic", lang));
+        (aff.is_file() && dic.is_file()).then_some((aff, dic))
+    })
+}
+
+fn word_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\p{L}+(?:['’]\p{L}+)*").unwrap())
+}
+
+/// Words worth checking: skips acronyms and single letters, which are
+/// mostly abbreviations rather than typos.
+fn candidate_words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    word_pattern()
+        .find_iter(text)
+        .map(|m| (m.start(), m.as_str()))
+        .filter(|(_, word)| word.chars().count() > 1 && !word.chars().all(char::is_uppercase))
+}
+
+pub fn check(dict: &Dictionary, text: &str, with_suggestions: bool) -> Vec<Misspelling> {
+    candidate_words(text)
+        .filter(|(_, word)| !dict.check(word))
+        .map(|(offset, word)| {
+            let mut suggestions = Vec::new();
+            if with_suggestions {
+                dict.suggest(word, &mut suggestions);
+                suggestions.truncate(MAX_SUGGESTIONS);
+            }
+            Misspelling {
+                word: word.to_string(),
+                offset,
+                suggestions,
+            }
+        })
+        .collect()
+}
+
+/// Pipeline hook: flags captures that contain likely typos.
+pub fn flag_typos(app: &AppHandle, event: &SelectionEvent) {
+    let settings = settings::current(app).spellcheck;
+    if !settings.flag_captures {
+        return;
+    }
+    let Some(checker) = app.try_state::<SpellChecker>() else {
+        return;
+    };
+    let Some(language) = settings.language else {
+        return;
+    };
+    let dict = match checker.dictionary(app, &language) {
+        Ok(dict) => dict,
+        Err(e) => {
+            eprintln!("Spellcheck skipped: {}", e);
+            return;
+        }
+    };
+
+    let words: Vec<String> = check(&dict, &event.text, false).into_iter().map(|m| m.word).collect();
+    if !words.is_empty() {
+        let _ = app.emit(
+            "capture-typos-flagged",
+            TyposFlagged {
+                text: &event.text,
+                app_name: &event.app_name,
+                words,
+            },
+        );
+    }
+}
+
+#[tauri::command]
+pub async fn spellcheck_selection(
+    app: AppHandle,
+    checker: State<'_, SpellChecker>,
+    text: String,
+    lang: Option<String>,
+) -> Result<Vec<Misspelling>, String> {
+    let lang = lang
+        .or(settings::current(&app).spellcheck.language)
+        .ok_or("Choose a spellcheck language in settings first")?;
+    let dict = checker.dictionary(&app, &lang)?;
+    Ok(check(&dict, &text, true))
+}
@@ -0,0 +1,198 @@
+//! Publishes whether detection is on and what was captured last, so other
+//! desktop utilities (status bars, widgets) can reflect it without polling:
+//!
+//! - Linux: the `com.acmi_desktop.State1` interface at
+//!   `/com/acmi_desktop/State` on the session bus, with change signals.
+//! - macOS: the `com.acmi-desktop.stateChanged` distributed notification,
+//!   whose object is the state as JSON.
+//!
+//! Off unless turned on in settings, and the capture's text is only
+//! included with `include_preview`. Nothing is published in kiosk mode.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::capture::Capture;
+use crate::{kiosk, settings, text_utils};
+
+const PREVIEW_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateBroadcastSettings {
+    pub enabled: bool,
+    /// Publish the start of each capture's text, not just where it came
+    /// from.
+    pub include_preview: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PublicState {
+    pub detecting: bool,
+    pub last_capture: Option<CaptureSummary>,
+}
+
+/// Just enough of a capture for a status display.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaptureSummary {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    pub app_name: String,
+    pub timestamp: u64,
+}
+
+pub struct StateBroadcast(watch::Sender<PublicState>);
+
+pub fn init(app: &AppHandle) {
+    let (sender, receiver) = watch::channel(PublicState::default());
+    app.manage(StateBroadcast(sender));
+    if settings::current(app).state_broadcast.enabled {
+        tauri::async_runtime::spawn(platform::publish(receiver));
+    }
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut PublicState)) {
+    if let Some(broadcast) = app.try_state::<StateBroadcast>() {
+        broadcast.0.send_if_modified(|state| {
+            let before = state.clone();
+            change(state);
+            *state != before
+        });
+    }
+}
+
+pub fn set_detecting(app: &AppHandle, detecting: bool) {
+    update(app, |state| state.detecting = detecting);
+}
+
+pub fn captured(app: &AppHandle, capture: &Capture) {
+    if kiosk::is_locked(app) {
+        return;
+    }
+    let include_preview = settings::current(app).state_broadcast.include_preview;
+    update(app, |state| {
+        state.last_capture = Some(CaptureSummary {
+            preview: include_preview.then(|| text_utils::preview(&capture.event.text, PREVIEW_CHARS)),
+            app_name: capture.event.app_name.clone(),
+            timestamp: capture.event.timestamp,
+        })
+    });
+}
+
+/// App the last capture came from.
+pub fn last_app(app: &AppHandle) -> Option<String> {
+    let broadcast = app.try_state::<StateBroadcast>()?;
+    let state = broadcast.0.borrow();
+    state.last_capture.as_ref().map(|capture| capture.app_name.clone())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::PublicState;
+    use tokio::sync::watch;
+    use zbus::object_server::SignalEmitter;
+
+    const BUS_NAME: &str = "com.acmi_desktop.State";
+    const OBJECT_PATH: &str = "/com/acmi_desktop/State";
+
+    struct StateInterface {
+        state: PublicState,
+    }
+
+    #[zbus::interface(name = "com.acmi_desktop.State1")]
+    impl StateInterface {
+        #[zbus(property)]
+        fn detecting(&self) -> bool {
+            self.state.detecting
+        }
+
+        /// JSON, or an empty string before the first capture.
+        #[zbus(property)]
+        fn last_capture(&self) -> String {
+            self.state
+                .last_capture
+                .as_ref()
+                .and_then(|capture| serde_json::to_string(capture).ok())
+                .unwrap_or_default()
+        }
+    }
+
+    pub async fn publish(mut receiver: watch::Receiver<PublicState>) {
+        let connection = async {
+            zbus::connection::Builder::session()?
+                .name(BUS_NAME)?
+                .serve_at(OBJECT_PATH, StateInterface { state: PublicState::default() })?
+                .build()
+                .await
+        };
+        let connection = match connection.await {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("Failed to publish state on D-Bus: {}", e);
+                return;
+            }
+        };
+
+        while receiver.changed().await.is_ok() {
+            let state = receiver.borrow_and_update().clone();
+            if let Err(e) = update(&connection, state).await {
+                eprintln!("Failed to update D-Bus state: {}", e);
+            }
+        }
+    }
+
+    async fn update(connection: &zbus::Connection, state: PublicState) -> zbus::Result<()> {
+        let iface = connection.object_server().interface::<_, StateInterface>(OBJECT_PATH).await?;
+        let emitter: &SignalEmitter<'_> = iface.signal_emitter();
+        let mut interface = iface.get_mut().await;
+        let detecting_changed = interface.state.detecting != state.detecting;
+        interface.state = state;
+        if detecting_changed {
+            interface.detecting_changed(emitter).await?;
+        }
+        interface.last_capture_changed(emitter).await
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::PublicState;
+    use objc::runtime::{Object, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CString;
+    use tokio::sync::watch;
+
+    const NOTIFICATION_NAME: &std::ffi::CStr = c"com.acmi-desktop.stateChanged";
+
+    pub async fn publish(mut receiver: watch::Receiver<PublicState>) {
+        while receiver.changed().await.is_ok() {
+            let state = receiver.borrow_and_update().clone();
+            if let Ok(json) = serde_json::to_string(&state) {
+                post(&json);
+            }
+        }
+    }
+
+    fn post(json: &str) {
+        let Ok(json) = CString::new(json) else {
+            return;
+        };
+        unsafe {
+            let center: *mut Object = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+            let name: *mut Object = msg_send![class!(NSString), stringWithUTF8String: NOTIFICATION_NAME.as_ptr()];
+            let object: *mut Object = msg_send![class!(NSString), stringWithUTF8String: json.as_ptr()];
+            let nil: *mut Object = std::ptr::null_mut();
+            let deliver_immediately: BOOL = YES;
+            let _: () = msg_send![center, postNotificationName: name object: object userInfo: nil deliverImmediately: deliver_immediately];
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod platform {
+    use super::PublicState;
+    use tokio::sync::watch;
+
+    pub async fn publish(_receiver: watch::Receiver<PublicState>) {}
+}
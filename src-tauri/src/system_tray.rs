@@ -1,36 +1,206 @@
-use tauri::{AppHandle, Manager, menu::{Menu, MenuItem, PredefinedMenuItem}, tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState}};
+use tauri::{AppHandle, Emitter, Manager, Wry, menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu}, tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState}};
 use std::sync::Mutex;
-use crate::TextDetector;
+use tauri::image::Image;
+use crate::history::HistoryStore;
+use crate::settings::SettingsStore;
+use crate::{app_mute, hotkey, kiosk, main_window, pause, profiles, settings};
+use crate::text_detector::TextDetector;
+use crate::translation;
+use text_detection::Detector;
+
+const TRAY_ID: &str = "main";
+const TRANSLATION_PAIR_PREFIX: &str = "translation_pair:";
+const PROFILE_PREFIX: &str = "profile:";
+const APP_PREFIX: &str = "app:";
+/// How many recent source apps the "Monitored Apps" menu offers.
+const RECENT_APPS: u32 = 10;
+
+/// Apps currently listed in the "Monitored Apps" menu.
+static LISTED_APPS: Mutex<Vec<String>> = Mutex::new(Vec::new());
 
 pub fn create_system_tray(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app)?;
+    let paused = pause::is_paused(app);
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(tooltip(app, paused));
+    if let Some(icon) = icon(app, paused) {
+        builder = builder.icon(icon);
+    }
+    let _tray = builder
+        .on_menu_event(move |tray, event| {
+            handle_menu_event(tray.app_handle(), event);
+        })
+        .on_tray_icon_event(|tray, event| {
+            handle_tray_click_event(tray.app_handle(), event);
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+fn tooltip(app: &AppHandle, paused: bool) -> String {
+    if paused {
+        return "ACMI Desktop - Paused".to_string();
+    }
+    match hotkey::display::describe(&hotkey::capture_shortcut(app)) {
+        Ok(shortcut) => format!("ACMI Desktop - Text Selection Monitor ({} to capture)", shortcut),
+        Err(_) => "ACMI Desktop - Text Selection Monitor".to_string(),
+    }
+}
+
+/// The app icon, or a faded copy of it while everything is paused.
+fn icon(app: &AppHandle, paused: bool) -> Option<Image<'static>> {
+    let icon = app.default_window_icon()?;
+    if !paused {
+        return Some(icon.clone().to_owned());
+    }
+    let mut rgba = icon.rgba().to_vec();
+    for pixel in rgba.chunks_exact_mut(4) {
+        let gray = (pixel[0] as u32 * 30 + pixel[1] as u32 * 59 + pixel[2] as u32 * 11) / 100;
+        pixel[..3].fill(gray as u8);
+        pixel[3] /= 3;
+    }
+    Some(Image::new_owned(rgba, icon.width(), icon.height()))
+}
+
+/// Updates the tray icon, tooltip and menu for the master switch.
+pub fn show_paused(app: &AppHandle, paused: bool) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Some(icon) = icon(app, paused) {
+            let _ = tray.set_icon(Some(icon));
+        }
+        let _ = tray.set_tooltip(Some(tooltip(app, paused)));
+    }
+    refresh_menu(app);
+}
+
+/// Rebuilds the tray menu so it reflects the current settings.
+pub fn refresh_menu(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        match build_menu(app) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!("Failed to rebuild tray menu: {}", e),
+        }
+    }
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
     let show_item = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let paused = pause::is_paused(app);
+    let pause_item = CheckMenuItem::with_id(app, "pause_all", "Pause Everything", true, paused, None::<&str>)?;
     let start_detection_item = MenuItem::with_id(app, "start_detection", "Start Detection", true, None::<&str>)?;
     let stop_detection_item = MenuItem::with_id(app, "stop_detection", "Stop Detection", true, None::<&str>)?;
+    let translation_menu = build_translation_menu(app)?;
+    let profile_menu = build_profile_menu(app)?;
+    let apps_menu = build_apps_menu(app)?;
+    let mute_item = MenuItem::with_id(app, "mute_app", "Mute Last App for 1 Hour", true, None::<&str>)?;
     let permissions_item = MenuItem::with_id(app, "permissions", "Check Permissions", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     
-    let menu = Menu::with_items(app, &[
+    Menu::with_items(app, &[
         &show_item,
+        &pause_item,
         &PredefinedMenuItem::separator(app)?,
         &start_detection_item,
         &stop_detection_item,
+        &apps_menu,
+        &mute_item,
+        &PredefinedMenuItem::separator(app)?,
+        &translation_menu,
+        &profile_menu,
         &PredefinedMenuItem::separator(app)?,
         &permissions_item,
         &PredefinedMenuItem::separator(app)?,
         &quit_item,
-    ])?;
+    ])
+}
 
-    let _tray = TrayIconBuilder::new()
-        .menu(&menu)
-        .tooltip("ACMI Desktop - Text Selection Monitor")
-        .on_menu_event(move |tray, event| {
-            handle_menu_event(tray.app_handle(), event);
+fn build_translation_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let translation = settings::current(app).translation;
+    let active = translation.active().cloned();
+
+    let pair_items = translation
+        .pairs
+        .iter()
+        .enumerate()
+        .map(|(i, pair)| {
+            let id = format!("{}{}", TRANSLATION_PAIR_PREFIX, i);
+            CheckMenuItem::with_id(app, id, pair.to_string(), true, Some(pair) == active.as_ref(), None::<&str>)
         })
-        .on_tray_icon_event(|tray, event| {
-            handle_tray_click_event(tray.app_handle(), event);
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let pair_refs: Vec<&dyn IsMenuItem<Wry>> = pair_items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Translate To", !pair_refs.is_empty(), &pair_refs)
+}
+
+fn build_profile_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let profile_items = profiles::list(app)
+        .into_iter()
+        .map(|profile| {
+            let id = format!("{}{}", PROFILE_PREFIX, profile.name);
+            CheckMenuItem::with_id(app, id, &profile.name, true, profile.active, None::<&str>)
         })
-        .build(app)?;
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let profile_refs: Vec<&dyn IsMenuItem<Wry>> = profile_items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+
+    Submenu::with_items(app, "Profile", true, &profile_refs)
+}
+
+/// Recent source apps, checked while they are monitored. Excluded apps stay
+/// listed even once they're no longer recent, so they can be let back in.
+fn build_apps_menu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let excluded = settings::current(app).detection.excluded_apps;
+    let mut apps = app
+        .try_state::<HistoryStore>()
+        .and_then(|history| history.recent_apps(RECENT_APPS).ok())
+        .unwrap_or_default();
+    for name in &excluded {
+        if !apps.contains(name) {
+            apps.push(name.clone());
+        }
+    }
+
+    let app_items = apps
+        .iter()
+        .map(|name| {
+            let id = format!("{}{}", APP_PREFIX, name);
+            CheckMenuItem::with_id(app, id, name, true, !excluded.contains(name), None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let app_refs: Vec<&dyn IsMenuItem<Wry>> = app_items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+    *LISTED_APPS.lock().unwrap() = apps;
+
+    Submenu::with_items(app, "Monitored Apps", !app_refs.is_empty(), &app_refs)
+}
+
+/// Called for every stored capture, so an app seen for the first time shows
+/// up in "Monitored Apps".
+pub fn note_app(app: &AppHandle, name: &str) {
+    let listed = LISTED_APPS.lock().unwrap().iter().any(|listed| listed == name);
+    if !listed {
+        refresh_menu(app);
+    }
+}
 
+/// Starts or stops ignoring selections from `name`.
+fn toggle_app(app: &AppHandle, name: &str) -> Result<(), String> {
+    if kiosk::is_locked(app) {
+        return Err("Monitored apps can't be changed in kiosk mode".to_string());
+    }
+    let updated = app.state::<SettingsStore>().update(|s| {
+        let excluded = &mut s.detection.excluded_apps;
+        match excluded.iter().position(|excluded| excluded == name) {
+            Some(index) => {
+                excluded.remove(index);
+            }
+            None => excluded.push(name.to_string()),
+        }
+    })?;
+    let _ = app.emit("settings-changed", &updated.redacted());
     Ok(())
 }
 
@@ -43,9 +213,8 @@ pub fn handle_tray_click_event(app: &AppHandle, event: TrayIconEvent) {
         } => {
             println!("System tray received a left click");
             // Show the main window on left click
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
+            if let Err(e) = main_window::show(app) {
+                eprintln!("Failed to show main window: {}", e);
             }
         }
         _ => {}
@@ -58,12 +227,20 @@ pub fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
             std::process::exit(0);
         }
         "show" => {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
+            if let Err(e) = main_window::show(app) {
+                eprintln!("Failed to show main window: {}", e);
+            }
+        }
+        "pause_all" => {
+            if let Err(e) = pause::toggle(app) {
+                eprintln!("Failed to toggle pause: {}", e);
             }
         }
         "start_detection" => {
+            if pause::is_paused(app) {
+                eprintln!("Not starting text detection: everything is paused");
+                return;
+            }
             // Start text detection
             let detector_state = app.state::<Mutex<Option<TextDetector>>>();
             let mut detector = detector_state.lock().unwrap();
@@ -92,23 +269,44 @@ pub fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
         }
         "permissions" => {
             // Check permissions
-            #[cfg(target_os = "macos")]
-            {
-                use crate::macos;
-                let has_permissions = macos::check_accessibility_permissions();
-                let message = if has_permissions {
-                    "✅ Accessibility permissions are granted!"
-                } else {
-                    "❌ Accessibility permissions are required. Please grant them in System Settings."
-                };
-                
-                println!("{}", message);
-                
-                if !has_permissions {
-                    let _ = macos::request_accessibility_permissions();
+            let detector = Detector::new();
+            let has_permissions = detector.has_permissions();
+            let message = if has_permissions {
+                "✅ Accessibility permissions are granted!"
+            } else {
+                "❌ Accessibility permissions are required. Please grant them in System Settings."
+            };
+
+            println!("{}", message);
+
+            if !has_permissions {
+                let _ = detector.request_permissions();
+            }
+        }
+        id if id.starts_with(TRANSLATION_PAIR_PREFIX) => {
+            if let Ok(index) = id[TRANSLATION_PAIR_PREFIX.len()..].parse::<usize>() {
+                if let Err(e) = translation::set_active_pair(app, index) {
+                    eprintln!("Failed to switch translation pair: {}", e);
                 }
             }
         }
+        "mute_app" => {
+            if let Err(e) = app_mute::mute(app, None, app_mute::DEFAULT_MUTE) {
+                eprintln!("Failed to mute app: {}", e);
+            }
+        }
+        id if id.starts_with(APP_PREFIX) => {
+            if let Err(e) = toggle_app(app, &id[APP_PREFIX.len()..]) {
+                eprintln!("Failed to change monitored apps: {}", e);
+            }
+            // Put the check mark back in line with the settings either way
+            refresh_menu(app);
+        }
+        id if id.starts_with(PROFILE_PREFIX) => {
+            if let Err(e) = profiles::switch(app, &id[PROFILE_PREFIX.len()..]) {
+                eprintln!("Failed to switch profile: {}", e);
+            }
+        }
         _ => {}
     }
 }
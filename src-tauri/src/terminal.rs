@@ -0,0 +1,132 @@
+//! Capture adapter for terminal emulators. Their focused value is the whole
+//! scrollback, so the usual focused-text fallback would capture megabytes;
+//! here a real selection is preferred and the fallback is limited to what
+//! is on screen.
+
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use text_detection::{Detector, DetectorError, SelectionEvent, SelectionType};
+
+use crate::definitions::Definitions;
+use crate::text_detector::DetectionSettings;
+
+/// iTerm2 and kitty are asked through a new process, too slow to start on
+/// every poll; in between, the last answer is reused.
+const QUERY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The last selection read from a terminal's own API, and when.
+static LAST_QUERY: Mutex<Option<(Terminal, Instant, Option<String>)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminal {
+    /// Terminal.app
+    Apple,
+    ITerm2,
+    Kitty,
+}
+
+impl Terminal {
+    fn from_bundle_id(bundle_id: &str) -> Option<Self> {
+        match bundle_id {
+            "com.apple.Terminal" => Some(Self::Apple),
+            "com.googlecode.iterm2" => Some(Self::ITerm2),
+            "net.kovidgoyal.kitty" => Some(Self::Kitty),
+            _ => None,
+        }
+    }
+}
+
+/// The terminal that currently has focus, if it is one we handle, going by
+/// `definitions` before the built-in list.
+pub fn frontmost(detector: &Detector, definitions: &Definitions) -> Option<Terminal> {
+    let pid = detector.frontmost_app_pid()?;
+    let bundle_id = detector.bundle_id(pid)?;
+    match definitions.adapter(&bundle_id) {
+        Some(adapter) => adapter.terminal(),
+        None => Terminal::from_bundle_id(&bundle_id),
+    }
+}
+
+/// [`Detector::selection`] for a terminal.
+pub fn selection(
+    detector: &Detector,
+    terminal: Terminal,
+    settings: &DetectionSettings,
+) -> Result<Option<SelectionEvent>, DetectorError> {
+    if let Some(text) = selected_text(detector, terminal, settings.kitty_socket.as_deref())? {
+        return Ok(Some(SelectionEvent::new(text, "Unknown", SelectionType::Selected)));
+    }
+    if detector.is_selected_only() {
+        return Ok(None);
+    }
+
+    Ok(detector
+        .visible_text()?
+        .map(|text| last_lines(&text, settings.terminal_fallback_lines))
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| SelectionEvent::new(text, "Unknown", SelectionType::Focused)))
+}
+
+fn selected_text(
+    detector: &Detector,
+    terminal: Terminal,
+    kitty_socket: Option<&str>,
+) -> Result<Option<String>, DetectorError> {
+    match terminal {
+        Terminal::ITerm2 => match throttled(terminal, iterm2_selection) {
+            Some(text) => Ok(Some(text)),
+            None => detector.selected_text(),
+        },
+        // kitty has no accessibility tree, only its remote control protocol
+        Terminal::Kitty => Ok(kitty_socket.and_then(|socket| throttled(terminal, || kitty_selection(socket)))),
+        Terminal::Apple => detector.selected_text(),
+    }
+}
+
+/// `query`'s answer, or the last one if `terminal` was asked less than
+/// [`QUERY_INTERVAL`] ago.
+fn throttled(terminal: Terminal, query: impl FnOnce() -> Option<String>) -> Option<String> {
+    let mut last = LAST_QUERY.lock().unwrap();
+    if let Some((_, _, text)) = last
+        .as_ref()
+        .filter(|(asked, at, _)| *asked == terminal && at.elapsed() < QUERY_INTERVAL)
+    {
+        return text.clone();
+    }
+    let text = query();
+    *last = Some((terminal, Instant::now(), text.clone()));
+    text
+}
+
+/// The selection in iTerm2's current session, read from its `selection`
+/// session variable through the AppleScript API.
+fn iterm2_selection() -> Option<String> {
+    let script = r#"tell application "iTerm2" to tell current session of current window to get variable named "selection""#;
+    let output = Command::new("osascript").args(["-e", script]).output().ok()?;
+    non_empty_stdout(output)
+}
+
+/// Needs `allow_remote_control` and `listen_on` in kitty.conf, with the
+/// same socket as `socket`: run from outside kitty, `kitty @` has no other
+/// way to reach it.
+fn kitty_selection(socket: &str) -> Option<String> {
+    let output = Command::new("kitty")
+        .args(["@", "--to", socket, "get-text", "--extent", "selection"])
+        .output()
+        .ok()?;
+    non_empty_stdout(output)
+}
+
+fn non_empty_stdout(output: std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn last_lines(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(max_lines)..].join("\n")
+}
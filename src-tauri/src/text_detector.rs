@@ -1,80 +1,320 @@
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use text_detection::{Detector, DetectorError, SelectionEvent};
+
+use crate::backends::{self, Backend, ClipboardSource, Failover};
+use crate::settings::Settings;
+use crate::coalesce::Coalescer;
+use crate::history::{sources, thumbnails};
+use crate::hotkey::taps::CaptureTrigger;
+use crate::{
+    app_mute, capture, debug, definitions, ide, image_selection, kiosk, noise, pause, settings, state_broadcast, terminal,
+    trace,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Poll rate while streaming a selection that is still being made, or while
+/// boosted.
+const FAST_INTERVAL: Duration = Duration::from_millis(100);
+/// Longest a single [`boost_detection`] can keep polling fast.
+const MAX_BOOST: Duration = Duration::from_secs(60);
+
+/// Ticks to go without a full fetch while focus looks unchanged.
+const FULL_FETCH_EVERY: u32 = 20;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SelectionEvent {
-    pub text: String,
-    pub app_name: String,
-    pub timestamp: u64,
-    pub selection_type: SelectionType,
+#[serde(default)]
+pub struct DetectionSettings {
+    /// How long to wait for an application to answer an accessibility query.
+    pub timeout_ms: u64,
+    /// Per-app overrides of `timeout_ms`, keyed by bundle identifier.
+    pub app_timeouts_ms: HashMap<String, u64>,
+    /// Captures over this many bytes are announced with `selection-large`
+    /// and fetched on demand instead of being sent in the event.
+    pub large_capture_bytes: usize,
+    /// When nothing is selected in a terminal, capture at most this many of
+    /// the last visible lines.
+    pub terminal_fallback_lines: usize,
+    /// kitty's remote control socket, as in its `listen_on` option, e.g.
+    /// `unix:/tmp/kitty`. Without one, selections in kitty can't be read.
+    pub kitty_socket: Option<String>,
+    /// Strict mode: only emit genuine selections, never the focused-text
+    /// fallback.
+    pub selected_only: bool,
+    /// Changes from the same app closer together than this are merged into
+    /// one capture, emitted once the selection settles. 0 turns it off.
+    pub coalesce_ms: u64,
+    /// Live preview: while a selection is still changing, send it as
+    /// `selection-updated` about ten times a second, then a
+    /// `selection-committed` capture once it settles.
+    pub stream_selection: bool,
+    /// Apps, by name as shown in history, whose selections are ignored.
+    /// Toggled from the tray's "Monitored Apps" menu.
+    pub excluded_apps: Vec<String>,
+    /// Capture backends to use, most preferred first.
+    pub backends: Vec<Backend>,
+    /// Consecutive errors from a backend before moving to the next one.
+    pub failover_after: u32,
+    /// The global shortcut that captures the selection. Picked on first
+    /// run, avoiding shortcuts known to clash; see `hotkey::defaults`.
+    pub capture_shortcut: Option<String>,
+    /// Whether captures are triggered by that shortcut or by tapping a
+    /// modifier.
+    pub capture_trigger: CaptureTrigger,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum SelectionType {
-    Selected,
-    Hovered,
-    Focused,
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 1000,
+            app_timeouts_ms: HashMap::new(),
+            large_capture_bytes: 512 * 1024,
+            terminal_fallback_lines: 40,
+            kitty_socket: None,
+            selected_only: false,
+            coalesce_ms: 750,
+            stream_selection: false,
+            excluded_apps: Vec::new(),
+            backends: backends::default_backends(),
+            failover_after: 10,
+            capture_shortcut: None,
+            capture_trigger: CaptureTrigger::default(),
+        }
+    }
+}
+
+/// What `selection-updated` carries: the selection so far, unprocessed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionUpdate<'a> {
+    #[serde(flatten)]
+    pub event: &'a SelectionEvent,
+    pub revisions: u32,
+}
+
+fn poll_interval(period: Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(period);
+    // Never fire a burst of missed ticks, e.g. after system sleep
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    interval
+}
+
+/// 64-bit hash of `text`, to tell whether the selection changed without
+/// keeping it. With only 32 bits, a colliding new selection would be
+/// silently dropped.
+fn fingerprint(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A detector configured from the current settings.
+pub fn detector(app: &AppHandle) -> Detector {
+    let Settings { detection: settings, noise, .. } = settings::current(app);
+    let noise = noise::with_definitions(noise, &definitions::current(app));
+    let detector = Detector::new()
+        .with_timeout(Duration::from_millis(settings.timeout_ms))
+        .with_selected_only(settings.selected_only)
+        .with_ignored_roles(if noise.enabled { noise.ignored_roles } else { Vec::new() });
+    settings
+        .app_timeouts_ms
+        .into_iter()
+        .fold(detector, |detector, (app, ms)| detector.with_app_timeout(app, Duration::from_millis(ms)))
 }
 
 pub struct TextDetector {
     app_handle: AppHandle,
+    detector: Detector,
     is_running: Arc<Mutex<bool>>,
-    last_selection: Arc<Mutex<Option<String>>>,
+    /// [`fingerprint`] of the last selection emitted; huge selections aren't
+    /// kept around just to compare against the next tick.
+    last_selection: Arc<Mutex<Option<u64>>>,
+    /// Poll at the fast rate until then.
+    boost_until: Arc<Mutex<Option<Instant>>>,
 }
 
 impl TextDetector {
     pub fn new(app_handle: AppHandle) -> Self {
         Self {
+            detector: detector(&app_handle),
             app_handle,
             is_running: Arc::new(Mutex::new(false)),
             last_selection: Arc::new(Mutex::new(None)),
+            boost_until: Arc::new(Mutex::new(None)),
         }
     }
 
     pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Check for accessibility permissions first
+        if !self.detector.has_permissions() {
+            return Err(DetectorError::PermissionDenied.into());
+        }
+
         let mut is_running = self.is_running.lock().unwrap();
         if *is_running {
             return Ok(());
         }
         *is_running = true;
         drop(is_running);
+        state_broadcast::set_detecting(&self.app_handle, true);
 
         let app_handle = self.app_handle.clone();
+        let detector = self.detector.clone();
         let is_running_clone = Arc::clone(&self.is_running);
         let last_selection_clone = Arc::clone(&self.last_selection);
-
-        // Check for accessibility permissions first
-        #[cfg(target_os = "macos")]
-        if !self.check_accessibility_permissions() {
-            return Err("Accessibility permissions not granted".into());
-        }
+        let boost_until = Arc::clone(&self.boost_until);
 
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_millis(500));
-            
+            let mut period = POLL_INTERVAL;
+            let mut interval = poll_interval(period);
+
+            let mut last_focus = None;
+            let mut unchanged_ticks = 0;
+            let mut elevated_app = None;
+            let detection = settings::current(&app_handle).detection;
+            let stream = detection.stream_selection;
+            let mut coalescer = Coalescer::new(Duration::from_millis(detection.coalesce_ms));
+            let coalesce_off = detection.coalesce_ms == 0;
+            let mut failover = Failover::new(detection.backends, detection.failover_after);
+            let mut clipboard = ClipboardSource::default();
+            // What read the selection the coalescer is holding on to
+            let mut read_with = "accessibility";
+
             loop {
+                // Speed up while boosted or a streamed selection is still
+                // being made
+                let boosted = boost_until.lock().unwrap().is_some_and(|until| Instant::now() < until);
+                let streaming = stream && coalescer.pending().is_some();
+                let wanted = if boosted || streaming { FAST_INTERVAL } else { POLL_INTERVAL };
+                if wanted != period {
+                    period = wanted;
+                    interval = poll_interval(period);
+                }
                 interval.tick().await;
-                
+
                 let is_running = {
                     let guard = is_running_clone.lock().unwrap();
                     *guard
                 };
-                
+
                 if !is_running {
                     break;
                 }
 
-                if let Some(selection) = Self::get_current_selection().await {
-                    let mut last = last_selection_clone.lock().unwrap();
-                    
-                    // Only emit if the selection has changed
-                    if last.as_ref() != Some(&selection.text) {
-                        *last = Some(selection.text.clone());
-                        let _ = app_handle.emit("text-selection-changed", &selection);
+                if let Some((selection, revisions)) = coalescer.flush_due() {
+                    emit_selection(&app_handle, selection, revisions, stream, read_with);
+                }
+
+                if failover.active() == Backend::Clipboard {
+                    if failover.should_retry_preferred() && detector.selection().is_ok() {
+                        backends::announce(&app_handle, &failover.recovered());
+                    } else {
+                        if let Some(selection) = clipboard.poll(&app_handle) {
+                            emit_selection(&app_handle, selection, 1, false, "clipboard");
+                        }
+                        continue;
+                    }
+                }
+
+                // Same app, element and selection range as last tick: skip
+                // the full fetch, but still refresh every so often in case
+                // the element's value changed underneath us
+                let focus = detector.focus_fingerprint();
+                if focus.is_some() && focus == last_focus && unchanged_ticks < FULL_FETCH_EVERY {
+                    unchanged_ticks += 1;
+                    continue;
+                }
+                last_focus = focus;
+                unchanged_ticks = 0;
+
+                let settings = settings::current(&app_handle);
+                let definitions = definitions::current(&app_handle);
+                let noise = noise::with_definitions(settings.noise.clone(), &definitions);
+                if settings.images.enabled && !kiosk::is_locked(&app_handle) {
+                    if let Ok(Some(region)) = detector.selected_image() {
+                        let hash = fingerprint(&format!("{:?}", region));
+                        let changed = last_selection_clone.lock().unwrap().replace(hash) != Some(hash);
+                        if changed {
+                            // screencapture and sips take a while; keep polling meanwhile
+                            let app_handle = app_handle.clone();
+                            tauri::async_runtime::spawn_blocking(move || {
+                                match image_selection::capture(&app_handle, region) {
+                                    Ok((event, image)) => {
+                                        if let Some(capture) = capture::process_image(&app_handle, event, image) {
+                                            trace::source(&app_handle, capture.id, "image");
+                                            capture::emit(&app_handle, "text-selection-changed", &capture);
+                                        }
+                                    }
+                                    Err(e) => eprintln!("Failed to capture selected image: {}", e),
+                                }
+                            });
+                        }
+                        continue;
                     }
                 }
+
+                let bridged = match ide::frontmost(&detector, &definitions).filter(|_| settings.ide.enabled) {
+                    Some(ide) => ide::selection(&settings.ide, ide).await,
+                    None => None,
+                };
+                let (selection, source) = match (bridged, terminal::frontmost(&detector, &definitions)) {
+                    (Some(selection), _) => (Ok(Some(selection)), "ide"),
+                    (None, Some(terminal)) => {
+                        (terminal::selection(&detector, terminal, &settings.detection), "terminal")
+                    }
+                    (None, None) => (detector.selection(), "accessibility"),
+                };
+
+                if !matches!(selection, Err(DetectorError::ElevationRequired)) {
+                    elevated_app = None;
+                }
+                if selection.is_ok() {
+                    failover.succeeded();
+                }
+                match selection {
+                    Ok(Some(selection)) if noise::is_chrome(&noise, &selection) => {}
+                    Ok(Some(selection)) if app_mute::is_ignored(&app_handle, &settings.detection, &selection.app_name) => {}
+                    Ok(Some(selection)) => {
+                        let mut last = last_selection_clone.lock().unwrap();
+
+                        // Only emit if the selection has changed
+                        let hash = fingerprint(&selection.text);
+                        if *last != Some(hash) {
+                            *last = Some(hash);
+                            let pid = detector.frontmost_app_pid();
+                            let held_with = std::mem::replace(&mut read_with, source);
+                            if let Some((selection, revisions)) = coalescer.push(selection, pid) {
+                                // This one when coalescing is off, else the one held back before it
+                                let source = if coalesce_off { source } else { held_with };
+                                emit_selection(&app_handle, selection, revisions, stream, source);
+                            }
+                            if let Some((event, revisions)) = coalescer.pending().filter(|_| stream) {
+                                let _ = app_handle.emit("selection-updated", SelectionUpdate { event, revisions });
+                            }
+                        }
+                    }
+                    Err(DetectorError::Timeout) => {
+                        let _ = app_handle.emit("detection-timed-out", detector.frontmost_app_pid());
+                    }
+                    // Once per app, not every tick while it stays in front
+                    Err(DetectorError::ElevationRequired) => {
+                        let pid = detector.frontmost_app_pid();
+                        if pid != elevated_app {
+                            elevated_app = pid;
+                            let _ = app_handle.emit("elevation-required", pid);
+                        }
+                    }
+                    // Nothing app-specific: the backend itself is failing
+                    Err(e) => {
+                        if let Some(switch) = failover.failed(e.to_string()) {
+                            backends::announce(&app_handle, &switch);
+                        }
+                    }
+                    Ok(None) => {}
+                }
             }
         });
 
@@ -84,145 +324,82 @@ impl TextDetector {
     pub fn stop(&self) {
         let mut is_running = self.is_running.lock().unwrap();
         *is_running = false;
+        state_broadcast::set_detecting(&self.app_handle, false);
     }
 
-    async fn get_current_selection() -> Option<SelectionEvent> {
-        #[cfg(target_os = "macos")]
-        return macos::get_selection().await;
-        
-        #[cfg(not(target_os = "macos"))]
-        None
-    }
-
-    #[cfg(target_os = "macos")]
-    fn check_accessibility_permissions(&self) -> bool {
-        macos::check_accessibility_permissions()
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    fn check_accessibility_permissions(&self) -> bool {
-        true // Assume permissions are OK on other platforms
+    /// Polls at the fast rate for `duration`, then drops back.
+    pub fn boost(&self, duration: Duration) {
+        *self.boost_until.lock().unwrap() = Some(Instant::now() + duration.min(MAX_BOOST));
     }
 
     pub fn request_permissions(&self) -> Result<(), String> {
-        #[cfg(target_os = "macos")]
-        return macos::request_accessibility_permissions();
-        
-        #[cfg(not(target_os = "macos"))]
-        Ok(())
+        self.detector.request_permissions().map_err(|e| e.to_string())
     }
 }
 
-// Platform-specific implementations
-#[cfg(target_os = "macos")]
-pub mod macos {
-    use super::*;
-    use accessibility_sys::*;
-    use core_foundation::string::{CFStringRef, CFString};
-    use core_foundation::base::{CFTypeRef, TCFType};
-    
-    pub async fn get_selection() -> Option<SelectionEvent> {
-        unsafe {
-            let system_wide = AXUIElementCreateSystemWide();
-            let mut focused: AXUIElementRef = std::ptr::null_mut();
-            
-            // Create CFString for the attribute
-            let focused_attr = CFString::new(kAXFocusedUIElementAttribute);
-            let result = AXUIElementCopyAttributeValue(
-                system_wide,
-                focused_attr.as_concrete_TypeRef(),
-                &mut focused as *mut _ as *mut CFTypeRef,
-            );
-
-            if result != kAXErrorSuccess || focused.is_null() {
-                return None;
-            }
-
-            // Try to get selected text first
-            if let Some(text) = get_selected_text(focused) {
-                return Some(SelectionEvent {
-                    text,
-                    app_name: "Unknown".to_string(),
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    selection_type: SelectionType::Selected,
-                });
-            }
-
-            // If no selected text, try to get focused text or value
-            if let Some(text) = get_focused_text(focused) {
-                return Some(SelectionEvent {
-                    text,
-                    app_name: "Unknown".to_string(),
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    selection_type: SelectionType::Focused,
-                });
-            }
-
-            None
-        }
+/// Processes and emits a settled selection. When streaming, it is also
+/// sent as `selection-committed` to close the preceding updates.
+/// `source` is what read the selection, for its trace.
+fn emit_selection(app: &AppHandle, selection: SelectionEvent, revisions: u32, stream: bool, source: &str) {
+    let Some(capture) = capture::process(app, selection) else {
+        return;
+    };
+    trace::source(app, capture.id, source);
+    let capture = capture::Capture {
+        revisions,
+        element: debug::element_dump(app),
+        ..capture
+    };
+    sources::remember(app, &capture);
+    thumbnails::grab_in_background(app, &capture);
+    capture::emit(app, "text-selection-changed", &capture);
+    if stream {
+        capture::emit(app, "selection-committed", &capture);
     }
+}
 
-    unsafe fn get_selected_text(element: AXUIElementRef) -> Option<String> {
-        let mut selected_text_ref: CFTypeRef = std::ptr::null_mut();
-        let selected_attr = CFString::new(kAXSelectedTextAttribute);
-        let result = AXUIElementCopyAttributeValue(
-            element,
-            selected_attr.as_concrete_TypeRef(),
-            &mut selected_text_ref,
-        );
-
-        if result == kAXErrorSuccess && !selected_text_ref.is_null() {
-            let cf_string: CFString = TCFType::wrap_under_create_rule(selected_text_ref as CFStringRef);
-            let text = cf_string.to_string();
-            if !text.trim().is_empty() {
-                return Some(text);
-            }
+/// Stops the running detector, if any. Returns whether one was running, so
+/// callers pausing temporarily know whether to [`resume`] later.
+pub fn pause(app: &AppHandle) -> bool {
+    let detector_state = app.state::<Mutex<Option<TextDetector>>>();
+    let mut detector = detector_state.lock().unwrap();
+    match detector.take() {
+        Some(text_detector) => {
+            text_detector.stop();
+            true
         }
-        None
+        None => false,
     }
+}
 
-    unsafe fn get_focused_text(element: AXUIElementRef) -> Option<String> {
-        // Try different attributes that might contain text
-        let attributes = [
-            kAXValueAttribute,
-            kAXTitleAttribute,
-            kAXDescriptionAttribute,
-            kAXHelpAttribute,
-        ];
-
-        for attr_name in &attributes {
-            let mut text_ref: CFTypeRef = std::ptr::null_mut();
-            let attr = CFString::new(attr_name);
-            let result = AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut text_ref);
-
-            if result == kAXErrorSuccess && !text_ref.is_null() {
-                let cf_string: CFString = TCFType::wrap_under_create_rule(text_ref as CFStringRef);
-                let text = cf_string.to_string();
-                if !text.trim().is_empty() && text.len() > 2 {
-                    return Some(text);
-                }
-            }
-        }
-        None
+/// Starts a detector again after [`pause`]. Refused while everything is
+/// paused; see [`crate::pause`].
+pub fn resume(app: &AppHandle) -> Result<(), String> {
+    if pause::is_paused(app) {
+        return Err("Everything is paused".to_string());
     }
-
-    pub fn check_accessibility_permissions() -> bool {
-        unsafe {
-            AXIsProcessTrusted()
-        }
+    let detector_state = app.state::<Mutex<Option<TextDetector>>>();
+    let mut detector = detector_state.lock().unwrap();
+    if detector.is_none() {
+        let text_detector = TextDetector::new(app.clone());
+        text_detector.start().map_err(|e| e.to_string())?;
+        *detector = Some(text_detector);
     }
+    Ok(())
+}
 
-    pub fn request_accessibility_permissions() -> Result<(), String> {
-        unsafe {
-            // Use a null pointer instead of creating a dictionary to avoid type conflicts
-            AXIsProcessTrustedWithOptions(std::ptr::null());
+/// Polls fast for the next `seconds` (at most a minute), e.g. while the
+/// popup is open and captures should show up right away.
+#[tauri::command]
+pub async fn boost_detection(
+    detector_state: tauri::State<'_, Mutex<Option<TextDetector>>>,
+    seconds: u64,
+) -> Result<(), String> {
+    match detector_state.lock().unwrap().as_ref() {
+        Some(text_detector) => {
+            text_detector.boost(Duration::from_secs(seconds));
+            Ok(())
         }
-        Ok(())
+        None => Err("Text detection is not running".to_string()),
     }
 }
@@ -0,0 +1,138 @@
+//! Measuring and cutting text without assuming it's English. Words follow
+//! Unicode word boundaries (UAX #29), so Chinese and Japanese, written
+//! without spaces, count each ideograph and kana as a word, as word
+//! counters for those languages do. Cuts follow grapheme clusters, and a
+//! cut inside a right-to-left embedding closes it.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Han ideographs, kana and Hangul: scripts where a single character can be
+/// a whole word.
+pub fn is_ideographic(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3134F}')
+}
+
+/// The words in `text`, leaving out punctuation and whitespace.
+pub fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.unicode_words()
+}
+
+/// `text` with a space at every word boundary, so a tokenizer that only
+/// splits on spaces and punctuation sees CJK words too.
+pub fn segment(text: &str) -> String {
+    text.split_word_bounds().collect::<Vec<_>>().join(" ")
+}
+
+/// The longest start of `text` with at most `max` user-perceived
+/// characters, so a cut never splits an emoji, a flag or a letter from its
+/// accent.
+pub fn truncate(text: &str, max: usize) -> &str {
+    match text.grapheme_indices(true).nth(max) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// Appends the terminators for embeddings, overrides and isolates still
+/// open at the end of `text`, so what follows isn't laid out as part of
+/// them.
+fn close_bidi(text: &mut String) {
+    let mut open = Vec::new();
+    for c in text.chars() {
+        match c {
+            '\u{202A}' | '\u{202B}' | '\u{202D}' | '\u{202E}' => open.push('\u{202C}'),
+            '\u{2066}' | '\u{2067}' | '\u{2068}' => open.push('\u{2069}'),
+            // Closing an isolate also closes the embeddings inside it
+            '\u{2069}' => {
+                if let Some(isolate) = open.iter().rposition(|&closer| closer == '\u{2069}') {
+                    open.truncate(isolate);
+                }
+            }
+            '\u{202C}' if open.last() == Some(&'\u{202C}') => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    text.extend(open.into_iter().rev());
+}
+
+/// Shortens `text` to at most `max_chars` user-perceived characters for
+/// list and widget previews, collapsing whitespace runs and appending an
+/// ellipsis when cut. Terminators added to close a cut bidi embedding are
+/// invisible and not counted.
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.graphemes(true).nth(max_chars).is_none() {
+        return collapsed;
+    }
+    let mut cut = truncate(&collapsed, max_chars.saturating_sub(1)).to_string();
+    close_bidi(&mut cut);
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_count(text: &str) -> usize {
+        words(text).count()
+    }
+
+    #[test]
+    fn counts_cjk_words_without_spaces() {
+        assert_eq!(word_count("東京は日本の首都です。"), 10);
+        assert_eq!(word_count("これはペンです"), 6);
+        assert_eq!(word_count("한국어 텍스트"), 2);
+        assert_eq!(word_count("Rust と 日本語"), 5);
+    }
+
+    #[test]
+    fn counts_rtl_words() {
+        assert_eq!(word_count("שלום עולם"), 2);
+        assert_eq!(word_count("مرحبا بالعالم!"), 2);
+        assert_eq!(word_count("Version 2 من البرنامج"), 4);
+    }
+
+    #[test]
+    fn counts_contractions_as_one_word() {
+        assert_eq!(word_count("Don't stop, it's fine."), 4);
+    }
+
+    #[test]
+    fn segments_cjk_into_words() {
+        assert_eq!(segment("東京タワー"), "東 京 タワー");
+        assert_eq!(segment("hello world"), "hello   world");
+    }
+
+    #[test]
+    fn truncates_cjk_by_character() {
+        assert_eq!(truncate("東京は日本の首都です", 4), "東京は日");
+        assert_eq!(preview("東京は日本の首都です", 5), "東京は日…");
+    }
+
+    #[test]
+    fn keeps_graphemes_whole() {
+        assert_eq!(truncate("e\u{301}e\u{301}e\u{301}", 2), "e\u{301}e\u{301}");
+        assert_eq!(preview("🇯🇵🇰🇷🇨🇳", 2), "🇯🇵…");
+    }
+
+    #[test]
+    fn truncates_rtl_text() {
+        assert_eq!(preview("שלום עולם ומלואו", 10), "שלום עולם…");
+        assert_eq!(preview("مرحبا بالعالم", 6), "مرحبا…");
+    }
+
+    #[test]
+    fn closes_cut_bidi_embeddings() {
+        assert_eq!(preview("a \u{2067}שלום עולם\u{2069} b", 6), "a \u{2067}של\u{2069}…");
+        assert_eq!(preview("\u{202B}abc\u{202C} \u{2067}def", 9), "\u{202B}abc\u{202C} \u{2067}d\u{2069}…");
+    }
+}
@@ -0,0 +1,140 @@
+//! What happened to each recent capture on its way through the pipeline:
+//! where it was read from, what changed its text, which rules matched,
+//! which actions fired and which sinks it was written to. Kept in memory
+//! for the last [`KEPT_TRACES`] captures, so the settings UI can draw the
+//! flow for one of them with [`trace_capture`].
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+const KEPT_TRACES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    /// The backend or adapter the text was read with.
+    Source,
+    /// Something that may have changed the text: normalization, plugins,
+    /// scripts.
+    Transform,
+    Rule,
+    Action,
+    Sink,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub stage: Stage,
+    pub name: String,
+    pub detail: Option<String>,
+    pub ok: bool,
+    /// Milliseconds since processing started.
+    pub at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Trace {
+    pub id: i64,
+    pub steps: Vec<Step>,
+}
+
+/// Steps taken before the capture has a history id; see [`begin`].
+pub struct Recorder {
+    started: Instant,
+    steps: Vec<Step>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Vec::new(),
+        }
+    }
+}
+
+impl Recorder {
+    pub fn step(&mut self, stage: Stage, name: &str, detail: Option<String>) {
+        self.steps.push(Step {
+            stage,
+            name: name.to_string(),
+            detail,
+            ok: true,
+            at_ms: self.started.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct Traces(Mutex<VecDeque<(Instant, Trace)>>);
+
+/// Starts the trace of capture `id` with what `recorder` saw. Captures that
+/// weren't stored aren't traced.
+pub fn begin(app: &AppHandle, id: Option<i64>, recorder: Recorder) {
+    let (Some(id), Some(traces)) = (id, app.try_state::<Traces>()) else {
+        return;
+    };
+    let mut traces = traces.0.lock().unwrap();
+    if traces.len() == KEPT_TRACES {
+        traces.pop_front();
+    }
+    traces.push_back((recorder.started, Trace { id, steps: recorder.steps }));
+}
+
+fn with_trace(app: &AppHandle, id: Option<i64>, change: impl FnOnce(Instant, &mut Trace)) {
+    let (Some(id), Some(traces)) = (id, app.try_state::<Traces>()) else {
+        return;
+    };
+    let mut traces = traces.0.lock().unwrap();
+    if let Some((started, trace)) = traces.iter_mut().rev().find(|(_, trace)| trace.id == id) {
+        change(*started, trace);
+    }
+}
+
+/// Adds a step to the trace of capture `id`, if it's still kept. Errors
+/// are kept as the step's detail.
+pub fn step(app: &AppHandle, id: Option<i64>, stage: Stage, name: &str, result: Result<Option<String>, String>) {
+    with_trace(app, id, |started, trace| {
+        let ok = result.is_ok();
+        trace.steps.push(Step {
+            stage,
+            name: name.to_string(),
+            detail: result.unwrap_or_else(Some),
+            ok,
+            at_ms: started.elapsed().as_millis() as u64,
+        });
+    });
+}
+
+/// Records where capture `id` was read from, ahead of everything else.
+/// Called by each capture path once the capture is processed.
+pub fn source(app: &AppHandle, id: Option<i64>, name: &str) {
+    with_trace(app, id, |_, trace| {
+        trace.steps.insert(
+            0,
+            Step {
+                stage: Stage::Source,
+                name: name.to_string(),
+                detail: None,
+                ok: true,
+                at_ms: 0,
+            },
+        );
+    });
+}
+
+/// The step-by-step record of capture `id`. Only the most recent captures
+/// of this session are kept.
+#[tauri::command]
+pub async fn trace_capture(traces: State<'_, Traces>, id: i64) -> Result<Trace, String> {
+    let traces = traces.0.lock().unwrap();
+    traces
+        .iter()
+        .rev()
+        .find(|(_, trace)| trace.id == id)
+        .map(|(_, trace)| trace.clone())
+        .ok_or_else(|| format!("No trace is kept for capture {}", id))
+}
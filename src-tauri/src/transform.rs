@@ -0,0 +1,123 @@
+//! Text transformations offered as popup actions on a capture. They run
+//! here rather than in the webview so multi-megabyte blobs stay cheap.
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+
+pub fn pretty_json(text: &str) -> Result<String, String> {
+    let value: serde_json::Value = serde_json::from_str(text.trim()).map_err(|e| format!("Not valid JSON: {}", e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// Accepts the standard and URL-safe alphabets, padded or not, ignoring
+/// line breaks.
+pub fn base64_decode(text: &str) -> Result<String, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = [STANDARD, URL_SAFE, STANDARD_NO_PAD, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(&compact).ok())
+        .ok_or("Not valid Base64")?;
+    let len = bytes.len();
+    String::from_utf8(bytes).map_err(|_| format!("Decoded data is not text ({} bytes)", len))
+}
+
+/// Percent-decoding, treating `+` as a space as in form-encoded query strings.
+pub fn percent_decode(text: &str) -> Result<String, String> {
+    let text = text.trim().replace('+', " ");
+    percent_encoding::percent_decode_str(&text)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|e| format!("Decoded data is not text: {}", e))
+}
+
+/// Resolves backslash escapes as written in JSON, JavaScript, Rust or
+/// C string literals, dropping surrounding quotes if present.
+pub fn unescape(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    let text = ['"', '\'', '`']
+        .iter()
+        .find_map(|&q| text.strip_prefix(q).and_then(|t| t.strip_suffix(q)))
+        .unwrap_or(text);
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('x') => out.push(hex_char(&mut chars, 2)?),
+            Some('u') => {
+                let mut lookahead = chars.clone();
+                if lookahead.next() == Some('{') {
+                    let digits: String = lookahead.by_ref().take_while(|&c| c != '}').collect();
+                    out.push(char_from_hex(&digits)?);
+                    chars = lookahead;
+                } else {
+                    out.push(unicode_escape(&mut chars)?);
+                }
+            }
+            Some(other) => out.push(other),
+            None => return Err("Trailing backslash".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+fn hex_char(chars: &mut std::str::Chars, len: usize) -> Result<char, String> {
+    let digits: String = chars.by_ref().take(len).collect();
+    char_from_hex(&digits)
+}
+
+/// `\uXXXX`, combining a following `\uXXXX` low surrogate if needed.
+fn unicode_escape(chars: &mut std::str::Chars) -> Result<char, String> {
+    let digits: String = chars.by_ref().take(4).collect();
+    let high = u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid escape \\u{}", digits))?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return char_from_hex(&digits);
+    }
+
+    let rest = chars.as_str();
+    let low = rest
+        .strip_prefix("\\u")
+        .and_then(|r| r.get(..4))
+        .and_then(|d| u32::from_str_radix(d, 16).ok())
+        .filter(|low| (0xDC00..0xE000).contains(low))
+        .ok_or("Unpaired surrogate in \\u escape")?;
+    chars.nth(5);
+    char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).ok_or_else(|| "Invalid surrogate pair".to_string())
+}
+
+fn char_from_hex(digits: &str) -> Result<char, String> {
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| format!("Invalid escape sequence \"{}\"", digits))
+}
+
+#[tauri::command]
+pub async fn pretty_print_json(text: String) -> Result<String, String> {
+    pretty_json(&text)
+}
+
+#[tauri::command]
+pub async fn decode_base64(text: String) -> Result<String, String> {
+    base64_decode(&text)
+}
+
+#[tauri::command]
+pub async fn url_decode(text: String) -> Result<String, String> {
+    percent_decode(&text)
+}
+
+#[tauri::command]
+pub async fn unescape_string(text: String) -> Result<String, String> {
+    unescape(&text)
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::{self, SettingsStore};
+use crate::quota::{self, Provider};
+use crate::{kiosk, secrets, system_tray};
+
+mod glossary;
+
+pub use glossary::GlossaryEntry;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguagePair {
+    /// ISO 639-1 code, or "auto" to let the provider detect it.
+    pub source: String,
+    pub target: String,
+}
+
+impl fmt::Display for LanguagePair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} → {}", self.source, self.target)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranslationSettings {
+    /// A LibreTranslate-compatible `/translate` endpoint.
+    pub endpoint: String,
+    pub api_key: Option<String>,
+    /// Favourite pairs, cycled through with `cycle_shortcut`.
+    pub pairs: Vec<LanguagePair>,
+    pub active_pair: usize,
+    pub cycle_shortcut: String,
+    /// Preferred renderings enforced on every translation.
+    pub glossary: Vec<GlossaryEntry>,
+}
+
+impl Default for TranslationSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://libretranslate.com/translate".to_string(),
+            api_key: None,
+            pairs: vec![LanguagePair {
+                source: "auto".to_string(),
+                target: "en".to_string(),
+            }],
+            active_pair: 0,
+            cycle_shortcut: "Alt+Shift+T".to_string(),
+            glossary: Vec::new(),
+        }
+    }
+}
+
+impl TranslationSettings {
+    pub fn active(&self) -> Option<&LanguagePair> {
+        self.pairs.get(self.active_pair).or_else(|| self.pairs.first())
+    }
+}
+
+#[derive(Serialize)]
+struct TranslateRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct TranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+pub async fn translate(settings: &TranslationSettings, text: &str, pair: &LanguagePair) -> Result<String, String> {
+    let protected = glossary::protect(text, &settings.glossary, &pair.target);
+    let translated = request_translation(settings, &protected.text, pair).await?;
+    Ok(glossary::restore(&translated, &protected))
+}
+
+async fn request_translation(settings: &TranslationSettings, text: &str, pair: &LanguagePair) -> Result<String, String> {
+    let api_key = settings.api_key.as_deref().map(secrets::resolve).transpose()?;
+    let request = TranslateRequest {
+        q: text,
+        source: &pair.source,
+        target: &pair.target,
+        format: "text",
+        api_key: api_key.as_deref(),
+    };
+
+    let response = reqwest::Client::new()
+        .post(&settings.endpoint)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Translation request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Translation provider returned {}", response.status()));
+    }
+
+    let body: TranslateResponse = response.json().await.map_err(|e| e.to_string())?;
+    Ok(body.translated_text)
+}
+
+/// Makes pair `index` the active target, notifying the tray and frontend.
+pub fn set_active_pair(app: &AppHandle, index: usize) -> Result<LanguagePair, String> {
+    // The tray and hotkey come through here too, not just the blocked commands.
+    if kiosk::is_locked(app) {
+        return Err("The translation target can't be changed in kiosk mode".to_string());
+    }
+    let store = app.try_state::<SettingsStore>().ok_or("Settings are not loaded")?;
+    let count = store.get().translation.pairs.len();
+    if index >= count {
+        return Err(format!("No translation pair {}; there are {}", index, count));
+    }
+    let updated = store.update(|s| s.translation.active_pair = index)?;
+
+    let pair = updated
+        .translation
+        .active()
+        .cloned()
+        .ok_or("No translation pairs configured")?;
+    system_tray::refresh_menu(app);
+    let _ = app.emit("translation-pair-changed", &pair);
+    Ok(pair)
+}
+
+/// Advances to the next favourite pair, wrapping around.
+pub fn cycle(app: &AppHandle) -> Result<LanguagePair, String> {
+    let translation = settings::current(app).translation;
+    if translation.pairs.is_empty() {
+        return Err("No translation pairs configured".to_string());
+    }
+    set_active_pair(app, (translation.active_pair + 1) % translation.pairs.len())
+}
+
+#[tauri::command]
+pub async fn translate_text(app: AppHandle, text: String, pair: Option<LanguagePair>) -> Result<String, String> {
+    let translation = settings::current(&app).translation;
+    let pair = match pair {
+        Some(pair) => pair,
+        None => translation.active().cloned().ok_or("No translation pairs configured")?,
+    };
+    quota::charge(&app, Provider::Translation, &text)?;
+    translate(&translation, &text, &pair).await
+}
+
+#[tauri::command]
+pub async fn cycle_translation_target(app: AppHandle) -> Result<LanguagePair, String> {
+    cycle(&app)
+}
+
+#[tauri::command]
+pub async fn set_translation_target(app: AppHandle, index: usize) -> Result<LanguagePair, String> {
+    set_active_pair(&app, index)
+}
+
+/// Merges entries from a CSV file into the glossary, replacing entries for
+/// the same term and target. Returns the number of entries read.
+#[tauri::command]
+pub async fn import_glossary(app: AppHandle, csv_path: String) -> Result<usize, String> {
+    let imported = glossary::import_csv(std::path::Path::new(&csv_path))?;
+    let count = imported.len();
+
+    app.state::<SettingsStore>().update(|s| {
+        let glossary = &mut s.translation.glossary;
+        for entry in imported {
+            glossary.retain(|e| !(e.term.eq_ignore_ascii_case(&entry.term) && e.target == entry.target));
+            glossary.push(entry);
+        }
+    })?;
+    Ok(count)
+}
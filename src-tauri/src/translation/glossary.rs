@@ -0,0 +1,99 @@
+//! User glossary enforcement.
+//!
+//! Glossary terms are swapped for opaque placeholders before the text is
+//! sent to the provider and replaced with the preferred translation
+//! afterwards, so the provider can't pick its own rendering of them.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    pub translation: String,
+    /// Only apply when translating into this language; `None` applies to all.
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// Text with glossary terms masked, plus what each placeholder stands for.
+pub struct Protected {
+    pub text: String,
+    replacements: Vec<String>,
+}
+
+pub fn protect(text: &str, glossary: &[GlossaryEntry], target: &str) -> Protected {
+    let mut text = text.to_string();
+    let mut replacements = Vec::new();
+
+    // Longest terms first so "machine learning" wins over "machine"
+    let mut entries: Vec<&GlossaryEntry> = glossary
+        .iter()
+        .filter(|e| !e.term.is_empty() && e.target.as_deref().is_none_or(|t| t == target))
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.term.chars().count()));
+
+    for entry in entries {
+        let Some(pattern) = term_pattern(&entry.term) else {
+            continue;
+        };
+        if !pattern.is_match(&text) {
+            continue;
+        }
+        let placeholder = placeholder(replacements.len());
+        text = pattern.replace_all(&text, placeholder.as_str()).into_owned();
+        replacements.push(entry.translation.clone());
+    }
+
+    Protected { text, replacements }
+}
+
+pub fn restore(translated: &str, protected: &Protected) -> String {
+    let mut text = translated.to_string();
+    for (i, replacement) in protected.replacements.iter().enumerate() {
+        text = text.replace(&placeholder(i), replacement);
+    }
+    text
+}
+
+/// Reads `term,translation[,target]` rows; a header row is skipped if its
+/// first cell is literally "term".
+pub fn import_csv(path: &Path) -> Result<Vec<GlossaryEntry>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Line {}: {}", line + 1, e))?;
+        let term = record.get(0).unwrap_or_default();
+        let translation = record.get(1).unwrap_or_default();
+        if line == 0 && term.eq_ignore_ascii_case("term") {
+            continue;
+        }
+        if term.is_empty() || translation.is_empty() {
+            return Err(format!("Line {}: expected term,translation", line + 1));
+        }
+        entries.push(GlossaryEntry {
+            term: term.to_string(),
+            translation: translation.to_string(),
+            target: record.get(2).filter(|t| !t.is_empty()).map(str::to_string),
+        });
+    }
+    Ok(entries)
+}
+
+fn term_pattern(term: &str) -> Option<Regex> {
+    RegexBuilder::new(&format!(r"\b{}\b", regex::escape(term)))
+        .case_insensitive(true)
+        .build()
+        .ok()
+}
+
+fn placeholder(index: usize) -> String {
+    format!("⟦{}⟧", index)
+}
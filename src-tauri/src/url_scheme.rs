@@ -0,0 +1,87 @@
+//! `acmi://capture?text=...&source=...` links, so Shortcuts, bookmarklets
+//! and other apps can send text into the capture pipeline. Opening a link
+//! while the app is running is handed to the running instance rather than
+//! starting another one.
+
+use tauri::{AppHandle, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::{capture, main_window, trace};
+
+pub const SCHEME: &str = "acmi";
+
+pub fn init(app: &AppHandle) {
+    // macOS registers the scheme from the bundle; elsewhere it is done at runtime
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("Failed to register the {}:// scheme: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, &url);
+        }
+    });
+
+    // A link that launched the app arrives before the handler exists
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app, &url);
+        }
+    }
+}
+
+/// Called in the running instance when the app is launched again. Links
+/// are forwarded to [`init`]'s handler by the deep link plugin; a plain
+/// relaunch brings up the main window.
+pub fn second_instance(app: &AppHandle, args: Vec<String>, _cwd: String) {
+    let prefix = format!("{}:", SCHEME);
+    if args.iter().skip(1).any(|arg| arg.starts_with(&prefix)) {
+        return;
+    }
+    if let Err(e) = main_window::show(app) {
+        eprintln!("Failed to show the main window: {}", e);
+    }
+}
+
+fn open(app: &AppHandle, url: &Url) {
+    match to_event(url) {
+        Ok(event) => {
+            if let Some(capture) = capture::process(app, event) {
+                trace::source(app, capture.id, "url scheme");
+                capture::emit(app, "text-selection-changed", &capture);
+            }
+        }
+        Err(e) => eprintln!("Ignoring link {}: {}", url, e),
+    }
+}
+
+fn to_event(url: &Url) -> Result<SelectionEvent, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("not an {}:// link", SCHEME));
+    }
+    // Both acmi://capture?... and acmi:capture?... are accepted
+    let action = url.host_str().unwrap_or_else(|| url.path().trim_matches('/'));
+    if action != "capture" {
+        return Err(format!("unknown action \"{}\"", action));
+    }
+
+    let mut text = None;
+    let mut source = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "text" => text = Some(value.into_owned()),
+            "source" => source = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+
+    let text = text.filter(|text| !text.trim().is_empty()).ok_or("no text to capture")?;
+    Ok(SelectionEvent::new(
+        text,
+        source.unwrap_or_else(|| "URL scheme".to_string()),
+        SelectionType::Selected,
+    ))
+}
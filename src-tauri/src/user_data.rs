@@ -0,0 +1,188 @@
+//! Everything the app keeps about the user, taken out in one go or removed
+//! for good: for moving machines, or for leaving cleanly.
+//!
+//! The export is a zip of the config, data and cache directories (settings,
+//! every profile's history, scripts, plugins, queued deliveries, caches) plus
+//! `history.json` and `action_log.json` for the active profile, in a form
+//! other tools can read. Keychain secrets are left out. The wipe overwrites
+//! every file before removing it, deletes every keychain secret and starts
+//! the app over on empty defaults.
+
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::history::audit::ActionLogFilter;
+use crate::history::{provenance, HistoryStore, DB_FILE_NAME};
+use crate::{locations, profiles, secrets, settings};
+
+/// Start of the name [`HistoryStore::snapshot`] writes the active database
+/// to, in the cache directory, while it's being archived.
+const SNAPSHOT_FILE_PREFIX: &str = "history-export";
+
+/// The config, data and cache directories. Ones that are the same as, or
+/// inside, another are left out.
+fn roots(app: &AppHandle) -> Result<Vec<(&'static str, PathBuf)>, String> {
+    let config = locations::config_dir(app).map_err(|e| e.to_string())?;
+    let data = locations::data_dir(app).map_err(|e| e.to_string())?;
+    let cache = locations::cache_dir(app).map_err(|e| e.to_string())?;
+    let mut roots: Vec<(&'static str, PathBuf)> = Vec::new();
+    for (prefix, dir) in [("config", config), ("data", data), ("cache", cache)] {
+        roots.retain(|(_, root)| !root.starts_with(&dir));
+        if !roots.iter().any(|(_, root)| dir.starts_with(root)) {
+            roots.push((prefix, dir));
+        }
+    }
+    Ok(roots)
+}
+
+fn files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            files(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+fn add_file<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, path: &Path) -> Result<(), String> {
+    let mut contents = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut contents))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    add_bytes(zip, name, &contents)
+}
+
+fn add_bytes<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, contents: &[u8]) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default()).map_err(|e| e.to_string())?;
+    zip.write_all(contents).map_err(|e| e.to_string())
+}
+
+/// Overwrites `path` with zeros and syncs before removing it, so the
+/// contents don't linger in free space. Best effort: SSDs and copy-on-write
+/// filesystems may still keep old blocks around.
+fn shred(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = vec![0u8; 64 * 1024];
+    let mut written = 0;
+    while written < len {
+        let chunk = zeros.len().min((len - written) as usize);
+        file.write_all(&zeros[..chunk])?;
+        written += chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+/// Writes everything to a zip at `path`. Returns how many files it holds.
+#[tauri::command]
+pub async fn export_all_user_data(app: AppHandle, path: String) -> Result<usize, String> {
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let mut count = 0;
+
+    let history = app.try_state::<HistoryStore>();
+    let live_db = profiles::data_dir(&app).map_err(|e| e.to_string())?.join(DB_FILE_NAME);
+    // Its journal is folded into the snapshot
+    let live_db_extras = format!("{}-", live_db.to_string_lossy());
+    let cache = locations::cache_dir(&app).map_err(|e| e.to_string())?;
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let snapshot = cache.join(format!("{}-{}-{}.db", SNAPSHOT_FILE_PREFIX, std::process::id(), nanos));
+
+    for (prefix, root) in roots(&app)? {
+        let mut paths = Vec::new();
+        files(&root, &mut paths);
+        for file in paths {
+            let name = format!("{}/{}", prefix, file.strip_prefix(&root).unwrap_or(&file).to_string_lossy());
+            let name = name.replace('\\', "/");
+            // The open database is copied through SQLite so it's consistent
+            match &history {
+                Some(history) if file == live_db => {
+                    std::fs::create_dir_all(&cache).map_err(|e| e.to_string())?;
+                    // SQLite won't write over an existing file
+                    let _ = std::fs::remove_file(&snapshot);
+                    history.snapshot(&snapshot).map_err(|e| e.to_string())?;
+                    let added = add_file(&mut zip, &name, &snapshot);
+                    let _ = std::fs::remove_file(&snapshot);
+                    added?;
+                }
+                _ if file.to_string_lossy().starts_with(&live_db_extras)
+                    || file == Path::new(&path)
+                    || file == snapshot =>
+                {
+                    continue
+                }
+                _ => add_file(&mut zip, &name, &file)?,
+            }
+            count += 1;
+        }
+    }
+
+    if let Some(history) = &history {
+        let entries = history.recent(u32::MAX).map_err(|e| e.to_string())?;
+        let entries = serde_json::to_vec_pretty(&entries).map_err(|e| e.to_string())?;
+        add_bytes(&mut zip, "history.json", &entries)?;
+        let filter = ActionLogFilter {
+            limit: Some(u32::MAX),
+            ..ActionLogFilter::default()
+        };
+        let log = history.action_log(&filter).map_err(|e| e.to_string())?;
+        let log = serde_json::to_vec_pretty(&log).map_err(|e| e.to_string())?;
+        add_bytes(&mut zip, "action_log.json", &log)?;
+        count += 2;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Securely deletes all settings, history, logs and other files in every
+/// profile, deletes the keychain secrets, and carries on with the default
+/// profile on defaults. Returns how many files were removed.
+#[tauri::command]
+pub async fn wipe_all_user_data(app: AppHandle) -> Result<usize, String> {
+    // Let go of the database so its file can be overwritten on every OS
+    if let Some(history) = app.try_state::<HistoryStore>() {
+        history.close().map_err(|e| e.to_string())?;
+    }
+
+    let mut removed = 0;
+    let mut failed = Vec::new();
+    let mut referenced = Vec::new();
+    for (_, root) in roots(&app)? {
+        let mut paths = Vec::new();
+        files(&root, &mut paths);
+        for file in paths {
+            // Every profile's settings, for secrets stored before they were indexed
+            if file.file_name().is_some_and(|name| name == settings::SETTINGS_FILE_NAME) {
+                if let Ok(contents) = std::fs::read_to_string(&file) {
+                    referenced.extend(secrets::references(&contents));
+                }
+            }
+            match shred(&file) {
+                Ok(()) => removed += 1,
+                Err(e) => failed.push(format!("{}: {}", file.display(), e)),
+            }
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+    referenced.push(provenance::KEY_NAME.to_string());
+    failed.extend(secrets::delete_all(&referenced));
+
+    profiles::load(&app, profiles::DEFAULT_PROFILE)?;
+    let _ = app.emit("user-data-wiped", removed);
+    if !failed.is_empty() {
+        return Err(format!("Some data couldn't be removed: {}", failed.join("; ")));
+    }
+    Ok(removed)
+}
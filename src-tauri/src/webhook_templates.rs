@@ -0,0 +1,141 @@
+//! JSON payload templates for webhook rules, so one capture can be shaped
+//! for Slack, Discord or any other endpoint from the settings alone.
+//! Templates live in the settings and travel with presets, so a team can
+//! share them.
+//!
+//! A template is a JSON document whose strings may hold Handlebars-style
+//! placeholders such as `{{text}}`, `{{app}}`, `{{tags}}`, `{{timestamp}}`
+//! or a path into the capture like `{{date.local}}`. A string that is only
+//! a placeholder takes the value as is, so `"tags": "{{tags}}"` stays a
+//! list; elsewhere values are written out as text. Since placeholders are
+//! filled in after parsing, captured text can never break the JSON.
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use text_detection::{SelectionEvent, SelectionType};
+
+use crate::capture::{self, Capture};
+use crate::history::HistoryStore;
+use crate::settings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookTemplate {
+    /// What webhook rules refer to it by.
+    pub name: String,
+    /// The JSON payload, with placeholders.
+    pub body: String,
+}
+
+/// Parses `body`, without filling anything in.
+pub fn parse(body: &str) -> Result<Value, String> {
+    serde_json::from_str(body).map_err(|e| format!("Template isn't valid JSON: {}", e))
+}
+
+/// What placeholders can refer to: the capture as webhooks receive it,
+/// plus `app`, its `tags` and `time`, the capture time as RFC 3339.
+pub fn context(app: &AppHandle, capture: &Capture) -> Value {
+    let mut context = serde_json::to_value(capture).unwrap_or_default();
+    let tags = capture
+        .id
+        .and_then(|id| app.try_state::<HistoryStore>()?.get(id).ok().flatten())
+        .map(|entry| entry.tags)
+        .unwrap_or_default();
+    let time = Local
+        .timestamp_opt(capture.event.timestamp as i64, 0)
+        .single()
+        .map(|time| time.to_rfc3339());
+    if let Value::Object(fields) = &mut context {
+        fields.insert("app".to_string(), capture.event.app_name.clone().into());
+        fields.insert("tags".to_string(), tags.into());
+        fields.insert("time".to_string(), time.into());
+    }
+    context
+}
+
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(context, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+/// `value` as it reads in running text.
+fn as_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) => items.iter().map(|item| as_text(Some(item))).collect::<Vec<_>>().join(", "),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// The path of the placeholder `text` consists of, if it's just one.
+fn sole_placeholder(text: &str) -> Option<&str> {
+    let path = text.trim().strip_prefix("{{")?.strip_suffix("}}")?;
+    (!path.contains("{{") && !path.contains("}}")).then(|| path.trim())
+}
+
+fn fill_text(text: &str, context: &Value) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&as_text(lookup(context, rest[start + 2..start + end].trim())));
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn fill(value: &mut Value, context: &Value) {
+    match value {
+        Value::String(text) => match sole_placeholder(text) {
+            Some(path) => *value = lookup(context, path).cloned().unwrap_or(Value::Null),
+            None => *text = fill_text(text, context),
+        },
+        Value::Array(items) => items.iter_mut().for_each(|item| fill(item, context)),
+        Value::Object(fields) => fields.values_mut().for_each(|field| fill(field, context)),
+        _ => {}
+    }
+}
+
+/// The payload `body` describes, with placeholders filled in from
+/// `context`.
+pub fn render(body: &str, context: &Value) -> Result<Value, String> {
+    let mut payload = parse(body)?;
+    fill(&mut payload, context);
+    Ok(payload)
+}
+
+/// What a webhook sends for `capture`: the template named `name` filled
+/// in, or the capture itself without one.
+pub fn payload(app: &AppHandle, name: Option<&str>, capture: &Capture) -> Result<Value, String> {
+    let Some(name) = name else {
+        return serde_json::to_value(capture).map_err(|e| e.to_string());
+    };
+    let templates = settings::current(app).webhook_templates;
+    let template = templates
+        .iter()
+        .find(|template| template.name == name)
+        .ok_or_else(|| format!("No webhook template named {}", name))?;
+    render(&template.body, &context(app, capture))
+}
+
+/// Fills in `body` for `sample_text` as if it had been selected in
+/// `app_name`, so a template can be checked before a rule uses it.
+#[tauri::command]
+pub async fn preview_webhook_template(
+    app: AppHandle,
+    body: String,
+    sample_text: String,
+    app_name: Option<String>,
+) -> Result<Value, String> {
+    let event = SelectionEvent::new(sample_text, app_name.unwrap_or_else(|| "Test".to_string()), SelectionType::Selected);
+    let capture = capture::annotate(&app, event);
+    render(&body, &context(&app, &capture))
+}
@@ -0,0 +1,27 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+
+import "./App.css";
+
+type FlashStyle = "hud" | "screen";
+
+function Flash() {
+  const [style, setStyle] = useState<FlashStyle>("hud");
+
+  useEffect(() => {
+    const unlisten = listen<FlashStyle>("capture-flash", (event) =>
+      setStyle(event.payload)
+    );
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  return (
+    <main className={`flash flash-${style}`}>
+      {style === "hud" && <span>Captured</span>}
+    </main>
+  );
+}
+
+export default Flash;
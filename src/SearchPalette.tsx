@@ -0,0 +1,108 @@
+import { useEffect, useRef, useState, type KeyboardEvent } from "react";
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+
+import "./App.css";
+
+interface HistoryEntry {
+  id: number;
+  text: string;
+  app_name: string;
+  timestamp: number;
+}
+
+interface SearchBatch {
+  query: string;
+  entries: HistoryEntry[];
+  done: boolean;
+}
+
+function SearchPalette() {
+  const [query, setQuery] = useState("");
+  const [results, setResults] = useState<HistoryEntry[]>([]);
+  const [selected, setSelected] = useState(0);
+  const input = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    // The window is hidden rather than closed, so refocus on every show
+    const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) {
+        input.current?.select();
+      }
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    const onResults = new Channel<SearchBatch>();
+    let first = true;
+    onResults.onmessage = (batch) => {
+      if (batch.query !== query) {
+        return;
+      }
+      setResults((prev) => (first ? batch.entries : [...prev, ...batch.entries]));
+      first = false;
+    };
+    setSelected(0);
+    invoke("search_history_stream", { query, onResults }).catch((error) =>
+      console.error("Error searching history:", error)
+    );
+  }, [query]);
+
+  const choose = async (entry: HistoryEntry | undefined) => {
+    if (entry) {
+      await navigator.clipboard.writeText(entry.text);
+    }
+    await getCurrentWindow().hide();
+  };
+
+  const onKeyDown = (event: KeyboardEvent) => {
+    switch (event.key) {
+      case "ArrowDown":
+        setSelected((i) => Math.min(i + 1, results.length - 1));
+        break;
+      case "ArrowUp":
+        setSelected((i) => Math.max(i - 1, 0));
+        break;
+      case "Enter":
+        choose(results[selected]);
+        break;
+      case "Escape":
+        getCurrentWindow().hide();
+        break;
+      default:
+        return;
+    }
+    event.preventDefault();
+  };
+
+  return (
+    <main className="search-palette">
+      <input
+        ref={input}
+        autoFocus
+        value={query}
+        placeholder="Search captures"
+        onChange={(event) => setQuery(event.target.value)}
+        onKeyDown={onKeyDown}
+      />
+      <ul>
+        {results.map((entry, index) => (
+          <li
+            key={entry.id}
+            className={index === selected ? "selected" : undefined}
+            onMouseEnter={() => setSelected(index)}
+            onClick={() => choose(entry)}
+          >
+            <span className="text-content">{entry.text}</span>
+            <span className="app-name">{entry.app_name}</span>
+          </li>
+        ))}
+      </ul>
+    </main>
+  );
+}
+
+export default SearchPalette;
@@ -1,9 +1,24 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import Flash from "./Flash";
+import SearchPalette from "./SearchPalette";
+
+// The search palette and capture flash windows load index.html with their
+// own route; everything else is the main window
+function Root() {
+  switch (window.location.hash) {
+    case "#/search":
+      return <SearchPalette />;
+    case "#/flash":
+      return <Flash />;
+    default:
+      return <App />;
+  }
+}
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    <Root />
   </React.StrictMode>,
 );